@@ -19,14 +19,20 @@
 -- ========================================
 
 -- Drop tables in reverse dependency order (children first, parents last)
+DROP TABLE IF EXISTS audit_event CASCADE;
+DROP TABLE IF EXISTS user_identity CASCADE;
+DROP TABLE IF EXISTS api_token CASCADE;
+DROP TABLE IF EXISTS recovery_code CASCADE;
 DROP TABLE IF EXISTS user_roles CASCADE;
+DROP TABLE IF EXISTS role_permission CASCADE;
+DROP TABLE IF EXISTS crate_version CASCADE;
 DROP TABLE IF EXISTS crate CASCADE;
 DROP TABLE IF EXISTS app_user CASCADE;
 DROP TABLE IF EXISTS author CASCADE;
 DROP TABLE IF EXISTS role CASCADE;
 DROP TABLE IF EXISTS schema_version CASCADE;
 
--- Drop types
+-- Drop types (roles used to be a fixed enum type)
 DROP TYPE IF EXISTS "RoleCodeMapping" CASCADE;
 
 -- ========================================
@@ -44,6 +50,11 @@ CREATE TABLE app_user (
   id SERIAL PRIMARY KEY,
   username varchar(64) NOT NULL UNIQUE,
   password varchar(128) NOT NULL,
+  totp_secret varchar(64),
+  totp_enabled BOOLEAN NOT NULL DEFAULT FALSE,
+  -- Last accepted TOTP time step, codes of this or an earlier step are replays
+  totp_last_step BIGINT,
+  disabled_at TIMESTAMP,
   created_at TIMESTAMP DEFAULT NOW() NOT NULL
 );
 
@@ -58,27 +69,62 @@ CREATE TABLE author (
 ALTER TABLE app_user
 ADD COLUMN author_id INTEGER UNIQUE REFERENCES author(id);
 
+-- Trigram similarity for typo-tolerant crate name search
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
 CREATE TABLE crate (
   id SERIAL PRIMARY KEY,
   author_id integer NOT NULL REFERENCES author(id),
   code varchar(64) NOT NULL,
   name varchar(128) NOT NULL,
   version varchar(64) NOT NULL,
+  -- version in SemVer precedence order when compared bytewise, for sort=version
+  version_sort_key text COLLATE "C" NOT NULL,
+  -- set while every version is yanked; version keeps the last one that counted
+  yanked BOOLEAN NOT NULL DEFAULT FALSE,
   description text,
-  created_at TIMESTAMP DEFAULT NOW() NOT NULL
+  created_at TIMESTAMP DEFAULT NOW() NOT NULL,
+  -- Full-text search document, name ranked above description
+  search_vector tsvector GENERATED ALWAYS AS (
+    setweight(to_tsvector('english', name), 'A') ||
+    setweight(to_tsvector('english', coalesce(description, '')), 'B')
+  ) STORED
+);
+
+CREATE INDEX crate_search_vector_idx ON crate USING GIN (search_vector);
+CREATE INDEX crate_name_trgm_idx ON crate USING GIN (name gin_trgm_ops);
+
+-- Version history; crate.version is the highest non-yanked version by SemVer
+-- precedence, preferring releases over pre-releases
+CREATE TABLE crate_version (
+  id SERIAL PRIMARY KEY,
+  crate_id INTEGER NOT NULL REFERENCES crate(id) ON DELETE CASCADE,
+  version varchar(64) NOT NULL,
+  description text,
+  yanked BOOLEAN NOT NULL DEFAULT FALSE,
+  published_at TIMESTAMP DEFAULT NOW() NOT NULL,
+  UNIQUE (crate_id, version)
 );
 
+CREATE INDEX crate_version_published_at_idx ON crate_version (published_at);
 
 
-CREATE TYPE "RoleCodeMapping" AS ENUM ('Admin', 'Editor', 'Viewer');
 
+-- Roles are data: Admin, Editor and Viewer are seeded below, others are created at runtime
 CREATE TABLE role (
   id SERIAL PRIMARY KEY,
-  code "RoleCodeMapping" NOT NULL UNIQUE,
+  code varchar(64) NOT NULL UNIQUE CHECK (code ~ '^[A-Za-z][A-Za-z0-9_-]*$'),
   name varchar(128) NOT NULL,
   created_at TIMESTAMP DEFAULT NOW() NOT NULL
 );
 
+-- Permissions granted by each role, e.g. 'crate:write'
+CREATE TABLE role_permission (
+  role_id INTEGER NOT NULL REFERENCES role(id) ON DELETE CASCADE,
+  permission varchar(64) NOT NULL,
+  CONSTRAINT role_permission_unique UNIQUE (role_id, permission)
+);
+
 
 
 CREATE TABLE user_roles (
@@ -87,7 +133,68 @@ CREATE TABLE user_roles (
   CONSTRAINT user_role_unique UNIQUE (user_id, role_id)
 );
 
+-- Two-factor recovery codes, stored as password hashes; used_at marks spent codes
+CREATE TABLE recovery_code (
+  id SERIAL PRIMARY KEY,
+  user_id INTEGER NOT NULL REFERENCES app_user(id) ON DELETE CASCADE,
+  code_hash varchar(128) NOT NULL,
+  used_at TIMESTAMP,
+  created_at TIMESTAMP DEFAULT NOW() NOT NULL
+);
+
+-- Identity provider subjects linked to users for single sign-on
+CREATE TABLE user_identity (
+  id SERIAL PRIMARY KEY,
+  user_id INTEGER NOT NULL REFERENCES app_user(id) ON DELETE CASCADE,
+  issuer varchar(255) NOT NULL,
+  subject varchar(255) NOT NULL,
+  created_at TIMESTAMP DEFAULT NOW() NOT NULL,
+  UNIQUE (issuer, subject)
+);
+
+-- Personal access tokens for automation; only the SHA-256 of the secret is stored
+CREATE TABLE api_token (
+  id SERIAL PRIMARY KEY,
+  user_id INTEGER NOT NULL REFERENCES app_user(id) ON DELETE CASCADE,
+  name varchar(64) NOT NULL,
+  token_hash varchar(64) NOT NULL UNIQUE,
+  scopes TEXT[] NOT NULL,
+  expires_at TIMESTAMP,
+  last_used_at TIMESTAMP,
+  created_at TIMESTAMP DEFAULT NOW() NOT NULL
+);
+
+-- Append-only audit log of create/update/delete events. actor_user_id is NULL for
+-- CLI changes and has no foreign key so events outlive the users who made them.
+CREATE TABLE audit_event (
+  id SERIAL PRIMARY KEY,
+  actor_user_id INTEGER,
+  action varchar(16) NOT NULL,
+  entity_type varchar(32) NOT NULL,
+  entity_id INTEGER NOT NULL,
+  before JSONB,
+  after JSONB,
+  created_at TIMESTAMP DEFAULT NOW() NOT NULL
+);
+CREATE INDEX audit_event_entity_idx ON audit_event (entity_type, entity_id);
+CREATE RULE audit_event_no_update AS ON UPDATE TO audit_event DO INSTEAD NOTHING;
+CREATE RULE audit_event_no_delete AS ON DELETE TO audit_event DO INSTEAD NOTHING;
+
 INSERT INTO role (code, name) VALUES
   ('Admin', 'Administrator'),
   ('Editor', 'Editor'),
   ('Viewer', 'Viewer');
+
+INSERT INTO role_permission (role_id, permission)
+SELECT r.id, p.permission
+FROM role r
+JOIN (VALUES
+  ('Admin', 'crate:read'), ('Admin', 'crate:write'), ('Admin', 'crate:write:own'),
+  ('Admin', 'crate:delete'),
+  ('Admin', 'author:read'), ('Admin', 'author:write'), ('Admin', 'author:delete'),
+  ('Admin', 'user:manage'), ('Admin', 'role:manage'), ('Admin', 'audit:read'),
+  ('Editor', 'crate:read'), ('Editor', 'crate:write'), ('Editor', 'crate:write:own'),
+  ('Editor', 'crate:delete'),
+  ('Editor', 'author:read'), ('Editor', 'author:write'), ('Editor', 'author:delete'),
+  ('Viewer', 'crate:read'), ('Viewer', 'crate:write:own'), ('Viewer', 'author:read')
+) AS p (code, permission) ON p.code = r.code;
@@ -2,32 +2,114 @@
 //!
 //! Provides a default implementation of the `PasswordHasherTrait`
 //! and exposes a constructor for use in both CLI and server contexts.
+//! Also hosts the RFC 6238 TOTP primitives used for two-factor authentication.
 
 use crate::domain::{PasswordHasherTrait, PasswordHasherTraitPtr};
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use argon2::{
     password_hash::{rand_core::OsRng, SaltString},
-    Argon2, PasswordHash, PasswordHasher, PasswordVerifier,
+    Algorithm, Argon2, Params, PasswordHash, PasswordHasher, PasswordVerifier, Version,
 };
+use data_encoding::BASE32_NOPAD;
+use hmac::{Hmac, Mac};
 use rand::prelude::*;
+use sha1::Sha1;
 use std::sync::Arc;
 
-struct Argon2PasswordHasher;
+/// Argon2id cost parameters, read from the environment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Argon2Config {
+    /// Memory cost in KiB.
+    pub memory_kib: u32,
+    /// Number of passes over the memory.
+    pub iterations: u32,
+    /// Degree of parallelism (lanes).
+    pub parallelism: u32,
+}
+
+impl Default for Argon2Config {
+    fn default() -> Self {
+        Self {
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+impl Argon2Config {
+    // ---
+    /// Respects env vars:
+    /// - `CR8S_ARGON2_MEMORY_KIB` (default: 19456)
+    /// - `CR8S_ARGON2_ITERATIONS` (default: 2)
+    /// - `CR8S_ARGON2_PARALLELISM` (default: 1)
+    pub fn from_env() -> Self {
+        // ---
+        let defaults = Self::default();
+        Self {
+            memory_kib: crate::get_env_with_default!(
+                u32,
+                "CR8S_ARGON2_MEMORY_KIB",
+                defaults.memory_kib
+            ),
+            iterations: crate::get_env_with_default!(
+                u32,
+                "CR8S_ARGON2_ITERATIONS",
+                defaults.iterations
+            ),
+            parallelism: crate::get_env_with_default!(
+                u32,
+                "CR8S_ARGON2_PARALLELISM",
+                defaults.parallelism
+            ),
+        }
+    }
+}
+
+struct Argon2PasswordHasher {
+    params: Params,
+}
 
-/// Return a default password hasher implementation using Argon2id.
+/// Return a default password hasher implementation using Argon2id, with the cost
+/// parameters from `Argon2Config::from_env`.
 pub fn create_password_hasher() -> Result<PasswordHasherTraitPtr> {
     // ---
-    Ok(Arc::new(Argon2PasswordHasher {}))
+    create_password_hasher_with(&Argon2Config::from_env())
+}
+
+/// Return an Argon2id password hasher with explicit cost parameters.
+///
+/// Fails if the parameters are out of Argon2's range (e.g. less than 8 KiB of memory
+/// per lane).
+pub fn create_password_hasher_with(config: &Argon2Config) -> Result<PasswordHasherTraitPtr> {
+    // ---
+    let params = Params::new(
+        config.memory_kib,
+        config.iterations,
+        config.parallelism,
+        None,
+    )
+    .map_err(|e| anyhow!("Invalid Argon2 parameters {config:?}: {e}"))?;
+
+    Ok(Arc::new(Argon2PasswordHasher { params }))
 }
 
 const SESSION_TOKEN_LEN: usize = 128;
 
+impl Argon2PasswordHasher {
+    // ---
+    fn argon2(&self) -> Argon2<'static> {
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, self.params.clone())
+    }
+}
+
 impl PasswordHasherTrait for Argon2PasswordHasher {
     // ---
 
     fn hash_password(&self, password: &str) -> Result<String> {
         let salt = SaltString::generate(OsRng);
-        let hashed = Argon2::default()
+        let hashed = self
+            .argon2()
             .hash_password(password.as_bytes(), &salt)
             .map_err(anyhow::Error::msg)?
             .to_string();
@@ -36,9 +118,11 @@ impl PasswordHasherTrait for Argon2PasswordHasher {
 
     // ---
 
+    /// Verification uses the algorithm and parameters recorded in the hash itself,
+    /// so hashes made with older settings keep working.
     fn verify_password(&self, hashed: &str, candidate: &str) -> Result<()> {
         let parsed_hash = PasswordHash::new(hashed).map_err(anyhow::Error::msg)?;
-        Argon2::default()
+        self.argon2()
             .verify_password(candidate.as_bytes(), &parsed_hash)
             .map_err(anyhow::Error::msg)?;
         Ok(())
@@ -46,6 +130,26 @@ impl PasswordHasherTrait for Argon2PasswordHasher {
 
     // ---
 
+    /// A hash needs upgrading if it is not Argon2id v19 or any of its costs is below
+    /// the configured one. Hashes with higher costs are left alone.
+    fn needs_rehash(&self, hashed: &str) -> bool {
+        // ---
+        let Ok(parsed_hash) = PasswordHash::new(hashed) else {
+            return false;
+        };
+        let Ok(params) = Params::try_from(&parsed_hash) else {
+            return true;
+        };
+
+        parsed_hash.algorithm != Algorithm::Argon2id.ident()
+            || parsed_hash.version != Some(Version::V0x13.into())
+            || params.m_cost() < self.params.m_cost()
+            || params.t_cost() < self.params.t_cost()
+            || params.p_cost() < self.params.p_cost()
+    }
+
+    // ---
+
     fn generate_session_token(&self) -> String {
         // ---
         rand::rng()
@@ -55,3 +159,148 @@ impl PasswordHasherTrait for Argon2PasswordHasher {
             .collect()
     }
 }
+
+// --- TOTP (RFC 6238, HMAC-SHA1, 6 digits, 30 second steps)
+
+const TOTP_STEP_SECS: u64 = 30;
+const TOTP_DIGITS: u32 = 6;
+const TOTP_SECRET_LEN: usize = 20;
+
+/// Generate a random TOTP secret, base32-encoded without padding.
+pub fn generate_totp_secret() -> String {
+    // ---
+    BASE32_NOPAD.encode(&rand::random::<[u8; TOTP_SECRET_LEN]>())
+}
+
+/// Compute the TOTP code of a base32 `secret` at `unix_secs`.
+pub fn totp_code_at(secret: &str, unix_secs: u64) -> Result<String> {
+    // ---
+    let key = BASE32_NOPAD
+        .decode(secret.as_bytes())
+        .map_err(|e| anyhow!("Invalid TOTP secret: {e}"))?;
+
+    let mut mac = Hmac::<Sha1>::new_from_slice(&key).map_err(anyhow::Error::msg)?;
+    mac.update(&(unix_secs / TOTP_STEP_SECS).to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+
+    // Dynamic truncation (RFC 4226 section 5.3)
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        hash[offset] & 0x7f,
+        hash[offset + 1],
+        hash[offset + 2],
+        hash[offset + 3],
+    ]);
+
+    Ok(format!(
+        "{:0width$}",
+        binary % 10u32.pow(TOTP_DIGITS),
+        width = TOTP_DIGITS as usize
+    ))
+}
+
+/// Check a TOTP code at `unix_secs`, accepting one step of clock drift either way.
+pub fn verify_totp_code(secret: &str, code: &str, unix_secs: u64) -> bool {
+    matching_totp_step(secret, code, unix_secs).is_some()
+}
+
+/// The time step (`unix_secs / 30`) whose code matches, accepting one step of clock
+/// drift either way, or `None` if the code is wrong. Callers remember the last accepted
+/// step so that a code cannot be used twice.
+pub fn matching_totp_step(secret: &str, code: &str, unix_secs: u64) -> Option<u64> {
+    // ---
+    let code = code.trim();
+    if code.len() != TOTP_DIGITS as usize {
+        return None;
+    }
+
+    [
+        unix_secs.saturating_sub(TOTP_STEP_SECS),
+        unix_secs,
+        unix_secs + TOTP_STEP_SECS,
+    ]
+    .into_iter()
+    .find(|t| totp_code_at(secret, *t).is_ok_and(|expected| expected == code))
+    .map(|t| t / TOTP_STEP_SECS)
+}
+
+/// Build the `otpauth://` URI that authenticator apps import (usually as a QR code).
+pub fn totp_uri(issuer: &str, account: &str, secret: &str) -> String {
+    // ---
+    format!(
+        "otpauth://totp/{issuer}:{account}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits={TOTP_DIGITS}&period={TOTP_STEP_SECS}"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 6238 appendix B test key "12345678901234567890", base32-encoded
+    const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    #[test]
+    fn test_totp_matches_rfc_6238_vectors() {
+        // ---
+        // The RFC lists 8-digit codes; a 6-digit code is their last six digits
+        assert_eq!(totp_code_at(RFC_SECRET, 59).unwrap(), "287082");
+        assert_eq!(totp_code_at(RFC_SECRET, 1111111109).unwrap(), "081804");
+        assert_eq!(totp_code_at(RFC_SECRET, 2000000000).unwrap(), "279037");
+    }
+
+    #[test]
+    fn test_rehash_only_when_costs_are_raised() {
+        // ---
+        let weak = Argon2Config {
+            memory_kib: 64,
+            iterations: 1,
+            parallelism: 1,
+        };
+        let strong = Argon2Config {
+            memory_kib: 128,
+            ..weak
+        };
+        let weak_hasher = create_password_hasher_with(&weak).unwrap();
+        let strong_hasher = create_password_hasher_with(&strong).unwrap();
+
+        let weak_hash = weak_hasher.hash_password("correct horse").unwrap();
+        let strong_hash = strong_hasher.hash_password("correct horse").unwrap();
+
+        // Old hashes still verify after the costs are raised
+        assert!(strong_hasher
+            .verify_password(&weak_hash, "correct horse")
+            .is_ok());
+        assert!(strong_hasher.verify_password(&weak_hash, "wrong").is_err());
+
+        assert!(strong_hasher.needs_rehash(&weak_hash));
+        assert!(!strong_hasher.needs_rehash(&strong_hash));
+        assert!(!weak_hasher.needs_rehash(&strong_hash));
+        assert!(!strong_hasher.needs_rehash("not a phc string"));
+
+        assert!(create_password_hasher_with(&Argon2Config {
+            memory_kib: 1,
+            ..weak
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_verify_totp_code_allows_one_step_of_drift() {
+        // ---
+        let secret = generate_totp_secret();
+        let now = 1_700_000_000;
+        let code = totp_code_at(&secret, now).unwrap();
+
+        assert!(verify_totp_code(&secret, &code, now));
+        assert!(verify_totp_code(&secret, &code, now + TOTP_STEP_SECS));
+        assert!(!verify_totp_code(&secret, &code, now + 3 * TOTP_STEP_SECS));
+        assert!(!verify_totp_code(&secret, "12345", now));
+
+        let step = now / TOTP_STEP_SECS;
+        assert_eq!(matching_totp_step(&secret, &code, now), Some(step));
+        assert_eq!(
+            matching_totp_step(&secret, &code, now + TOTP_STEP_SECS),
+            Some(step)
+        );
+    }
+}
@@ -1,12 +1,12 @@
 // src/bin/cli/cli.rs
 //! CLI argument definitions for `cr8s-cli` using `clap`.
 //!
-//! - Defines top-level commands for user, role, and schema management
+//! - Defines top-level commands for user, role, API token, audit log, and schema management
 //! - Supports comma-delimited role parsing and flexible case-insensitive input
 //! - Includes full unit test coverage of CLI argument parsing
 
 use clap::{Parser, Subcommand};
-use cr8s::domain::RoleCode;
+use cr8s::domain::{ApiScope, AuditAction, AuditEntity, Permission, RoleCode};
 
 // ---
 
@@ -44,14 +44,14 @@ pub enum Commands {
         roles: Vec<CliRoleCode>,
     },
 
-    /// Delete a user by numeric ID.
+    /// Delete a disabled user by numeric ID.
     DeleteUser {
         /// User ID to delete.
         #[arg(allow_hyphen_values = true)]
         user_id: i32,
     },
 
-    /// Delete a user by username.
+    /// Delete a disabled user by username.
     DeleteUserByName {
         /// Username to delete.
         username: String,
@@ -60,6 +60,97 @@ pub enum Commands {
     /// List all users with their assigned roles.
     ListUsers,
 
+    /// Set a new password for a user and revoke all of their sessions.
+    ResetPassword {
+        /// Username whose password is reset.
+        #[arg(short, long)]
+        username: String,
+
+        /// New password for the user.
+        #[arg(short, long)]
+        password: String,
+    },
+
+    /// Clear failed-login counters and lockouts for a user.
+    UnlockUser {
+        /// Username to unlock.
+        username: String,
+
+        /// Also clear the lockout of this client IP address.
+        #[arg(long)]
+        ip: Option<String>,
+    },
+
+    /// Disable a user: they can no longer log in and their sessions are revoked.
+    DisableUser {
+        /// Username to disable.
+        username: String,
+    },
+
+    /// Re-enable a disabled user.
+    EnableUser {
+        /// Username to enable.
+        username: String,
+    },
+
+    /// Create, list, or revoke a user's scoped API tokens.
+    Token {
+        #[command(subcommand)]
+        action: TokenCommands,
+    },
+
+    /// List, create, update, or delete roles and the permissions they grant.
+    Role {
+        #[command(subcommand)]
+        action: RoleCommands,
+    },
+
+    /// Give a user more roles; their live sessions pick up the change.
+    GrantRole {
+        /// Username receiving the roles.
+        #[arg(short, long)]
+        username: String,
+
+        /// Roles to grant (comma-separated), e.g. Editor,Publisher.
+        #[arg(short, long, value_delimiter = ',', required = true, value_parser = parse_role_code)]
+        roles: Vec<RoleCode>,
+    },
+
+    /// Take roles away from a user; their live sessions pick up the change.
+    RevokeRole {
+        /// Username losing the roles.
+        #[arg(short, long)]
+        username: String,
+
+        /// Roles to revoke (comma-separated), e.g. Editor,Publisher.
+        #[arg(short, long, value_delimiter = ',', required = true, value_parser = parse_role_code)]
+        roles: Vec<RoleCode>,
+    },
+
+    /// Link a user to the author profile they publish as, so they can edit its crates.
+    LinkAuthor {
+        /// Username to link.
+        #[arg(short, long)]
+        username: String,
+
+        /// Id of the author profile; it must not be linked to another user.
+        #[arg(short, long)]
+        author_id: i32,
+    },
+
+    /// Remove the author profile link of a user.
+    UnlinkAuthor {
+        /// Username to unlink.
+        #[arg(short, long)]
+        username: String,
+    },
+
+    /// Read the audit log of crate, author and user changes.
+    Audit {
+        #[command(subcommand)]
+        action: AuditCommands,
+    },
+
     /// Check whether a user exists.
     UserExists {
         /// Username to check for existence.
@@ -81,6 +172,131 @@ pub enum Commands {
     LoadSchema,
 }
 
+/// Subcommands of `cr8s-cli token`.
+#[derive(Subcommand)]
+pub enum TokenCommands {
+    // ---
+    /// Create an API token and print its secret (shown only once).
+    Create {
+        /// Username that owns the token.
+        #[arg(short, long)]
+        username: String,
+
+        /// Name to recognize the token by, e.g. the pipeline using it.
+        #[arg(short, long)]
+        name: String,
+
+        /// Scopes to grant (comma-separated).
+        /// Valid scopes: crates:read, crates:write, authors:read, authors:write.
+        #[arg(short, long, value_delimiter = ',', required = true)]
+        scopes: Vec<ApiScope>,
+
+        /// Days until the token expires; omit for a token that never expires.
+        #[arg(long)]
+        expires_days: Option<u32>,
+    },
+
+    /// List a user's API tokens.
+    List {
+        /// Username whose tokens are listed.
+        #[arg(short, long)]
+        username: String,
+    },
+
+    /// Revoke one of a user's API tokens by ID.
+    Revoke {
+        /// Username that owns the token.
+        #[arg(short, long)]
+        username: String,
+
+        /// Token ID to revoke.
+        id: i32,
+    },
+}
+
+/// Subcommands of `cr8s-cli audit`.
+#[derive(Subcommand)]
+pub enum AuditCommands {
+    // ---
+    /// Print the most recent audit events, oldest first.
+    Tail {
+        /// Number of events to print (at most 500).
+        #[arg(short = 'n', long, default_value = "20")]
+        lines: i64,
+
+        /// Only events by this user ID.
+        #[arg(long)]
+        actor: Option<i32>,
+
+        /// Only this action: create, update, delete, yank or unyank.
+        #[arg(long)]
+        action: Option<AuditAction>,
+
+        /// Only this entity type: crate, author or user.
+        #[arg(long)]
+        entity_type: Option<AuditEntity>,
+
+        /// Only events about this entity ID.
+        #[arg(long)]
+        entity_id: Option<i32>,
+
+        /// Keep printing new events as they are recorded.
+        #[arg(short, long)]
+        follow: bool,
+    },
+}
+
+/// Subcommands of `cr8s-cli role`.
+#[derive(Subcommand)]
+pub enum RoleCommands {
+    // ---
+    /// List all roles with their permissions.
+    List,
+
+    /// Create a role.
+    Create {
+        /// Unique role code, e.g. Publisher.
+        #[arg(short, long, value_parser = parse_role_code)]
+        code: RoleCode,
+
+        /// Display name of the role.
+        #[arg(short, long)]
+        name: String,
+
+        /// Permissions to grant (comma-separated), e.g. crate:read,crate:write.
+        #[arg(short, long, value_delimiter = ',')]
+        permissions: Vec<Permission>,
+    },
+
+    /// Rename a role and/or replace its permissions.
+    Update {
+        /// Code of the role to change.
+        #[arg(value_parser = parse_role_code)]
+        code: RoleCode,
+
+        /// New display name.
+        #[arg(short, long)]
+        name: Option<String>,
+
+        /// New permissions (comma-separated), replacing the current ones.
+        #[arg(short, long, value_delimiter = ',')]
+        permissions: Option<Vec<Permission>>,
+    },
+
+    /// Delete a role; the seeded Admin, Editor and Viewer roles cannot be deleted.
+    Delete {
+        /// Code of the role to delete.
+        #[arg(value_parser = parse_role_code)]
+        code: RoleCode,
+    },
+}
+
+fn parse_role_code(s: &str) -> Result<RoleCode, String> {
+    s.parse().map_err(|_| {
+        format!("Invalid role code: '{s}'. Use letters, digits, '_' or '-', starting with a letter")
+    })
+}
+
 // ---
 
 /// Wrapper enum for parsing role codes from CLI input.
@@ -299,6 +515,368 @@ mod tests {
 
     // ---
 
+    #[test]
+    fn test_reset_password() -> Result<()> {
+        // ---
+
+        let args = Cli::parse_from([
+            "cr8s-cli",
+            "reset-password",
+            "--username",
+            "alice",
+            "--password",
+            "n3w-secret",
+        ]);
+
+        match args.command {
+            Commands::ResetPassword { username, password } => {
+                ensure!(username == "alice");
+                ensure!(password == "n3w-secret");
+            }
+            _ => anyhow::bail!("Expected ResetPassword command"),
+        }
+
+        Ok(())
+    }
+
+    // ---
+
+    #[test]
+    fn test_unlock_user() -> Result<()> {
+        // ---
+
+        let args = Cli::parse_from(["cr8s-cli", "unlock-user", "alice"]);
+
+        match args.command {
+            Commands::UnlockUser { username, ip } => {
+                ensure!(username == "alice");
+                ensure!(ip.is_none());
+            }
+            _ => anyhow::bail!("Expected UnlockUser command"),
+        }
+
+        let args = Cli::parse_from(["cr8s-cli", "unlock-user", "alice", "--ip", "10.0.0.1"]);
+
+        match args.command {
+            Commands::UnlockUser { username, ip } => {
+                ensure!(username == "alice");
+                ensure!(ip.as_deref() == Some("10.0.0.1"));
+            }
+            _ => anyhow::bail!("Expected UnlockUser command"),
+        }
+
+        Ok(())
+    }
+
+    // ---
+
+    #[test]
+    fn test_disable_and_enable_user() -> Result<()> {
+        // ---
+
+        let args = Cli::parse_from(["cr8s-cli", "disable-user", "alice"]);
+
+        match args.command {
+            Commands::DisableUser { username } => ensure!(username == "alice"),
+            _ => anyhow::bail!("Expected DisableUser command"),
+        }
+
+        let args = Cli::parse_from(["cr8s-cli", "enable-user", "alice"]);
+
+        match args.command {
+            Commands::EnableUser { username } => ensure!(username == "alice"),
+            _ => anyhow::bail!("Expected EnableUser command"),
+        }
+
+        ensure!(Cli::try_parse_from(["cr8s-cli", "disable-user"]).is_err());
+        Ok(())
+    }
+
+    // ---
+
+    #[test]
+    fn test_token_create() -> Result<()> {
+        // ---
+
+        let args = Cli::parse_from([
+            "cr8s-cli",
+            "token",
+            "create",
+            "--username",
+            "ci@example.com",
+            "--name",
+            "pipeline",
+            "--scopes",
+            "crates:read,crates:write",
+            "--expires-days",
+            "30",
+        ]);
+
+        match args.command {
+            Commands::Token {
+                action:
+                    TokenCommands::Create {
+                        username,
+                        name,
+                        scopes,
+                        expires_days,
+                    },
+            } => {
+                ensure!(username == "ci@example.com");
+                ensure!(name == "pipeline");
+                ensure!(scopes == vec![ApiScope::CratesRead, ApiScope::CratesWrite]);
+                ensure!(expires_days == Some(30));
+            }
+            _ => anyhow::bail!("Expected Token Create command"),
+        }
+
+        Ok(())
+    }
+
+    // ---
+
+    #[test]
+    fn test_token_list_and_revoke() -> Result<()> {
+        // ---
+
+        let args = Cli::parse_from(["cr8s-cli", "token", "list", "-u", "ci@example.com"]);
+
+        match args.command {
+            Commands::Token {
+                action: TokenCommands::List { username },
+            } => ensure!(username == "ci@example.com"),
+            _ => anyhow::bail!("Expected Token List command"),
+        }
+
+        let args = Cli::parse_from(["cr8s-cli", "token", "revoke", "-u", "ci@example.com", "7"]);
+
+        match args.command {
+            Commands::Token {
+                action: TokenCommands::Revoke { username, id },
+            } => {
+                ensure!(username == "ci@example.com");
+                ensure!(id == 7);
+            }
+            _ => anyhow::bail!("Expected Token Revoke command"),
+        }
+
+        Ok(())
+    }
+
+    // ---
+
+    #[test]
+    fn test_token_invalid_scope() {
+        // ---
+
+        let result = Cli::try_parse_from([
+            "cr8s-cli",
+            "token",
+            "create",
+            "-u",
+            "ci@example.com",
+            "-n",
+            "pipeline",
+            "-s",
+            "crates:delete",
+        ]);
+        assert!(result.is_err(), "Unknown scopes must be rejected");
+    }
+
+    // ---
+
+    #[test]
+    fn test_audit_tail() -> Result<()> {
+        // ---
+
+        let args = Cli::parse_from([
+            "cr8s-cli",
+            "audit",
+            "tail",
+            "-n",
+            "5",
+            "--action",
+            "delete",
+            "--entity-type",
+            "crate",
+            "-f",
+        ]);
+
+        match args.command {
+            Commands::Audit {
+                action:
+                    AuditCommands::Tail {
+                        lines,
+                        actor,
+                        action,
+                        entity_type,
+                        entity_id,
+                        follow,
+                    },
+            } => {
+                ensure!(lines == 5);
+                ensure!(actor.is_none() && entity_id.is_none());
+                ensure!(action == Some(AuditAction::Delete));
+                ensure!(entity_type == Some(AuditEntity::Crate));
+                ensure!(follow);
+            }
+            _ => anyhow::bail!("Expected Audit Tail command"),
+        }
+
+        let result = Cli::try_parse_from(["cr8s-cli", "audit", "tail", "--entity-type", "role"]);
+        ensure!(result.is_err(), "Unknown entity types must be rejected");
+
+        Ok(())
+    }
+
+    // ---
+
+    #[test]
+    fn test_role_create_and_update() -> Result<()> {
+        // ---
+
+        let args = Cli::parse_from([
+            "cr8s-cli",
+            "role",
+            "create",
+            "--code",
+            "Publisher",
+            "--name",
+            "Crate publisher",
+            "--permissions",
+            "crate:read,crate:write",
+        ]);
+
+        match args.command {
+            Commands::Role {
+                action:
+                    RoleCommands::Create {
+                        code,
+                        name,
+                        permissions,
+                    },
+            } => {
+                ensure!(code == RoleCode::Custom("Publisher".into()));
+                ensure!(name == "Crate publisher");
+                ensure!(permissions == vec![Permission::CrateRead, Permission::CrateWrite]);
+            }
+            _ => anyhow::bail!("Expected Role Create command"),
+        }
+
+        let args = Cli::parse_from(["cr8s-cli", "role", "update", "Editor", "--name", "Writer"]);
+
+        match args.command {
+            Commands::Role {
+                action:
+                    RoleCommands::Update {
+                        code,
+                        name,
+                        permissions,
+                    },
+            } => {
+                ensure!(code == RoleCode::Editor);
+                ensure!(name.as_deref() == Some("Writer"));
+                ensure!(permissions.is_none());
+            }
+            _ => anyhow::bail!("Expected Role Update command"),
+        }
+
+        Ok(())
+    }
+
+    // ---
+
+    #[test]
+    fn test_role_invalid_code_and_permission() {
+        // ---
+
+        let result = Cli::try_parse_from(["cr8s-cli", "role", "delete", "not a role"]);
+        assert!(result.is_err());
+
+        let result = Cli::try_parse_from([
+            "cr8s-cli",
+            "role",
+            "create",
+            "--code",
+            "Publisher",
+            "--name",
+            "Publisher",
+            "--permissions",
+            "crate:publish",
+        ]);
+        assert!(result.is_err());
+    }
+
+    // ---
+
+    #[test]
+    fn test_grant_and_revoke_role() -> Result<()> {
+        // ---
+
+        let cli = Cli::try_parse_from([
+            "cr8s-cli",
+            "grant-role",
+            "--username",
+            "alice",
+            "--roles",
+            "Editor,Publisher",
+        ])?;
+
+        match cli.command {
+            Commands::GrantRole { username, roles } => {
+                assert_eq!(username, "alice");
+                assert_eq!(
+                    roles,
+                    vec![RoleCode::Editor, RoleCode::Custom("Publisher".into())]
+                );
+            }
+            _ => panic!("Expected GrantRole command"),
+        }
+
+        let cli = Cli::try_parse_from(["cr8s-cli", "revoke-role", "-u", "alice", "-r", "Viewer"])?;
+
+        match cli.command {
+            Commands::RevokeRole { username, roles } => {
+                assert_eq!(username, "alice");
+                assert_eq!(roles, vec![RoleCode::Viewer]);
+            }
+            _ => panic!("Expected RevokeRole command"),
+        }
+
+        let result = Cli::try_parse_from(["cr8s-cli", "grant-role", "--username", "alice"]);
+        assert!(result.is_err(), "roles are required");
+        Ok(())
+    }
+
+    #[test]
+    fn test_link_and_unlink_author() -> Result<()> {
+        // ---
+
+        let cli = Cli::try_parse_from(["cr8s-cli", "link-author", "-u", "alice", "-a", "42"])?;
+
+        match cli.command {
+            Commands::LinkAuthor {
+                username,
+                author_id,
+            } => {
+                assert_eq!(username, "alice");
+                assert_eq!(author_id, 42);
+            }
+            _ => panic!("Expected LinkAuthor command"),
+        }
+
+        let cli = Cli::try_parse_from(["cr8s-cli", "unlink-author", "--username", "alice"])?;
+
+        match cli.command {
+            Commands::UnlinkAuthor { username } => assert_eq!(username, "alice"),
+            _ => panic!("Expected UnlinkAuthor command"),
+        }
+
+        let result = Cli::try_parse_from(["cr8s-cli", "link-author", "-u", "alice", "-a", "x"]);
+        assert!(result.is_err(), "author id must be a number");
+        Ok(())
+    }
+
     #[test]
     fn test_user_exists() -> Result<()> {
         // ---
@@ -3,25 +3,58 @@
 //!
 //! Each function corresponds to a `Commands` variant defined in `cli.rs`.
 //! Logic here is domain-aware and interacts with repositories, mailers, and password hashing.
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use cr8s::domain::{
     //
+    assign_roles,
+    create_api_token_repo,
     create_app_user_repo,
+    create_audit_log_repo,
+    create_author_repo,
+    create_cache_context,
     create_crate_repo,
     create_mailer,
     create_password_hasher,
+    create_role,
+    create_role_code_repo,
+    delete_role,
+    issue_api_token,
+    link_author,
+    record_audit_event,
+    set_account_disabled,
+    set_password,
+    unlock_login,
+    update_role,
+    ApiScope,
+    AppUser,
+    AppUserTableTraitPtr,
+    AuditEntity,
+    AuditEvent,
+    AuditFilter,
+    NewApiToken,
+    NewAuditEvent,
+    NewRole,
     NewUser,
+    PasswordPolicy,
+    Permission,
+    RoleAssignment,
     RoleCode,
+    RoleUpdate,
+    UserSummary,
+    MAX_AUDIT_LIMIT,
 };
+use serde_json::Value;
+use std::time::Duration;
 
 // ---
 
 /// Creates a new user and assigns one or more roles.
 ///
-/// Hashes the provided password using the domain-level password hasher
-/// and persists the user via `AppUserRepo`.
+/// Checks the password against the `PasswordPolicy`, hashes it using the
+/// domain-level password hasher and persists the user via `AppUserRepo`.
 ///
-/// Prints confirmation and assigned roles on success.
+/// Prints confirmation and assigned roles on success. Like every user change made
+/// here, the creation is recorded in the audit log without an actor.
 pub async fn create_user(
     username: String,
     password: String,
@@ -38,6 +71,10 @@ pub async fn create_user(
 
     let user_repo = create_app_user_repo();
 
+    PasswordPolicy::from_env()
+        .check(Some(&username), &password)
+        .with_context(|| format!("Password rejected for: {username}"))?;
+
     let password_hash = create_password_hasher()?
         .hash_password(&password)
         .map_err(|e| anyhow!("Password hashing failed: {e}"))?;
@@ -57,6 +94,9 @@ pub async fn create_user(
     tracing::info!("User created: {:?}", user);
     println!("✅ Created user: {} (ID: {})", user.username, user.id);
 
+    let created = UserSummary::from((user.clone(), role_codes.clone()));
+    audit_user_change(NewAuditEvent::created(AuditEntity::User, user.id, &created)).await;
+
     // ---
 
     if !role_codes.is_empty() {
@@ -69,44 +109,572 @@ pub async fn create_user(
 
 // ---
 
-/// Deletes a user from the database by numeric ID.
+/// Deletes a disabled user from the database by numeric ID.
 ///
-/// Succeeds silently if no user with the given ID exists.  Prints
-/// confirmation if deletion is attempted.
+/// Succeeds silently if no user with the given ID exists, and fails if the
+/// user has not been disabled first.  Prints confirmation if deletion is
+/// attempted.
 pub async fn delete_user_by_id(user_id: i32) -> Result<()> {
     // ---
 
     let user_repo = create_app_user_repo();
+    let existing = match user_repo.find(user_id).await {
+        Ok(user) => Some(user_snapshot(&user_repo, user).await),
+        Err(_) => None,
+    };
+    if let Some(existing) = &existing {
+        ensure_disabled(existing)?;
+    }
+
     user_repo
         .delete_by_id(user_id)
         .await
         .with_context(|| format!("Failed to delete user with ID: {}", user_id))?;
 
+    if let Some(existing) = existing {
+        audit_user_change(NewAuditEvent::deleted(
+            AuditEntity::User,
+            user_id,
+            Some(&existing),
+        ))
+        .await;
+    }
+
     println!("✅ Deleted user with ID: {}", user_id);
     Ok(())
 }
 
 // ---
 
-/// Deletes a user from the database by username
+/// Deletes a disabled user from the database by username
 ///
-/// Succeeds silently if no user with the given name exists.  Prints
-/// confirmation if deletion is attempted.
+/// Succeeds silently if no user with the given name exists, and fails if the
+/// user has not been disabled first.  Prints confirmation if deletion is
+/// attempted.
 pub async fn delete_user_by_username(name: &str) -> Result<()> {
     // ---
 
     let user_repo = create_app_user_repo();
+    let existing = match user_repo.find_by_username(name).await {
+        Ok(user) => Some(user_snapshot(&user_repo, user).await),
+        Err(_) => None,
+    };
+    if let Some(existing) = &existing {
+        ensure_disabled(existing)?;
+    }
+
     user_repo
         .delete_by_username(name)
         .await
         .with_context(|| format!("Failed to delete user: {}", name))?;
 
+    if let Some(existing) = existing {
+        let event = NewAuditEvent::deleted(AuditEntity::User, existing.id, Some(&existing));
+        audit_user_change(event).await;
+    }
+
     println!("✅ Deleted user: {}", name);
     Ok(())
 }
 
 // ---
 
+/// Sets a new password for an existing user and revokes all of their sessions.
+///
+/// Fails if no user with the given name exists.
+pub async fn reset_password(username: &str, password: &str) -> Result<()> {
+    // ---
+
+    let user_repo = create_app_user_repo();
+    let user = user_repo
+        .find_by_username(username)
+        .await
+        .with_context(|| format!("Failed to find user: {username}"))?;
+
+    let user_id = user.id;
+    let before = user_snapshot(&user_repo, user).await;
+
+    set_password(user_repo, user_id, password)
+        .await
+        .with_context(|| format!("Failed to reset password for: {username}"))?;
+
+    // The hash is never audited; mark the change instead
+    let mut after = serde_json::to_value(&before)?;
+    after["password_changed"] = Value::Bool(true);
+    audit_user_change(NewAuditEvent::updated(
+        AuditEntity::User,
+        user_id,
+        Some(&before),
+        &after,
+    ))
+    .await;
+
+    let revoked = create_cache_context()
+        .clear_all_session_tokens(user_id)
+        .await
+        .with_context(|| format!("Failed to revoke sessions for: {username}"))?;
+
+    println!("✅ Reset password for user: {username} (revoked {revoked} session(s))");
+    Ok(())
+}
+
+// ---
+
+/// Clears failed-login counters and lockouts for a username, and optionally for a
+/// client IP address.
+///
+/// Succeeds even if the user does not exist or was not locked out.
+pub async fn unlock_user(username: &str, ip: Option<&str>) -> Result<()> {
+    // ---
+
+    unlock_login(&create_cache_context(), username, ip)
+        .await
+        .with_context(|| format!("Failed to unlock user: {username}"))?;
+
+    match ip {
+        Some(ip) => println!("✅ Unlocked user: {username} and IP: {ip}"),
+        None => println!("✅ Unlocked user: {username}"),
+    }
+    Ok(())
+}
+
+// ---
+
+/// Disables or re-enables a user; disabling also revokes all of their sessions.
+///
+/// The last active Admin cannot be disabled.
+pub async fn user_set_disabled(username: &str, disabled: bool) -> Result<()> {
+    // ---
+
+    let user_repo = create_app_user_repo();
+    let user = user_repo
+        .find_by_username(username)
+        .await
+        .with_context(|| format!("Failed to find user: {username}"))?;
+
+    let user_id = user.id;
+    let before = user_snapshot(&user_repo, user).await;
+
+    let after = set_account_disabled(&user_repo, &create_cache_context(), None, user_id, disabled)
+        .await
+        .with_context(|| format!("Failed to change account state of: {username}"))?;
+
+    audit_user_change(NewAuditEvent::updated(
+        AuditEntity::User,
+        user_id,
+        Some(&before),
+        &after,
+    ))
+    .await;
+
+    if disabled {
+        println!("✅ Disabled user: {username}");
+    } else {
+        println!("✅ Enabled user: {username}");
+    }
+    Ok(())
+}
+
+// ---
+
+/// Creates a scoped API token for a user and prints its secret.
+///
+/// The secret is shown only here; it cannot be recovered later.
+pub async fn token_create(
+    username: &str,
+    name: String,
+    scopes: Vec<ApiScope>,
+    expires_days: Option<u32>,
+) -> Result<()> {
+    // ---
+
+    let user = create_app_user_repo()
+        .find_by_username(username)
+        .await
+        .with_context(|| format!("Failed to find user: {username}"))?;
+
+    let new_token = NewApiToken {
+        name,
+        scopes,
+        expires_in_days: expires_days,
+    };
+
+    let (token, secret) = issue_api_token(create_api_token_repo(), user.id, new_token)
+        .await
+        .with_context(|| format!("Failed to create API token for: {username}"))?;
+
+    println!("✅ Created API token '{}' (ID: {})", token.name, token.id);
+    println!("🔑 {secret}");
+    println!("⚠️  Store this token now, it will not be shown again");
+    Ok(())
+}
+
+// ---
+
+/// Returns a formatted table of a user's API tokens (never their secrets).
+pub async fn token_list_formatted(username: &str) -> Result<Vec<String>> {
+    // ---
+
+    let user = create_app_user_repo()
+        .find_by_username(username)
+        .await
+        .with_context(|| format!("Failed to find user: {username}"))?;
+
+    let tokens = create_api_token_repo()
+        .find_by_user(user.id)
+        .await
+        .with_context(|| format!("Failed to list API tokens for: {username}"))?;
+
+    let format_time = |time: Option<chrono::NaiveDateTime>| {
+        time.map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|| "-".into())
+    };
+
+    let mut lines = Vec::with_capacity(2 + tokens.len());
+    lines.push(format!(
+        "{:<6} {:<20} {:<17} {:<17} {}",
+        "ID", "Name", "Expires", "Last Used", "Scopes"
+    ));
+    lines.push("-".repeat(80));
+
+    for token in tokens {
+        // ---
+
+        let scopes = token
+            .scopes
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        lines.push(format!(
+            "{:<6} {:<20} {:<17} {:<17} {}",
+            token.id,
+            token.name,
+            format_time(token.expires_at),
+            format_time(token.last_used_at),
+            scopes
+        ));
+    }
+
+    Ok(lines)
+}
+
+// ---
+
+/// Revokes one of a user's API tokens.
+///
+/// Fails if the user does not exist or does not own a token with this ID.
+pub async fn token_revoke(username: &str, id: i32) -> Result<()> {
+    // ---
+
+    let user = create_app_user_repo()
+        .find_by_username(username)
+        .await
+        .with_context(|| format!("Failed to find user: {username}"))?;
+
+    let found = create_api_token_repo()
+        .revoke(user.id, id)
+        .await
+        .with_context(|| format!("Failed to revoke API token {id} for: {username}"))?;
+
+    if !found {
+        return Err(anyhow!("No API token with ID {id} for user: {username}"));
+    }
+
+    println!("✅ Revoked API token {id} for user: {username}");
+    Ok(())
+}
+
+// ---
+
+/// Returns a formatted table of all roles and the permissions they grant.
+pub async fn role_list_formatted() -> Result<Vec<String>> {
+    // ---
+
+    let roles = create_role_code_repo()?
+        .find_roles()
+        .await
+        .context("Failed to list roles")?;
+
+    let mut lines = Vec::with_capacity(2 + roles.len());
+    lines.push(format!("{:<16} {:<24} {}", "Code", "Name", "Permissions"));
+    lines.push("-".repeat(80));
+
+    for role in roles {
+        // ---
+
+        let permissions = role
+            .permissions
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        lines.push(format!(
+            "{:<16} {:<24} {}",
+            role.code.to_string(),
+            role.name,
+            permissions
+        ));
+    }
+
+    Ok(lines)
+}
+
+// ---
+
+/// Creates a role granting `permissions`.
+pub async fn role_create(code: RoleCode, name: String, permissions: Vec<Permission>) -> Result<()> {
+    // ---
+
+    let new_role = NewRole {
+        code: code.clone(),
+        name,
+        permissions,
+    };
+
+    let role = create_role(&create_role_code_repo()?, new_role)
+        .await
+        .with_context(|| format!("Failed to create role: {code}"))?;
+
+    println!("✅ Created role {} (ID: {})", role.code, role.id);
+    Ok(())
+}
+
+// ---
+
+/// Renames a role and/or replaces its permissions.
+pub async fn role_update(
+    code: RoleCode,
+    name: Option<String>,
+    permissions: Option<Vec<Permission>>,
+) -> Result<()> {
+    // ---
+
+    let update = RoleUpdate { name, permissions };
+
+    let role = update_role(&create_role_code_repo()?, &code, update)
+        .await
+        .with_context(|| format!("Failed to update role: {code}"))?;
+
+    println!(
+        "✅ Updated role {} ({}), {} permission(s)",
+        role.code,
+        role.name,
+        role.permissions.len()
+    );
+    Ok(())
+}
+
+// ---
+
+/// Deletes a runtime-created role, removing it from every user holding it.
+pub async fn role_delete(code: RoleCode) -> Result<()> {
+    // ---
+
+    delete_role(&create_role_code_repo()?, &code)
+        .await
+        .with_context(|| format!("Failed to delete role: {code}"))?;
+
+    println!("✅ Deleted role {code}");
+    Ok(())
+}
+
+/// Grants or revokes roles of a user and updates the roles recorded on the user's
+/// live sessions.
+pub async fn user_roles_assign(username: &str, assignment: RoleAssignment) -> Result<()> {
+    // ---
+
+    let user_repo = create_app_user_repo();
+    let user = user_repo
+        .find_by_username(username)
+        .await
+        .with_context(|| format!("Failed to find user: {username}"))?;
+
+    let before = user_snapshot(&user_repo, user.clone()).await;
+
+    let assigned = assign_roles(
+        &user_repo,
+        &create_role_code_repo()?,
+        &create_cache_context(),
+        &user,
+        assignment,
+    )
+    .await
+    .with_context(|| format!("Failed to change roles of: {username}"))?;
+
+    let after = UserSummary::from((user, assigned.roles.clone()));
+    audit_user_change(NewAuditEvent::updated(
+        AuditEntity::User,
+        after.id,
+        Some(&before),
+        &after,
+    ))
+    .await;
+
+    let roles: Vec<String> = assigned.roles.iter().map(RoleCode::to_string).collect();
+    println!(
+        "✅ {username} now has roles: [{}] ({} session(s) updated)",
+        roles.join(", "),
+        assigned.sessions_updated
+    );
+    Ok(())
+}
+
+/// Links a user to an author profile, or removes the link with `None`.
+///
+/// Unlike the self-service `PUT /me/author` route, no token is mailed to the author
+/// to confirm the link.
+pub async fn user_author_link(username: &str, author_id: Option<i32>) -> Result<()> {
+    // ---
+
+    let user_repo = create_app_user_repo();
+    let user = user_repo
+        .find_by_username(username)
+        .await
+        .with_context(|| format!("Failed to find user: {username}"))?;
+
+    let before = user_snapshot(&user_repo, user.clone()).await;
+
+    let linked = link_author(&user_repo, &create_author_repo(), &user, author_id)
+        .await
+        .with_context(|| format!("Failed to change author link of: {username}"))?;
+
+    let after = UserSummary {
+        author_id: linked.author_id,
+        ..before.clone()
+    };
+    audit_user_change(NewAuditEvent::updated(
+        AuditEntity::User,
+        user.id,
+        Some(&before),
+        &after,
+    ))
+    .await;
+
+    match linked.author_id {
+        Some(author_id) => println!("✅ {username} is linked to author {author_id}"),
+        None => println!("✅ {username} is no longer linked to an author"),
+    }
+    Ok(())
+}
+
+// ---
+
+/// Refuses to delete an active account, like `DELETE /users/<id>` does.
+fn ensure_disabled(user: &UserSummary) -> Result<()> {
+    // ---
+
+    if user.disabled_at.is_none() {
+        bail!(
+            "User {} is active; run `cr8s-cli disable-user {}` before deleting it",
+            user.username,
+            user.username
+        );
+    }
+    Ok(())
+}
+
+/// Snapshot of a user for the audit log: `UserSummary` leaves out the password hash.
+/// Roles that cannot be read are logged and left empty.
+async fn user_snapshot(user_repo: &AppUserTableTraitPtr, user: AppUser) -> UserSummary {
+    // ---
+
+    let roles = user_repo
+        .find_roles_by_user(&user)
+        .await
+        .unwrap_or_else(|err| {
+            tracing::warn!(
+                "Failed to read roles of user {} for audit: {err:#}",
+                user.id
+            );
+            vec![]
+        });
+    UserSummary::from((user, roles))
+}
+
+/// Records a user change in the audit log; changes made with the CLI have no actor.
+async fn audit_user_change(event: NewAuditEvent) {
+    record_audit_event(&create_audit_log_repo(), event).await
+}
+
+// ---
+
+/// Prints the most recent audit events matching `filter`, oldest first, and with
+/// `follow` keeps polling for new ones until interrupted.
+pub async fn audit_tail(mut filter: AuditFilter, follow: bool) -> Result<()> {
+    // ---
+
+    const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+    let audit_log = create_audit_log_repo();
+    let mut events = audit_log
+        .find(&filter)
+        .await
+        .context("Failed to read audit log")?;
+
+    loop {
+        // ---
+
+        // Queries return newest first; print in the order things happened
+        events.reverse();
+        for event in &events {
+            println!("{}", format_audit_event(event));
+        }
+
+        if !follow {
+            return Ok(());
+        }
+
+        if let Some(last) = events.last() {
+            filter.after_id = Some(last.id);
+        }
+        filter.limit = Some(MAX_AUDIT_LIMIT);
+
+        tokio::time::sleep(POLL_INTERVAL).await;
+        events = audit_log
+            .find(&filter)
+            .await
+            .context("Failed to read audit log")?;
+    }
+}
+
+/// One line per event: id, time, actor, action, entity and the fields an update changed.
+fn format_audit_event(event: &AuditEvent) -> String {
+    // ---
+
+    let actor = event
+        .actor_user_id
+        .map(|id| format!("user:{id}"))
+        .unwrap_or_else(|| "cli".into());
+
+    let mut line = format!(
+        "{:<6} {} {:<10} {:<6} {} {}",
+        event.id,
+        event.created_at.format("%Y-%m-%d %H:%M:%S"),
+        actor,
+        event.action,
+        event.entity_type,
+        event.entity_id
+    );
+
+    if let (Some(Value::Object(before)), Some(Value::Object(after))) = (&event.before, &event.after)
+    {
+        let changed: Vec<&str> = after
+            .iter()
+            .filter(|(field, value)| before.get(*field) != Some(value))
+            .map(|(field, _)| field.as_str())
+            .collect();
+        if !changed.is_empty() {
+            line.push_str(&format!(" (changed: {})", changed.join(", ")));
+        }
+    }
+    line
+}
+
+// ---
+
 /// Fetches all users along with their assigned roles and returns a
 /// formatted table as a vector of strings.
 ///
@@ -161,12 +729,15 @@ pub async fn list_users_formatted() -> Result<Vec<String>> {
             .collect::<Vec<_>>()
             .join(", ");
 
+        let state = if user.is_active() { "" } else { " (disabled)" };
+
         lines.push(format!(
-            "{:<width$} {:<6} {:<20} {}",
+            "{:<width$} {:<6} {:<20} {}{}",
             user.username,
             user.id,
             user.created_at.format("%Y-%m-%d %H:%M"),
             role_labels,
+            state,
             width = username_col_width
         ));
     }
@@ -1,7 +1,7 @@
 // src/bin/cli/main.rs
 //! Entry point for the cr8s CLI binary.
 //!
-//! Provides developer/admin tooling for managing users, schema, and email digests.
+//! Provides developer/admin tooling for managing users, roles, schema, and email digests.
 //! Routes subcommands to functions in `commands.rs` after initializing infrastructure.
 //!
 //! See also:
@@ -19,15 +19,28 @@ mod commands;
 // Internal-only imports (no pub use needed - binary has no external consumers)
 use anyhow::{Context, Result};
 use clap::Parser;
-use cli::{Cli, Commands};
+use cli::{AuditCommands, Cli, Commands, RoleCommands, TokenCommands};
 use commands::{
     //
+    audit_tail,
     create_user,
     delete_user_by_id,
     delete_user_by_username,
     digest_send,
     list_users_formatted,
+    reset_password,
+    role_create,
+    role_delete,
+    role_list_formatted,
+    role_update,
+    token_create,
+    token_list_formatted,
+    token_revoke,
+    unlock_user,
+    user_author_link,
     user_exists,
+    user_roles_assign,
+    user_set_disabled,
 };
 use cr8s::domain::{
     //
@@ -35,6 +48,8 @@ use cr8s::domain::{
     init_database_with_retry_from_env,
     // -- Call into dab module to initialize cr8s schema and default roles
     load_schema_from_sql_file,
+    AuditFilter,
+    RoleAssignment,
 };
 
 // ---
@@ -91,6 +106,104 @@ async fn main() -> Result<()> {
             Ok(())
         }
 
+        Commands::ResetPassword { username, password } => {
+            reset_password(&username, &password).await
+        }
+
+        Commands::UnlockUser { username, ip } => unlock_user(&username, ip.as_deref()).await,
+
+        Commands::DisableUser { username } => user_set_disabled(&username, true).await,
+
+        Commands::EnableUser { username } => user_set_disabled(&username, false).await,
+
+        Commands::Token { action } => match action {
+            // ---
+            TokenCommands::Create {
+                username,
+                name,
+                scopes,
+                expires_days,
+            } => token_create(&username, name, scopes, expires_days).await,
+
+            TokenCommands::List { username } => {
+                // ---
+
+                let lines = token_list_formatted(&username).await?;
+                for line in lines {
+                    println!("{}", line);
+                }
+                Ok(())
+            }
+
+            TokenCommands::Revoke { username, id } => token_revoke(&username, id).await,
+        },
+
+        Commands::Role { action } => match action {
+            // ---
+            RoleCommands::List => {
+                // ---
+
+                let lines = role_list_formatted().await?;
+                for line in lines {
+                    println!("{}", line);
+                }
+                Ok(())
+            }
+
+            RoleCommands::Create {
+                code,
+                name,
+                permissions,
+            } => role_create(code, name, permissions).await,
+
+            RoleCommands::Update {
+                code,
+                name,
+                permissions,
+            } => role_update(code, name, permissions).await,
+
+            RoleCommands::Delete { code } => role_delete(code).await,
+        },
+
+        Commands::GrantRole { username, roles } => {
+            user_roles_assign(&username, RoleAssignment::Grant(roles)).await
+        }
+
+        Commands::RevokeRole { username, roles } => {
+            user_roles_assign(&username, RoleAssignment::Revoke(roles)).await
+        }
+
+        Commands::LinkAuthor {
+            username,
+            author_id,
+        } => user_author_link(&username, Some(author_id)).await,
+
+        Commands::UnlinkAuthor { username } => user_author_link(&username, None).await,
+
+        Commands::Audit { action } => match action {
+            // ---
+            AuditCommands::Tail {
+                lines,
+                actor,
+                action,
+                entity_type,
+                entity_id,
+                follow,
+            } => {
+                // ---
+
+                let filter = AuditFilter {
+                    actor_user_id: actor,
+                    action,
+                    entity_type,
+                    entity_id,
+                    after_id: None,
+                    limit: Some(lines),
+                };
+                audit_tail(filter, follow).await
+            }
+        },
+
         Commands::UserExists { username } => {
             // ---
 
@@ -52,7 +52,7 @@ pub async fn run() -> Result<(), anyhow::Error> {
 
     // Always initialize services and build rocket
     init_servcies!()?;
-    let rocket = build_rocket()?;
+    let rocket = build_rocket().await?;
 
     // Process inspection flags if requested (but don't exit)
     if cli.check || cli.dump_state_traits || cli.output.is_some() {
@@ -109,32 +109,64 @@ macro_rules! debug_managed_type {
 ///
 /// Injects repositories, password hasher, cache context, and health services.
 /// Mounts all HTTP endpoints and attaches CORS.
-fn build_rocket() -> Result<rocket::Rocket<rocket::Build>, anyhow::Error> {
+async fn build_rocket() -> Result<rocket::Rocket<rocket::Build>, anyhow::Error> {
     // ---
 
     let app_user_repo = cr8s::domain::create_app_user_repo();
+    let api_token_repo = cr8s::domain::create_api_token_repo();
+    let audit_log = cr8s::domain::create_audit_log_repo();
     let crate_repo = cr8s::domain::create_crate_repo();
     let author_repo = cr8s::domain::create_author_repo();
+    let role_repo = cr8s::domain::create_role_code_repo()?;
     let cache_context = cr8s::domain::create_cache_context();
     let password_hasher = cr8s::domain::create_password_hasher()?;
     let health_service = cr8s::domain::create_cache_health_service()?;
 
+    // Mail is optional for the server: without SMTP settings, password reset emails fail
+    // (and are logged) instead of blocking startup.
+    let mailer = cr8s::domain::create_mailer().unwrap_or_else(|err| {
+        tracing::warn!("⚠️ Mailer not configured, emails will not be sent: {err:#}");
+        cr8s::domain::create_unconfigured_mailer(format!("{err:#}"))
+    });
+
+    // Single sign-on is optional: without an IdP (or when it cannot be reached at
+    // startup) the SSO routes answer 404.
+    let oidc_provider = match std::env::var("CR8S_OIDC_ISSUER") {
+        Err(_) => cr8s::domain::create_unconfigured_oidc_provider("CR8S_OIDC_ISSUER is not set"),
+        Ok(_) => cr8s::domain::create_oidc_provider()
+            .await
+            .unwrap_or_else(|err| {
+                tracing::warn!("⚠️ Single sign-on not available: {err:#}");
+                cr8s::domain::create_unconfigured_oidc_provider(format!("{err:#}"))
+            }),
+    };
+
     // DEBUG: Log what we're managing
     tracing::info!("🔧 Managing state types:");
     debug_managed_type!("AppUserRepo", &app_user_repo);
+    debug_managed_type!("ApiTokenRepo", &api_token_repo);
+    debug_managed_type!("AuditLog", &audit_log);
     debug_managed_type!("CrateRepo", &crate_repo);
     debug_managed_type!("AuthorRepo", &author_repo);
+    debug_managed_type!("RoleRepo", &role_repo);
     debug_managed_type!("CacheContext", &cache_context);
     debug_managed_type!("PasswordHasher", &password_hasher);
     debug_managed_type!("HealthService", &health_service);
+    debug_managed_type!("Mailer", &mailer);
+    debug_managed_type!("OidcProvider", &oidc_provider);
 
     Ok(rocket::build()
         .manage(app_user_repo)
+        .manage(api_token_repo)
+        .manage(audit_log)
         .manage(crate_repo)
         .manage(author_repo)
+        .manage(role_repo)
         .manage(cache_context)
         .manage(password_hasher)
         .manage(health_service)
+        .manage(mailer)
+        .manage(oidc_provider)
         .mount("/", rocket::routes![cr8s::rocket_routes::index])
         .mount(
             "/cr8s",
@@ -142,17 +174,54 @@ fn build_rocket() -> Result<rocket::Rocket<rocket::Build>, anyhow::Error> {
                 cr8s::rocket_routes::health_endpoint,
                 cr8s::rocket_routes::options,
                 cr8s::rocket_routes::me,
+                cr8s::rocket_routes::list_my_sessions,
+                cr8s::rocket_routes::delete_my_session,
+                cr8s::rocket_routes::list_my_api_tokens,
+                cr8s::rocket_routes::create_my_api_token,
+                cr8s::rocket_routes::revoke_my_api_token,
+                cr8s::rocket_routes::change_my_password,
+                cr8s::rocket_routes::reset_user_password,
+                cr8s::rocket_routes::list_roles,
+                cr8s::rocket_routes::create_role,
+                cr8s::rocket_routes::update_role,
+                cr8s::rocket_routes::delete_role,
+                cr8s::rocket_routes::set_user_roles,
+                cr8s::rocket_routes::list_users,
+                cr8s::rocket_routes::view_user,
+                cr8s::rocket_routes::create_user,
+                cr8s::rocket_routes::delete_user,
+                cr8s::rocket_routes::disable_user,
+                cr8s::rocket_routes::enable_user,
+                cr8s::rocket_routes::list_audit_events,
+                cr8s::rocket_routes::enroll_my_totp,
+                cr8s::rocket_routes::confirm_my_totp,
                 cr8s::rocket_routes::login,
+                cr8s::rocket_routes::login_second_factor,
+                cr8s::rocket_routes::logout,
+                cr8s::rocket_routes::logout_all,
+                cr8s::rocket_routes::refresh_access_token,
+                cr8s::rocket_routes::oidc_start,
+                cr8s::rocket_routes::oidc_callback,
+                cr8s::rocket_routes::password_reset_request,
+                cr8s::rocket_routes::password_reset_confirm,
                 cr8s::rocket_routes::get_rustaceans,
                 cr8s::rocket_routes::view_rustacean,
                 cr8s::rocket_routes::create_rustacean,
                 cr8s::rocket_routes::update_rustacean,
                 cr8s::rocket_routes::delete_rustacean,
+                cr8s::rocket_routes::link_my_author,
+                cr8s::rocket_routes::confirm_my_author,
+                cr8s::rocket_routes::link_user_author,
                 cr8s::rocket_routes::get_crates,
+                cr8s::rocket_routes::search_crates,
                 cr8s::rocket_routes::view_crate,
                 cr8s::rocket_routes::create_crate,
                 cr8s::rocket_routes::update_crate,
                 cr8s::rocket_routes::delete_crate,
+                cr8s::rocket_routes::list_crate_versions,
+                cr8s::rocket_routes::create_crate_version,
+                cr8s::rocket_routes::yank_crate_version,
+                cr8s::rocket_routes::unyank_crate_version,
             ],
         )
         .attach(cr8s::rocket_routes::Cors))
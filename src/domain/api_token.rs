@@ -0,0 +1,242 @@
+// src/domain/api_token.rs
+//! Long-lived, scoped personal access tokens for automation (e.g. CI pipelines).
+//!
+//! Unlike interactive sessions, API tokens live in Postgres, carry a name, an optional
+//! expiry and a list of scopes, and are only valid for the resources those scopes
+//! cover. Tokens start with `cr8s_pat_` so guards can tell them from session tokens;
+//! only a SHA-256 hash is stored.
+
+use anyhow::Result;
+use chrono::{Duration, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Prefix that marks a bearer token as an API token rather than a session token.
+pub const API_TOKEN_PREFIX: &str = "cr8s_pat_";
+
+/// What an API token may do; writes do not imply reads.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Hash)]
+pub enum ApiScope {
+    #[serde(rename = "crates:read")]
+    CratesRead,
+    #[serde(rename = "crates:write")]
+    CratesWrite,
+    #[serde(rename = "authors:read")]
+    AuthorsRead,
+    #[serde(rename = "authors:write")]
+    AuthorsWrite,
+}
+
+impl ApiScope {
+    // ---
+    /// Scope needed to call a route on `resource` (the first path segment below the
+    /// mount point, e.g. `crates`), or `None` if API tokens may not call it at all.
+    pub fn for_resource(resource: &str, read_only: bool) -> Option<Self> {
+        // ---
+        match (resource, read_only) {
+            ("crates", true) => Some(ApiScope::CratesRead),
+            ("crates", false) => Some(ApiScope::CratesWrite),
+            ("rustaceans", true) => Some(ApiScope::AuthorsRead),
+            ("rustaceans", false) => Some(ApiScope::AuthorsWrite),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ApiScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            ApiScope::CratesRead => "crates:read",
+            ApiScope::CratesWrite => "crates:write",
+            ApiScope::AuthorsRead => "authors:read",
+            ApiScope::AuthorsWrite => "authors:write",
+        };
+        write!(f, "{s}")
+    }
+}
+
+impl FromStr for ApiScope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "crates:read" => Ok(ApiScope::CratesRead),
+            "crates:write" => Ok(ApiScope::CratesWrite),
+            "authors:read" => Ok(ApiScope::AuthorsRead),
+            "authors:write" => Ok(ApiScope::AuthorsWrite),
+            _ => Err(format!(
+                "Invalid scope: '{s}'. Valid scopes: crates:read, crates:write, authors:read, authors:write"
+            )),
+        }
+    }
+}
+
+/// Stored API token metadata; the secret itself is never kept.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ApiToken {
+    pub id: i32,
+    pub user_id: i32,
+    pub name: String,
+    pub scopes: Vec<ApiScope>,
+    pub created_at: NaiveDateTime,
+    pub expires_at: Option<NaiveDateTime>,
+    pub last_used_at: Option<NaiveDateTime>,
+}
+
+impl ApiToken {
+    // ---
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= Utc::now().naive_utc())
+    }
+
+    pub fn allows(&self, scope: ApiScope) -> bool {
+        self.scopes.contains(&scope)
+    }
+}
+
+/// Request body for creating an API token.
+#[derive(Debug, Deserialize, Clone)]
+pub struct NewApiToken {
+    pub name: String,
+    pub scopes: Vec<ApiScope>,
+    /// Days until the token expires; `None` for a token that never expires.
+    pub expires_in_days: Option<u32>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ApiTokenError {
+    #[error("invalid token request: {0}")]
+    Invalid(String),
+
+    #[error("internal error: {0}")]
+    Internal(String),
+}
+
+impl From<anyhow::Error> for ApiTokenError {
+    fn from(err: anyhow::Error) -> Self {
+        ApiTokenError::Internal(err.to_string())
+    }
+}
+
+#[async_trait::async_trait]
+pub trait ApiTokenTableTrait: Send + Sync {
+    // ---
+    /// Store a new token for `user_id`; `token_hash` comes from `hash_api_token`.
+    async fn create(
+        &self,
+        user_id: i32,
+        name: &str,
+        token_hash: &str,
+        scopes: &[ApiScope],
+        expires_at: Option<NaiveDateTime>,
+    ) -> Result<ApiToken>;
+
+    /// All tokens of a user, including expired ones, newest first.
+    async fn find_by_user(&self, user_id: i32) -> Result<Vec<ApiToken>>;
+
+    /// Look up a token by the hash of its secret.
+    async fn find_by_hash(&self, token_hash: &str) -> Result<Option<ApiToken>>;
+
+    /// Delete one of the user's tokens, return `false` if it did not exist.
+    async fn revoke(&self, user_id: i32, token_id: i32) -> Result<bool>;
+
+    /// Record that the token was just used.
+    async fn touch(&self, _token_id: i32) -> Result<()> {
+        Ok(()) // Default: no-op for tests
+    }
+}
+
+pub type ApiTokenTableTraitPtr = Arc<dyn ApiTokenTableTrait + Send + Sync>;
+
+pub use crate::repository::create_api_token_repo;
+
+/// `true` if a bearer token is an API token rather than a session token.
+pub fn is_api_token(raw_token: &str) -> bool {
+    raw_token.starts_with(API_TOKEN_PREFIX)
+}
+
+/// Hash stored in place of the token.
+///
+/// Unkeyed SHA-256 is enough for 256-bit random secrets and, unlike the session HMAC,
+/// stays valid if the server secret changes.
+pub fn hash_api_token(raw_token: &str) -> String {
+    hex::encode(Sha256::digest(raw_token.as_bytes()))
+}
+
+/// Creates a token for `user_id` and returns its metadata plus the secret, which is
+/// shown to the caller once and never stored.
+pub async fn issue_api_token(
+    repo: ApiTokenTableTraitPtr,
+    user_id: i32,
+    new_token: NewApiToken,
+) -> Result<(ApiToken, String), ApiTokenError> {
+    // ---
+    let name = new_token.name.trim();
+    if name.is_empty() || name.len() > 64 {
+        return Err(ApiTokenError::Invalid(
+            "name must be 1 to 64 characters".into(),
+        ));
+    }
+
+    let mut scopes = new_token.scopes;
+    scopes.sort_by_key(|scope| scope.to_string());
+    scopes.dedup();
+    if scopes.is_empty() {
+        return Err(ApiTokenError::Invalid(
+            "at least one scope is required".into(),
+        ));
+    }
+
+    let expires_at = match new_token.expires_in_days {
+        Some(0) => {
+            return Err(ApiTokenError::Invalid(
+                "expires_in_days must be at least 1".into(),
+            ))
+        }
+        Some(days) => Some(Utc::now().naive_utc() + Duration::days(days.into())),
+        None => None,
+    };
+
+    let raw_token = format!(
+        "{API_TOKEN_PREFIX}{}",
+        hex::encode(rand::random::<[u8; 32]>())
+    );
+    let token = repo
+        .create(
+            user_id,
+            name,
+            &hash_api_token(&raw_token),
+            &scopes,
+            expires_at,
+        )
+        .await?;
+
+    Ok((token, raw_token))
+}
+
+/// Resolves a presented API token, returning `None` if it is unknown or expired.
+pub async fn authenticate_api_token(
+    repo: &ApiTokenTableTraitPtr,
+    raw_token: &str,
+) -> Result<Option<ApiToken>> {
+    // ---
+    let Some(token) = repo.find_by_hash(&hash_api_token(raw_token)).await? else {
+        return Ok(None);
+    };
+
+    if token.is_expired() {
+        tracing::debug!("API token {} has expired", token.id);
+        return Ok(None);
+    }
+
+    // Usage tracking is best effort and must not reject the request
+    if let Err(err) = repo.touch(token.id).await {
+        tracing::warn!("Failed to record API token use: {err}");
+    }
+
+    Ok(Some(token))
+}
@@ -17,6 +17,19 @@ pub struct AppUser {
     pub username: String,
     pub(crate) password: String,
     pub created_at: NaiveDateTime,
+    /// Author profile this user publishes as, if linked.
+    #[serde(default)]
+    pub author_id: Option<i32>,
+    /// When the account was disabled; disabled users cannot log in or use sessions.
+    #[serde(default)]
+    pub disabled_at: Option<NaiveDateTime>,
+}
+
+impl AppUser {
+    /// `true` unless the account has been disabled.
+    pub fn is_active(&self) -> bool {
+        self.disabled_at.is_none()
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -33,6 +46,20 @@ pub struct NewUser {
 
 pub type AppUserWithRoleCodes = (AppUser, Vec<RoleCode>);
 
+/// TOTP secret stored on a user; `enabled` is false until enrollment is confirmed.
+#[derive(Debug, Clone)]
+pub struct TotpSecret {
+    pub secret: String,
+    pub enabled: bool,
+}
+
+/// An unused two-factor recovery code, stored only as a password hash.
+#[derive(Debug, Clone)]
+pub struct RecoveryCode {
+    pub id: i32,
+    pub code_hash: String,
+}
+
 #[async_trait::async_trait]
 pub trait AppUserTableTrait: Send + Sync {
     // ---
@@ -50,6 +77,10 @@ pub trait AppUserTableTrait: Send + Sync {
     /// Finds a user by their unique username.
     async fn find_by_username(&self, username: &str) -> Result<AppUser>;
 
+    /// Replace the stored password hash of a user; `password_hash` must already be
+    /// hashed (see `PasswordHasherTrait`). Fails if the user does not exist.
+    async fn update_password(&self, user_id: i32, password_hash: &str) -> Result<()>;
+
     // Administrative methods with test-friendly defaults
 
     /// Deletes a user from the system by their unique ID.
@@ -66,6 +97,83 @@ pub trait AppUserTableTrait: Send + Sync {
     async fn find_with_roles(&self) -> Result<Vec<AppUserWithRoleCodes>> {
         Ok(vec![]) // Default: empty list for tests
     }
+
+    /// Add roles to a user; roles the user already holds are ignored.
+    async fn grant_roles(&self, _user_id: i32, _role_codes: &[RoleCode]) -> Result<()> {
+        Ok(()) // Default: no-op for tests
+    }
+
+    /// Remove roles from a user; roles the user does not hold are ignored.
+    async fn revoke_roles(&self, _user_id: i32, _role_codes: &[RoleCode]) -> Result<()> {
+        Ok(()) // Default: no-op for tests
+    }
+
+    // Two-factor methods with test-friendly defaults
+
+    /// The user's TOTP secret, or `None` if they never started enrollment.
+    async fn find_totp(&self, _user_id: i32) -> Result<Option<TotpSecret>> {
+        Ok(None) // Default: not enrolled for tests
+    }
+
+    /// Store a new unconfirmed TOTP secret, replacing any previous one.
+    async fn set_pending_totp(&self, _user_id: i32, _secret: &str) -> Result<()> {
+        Ok(()) // Default: no-op for tests
+    }
+
+    /// Enable two-factor login for the stored secret and replace the user's recovery
+    /// codes with `recovery_code_hashes`.
+    async fn enable_totp(&self, _user_id: i32, _recovery_code_hashes: &[String]) -> Result<()> {
+        Ok(()) // Default: no-op for tests
+    }
+
+    /// The user's unused recovery codes.
+    async fn find_recovery_codes(&self, _user_id: i32) -> Result<Vec<RecoveryCode>> {
+        Ok(vec![]) // Default: empty list for tests
+    }
+
+    /// Mark a recovery code as used, return `false` if it was already used.
+    async fn consume_recovery_code(&self, _code_id: i32) -> Result<bool> {
+        Ok(false) // Default: nothing consumed for tests
+    }
+
+    /// Record `step` as the last TOTP time step accepted for the user, return `false`
+    /// if a code of this or a later step was already accepted (a replayed code).
+    async fn use_totp_step(&self, _user_id: i32, _step: u64) -> Result<bool> {
+        Ok(true) // Default: every step is fresh for tests
+    }
+
+    // Single sign-on methods with test-friendly defaults
+
+    /// The user linked to an identity provider subject, if any.
+    async fn find_by_identity(&self, _issuer: &str, _subject: &str) -> Result<Option<AppUser>> {
+        Ok(None) // Default: no linked identities for tests
+    }
+
+    /// Link an identity provider subject to a user.
+    async fn link_identity(&self, _user_id: i32, _issuer: &str, _subject: &str) -> Result<()> {
+        Ok(()) // Default: no-op for tests
+    }
+
+    // Author profile methods with test-friendly defaults
+
+    /// The user linked to an author profile, if any.
+    async fn find_by_author(&self, _author_id: i32) -> Result<Option<AppUser>> {
+        Ok(None) // Default: no linked authors for tests
+    }
+
+    /// Link a user to an author profile, or unlink it with `None`. An author can be
+    /// linked to one user at most.
+    async fn set_author(&self, _user_id: i32, _author_id: Option<i32>) -> Result<()> {
+        Ok(()) // Default: no-op for tests
+    }
+
+    // Account state methods with test-friendly defaults
+
+    /// Disable an account (keeping the first `disabled_at`) or re-enable it, return
+    /// `false` if the user does not exist.
+    async fn set_disabled(&self, _user_id: i32, _disabled: bool) -> Result<bool> {
+        Ok(false) // Default: nothing found for tests
+    }
 }
 
 /// Shared trait object for user data access.
@@ -0,0 +1,314 @@
+// src/domain/audit.rs
+//! Append-only record of who created, updated or deleted a crate, author or user, and
+//! who yanked or un-yanked a crate version.
+//!
+//! Each event keeps the acting user, the entity and JSON snapshots of it before and
+//! after the change. Events are written after the change succeeded and only ever
+//! inserted; a failure to record one is logged rather than undoing the change.
+
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Events returned when a query does not ask for a limit.
+pub const DEFAULT_AUDIT_LIMIT: i64 = 50;
+
+/// Most events a single query may return.
+pub const MAX_AUDIT_LIMIT: i64 = 500;
+
+/// What happened to the entity.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditAction {
+    Create,
+    Update,
+    Delete,
+    Yank,
+    Unyank,
+}
+
+impl fmt::Display for AuditAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AuditAction::Create => "create",
+            AuditAction::Update => "update",
+            AuditAction::Delete => "delete",
+            AuditAction::Yank => "yank",
+            AuditAction::Unyank => "unyank",
+        })
+    }
+}
+
+impl FromStr for AuditAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "create" => Ok(AuditAction::Create),
+            "update" => Ok(AuditAction::Update),
+            "delete" => Ok(AuditAction::Delete),
+            "yank" => Ok(AuditAction::Yank),
+            "unyank" => Ok(AuditAction::Unyank),
+            _ => Err(format!(
+                "Invalid action: '{s}'. Valid actions: create, update, delete, yank, unyank"
+            )),
+        }
+    }
+}
+
+/// Kind of entity an event is about.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditEntity {
+    Crate,
+    Author,
+    User,
+}
+
+impl fmt::Display for AuditEntity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AuditEntity::Crate => "crate",
+            AuditEntity::Author => "author",
+            AuditEntity::User => "user",
+        })
+    }
+}
+
+impl FromStr for AuditEntity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "crate" => Ok(AuditEntity::Crate),
+            "author" => Ok(AuditEntity::Author),
+            "user" => Ok(AuditEntity::User),
+            _ => Err(format!(
+                "Invalid entity type: '{s}'. Valid types: crate, author, user"
+            )),
+        }
+    }
+}
+
+/// A stored audit event.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct AuditEvent {
+    pub id: i32,
+    /// User who made the change; `None` for changes made with `cr8s-cli`.
+    pub actor_user_id: Option<i32>,
+    pub action: AuditAction,
+    pub entity_type: AuditEntity,
+    pub entity_id: i32,
+    /// The entity before the change; `None` for creates.
+    pub before: Option<Value>,
+    /// The entity after the change; `None` for deletes.
+    pub after: Option<Value>,
+    pub created_at: NaiveDateTime,
+}
+
+/// An event to record; see the `created`, `updated`, `deleted` and `yank_changed`
+/// constructors.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewAuditEvent {
+    pub actor_user_id: Option<i32>,
+    pub action: AuditAction,
+    pub entity_type: AuditEntity,
+    pub entity_id: i32,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+impl NewAuditEvent {
+    // ---
+    pub fn created(entity_type: AuditEntity, entity_id: i32, after: &impl Serialize) -> Self {
+        Self::new(
+            AuditAction::Create,
+            entity_type,
+            entity_id,
+            None,
+            snapshot(after),
+        )
+    }
+
+    pub fn updated(
+        entity_type: AuditEntity,
+        entity_id: i32,
+        before: Option<&impl Serialize>,
+        after: &impl Serialize,
+    ) -> Self {
+        Self::new(
+            AuditAction::Update,
+            entity_type,
+            entity_id,
+            before.and_then(snapshot),
+            snapshot(after),
+        )
+    }
+
+    pub fn deleted(
+        entity_type: AuditEntity,
+        entity_id: i32,
+        before: Option<&impl Serialize>,
+    ) -> Self {
+        Self::new(
+            AuditAction::Delete,
+            entity_type,
+            entity_id,
+            before.and_then(snapshot),
+            None,
+        )
+    }
+
+    /// A crate version was yanked (`yanked`) or un-yanked; the snapshots are of the
+    /// version, the entity is its crate.
+    pub fn yank_changed(
+        crate_id: i32,
+        before: &impl Serialize,
+        after: &impl Serialize,
+        yanked: bool,
+    ) -> Self {
+        let action = if yanked {
+            AuditAction::Yank
+        } else {
+            AuditAction::Unyank
+        };
+        Self::new(
+            action,
+            AuditEntity::Crate,
+            crate_id,
+            snapshot(before),
+            snapshot(after),
+        )
+    }
+
+    /// Attribute the event to a user; events without an actor come from the CLI.
+    pub fn by(mut self, actor_user_id: i32) -> Self {
+        self.actor_user_id = Some(actor_user_id);
+        self
+    }
+
+    fn new(
+        action: AuditAction,
+        entity_type: AuditEntity,
+        entity_id: i32,
+        before: Option<Value>,
+        after: Option<Value>,
+    ) -> Self {
+        Self {
+            actor_user_id: None,
+            action,
+            entity_type,
+            entity_id,
+            before,
+            after,
+        }
+    }
+}
+
+fn snapshot(entity: &impl Serialize) -> Option<Value> {
+    // ---
+    serde_json::to_value(entity)
+        .map_err(|err| tracing::warn!("Audit snapshot failed: {err}"))
+        .ok()
+}
+
+/// Query for audit events; every filter that is set must match.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AuditFilter {
+    pub actor_user_id: Option<i32>,
+    pub action: Option<AuditAction>,
+    pub entity_type: Option<AuditEntity>,
+    pub entity_id: Option<i32>,
+    /// Only events newer than this event id, e.g. to follow the log.
+    pub after_id: Option<i32>,
+    /// Most events to return; `None` for `DEFAULT_AUDIT_LIMIT`.
+    pub limit: Option<i64>,
+}
+
+impl AuditFilter {
+    // ---
+    /// The requested limit, defaulted and clamped to `1..=MAX_AUDIT_LIMIT`.
+    pub fn effective_limit(&self) -> i64 {
+        self.limit
+            .unwrap_or(DEFAULT_AUDIT_LIMIT)
+            .clamp(1, MAX_AUDIT_LIMIT)
+    }
+}
+
+#[async_trait::async_trait]
+pub trait AuditLogTrait: Send + Sync {
+    // ---
+    /// Append an event.
+    async fn record(&self, event: NewAuditEvent) -> Result<AuditEvent>;
+
+    /// Events matching `filter`, newest first, at most `filter.effective_limit()`.
+    async fn find(&self, filter: &AuditFilter) -> Result<Vec<AuditEvent>>;
+}
+
+pub type AuditLogTraitPtr = Arc<dyn AuditLogTrait + Send + Sync>;
+
+pub use crate::repository::create_audit_log_repo;
+
+/// Append an event, logging instead of failing: the change it describes has already
+/// been made and must not be reported as failed.
+pub async fn record_audit_event(audit_log: &AuditLogTraitPtr, event: NewAuditEvent) {
+    // ---
+    let (action, entity_type, entity_id) = (event.action, event.entity_type, event.entity_id);
+    if let Err(err) = audit_log.record(event).await {
+        tracing::error!("Failed to record audit event {action} {entity_type} {entity_id}: {err:#}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // ---
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_new_audit_event_snapshots() {
+        // ---
+        let created = NewAuditEvent::created(AuditEntity::Crate, 3, &json!({ "name": "serde" }));
+        assert_eq!(created.action, AuditAction::Create);
+        assert_eq!(created.actor_user_id, None);
+        assert_eq!(created.before, None);
+        assert_eq!(created.after, Some(json!({ "name": "serde" })));
+
+        let updated = NewAuditEvent::updated(
+            AuditEntity::Author,
+            4,
+            Some(&json!({ "name": "old" })),
+            &json!({ "name": "new" }),
+        )
+        .by(1);
+        assert_eq!(updated.actor_user_id, Some(1));
+        assert_eq!(updated.before, Some(json!({ "name": "old" })));
+
+        let deleted = NewAuditEvent::deleted(AuditEntity::User, 5, None::<&Value>);
+        assert_eq!(deleted.action, AuditAction::Delete);
+        assert_eq!((deleted.before, deleted.after), (None, None));
+    }
+
+    #[test]
+    fn test_audit_filter_limit_and_parsing() {
+        // ---
+        assert_eq!(
+            AuditFilter::default().effective_limit(),
+            DEFAULT_AUDIT_LIMIT
+        );
+        let huge = AuditFilter {
+            limit: Some(10_000),
+            ..AuditFilter::default()
+        };
+        assert_eq!(huge.effective_limit(), MAX_AUDIT_LIMIT);
+
+        assert_eq!("Delete".parse::<AuditAction>(), Ok(AuditAction::Delete));
+        assert_eq!("crate".parse::<AuditEntity>(), Ok(AuditEntity::Crate));
+        assert!("role".parse::<AuditEntity>().is_err());
+        assert_eq!(json!(AuditEntity::Author), json!("author"));
+    }
+}
@@ -3,11 +3,23 @@
 //! Domain-facing interface and types for authors.
 //!
 //! Authors are linked to published crates and may optionally be linked to app users.
+//! A linked user may update the crates of that author (`crate:write:own`).
+//!
+//! Users claim an author profile themselves in two steps: `request_author_claim`
+//! emails a single-use token to the author's address, and `confirm_author_claim`
+//! links the user who presents it. Administrators link directly with `link_author`.
 
+use super::{
+    timestamp_cursor_key, AppUser, AppUserTableTraitPtr, CacheContextTraitPtr, Cursor,
+    MailerTraitPtr, Page, SortOrder,
+};
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fmt;
+use std::str::FromStr;
 use std::sync::Arc;
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -33,9 +45,230 @@ pub trait AuthorTableTrait: Send + Sync {
     // ---
     async fn find(&self, id: i32) -> Result<Author>;
     async fn find_multiple(&self, limit: i64) -> Result<Vec<Author>>;
+    async fn find_page(&self, query: &AuthorQuery) -> Result<Page<Author>>;
     async fn create(&self, author: NewAuthor) -> Result<Author>;
     async fn update(&self, id: i32, author: Author) -> Result<Author>;
     async fn delete(&self, id: i32) -> Result<()>;
 }
 
 pub use crate::repository::create_author_repo;
+
+/// Sort keys for author lists; ties are broken by id.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthorSort {
+    Name,
+    #[default]
+    CreatedAt,
+}
+
+impl AuthorSort {
+    // ---
+    /// Cursor text of this sort key's value for `author`.
+    pub fn cursor_for(self, author: &Author) -> Cursor {
+        let key = match self {
+            AuthorSort::Name => author.name.clone(),
+            AuthorSort::CreatedAt => timestamp_cursor_key(&author.created_at),
+        };
+        Cursor::new(self, key, author.id)
+    }
+}
+
+impl fmt::Display for AuthorSort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AuthorSort::Name => "name",
+            AuthorSort::CreatedAt => "created_at",
+        })
+    }
+}
+
+impl FromStr for AuthorSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "name" => Ok(AuthorSort::Name),
+            "created_at" => Ok(AuthorSort::CreatedAt),
+            _ => Err(format!(
+                "Invalid sort: '{s}'. Valid sorts: name, created_at"
+            )),
+        }
+    }
+}
+
+/// Query for one page of authors; every filter that is set must match.
+///
+/// The default lists authors oldest first.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AuthorQuery {
+    /// Case-insensitive prefix of the author name.
+    pub name_prefix: Option<String>,
+    /// Only authors created at or after this time.
+    pub created_since: Option<NaiveDateTime>,
+    pub sort: AuthorSort,
+    pub order: SortOrder,
+    /// Continue after this position; must have been issued for `sort`.
+    pub cursor: Option<Cursor>,
+    /// Most authors to return; `None` for `DEFAULT_PAGE_LIMIT`.
+    pub limit: Option<i64>,
+}
+
+/// Tera template rendered for the author claim email.
+const AUTHOR_CLAIM_TEMPLATE: &str = "email/author_claim.html";
+
+/// Request body for linking a user to an author profile; `null` unlinks.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuthorLink {
+    pub author_id: Option<i32>,
+}
+
+/// Request body completing an author claim with the emailed token.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuthorClaimConfirm {
+    pub token: String,
+}
+
+/// Author claim waiting for its token, as stored in the cache.
+#[derive(Serialize, Deserialize)]
+struct PendingAuthorClaim {
+    user_id: i32,
+    author_id: i32,
+    /// Address the token was sent to; the claim fails if the author's email changed.
+    email: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AuthorLinkError {
+    #[error("author not found: {0}")]
+    NotFound(i32),
+
+    #[error("author {0} is already linked to another user")]
+    AlreadyLinked(i32),
+
+    #[error("author claim token is invalid or expired")]
+    InvalidClaim,
+
+    #[error("internal error: {0}")]
+    Internal(String),
+}
+
+impl From<anyhow::Error> for AuthorLinkError {
+    fn from(err: anyhow::Error) -> Self {
+        AuthorLinkError::Internal(format!("{err:#}"))
+    }
+}
+
+/// Link `user` to an author profile, or unlink it with `None`, and return the updated
+/// user.
+///
+/// Nothing proves that the user owns the profile; users claiming one themselves go
+/// through `request_author_claim` and `confirm_author_claim` instead.
+pub async fn link_author(
+    app_user_repo: &AppUserTableTraitPtr,
+    author_repo: &AuthorTableTraitPtr,
+    user: &AppUser,
+    author_id: Option<i32>,
+) -> Result<AppUser, AuthorLinkError> {
+    // ---
+    if let Some(author_id) = author_id {
+        check_linkable(app_user_repo, author_repo, user, author_id).await?;
+    }
+
+    app_user_repo.set_author(user.id, author_id).await?;
+    Ok(AppUser {
+        author_id,
+        ..user.clone()
+    })
+}
+
+/// Emails a single-use token to the author's address so `user` can prove they own the
+/// profile; the link is made by `confirm_author_claim`.
+///
+/// Respects env var `CR8S_AUTHOR_CLAIM_TTL_SECS` (default: 86400).
+pub async fn request_author_claim(
+    app_user_repo: &AppUserTableTraitPtr,
+    author_repo: &AuthorTableTraitPtr,
+    cache: &CacheContextTraitPtr,
+    mailer: &MailerTraitPtr,
+    user: &AppUser,
+    author_id: i32,
+) -> Result<(), AuthorLinkError> {
+    // ---
+    let author = check_linkable(app_user_repo, author_repo, user, author_id).await?;
+
+    let ttl_secs = crate::get_env_with_default!(u64, "CR8S_AUTHOR_CLAIM_TTL_SECS", 86400);
+    let token = crate::auth::create_password_hasher()?.generate_session_token();
+    let pending = PendingAuthorClaim {
+        user_id: user.id,
+        author_id,
+        email: author.email.clone(),
+    };
+    let pending = serde_json::to_string(&pending).map_err(anyhow::Error::from)?;
+    cache.set_author_claim(&token, &pending, ttl_secs).await?;
+
+    let context = json!({
+        "name": author.name,
+        "username": user.username,
+        "token": token,
+        "expires_hours": ttl_secs.div_ceil(3600),
+    });
+    mailer
+        .send_templated(
+            &author.email,
+            "✍️ Confirm your cr8s author profile",
+            AUTHOR_CLAIM_TEMPLATE,
+            context,
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Consumes an author claim token and links `user`, who must be the user it was issued
+/// to, to the claimed author. Returns the updated user.
+pub async fn confirm_author_claim(
+    app_user_repo: &AppUserTableTraitPtr,
+    author_repo: &AuthorTableTraitPtr,
+    cache: &CacheContextTraitPtr,
+    user: &AppUser,
+    token: &str,
+) -> Result<AppUser, AuthorLinkError> {
+    // ---
+    let Some(pending) = cache.take_author_claim(token).await? else {
+        return Err(AuthorLinkError::InvalidClaim);
+    };
+    let pending: PendingAuthorClaim =
+        serde_json::from_str(&pending).map_err(|_| AuthorLinkError::InvalidClaim)?;
+    if pending.user_id != user.id {
+        return Err(AuthorLinkError::InvalidClaim);
+    }
+
+    let author = check_linkable(app_user_repo, author_repo, user, pending.author_id).await?;
+    if author.email != pending.email {
+        return Err(AuthorLinkError::InvalidClaim);
+    }
+
+    app_user_repo.set_author(user.id, Some(author.id)).await?;
+    Ok(AppUser {
+        author_id: Some(author.id),
+        ..user.clone()
+    })
+}
+
+/// Returns the author if it exists and is not linked to a user other than `user`.
+async fn check_linkable(
+    app_user_repo: &AppUserTableTraitPtr,
+    author_repo: &AuthorTableTraitPtr,
+    user: &AppUser,
+    author_id: i32,
+) -> Result<Author, AuthorLinkError> {
+    // ---
+    let Ok(author) = author_repo.find(author_id).await else {
+        return Err(AuthorLinkError::NotFound(author_id));
+    };
+    match app_user_repo.find_by_author(author_id).await? {
+        Some(linked) if linked.id != user.id => Err(AuthorLinkError::AlreadyLinked(author_id)),
+        _ => Ok(author),
+    }
+}
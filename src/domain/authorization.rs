@@ -1,5 +1,12 @@
 // src/domain/auth.rs
-use super::{AppUser, AppUserTableTraitPtr};
+use super::login_throttle::{
+    login_lockout_remaining, login_throttle_keys, record_login_failure, LockoutPolicy,
+};
+use super::two_factor::second_factor_enabled;
+use super::{
+    AppUser, AppUserTableTraitPtr, CacheContextTraitPtr, PasswordHasherTrait, PasswordPolicy,
+    PasswordPolicyError,
+};
 use anyhow::Result;
 use serde::Deserialize;
 
@@ -22,18 +29,105 @@ impl Credentials {
 
 pub use crate::auth::create_password_hasher;
 
+/// Result of a password check that succeeded.
+#[derive(Debug)]
+pub enum LoginOutcome {
+    /// The user is logged in; the token still has to be stored as a session.
+    Authenticated(AppUser, String),
+    /// The user has 2FA enabled; the challenge token must be exchanged together with a
+    /// TOTP or recovery code (see `complete_second_factor`).
+    SecondFactorRequired { challenge_token: String },
+}
+
 /// Authenticates a user and returns a session token.
 ///
 /// The session token is a temporary identifier for a logged-in user. It is stored in Redis
 /// and used to authorize future requests.
 ///
-/// Returns `Ok((user, token))` on success, or `Err(LoginError)` if authentication fails.
+/// Failed attempts are counted per username and per `client_ip` (see `login_throttle`);
+/// while either is locked out the password is not even checked.
+///
+/// Users with two-factor authentication get a short-lived, single-use challenge token
+/// instead of a session token; their username counter is only reset once the second
+/// factor is accepted too. Disabled accounts are refused once the password has been
+/// accepted, so the answer does not reveal whether an account exists.
+///
+/// Respects env vars:
+/// - `CR8S_2FA_CHALLENGE_TTL_SECS` (default: 300)
 pub async fn authenticate_user(
     repo: AppUserTableTraitPtr,
+    cache: CacheContextTraitPtr,
     creds: Credentials,
-) -> Result<(AppUser, String), LoginError> {
+    client_ip: Option<&str>,
+) -> Result<LoginOutcome, LoginError> {
     // ---
     let user_tag = "[user redacted]";
+    let policy = LockoutPolicy::from_env();
+    let throttle_keys = login_throttle_keys(&creds.username, client_ip);
+
+    if let Some(retry_after_secs) = login_lockout_remaining(&cache, &throttle_keys)
+        .await
+        .map_err(|err| LoginError::Internal(err.to_string()))?
+    {
+        tracing::warn!("Login rejected, {user_tag} locked out for {retry_after_secs}s");
+        return Err(LoginError::LockedOut { retry_after_secs });
+    }
+
+    match verify_credentials(repo.clone(), &creds, user_tag).await {
+        Ok((user, token)) => {
+            if !user.is_active() {
+                tracing::warn!("Login rejected, {user_tag} is disabled");
+                return Err(LoginError::AccountDisabled);
+            }
+
+            if !second_factor_enabled(&repo, user.id)
+                .await
+                .map_err(|err| LoginError::Internal(err.to_string()))?
+            {
+                // Only the username counter is reset; an IP keeps its history
+                cache
+                    .clear_login_failures(&throttle_keys[0])
+                    .await
+                    .map_err(|err| LoginError::Internal(err.to_string()))?;
+                return Ok(LoginOutcome::Authenticated(user, token));
+            }
+
+            // The freshly minted token becomes the challenge; it is never a session
+            let ttl_secs = crate::get_env_with_default!(u64, "CR8S_2FA_CHALLENGE_TTL_SECS", 300);
+            cache
+                .set_login_challenge(&token, user.id, ttl_secs)
+                .await
+                .map_err(|err| LoginError::Internal(err.to_string()))?;
+
+            tracing::info!("Password accepted for {user_tag}, second factor required");
+            Ok(LoginOutcome::SecondFactorRequired {
+                challenge_token: token,
+            })
+        }
+        Err(LoginError::InvalidCredentials) => {
+            let locked = record_login_failure(&cache, &policy, &throttle_keys)
+                .await
+                .map_err(|err| LoginError::Internal(err.to_string()))?;
+
+            match locked {
+                Some(retry_after_secs) => {
+                    tracing::warn!("Locking out {user_tag} for {retry_after_secs}s");
+                    Err(LoginError::LockedOut { retry_after_secs })
+                }
+                None => Err(LoginError::InvalidCredentials),
+            }
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Check a username/password pair and mint a session token.
+async fn verify_credentials(
+    repo: AppUserTableTraitPtr,
+    creds: &Credentials,
+    user_tag: &str,
+) -> Result<(AppUser, String), LoginError> {
+    // ---
     let user = repo
         .find_by_username(&creds.username)
         .await
@@ -54,16 +148,138 @@ pub async fn authenticate_user(
             LoginError::InvalidCredentials
         })?;
 
+    if hasher.needs_rehash(&user.password) {
+        rehash_password(&repo, &*hasher, &user, &creds.password, user_tag).await;
+    }
+
     let token = hasher.generate_session_token();
 
     Ok((user, token))
 }
 
+/// Replace a hash made with weaker Argon2 settings by one made with the current
+/// settings. Failures are logged and do not fail the login; the next one retries.
+async fn rehash_password(
+    repo: &AppUserTableTraitPtr,
+    hasher: &dyn PasswordHasherTrait,
+    user: &AppUser,
+    password: &str,
+    user_tag: &str,
+) {
+    // ---
+    let rehashed = match hasher.hash_password(password) {
+        Ok(hash) => repo.update_password(user.id, &hash).await,
+        Err(err) => Err(err),
+    };
+
+    match rehashed {
+        Ok(()) => tracing::info!("Upgraded password hash for {user_tag}"),
+        Err(err) => tracing::warn!("Password rehash failed for {user_tag}: {err}"),
+    }
+}
+
+/// Request body for a self-service password change.
+#[derive(Debug, Deserialize, Clone)]
+pub struct PasswordChange {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+/// Request body for an administrative password reset.
+#[derive(Debug, Deserialize, Clone)]
+pub struct PasswordReset {
+    pub new_password: String,
+}
+
+/// Changes a user's password after re-checking their current one.
+///
+/// Returns `Err(LoginError::InvalidCredentials)` if `current_password` does not match,
+/// or `Err(LoginError::WeakPassword)` if the new password breaks the password policy.
+/// A wrong current password counts as a failed login for the username and client IP,
+/// so a stolen session cannot be used to guess the password; while either is locked
+/// out this fails with `LoginError::LockedOut`. Revoking the user's other sessions is
+/// left to the caller.
+pub async fn change_password(
+    repo: AppUserTableTraitPtr,
+    cache: CacheContextTraitPtr,
+    user: &AppUser,
+    change: PasswordChange,
+    client_ip: Option<&str>,
+) -> Result<(), LoginError> {
+    // ---
+    let internal = |err: anyhow::Error| LoginError::Internal(err.to_string());
+    let throttle_keys = login_throttle_keys(&user.username, client_ip);
+
+    if let Some(retry_after_secs) = login_lockout_remaining(&cache, &throttle_keys)
+        .await
+        .map_err(internal)?
+    {
+        tracing::warn!("Password change rejected, user id {} locked out", user.id);
+        return Err(LoginError::LockedOut { retry_after_secs });
+    }
+
+    let hasher = crate::auth::create_password_hasher().map_err(internal)?;
+
+    if let Err(err) = hasher.verify_password(&user.password, &change.current_password) {
+        tracing::warn!("password change rejected for user id {}: {err}", user.id);
+        let policy = LockoutPolicy::from_env();
+        return match record_login_failure(&cache, &policy, &throttle_keys)
+            .await
+            .map_err(internal)?
+        {
+            Some(retry_after_secs) => Err(LoginError::LockedOut { retry_after_secs }),
+            None => Err(LoginError::InvalidCredentials),
+        };
+    }
+    cache
+        .clear_login_failures(&throttle_keys[0])
+        .await
+        .map_err(internal)?;
+
+    set_password(repo, user.id, &change.new_password)
+        .await
+        .map_err(|err| match err.downcast::<PasswordPolicyError>() {
+            Ok(PasswordPolicyError::ListUnavailable(err)) => LoginError::Internal(err),
+            Ok(err) => LoginError::WeakPassword(err),
+            Err(err) => LoginError::Internal(err.to_string()),
+        })
+}
+
+/// Hashes `new_password` and stores it for `user_id` without checking the old one.
+///
+/// Intended for administrative resets (REST or CLI); callers must authorize first.
+/// A password rejected by the `PasswordPolicy` fails with a `PasswordPolicyError`
+/// that callers can recover with `anyhow::Error::downcast_ref`.
+pub async fn set_password(
+    repo: AppUserTableTraitPtr,
+    user_id: i32,
+    new_password: &str,
+) -> Result<()> {
+    // ---
+    let user = repo.find(user_id).await?;
+    PasswordPolicy::from_env().check(Some(&user.username), new_password)?;
+
+    let password_hash = crate::auth::create_password_hasher()?
+        .hash_password(new_password)
+        .map_err(|e| anyhow::anyhow!("Password hashing failed: {e}"))?;
+
+    repo.update_password(user_id, &password_hash).await
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum LoginError {
     #[error("invalid username or password")]
     InvalidCredentials,
 
+    #[error("too many failed login attempts, retry in {retry_after_secs}s")]
+    LockedOut { retry_after_secs: u64 },
+
+    #[error("account is disabled")]
+    AccountDisabled,
+
+    #[error(transparent)]
+    WeakPassword(PasswordPolicyError),
+
     #[error("internal error: {0}")]
     Internal(String),
 }
@@ -6,21 +6,220 @@
 //! Implementations should live in `repository/` and be injected here
 //! via factory functions like `create_cache_context()`.
 
+use super::{AppUser, RoleCode};
 use anyhow::Result;
 use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
+/// Details captured at login time for a new session.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NewSession {
+    pub user_id: i32,
+    pub client_ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub role_codes: Vec<RoleCode>,
+}
+
+/// Stored record describing one active session.
+///
+/// `id` is a public handle for the session and is unrelated to the bearer token,
+/// which is never exposed once issued.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SessionRecord {
+    pub id: String,
+    pub user_id: i32,
+    pub created_at: NaiveDateTime,
+    pub last_seen: NaiveDateTime,
+    pub client_ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub role_codes: Vec<RoleCode>,
+}
+
+/// Short-lived signed access token, issued only in JWT auth mode.
+#[derive(Debug, Clone, Serialize)]
+pub struct AccessToken {
+    pub token: String,
+    /// Seconds until the token expires.
+    pub expires_in: u64,
+}
+
+/// Identity carried by a verified access token; enough to authorize a request
+/// without a cache or database lookup.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccessClaims {
+    pub user_id: i32,
+    pub username: String,
+    pub user_created_at: NaiveDateTime,
+    pub author_id: Option<i32>,
+    pub role_codes: Vec<RoleCode>,
+    /// Public id of the session (refresh token) the access token was issued for.
+    pub session_id: String,
+}
+
 /// Abstract trait over session/token cache behavior.
+///
+/// Methods take the raw bearer token as presented by the client; implementations
+/// must not persist it as-is (the Redis implementation stores an HMAC of it).
 #[async_trait]
 pub trait CacheContextTrait: Send + Sync {
     /// Validate a session token and return the associated user ID, or `None` if invalid.
     async fn get_user_id_by_session_token(&self, token: &str) -> Result<Option<i32>>;
 
-    /// Write a new session token for a user (or update existing one).
-    async fn set_user_session_token(&self, user_id: i32, token: &str) -> Result<()>;
+    /// Write a new session record under the given token.
+    async fn set_user_session_token(
+        &self,
+        token: &str,
+        new_session: NewSession,
+    ) -> Result<SessionRecord>;
+
+    /// Refresh the idle timeout and last-seen time of a session that was just used.
+    async fn touch_session_token(&self, token: &str) -> Result<()>;
 
     /// Delete a session token (e.g., logout), return `true` if found else `false.
     async fn clear_session_token(&self, token: &str) -> Result<bool>;
+
+    /// Delete every session token issued to a user (e.g., logout everywhere), return
+    /// the number of sessions that were revoked.
+    async fn clear_all_session_tokens(&self, user_id: i32) -> Result<u64>;
+
+    // Session management methods with test-friendly defaults
+
+    /// List the active sessions of a user, most recently used first.
+    async fn list_user_sessions(&self, _user_id: i32) -> Result<Vec<SessionRecord>> {
+        Ok(vec![]) // Default: empty list for tests
+    }
+
+    /// Delete one session of a user by its public id, return `true` if found.
+    async fn clear_user_session(&self, _user_id: i32, _session_id: &str) -> Result<bool> {
+        Ok(false) // Default: nothing found for tests
+    }
+
+    /// Delete every session of a user except the one identified by `keep_token`
+    /// (e.g., after a password change), return the number revoked.
+    async fn clear_other_session_tokens(&self, _user_id: i32, _keep_token: &str) -> Result<u64> {
+        Ok(0) // Default: nothing revoked for tests
+    }
+
+    /// Replace the roles recorded on every active session of a user (e.g., after a
+    /// grant or revoke), return the number of sessions updated.
+    async fn update_session_roles(&self, _user_id: i32, _role_codes: &[RoleCode]) -> Result<u64> {
+        Ok(0) // Default: nothing updated for tests
+    }
+
+    // Password reset methods with test-friendly defaults
+
+    /// Store a single-use password reset token for `user_id`, valid for `ttl_secs`.
+    async fn set_password_reset_token(
+        &self,
+        _token: &str,
+        _user_id: i32,
+        _ttl_secs: u64,
+    ) -> Result<()> {
+        Ok(()) // Default: no-op for tests
+    }
+
+    /// Return the user id a password reset token was issued for without consuming it,
+    /// or `None` if it is unknown, expired, or was already used.
+    async fn find_password_reset_token(&self, _token: &str) -> Result<Option<i32>> {
+        Ok(None) // Default: nothing found for tests
+    }
+
+    /// Consume a password reset token and return the user id it was issued for, or
+    /// `None` if it is unknown, expired, or was already used.
+    async fn take_password_reset_token(&self, _token: &str) -> Result<Option<i32>> {
+        Ok(None) // Default: nothing found for tests
+    }
+
+    // Two-factor login challenge methods with test-friendly defaults
+
+    /// Store a single-use login challenge for `user_id` whose password was accepted,
+    /// valid for `ttl_secs`.
+    async fn set_login_challenge(&self, _token: &str, _user_id: i32, _ttl_secs: u64) -> Result<()> {
+        Ok(()) // Default: no-op for tests
+    }
+
+    /// Consume a login challenge and return its user id, or `None` if it is unknown,
+    /// expired, or was already used.
+    async fn take_login_challenge(&self, _token: &str) -> Result<Option<i32>> {
+        Ok(None) // Default: nothing found for tests
+    }
+
+    // Single sign-on methods with test-friendly defaults
+
+    /// Store the pending state of an OIDC login (an opaque serialized value) under its
+    /// `state` parameter, valid for `ttl_secs`.
+    async fn set_oidc_login_state(&self, _state: &str, _value: &str, _ttl_secs: u64) -> Result<()> {
+        Ok(()) // Default: no-op for tests
+    }
+
+    /// Consume a pending OIDC login, or return `None` if it is unknown, expired, or was
+    /// already used.
+    async fn take_oidc_login_state(&self, _state: &str) -> Result<Option<String>> {
+        Ok(None) // Default: nothing found for tests
+    }
+
+    // Author claim methods with test-friendly defaults
+
+    /// Store a pending author claim (an opaque serialized value) under its emailed
+    /// token, valid for `ttl_secs`.
+    async fn set_author_claim(&self, _token: &str, _value: &str, _ttl_secs: u64) -> Result<()> {
+        Ok(()) // Default: no-op for tests
+    }
+
+    /// Consume a pending author claim, or return `None` if it is unknown, expired, or
+    /// was already used.
+    async fn take_author_claim(&self, _token: &str) -> Result<Option<String>> {
+        Ok(None) // Default: nothing found for tests
+    }
+
+    // Login throttling methods with test-friendly defaults
+
+    /// Count a failed login for a throttle key (e.g. `user:<name>` or `ip:<addr>`),
+    /// forgetting it after `window_secs` without further failures. Returns the new count.
+    async fn incr_login_failures(&self, _key: &str, _window_secs: u64) -> Result<u64> {
+        Ok(0) // Default: never counts for tests
+    }
+
+    /// Lock a throttle key out of logging in for `secs` seconds.
+    async fn lock_login(&self, _key: &str, _secs: u64) -> Result<()> {
+        Ok(()) // Default: no-op for tests
+    }
+
+    /// Seconds left on a throttle key's lockout, or `None` if it is not locked.
+    async fn login_lock_ttl(&self, _key: &str) -> Result<Option<u64>> {
+        Ok(None) // Default: never locked for tests
+    }
+
+    /// Clear the failure counter and any lockout of a throttle key.
+    async fn clear_login_failures(&self, _key: &str) -> Result<()> {
+        Ok(()) // Default: no-op for tests
+    }
+
+    // Access token methods (JWT auth mode) with test-friendly defaults
+
+    /// Sign an access token for a session that was just created or refreshed, or
+    /// return `None` if this cache does not issue access tokens.
+    async fn issue_access_token(
+        &self,
+        _user: &AppUser,
+        _session: &SessionRecord,
+    ) -> Result<Option<AccessToken>> {
+        Ok(None) // Default: session tokens only for tests
+    }
+
+    /// Verify a bearer token as an access token, without any I/O. Returns `None` if
+    /// it is not a valid, unexpired access token.
+    fn verify_access_token(&self, _token: &str) -> Option<AccessClaims> {
+        None // Default: no access tokens for tests
+    }
+
+    /// `true` if requests must carry access tokens; session tokens then act purely as
+    /// refresh tokens.
+    fn requires_access_tokens(&self) -> bool {
+        false // Default: session tokens are bearer tokens for tests
+    }
 }
 
 /// Shared trait object pointer for any cache context implementation.
@@ -0,0 +1,370 @@
+// src/domain/crate_version.rs
+//! Published versions of a crate.
+//!
+//! Every crate starts with the version it was created with, and each later release
+//! adds a row, so history survives updates. Versions are [SemVer](https://semver.org)
+//! and `Crate::version` always holds the latest one by SemVer precedence, ignoring
+//! pre-releases unless the crate has nothing else.
+//!
+//! Like on crates.io, a version can be yanked: it stays listed and viewable, but no
+//! longer counts as the latest version, matches version requirements or appears in
+//! digests.
+
+use super::{Crate, CrateTableTraitPtr};
+use chrono::NaiveDateTime;
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+
+/// Longest version string the `crate_version.version` column holds.
+const MAX_VERSION_LEN: usize = 64;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CrateVersion {
+    pub id: i32,
+    pub crate_id: i32,
+    pub version: String,
+    /// What changed in this release, if given.
+    pub description: Option<String>,
+    pub yanked: bool,
+    pub published_at: NaiveDateTime,
+}
+
+/// Request body for publishing a version.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NewCrateVersion {
+    pub version: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CrateVersionError {
+    #[error("invalid version: {0}")]
+    Invalid(String),
+
+    #[error("version {0} already exists")]
+    AlreadyExists(String),
+
+    #[error("version {0} not found")]
+    NotFound(String),
+
+    #[error("internal error: {0}")]
+    Internal(String),
+}
+
+impl From<anyhow::Error> for CrateVersionError {
+    fn from(err: anyhow::Error) -> Self {
+        CrateVersionError::Internal(format!("{err:#}"))
+    }
+}
+
+/// Parse a version as SemVer, e.g. `1.2.3` or `2.0.0-rc.1`.
+pub fn parse_version(version: &str) -> Result<Version, String> {
+    // ---
+    let version = version.trim();
+    if version.is_empty() {
+        return Err("version must not be empty".into());
+    }
+    if version.len() > MAX_VERSION_LEN {
+        return Err(format!(
+            "version must be at most {MAX_VERSION_LEN} characters"
+        ));
+    }
+    Version::parse(version)
+        .map_err(|err| format!("'{version}' is not a semantic version (MAJOR.MINOR.PATCH): {err}"))
+}
+
+/// Parse a version requirement such as `^1.2`, `~0.3.1` or `>=1.0, <2.0`.
+pub fn parse_version_req(req: &str) -> Result<VersionReq, String> {
+    VersionReq::parse(req.trim())
+        .map_err(|err| format!("Invalid version requirement '{}': {err}", req.trim()))
+}
+
+/// Sort versions by SemVer precedence, highest first. Versions recorded before they
+/// were validated, which do not parse, go last in their existing order.
+pub fn sort_by_precedence(versions: &mut [CrateVersion]) {
+    versions.sort_by_cached_key(|version| std::cmp::Reverse(Version::parse(&version.version).ok()));
+}
+
+/// Text that sorts like the version's SemVer precedence when compared byte by byte
+/// (`COLLATE "C"`), so SQL can order and page crates by version: `1.9.0` sorts
+/// before `1.10.0`, and `1.0.0-rc.1` before `1.0.0`. Versions that do not parse
+/// sort after all others, by their text.
+///
+/// Numbers are prefixed with their digit count, pre-release identifiers are marked
+/// numeric (`0`) or alphanumeric (`1`) and joined with `!`, which sorts below every
+/// identifier character, and a release ends in `~`, which sorts above a pre-release.
+pub fn version_sort_key(version: &str) -> String {
+    // ---
+    let Ok(parsed) = Version::parse(version) else {
+        return format!("~{version}");
+    };
+
+    let number = |digits: &str| format!("{:02}{digits}", digits.len());
+    let mut key = [parsed.major, parsed.minor, parsed.patch]
+        .map(|part| number(&part.to_string()))
+        .join(".");
+    if parsed.pre.is_empty() {
+        key.push('~');
+        return key;
+    }
+
+    let identifiers: Vec<String> = parsed
+        .pre
+        .as_str()
+        .split('.')
+        .map(|identifier| {
+            if identifier.bytes().all(|byte| byte.is_ascii_digit()) {
+                format!("0{}", number(identifier))
+            } else {
+                format!("1{identifier}")
+            }
+        })
+        .collect();
+    key.push('-');
+    key.push_str(&identifiers.join("!"));
+    key
+}
+
+/// The highest version by SemVer precedence, skipping pre-releases unless
+/// `include_prerelease` is set. Yanked versions never count.
+pub fn latest_version(
+    versions: &[CrateVersion],
+    include_prerelease: bool,
+) -> Option<&CrateVersion> {
+    highest(versions, |version| {
+        include_prerelease || version.pre.is_empty()
+    })
+}
+
+/// The version a crate reports as current: the latest release, or the latest
+/// pre-release while the crate has no release yet.
+pub fn current_version(versions: &[CrateVersion]) -> Option<&CrateVersion> {
+    latest_version(versions, false).or_else(|| latest_version(versions, true))
+}
+
+/// The highest version matching `req` that is not yanked, e.g. `1.4.2` for `^1.2`.
+///
+/// Like Cargo, a requirement only matches pre-releases it names itself (`>=2.0.0-rc.1`);
+/// with `include_prerelease` any pre-release whose release would match counts too.
+pub fn matching_version<'a>(
+    versions: &'a [CrateVersion],
+    req: &VersionReq,
+    include_prerelease: bool,
+) -> Option<&'a CrateVersion> {
+    // ---
+    highest(versions, |version| {
+        req.matches(version)
+            || (include_prerelease
+                && !version.pre.is_empty()
+                && req.matches(&Version::new(version.major, version.minor, version.patch)))
+    })
+}
+
+fn highest(versions: &[CrateVersion], accept: impl Fn(&Version) -> bool) -> Option<&CrateVersion> {
+    versions
+        .iter()
+        .filter(|version| !version.yanked)
+        .filter_map(|version| Some((Version::parse(&version.version).ok()?, version)))
+        .filter(|(parsed, _)| accept(parsed))
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, version)| version)
+}
+
+/// Publish a new version of `krate`; it becomes the crate's current version when it
+/// is the latest one. Returns the published version and the crate as it is now.
+pub async fn publish_crate_version(
+    crate_repo: &CrateTableTraitPtr,
+    krate: &Crate,
+    new_version: NewCrateVersion,
+) -> Result<(CrateVersion, Crate), CrateVersionError> {
+    // ---
+    let version = parse_version(&new_version.version)
+        .map_err(CrateVersionError::Invalid)?
+        .to_string();
+
+    let mut existing = crate_repo.find_versions(krate.id).await?;
+    if existing
+        .iter()
+        .any(|published| published.version == version)
+    {
+        return Err(CrateVersionError::AlreadyExists(version));
+    }
+
+    let new_version = NewCrateVersion {
+        version,
+        ..new_version
+    };
+    let published = crate_repo.publish_version(krate.id, new_version).await?;
+
+    existing.push(published.clone());
+    let current = current_version(&existing).unwrap_or(&published);
+    let updated = Crate {
+        version: current.version.clone(),
+        yanked: false,
+        ..krate.clone()
+    };
+    Ok((published, updated))
+}
+
+/// A version before and after a yank or un-yank.
+#[derive(Debug, Clone)]
+pub struct YankChange {
+    pub before: CrateVersion,
+    pub after: CrateVersion,
+}
+
+impl YankChange {
+    /// Whether the request changed anything; yanking a yanked version does not.
+    pub fn changed(&self) -> bool {
+        self.before.yanked != self.after.yanked
+    }
+}
+
+/// Yank (`yanked`) or un-yank a published version of the crate `crate_id`.
+pub async fn set_crate_version_yanked(
+    crate_repo: &CrateTableTraitPtr,
+    crate_id: i32,
+    version: &str,
+    yanked: bool,
+) -> Result<YankChange, CrateVersionError> {
+    // ---
+    let version = version.trim();
+    let before = crate_repo
+        .find_versions(crate_id)
+        .await?
+        .into_iter()
+        .find(|published| published.version == version)
+        .ok_or_else(|| CrateVersionError::NotFound(version.to_string()))?;
+
+    if before.yanked == yanked {
+        return Ok(YankChange {
+            after: before.clone(),
+            before,
+        });
+    }
+
+    let after = crate_repo
+        .set_version_yanked(crate_id, version, yanked)
+        .await?
+        .ok_or_else(|| CrateVersionError::NotFound(version.to_string()))?;
+    Ok(YankChange { before, after })
+}
+
+#[cfg(test)]
+mod tests {
+    // ---
+    use super::*;
+
+    fn versions(numbers: &[&str]) -> Vec<CrateVersion> {
+        numbers
+            .iter()
+            .enumerate()
+            .map(|(i, number)| CrateVersion {
+                id: i as i32 + 1,
+                crate_id: 1,
+                version: number.to_string(),
+                description: None,
+                yanked: false,
+                published_at: NaiveDateTime::default(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_version() {
+        // ---
+        assert_eq!(parse_version(" 1.2.3 ").unwrap(), Version::new(1, 2, 3));
+        assert!(parse_version("2.0.0-rc.1+build.5").is_ok());
+        assert!(parse_version("1.2").is_err());
+        assert!(parse_version("v1.2.3").is_err());
+        assert!(parse_version("").is_err());
+        assert!(parse_version_req("^1.2").is_ok());
+        assert!(parse_version_req("one").is_err());
+    }
+
+    #[test]
+    fn test_precedence_and_latest() {
+        // ---
+        let mut history = versions(&["1.10.0", "1.2.0", "2.0.0-rc.1", "1.9.3"]);
+        assert_eq!(latest_version(&history, false).unwrap().version, "1.10.0");
+        assert_eq!(
+            latest_version(&history, true).unwrap().version,
+            "2.0.0-rc.1"
+        );
+        assert_eq!(current_version(&history).unwrap().version, "1.10.0");
+
+        sort_by_precedence(&mut history);
+        let order: Vec<_> = history.iter().map(|v| v.version.as_str()).collect();
+        assert_eq!(order, ["2.0.0-rc.1", "1.10.0", "1.9.3", "1.2.0"]);
+
+        let only_pre = versions(&["0.1.0-alpha.1", "0.1.0-alpha.2"]);
+        assert_eq!(current_version(&only_pre).unwrap().version, "0.1.0-alpha.2");
+    }
+
+    #[test]
+    fn test_version_sort_key_follows_precedence() {
+        // ---
+        let ascending = [
+            "0.9.0",
+            "1.0.0-alpha",
+            "1.0.0-alpha.1",
+            "1.0.0-alpha.beta",
+            "1.0.0-alpha-x",
+            "1.0.0-beta.2",
+            "1.0.0-beta.11",
+            "1.0.0-rc.1",
+            "1.0.0",
+            "1.9.0",
+            "1.10.0",
+            "10.0.0",
+            "not-a-version",
+        ];
+        let keys: Vec<String> = ascending.iter().map(|v| version_sort_key(v)).collect();
+        for (pair, versions) in keys.windows(2).zip(ascending.windows(2)) {
+            assert!(
+                pair[0] < pair[1],
+                "{} should sort before {}",
+                versions[0],
+                versions[1]
+            );
+        }
+        assert!(version_sort_key("1.9.0") < version_sort_key("1.10.0"));
+        assert_eq!(version_sort_key("1.2.3+build.5"), version_sort_key("1.2.3"));
+    }
+
+    #[test]
+    fn test_yanked_versions_never_count() {
+        // ---
+        let mut history = versions(&["1.0.0", "1.1.0", "1.2.0-rc.1"]);
+        history[1].yanked = true;
+        assert_eq!(current_version(&history).unwrap().version, "1.0.0");
+        let caret = parse_version_req("^1.1").unwrap();
+        assert!(matching_version(&history, &caret, false).is_none());
+        assert_eq!(
+            matching_version(&history, &caret, true).unwrap().version,
+            "1.2.0-rc.1"
+        );
+
+        for version in history.iter_mut() {
+            version.yanked = true;
+        }
+        assert!(current_version(&history).is_none());
+    }
+
+    #[test]
+    fn test_matching_version() {
+        // ---
+        let history = versions(&["1.1.0", "1.2.5", "1.3.0-beta.1", "2.0.0"]);
+        let caret = parse_version_req("^1.2").unwrap();
+        assert_eq!(
+            matching_version(&history, &caret, false).unwrap().version,
+            "1.2.5"
+        );
+        assert_eq!(
+            matching_version(&history, &caret, true).unwrap().version,
+            "1.3.0-beta.1"
+        );
+        let none = parse_version_req(">=3").unwrap();
+        assert!(matching_version(&history, &none, true).is_none());
+    }
+}
@@ -4,15 +4,45 @@
 //! This trait abstracts over the storage mechanism for retrieving crate summaries.
 //! Used in services like `digest_send()` to decouple from the database backend.
 
+use super::{
+    timestamp_cursor_key, version_sort_key, AppUser, CrateVersion, Cursor, NewCrateVersion, Page,
+    SortOrder,
+};
 use anyhow::Result;
 use async_trait::async_trait;
+use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
 
-/// A simplified projection of a crate used in digest emails or summaries.
+/// Results returned when a search does not ask for a limit.
+pub const DEFAULT_SEARCH_LIMIT: i64 = 20;
+
+/// Most results a single search may return.
+pub const MAX_SEARCH_LIMIT: i64 = 100;
+
+/// Longest search query accepted, in characters.
+pub const MAX_SEARCH_QUERY_LEN: usize = 200;
+
+/// Whether a published version introduced a crate or released a new version of it.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReleaseKind {
+    NewCrate,
+    NewVersion,
+}
+
+/// A simplified projection of a published crate version used in digest emails or
+/// summaries.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct CrateSummary {
     pub name: String,
+    pub code: String,
     pub version: String,
+    /// The version's description, falling back to the crate's.
+    pub description: Option<String>,
+    pub published_at: NaiveDateTime,
+    pub kind: ReleaseKind,
 }
 
 #[async_trait]
@@ -21,20 +51,50 @@ pub trait CrateTableTrait: Send + Sync {
     /// Fetch a list of crates up to max limit.
     async fn find_multiple(&self, limit: i64) -> Result<Vec<Crate>>;
 
+    /// Fetch one page of crates matching `query`, with the total across all pages.
+    async fn find_page(&self, query: &CrateQuery) -> Result<Page<Crate>>;
+
+    /// Full-text search over crate names and descriptions, best matches first, at
+    /// most `limit` results. Names also match on trigram similarity, so small typos
+    /// still find the crate.
+    async fn search(&self, query: &str, limit: i64) -> Result<Vec<CrateSearchHit>>;
+
     /// Find a single crate by its ID.
     async fn find(&self, id: i32) -> Result<Crate>;
 
-    /// Create a new crate.
+    /// Create a new crate, publishing its first version.
     async fn create(&self, new: NewCrate) -> Result<Crate>;
 
-    /// Update an existing crate by ID.
+    /// Update an existing crate by ID; a changed version is published as a release,
+    /// and the crate's `version` stays the latest one by SemVer precedence.
     async fn update(&self, id: i32, updated: NewCrate) -> Result<Crate>;
 
     /// Delete a crate by ID.
     async fn delete(&self, id: i32) -> Result<()>;
 
-    /// Return crate summaries modified within the last N hours.
+    /// Return summaries of the versions published within the last N hours, oldest
+    /// first, leaving out yanked versions.
     async fn find_since(&self, hours_since: i32) -> Result<Vec<CrateSummary>>;
+
+    /// All versions of a crate, newest first.
+    async fn find_versions(&self, crate_id: i32) -> Result<Vec<CrateVersion>>;
+
+    /// Publish a version; it becomes the crate's current `version` if it is the latest
+    /// by SemVer precedence (see `domain::current_version`).
+    async fn publish_version(
+        &self,
+        crate_id: i32,
+        new_version: NewCrateVersion,
+    ) -> Result<CrateVersion>;
+
+    /// Yank or un-yank a version, keeping the crate's current `version` the latest
+    /// version that is not yanked. `None` if the crate has no such version.
+    async fn set_version_yanked(
+        &self,
+        crate_id: i32,
+        version: &str,
+        yanked: bool,
+    ) -> Result<Option<CrateVersion>>;
 }
 
 /// Shared pointer to trait object (dyn dispatch).
@@ -48,10 +108,33 @@ pub struct Crate {
     pub code: String,
     pub name: String,
     pub version: String,
+    /// Every version is yanked; `version` is the last one that counted.
+    #[serde(default)]
+    pub yanked: bool,
     pub description: Option<String>,
     pub created_at: chrono::NaiveDateTime,
 }
 
+impl Crate {
+    // ---
+    /// Whether `user` is linked to the author profile that owns this crate.
+    pub fn is_owned_by(&self, user: &AppUser) -> bool {
+        user.author_id == Some(self.author_id)
+    }
+}
+
+/// A crate matching a search.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CrateSearchHit {
+    #[serde(flatten)]
+    pub krate: Crate,
+    /// Full-text rank plus name similarity; higher is a better match.
+    pub rank: f32,
+    /// Excerpt of the description (the name when there is none) as HTML-escaped text,
+    /// with the matched words wrapped in `<mark>`.
+    pub snippet: String,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct NewCrate {
     pub author_id: i32,
@@ -60,3 +143,92 @@ pub struct NewCrate {
     pub version: String,
     pub description: Option<String>,
 }
+
+/// Sort keys for crate lists; ties are broken by id. `Version` follows SemVer
+/// precedence (see `version_sort_key`).
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CrateSort {
+    Name,
+    #[default]
+    CreatedAt,
+    Version,
+}
+
+impl CrateSort {
+    // ---
+    /// Order used when none is requested: newest first by date, ascending otherwise.
+    pub fn default_order(self) -> SortOrder {
+        match self {
+            CrateSort::CreatedAt => SortOrder::Desc,
+            CrateSort::Name | CrateSort::Version => SortOrder::Asc,
+        }
+    }
+
+    /// Cursor text of this sort key's value for `krate`.
+    pub fn cursor_for(self, krate: &Crate) -> Cursor {
+        let key = match self {
+            CrateSort::Name => krate.name.clone(),
+            CrateSort::CreatedAt => timestamp_cursor_key(&krate.created_at),
+            CrateSort::Version => version_sort_key(&krate.version),
+        };
+        Cursor::new(self, key, krate.id)
+    }
+}
+
+impl fmt::Display for CrateSort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CrateSort::Name => "name",
+            CrateSort::CreatedAt => "created_at",
+            CrateSort::Version => "version",
+        })
+    }
+}
+
+impl FromStr for CrateSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "name" => Ok(CrateSort::Name),
+            "created_at" => Ok(CrateSort::CreatedAt),
+            "version" => Ok(CrateSort::Version),
+            _ => Err(format!(
+                "Invalid sort: '{s}'. Valid sorts: name, created_at, version"
+            )),
+        }
+    }
+}
+
+/// Query for one page of crates; every filter that is set must match.
+///
+/// The default lists the newest crates first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrateQuery {
+    pub author_id: Option<i32>,
+    /// Case-insensitive prefix of the crate name.
+    pub name_prefix: Option<String>,
+    /// Only crates created at or after this time.
+    pub created_since: Option<NaiveDateTime>,
+    pub sort: CrateSort,
+    pub order: SortOrder,
+    /// Continue after this position; must have been issued for `sort`.
+    pub cursor: Option<Cursor>,
+    /// Most crates to return; `None` for `DEFAULT_PAGE_LIMIT`.
+    pub limit: Option<i64>,
+}
+
+impl Default for CrateQuery {
+    fn default() -> Self {
+        Self {
+            author_id: None,
+            name_prefix: None,
+            created_since: None,
+            sort: CrateSort::CreatedAt,
+            order: CrateSort::CreatedAt.default_order(),
+            cursor: None,
+            limit: None,
+        }
+    }
+}
@@ -0,0 +1,147 @@
+// src/domain/login_throttle.rs
+//! Brute-force protection for password logins.
+//!
+//! Failed attempts are counted in the cache per username and per client IP. Once a
+//! counter reaches the configured threshold the subject is locked out, and every
+//! further failure doubles the lockout (capped). Counters are cleared by a
+//! successful login (username only) or by an administrator (`cr8s-cli unlock-user`).
+
+use super::CacheContextTraitPtr;
+use anyhow::Result;
+
+const USER_KEY_PREFIX: &str = "user:";
+const IP_KEY_PREFIX: &str = "ip:";
+
+/// Lockout settings, read from the environment.
+#[derive(Debug, Clone, Copy)]
+pub struct LockoutPolicy {
+    /// Failed attempts allowed for a username before its first lockout.
+    pub max_failures: u64,
+    /// Failed attempts allowed from one client IP (across usernames) before its
+    /// first lockout; higher than `max_failures` since IPs can be shared.
+    pub max_failures_per_ip: u64,
+    /// Length of the first lockout; doubles with each further failure.
+    pub base_lockout_secs: u64,
+    /// Upper bound on a single lockout.
+    pub max_lockout_secs: u64,
+    /// Failure counters are forgotten after this long without a new failure.
+    pub failure_window_secs: u64,
+}
+
+impl LockoutPolicy {
+    // ---
+    /// Respects env vars:
+    /// - `CR8S_LOGIN_MAX_FAILURES` (default: 5)
+    /// - `CR8S_LOGIN_MAX_FAILURES_PER_IP` (default: 20)
+    /// - `CR8S_LOGIN_LOCKOUT_BASE_SECS` (default: 60)
+    /// - `CR8S_LOGIN_LOCKOUT_MAX_SECS` (default: 3600)
+    /// - `CR8S_LOGIN_FAILURE_WINDOW_SECS` (default: 900)
+    pub fn from_env() -> Self {
+        // ---
+        Self {
+            max_failures: crate::get_env_with_default!(u64, "CR8S_LOGIN_MAX_FAILURES", 5).max(1),
+            max_failures_per_ip: crate::get_env_with_default!(
+                u64,
+                "CR8S_LOGIN_MAX_FAILURES_PER_IP",
+                20
+            )
+            .max(1),
+            base_lockout_secs: crate::get_env_with_default!(
+                u64,
+                "CR8S_LOGIN_LOCKOUT_BASE_SECS",
+                60
+            ),
+            max_lockout_secs: crate::get_env_with_default!(
+                u64,
+                "CR8S_LOGIN_LOCKOUT_MAX_SECS",
+                3600
+            ),
+            failure_window_secs: crate::get_env_with_default!(
+                u64,
+                "CR8S_LOGIN_FAILURE_WINDOW_SECS",
+                900
+            ),
+        }
+    }
+
+    /// Lockout to apply to a throttle key after `failures` consecutive failures, or
+    /// `None` if it is still below its threshold.
+    pub fn lockout_secs(&self, key: &str, failures: u64) -> Option<u64> {
+        // ---
+        let threshold = if key.starts_with(IP_KEY_PREFIX) {
+            self.max_failures_per_ip
+        } else {
+            self.max_failures
+        };
+
+        let excess = failures.checked_sub(threshold)?;
+        let factor = 1u64.checked_shl(excess.min(63) as u32).unwrap_or(u64::MAX);
+
+        Some(
+            self.base_lockout_secs
+                .saturating_mul(factor)
+                .min(self.max_lockout_secs),
+        )
+    }
+}
+
+/// Cache keys throttling a login attempt: always the username, plus the client IP
+/// when known.
+pub fn login_throttle_keys(username: &str, client_ip: Option<&str>) -> Vec<String> {
+    // ---
+    let mut keys = vec![format!("{USER_KEY_PREFIX}{}", username.to_lowercase())];
+    if let Some(ip) = client_ip {
+        keys.push(format!("{IP_KEY_PREFIX}{ip}"));
+    }
+    keys
+}
+
+/// Longest remaining lockout among `keys`, in seconds, or `None` if none is locked.
+pub async fn login_lockout_remaining(
+    cache: &CacheContextTraitPtr,
+    keys: &[String],
+) -> Result<Option<u64>> {
+    // ---
+    let mut remaining = None;
+    for key in keys {
+        if let Some(secs) = cache.login_lock_ttl(key).await? {
+            remaining = remaining.max(Some(secs));
+        }
+    }
+    Ok(remaining)
+}
+
+/// Count a failed attempt against every key, locking those that crossed the
+/// threshold. Returns the longest lockout that was just applied, if any.
+pub async fn record_login_failure(
+    cache: &CacheContextTraitPtr,
+    policy: &LockoutPolicy,
+    keys: &[String],
+) -> Result<Option<u64>> {
+    // ---
+    let mut applied = None;
+    for key in keys {
+        let failures = cache
+            .incr_login_failures(key, policy.failure_window_secs)
+            .await?;
+
+        if let Some(secs) = policy.lockout_secs(key, failures) {
+            cache.lock_login(key, secs).await?;
+            applied = applied.max(Some(secs));
+        }
+    }
+    Ok(applied)
+}
+
+/// Clear failure counters and lockouts for a username and, optionally, a client IP.
+pub async fn unlock_login(
+    cache: &CacheContextTraitPtr,
+    username: &str,
+    client_ip: Option<&str>,
+) -> Result<()> {
+    // ---
+    for key in login_throttle_keys(username, client_ip) {
+        cache.clear_login_failures(&key).await?;
+    }
+    Ok(())
+}
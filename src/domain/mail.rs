@@ -7,6 +7,16 @@ use anyhow::Result;
 #[async_trait::async_trait]
 pub trait MailerTrait: Send + Sync {
     async fn send_digest(&self, to: &str, crates: &[CrateSummary]) -> Result<()>;
+
+    /// Render the named template (e.g. `"email/password_reset.html"`) with `context`
+    /// and send the result as an HTML email.
+    async fn send_templated(
+        &self,
+        to: &str,
+        subject: &str,
+        template: &str,
+        context: serde_json::Value,
+    ) -> Result<()>;
 }
 
 /// Type alias for dynamic mailer implementation.
@@ -18,3 +28,7 @@ pub type MailerTraitPtr = std::sync::Arc<dyn MailerTrait>;
 /// here to maintain domain-layer abstraction.  The concrete type (`HtmlMailer`) is
 /// deliberately hidden.
 pub use crate::mail::create_mailer;
+
+/// Fallback `MailerTrait` implementation for when SMTP is not configured; every send
+/// fails with the given reason so callers can log it.
+pub use crate::mail::create_unconfigured_mailer;
@@ -7,10 +7,21 @@
 //! | Area          | Module          | Description                                        |
 //! | ------------- | --------------  | -------------------------------------------------- |
 //! | authorization | `authorization` | Login identity and authentication behavior         |
+//! | API tokens    | `api_token`     | Scoped personal access tokens for automation       |
+//! | Audit log     | `audit`         | Append-only record of create/update/delete events  |
 //! | Contributors  | `author`        | Rust ecosystem contributors and authorship logic   |
 //! | Crates        | `crate_`        | Published Rust crates and associated metadata      |
-//! | RBAC          | `role`, `user_role` | Role-based access control and assignment       |
+//! | Crate versions | `crate_version` | Version history of each crate, SemVer helpers    |
+//! | Pagination    | `page`          | Cursor pages, sort order and list filters          |
+//! | RBAC          | `role_code`, `user_role` | Roles, and granting/revoking them on users |
+//! | User admin    | `user_admin`    | Creating, disabling and deleting user accounts     |
+//! | Permissions   | `permission`    | Permissions checked by route guards                |
 //! | Passwords     | `password`      | Password hashing and credential validation         |
+//! | Password policy | `password_policy` | Length, character class and breached-password rules |
+//! | Login throttling | `login_throttle` | Failed-login counters and exponential lockout  |
+//! | Password reset | `password_reset` | Emailed single-use password reset tokens         |
+//! | Two-factor    | `two_factor`    | TOTP enrollment, recovery codes, login challenges  |
+//! | Single sign-on | `oidc`         | OpenID Connect login and user provisioning         |
 //! | Email         | `mail`          | Outbound email dispatching via `MailerTrait`       |
 //!
 //! Traits in this layer define the **expected behavior** without prescribing the
@@ -32,15 +43,41 @@
 
 // Re-export domain models and traits for external use
 
+mod api_token;
 mod app_user;
+mod audit;
 mod author;
 mod authorization;
 mod cache;
+mod crate_version;
 mod health;
 mod krate;
+mod login_throttle;
 mod mail;
+mod oidc;
+mod page;
 mod password;
+mod password_policy;
+mod password_reset;
+mod permission;
 mod role_code;
+mod two_factor;
+mod user_admin;
+mod user_role;
+
+pub use api_token::{
+    //
+    authenticate_api_token,
+    create_api_token_repo,
+    is_api_token,
+    issue_api_token,
+    ApiScope,
+    ApiToken,
+    ApiTokenError,
+    ApiTokenTableTrait,
+    ApiTokenTableTraitPtr,
+    NewApiToken,
+};
 
 pub use app_user::{
     //
@@ -50,27 +87,82 @@ pub use app_user::{
     AppUserTableTraitPtr,
     AppUserWithRoleCodes,
     NewUser,
+    RecoveryCode,
+    TotpSecret,
+};
+
+pub use audit::{
+    //
+    create_audit_log_repo,
+    record_audit_event,
+    AuditAction,
+    AuditEntity,
+    AuditEvent,
+    AuditFilter,
+    AuditLogTrait,
+    AuditLogTraitPtr,
+    NewAuditEvent,
+    DEFAULT_AUDIT_LIMIT,
+    MAX_AUDIT_LIMIT,
 };
 
 pub use authorization::{
     //
     authenticate_user,
+    change_password,
     create_password_hasher,
+    set_password,
     Credentials,
     LoginError,
+    LoginOutcome,
+    PasswordChange,
+    PasswordReset,
 };
 
 pub use author::{
     //
+    confirm_author_claim,
     create_author_repo,
+    link_author,
+    request_author_claim,
     Author,
+    AuthorClaimConfirm,
+    AuthorLink,
+    AuthorLinkError,
+    AuthorQuery,
+    AuthorSort,
     AuthorTableTrait,
     AuthorTableTraitPtr,
     NewAuthor,
 };
 
-pub use cache::{create_cache_context, CacheContextTrait, CacheContextTraitPtr};
+pub use cache::{
+    //
+    create_cache_context,
+    AccessClaims,
+    AccessToken,
+    CacheContextTrait,
+    CacheContextTraitPtr,
+    NewSession,
+    SessionRecord,
+};
 
+pub use crate_version::{
+    //
+    current_version,
+    latest_version,
+    matching_version,
+    parse_version,
+    parse_version_req,
+    publish_crate_version,
+    set_crate_version_yanked,
+    sort_by_precedence,
+    version_sort_key,
+    CrateVersion,
+    CrateVersionError,
+    NewCrateVersion,
+    YankChange,
+};
 pub use health::{
     // ---
     create_cache_health_service,
@@ -82,21 +174,89 @@ pub use krate::{
     //
     create_crate_repo,
     Crate,
+    CrateQuery,
+    CrateSearchHit,
+    CrateSort,
     CrateSummary,
     CrateTableTrait,
     CrateTableTraitPtr,
     NewCrate,
+    ReleaseKind,
+    DEFAULT_SEARCH_LIMIT,
+    MAX_SEARCH_LIMIT,
+    MAX_SEARCH_QUERY_LEN,
+};
+pub use login_throttle::{unlock_login, LockoutPolicy};
+pub use mail::{create_mailer, create_unconfigured_mailer, MailerTrait, MailerTraitPtr};
+pub use oidc::{
+    //
+    complete_oidc_login,
+    create_oidc_provider,
+    create_unconfigured_oidc_provider,
+    start_oidc_login,
+    OidcError,
+    OidcIdentity,
+    OidcProviderTrait,
+    OidcProviderTraitPtr,
+};
+pub use page::{
+    //
+    effective_page_limit,
+    into_page,
+    parse_since,
+    timestamp_cursor_key,
+    Cursor,
+    Page,
+    SortOrder,
+    DEFAULT_PAGE_LIMIT,
+    MAX_PAGE_LIMIT,
 };
-pub use mail::{create_mailer, MailerTrait, MailerTraitPtr};
 pub use password::{PasswordHasherTrait, PasswordHasherTraitPtr};
+pub use password_policy::{CharacterClass, PasswordPolicy, PasswordPolicyError};
+pub use password_reset::{
+    //
+    confirm_password_reset,
+    request_password_reset,
+    PasswordResetConfirm,
+    PasswordResetRequest,
+};
+pub use permission::{roles_grant, Permission};
 pub use role_code::{
     //
+    create_role,
     create_role_code_repo,
+    delete_role,
+    update_role,
+    NewRole,
     Role,
     RoleCode,
     RoleCodeTableTrait,
     RoleCodeTableTraitPtr,
+    RoleError,
+    RoleUpdate,
+};
+pub use two_factor::{
+    //
+    complete_second_factor,
+    confirm_totp,
+    enroll_totp,
+    SecondFactorLogin,
+    TotpCode,
+    TotpEnrollment,
+    TwoFactorError,
+};
+pub use user_admin::{
+    //
+    check_can_manage,
+    create_user_account,
+    delete_user_account,
+    find_user,
+    set_account_disabled,
+    NewUserAccount,
+    UserAdminError,
+    UserSummary,
 };
+pub use user_role::{assign_roles, AssignedRoles, RoleAssignment, UserRoles};
 
 /// Public hook exposed to CLI/server to initialize DB at startup.
 pub async fn init_database_with_retry_from_env() -> anyhow::Result<()> {
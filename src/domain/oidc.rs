@@ -0,0 +1,451 @@
+// src/domain/oidc.rs
+//! OpenID Connect single sign-on (authorization code flow with PKCE).
+//!
+//! `start_oidc_login` keeps the PKCE verifier and nonce in the cache under a random
+//! `state` and returns the identity provider (IdP) URL to redirect the browser to.
+//! `complete_oidc_login` consumes that state, redeems the code through
+//! `OidcProviderTrait`, and maps the IdP subject to an `app_user`, provisioning one on
+//! first login. Callers then start an ordinary session for the returned user.
+
+use super::{AppUser, AppUserTableTraitPtr, CacheContextTraitPtr, NewUser, RoleCode};
+use anyhow::Result;
+use data_encoding::BASE64URL_NOPAD;
+use rand::distr::{Alphanumeric, SampleString};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+/// Length of the generated `state`, `nonce` and PKCE code verifier.
+const RANDOM_PARAM_LEN: usize = 43;
+
+/// Identity asserted by the IdP in a validated ID token.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OidcIdentity {
+    pub issuer: String,
+    pub subject: String,
+    pub email: Option<String>,
+    pub email_verified: bool,
+    pub preferred_username: Option<String>,
+}
+
+/// Client side of an OpenID Connect provider.
+#[async_trait::async_trait]
+pub trait OidcProviderTrait: Send + Sync {
+    /// `false` if single sign-on is not set up; every other method may then fail.
+    fn is_configured(&self) -> bool {
+        true
+    }
+
+    /// Authorization endpoint URL that starts a login with these parameters.
+    fn authorization_url(&self, state: &str, nonce: &str, code_challenge: &str) -> Result<String>;
+
+    /// Redeem an authorization code. The ID token's issuer, audience, expiry and
+    /// `nonce` must be checked before the identity is returned.
+    async fn exchange_code(
+        &self,
+        code: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> Result<OidcIdentity>;
+
+    /// Role given to users provisioned on their first single sign-on.
+    fn default_role(&self) -> RoleCode;
+}
+
+/// Shared trait object for the configured OpenID Connect provider.
+pub type OidcProviderTraitPtr = Arc<dyn OidcProviderTrait>;
+
+/// Factory for the `OidcProviderTrait` implementation.
+///
+/// Note: this is implemented in the `oidc` infrastructure layer, and re-exported here
+/// to maintain domain-layer abstraction.
+pub use crate::oidc::create_oidc_provider;
+
+/// Fallback `OidcProviderTrait` implementation for when single sign-on is not
+/// configured.
+pub use crate::oidc::create_unconfigured_oidc_provider;
+
+/// Failure modes of single sign-on.
+#[derive(Debug, thiserror::Error)]
+pub enum OidcError {
+    #[error("single sign-on is not configured")]
+    NotConfigured,
+    #[error("unknown or expired login state")]
+    InvalidState,
+    #[error("identity provider login failed: {0}")]
+    Rejected(String),
+    #[error("username {0} belongs to a local account")]
+    UsernameTaken(String),
+    #[error("account {0} is disabled")]
+    AccountDisabled(String),
+    #[error("internal error: {0}")]
+    Internal(String),
+}
+
+/// What is remembered between the redirect to the IdP and its callback.
+#[derive(Serialize, Deserialize)]
+struct PendingOidcLogin {
+    code_verifier: String,
+    nonce: String,
+}
+
+fn random_param() -> String {
+    // ---
+    Alphanumeric.sample_string(&mut rand::rng(), RANDOM_PARAM_LEN)
+}
+
+/// PKCE `S256` challenge for a code verifier (RFC 7636).
+fn pkce_challenge(code_verifier: &str) -> String {
+    // ---
+    BASE64URL_NOPAD.encode(&Sha256::digest(code_verifier.as_bytes()))
+}
+
+/// Begins a login: remembers a fresh PKCE verifier and nonce under a random `state`
+/// and returns the IdP URL to redirect to.
+///
+/// Respects env var `CR8S_OIDC_STATE_TTL_SECS` (default: 600), the time allowed to
+/// complete the login at the IdP.
+pub async fn start_oidc_login(
+    provider: &OidcProviderTraitPtr,
+    cache: &CacheContextTraitPtr,
+) -> Result<String, OidcError> {
+    // ---
+    if !provider.is_configured() {
+        return Err(OidcError::NotConfigured);
+    }
+
+    let ttl_secs = crate::get_env_with_default!(u64, "CR8S_OIDC_STATE_TTL_SECS", 600);
+    let state = random_param();
+    let pending = PendingOidcLogin {
+        code_verifier: random_param(),
+        nonce: random_param(),
+    };
+
+    let url = provider
+        .authorization_url(
+            &state,
+            &pending.nonce,
+            &pkce_challenge(&pending.code_verifier),
+        )
+        .map_err(|err| OidcError::Internal(err.to_string()))?;
+
+    let value =
+        serde_json::to_string(&pending).map_err(|err| OidcError::Internal(err.to_string()))?;
+    cache
+        .set_oidc_login_state(&state, &value, ttl_secs)
+        .await
+        .map_err(|err| OidcError::Internal(err.to_string()))?;
+
+    Ok(url)
+}
+
+/// Finishes a login from the IdP callback and returns the signed-in user.
+///
+/// The `state` is single use. Users are matched by IdP issuer and subject; on first
+/// login the user is linked to an existing account with the same username only if
+/// the IdP verified that email address, otherwise a new account is provisioned with
+/// the provider's default role and an unusable random password (see
+/// [`provisioned_username`]).
+pub async fn complete_oidc_login(
+    repo: AppUserTableTraitPtr,
+    cache: CacheContextTraitPtr,
+    provider: OidcProviderTraitPtr,
+    code: &str,
+    state: &str,
+) -> Result<AppUser, OidcError> {
+    // ---
+    if !provider.is_configured() {
+        return Err(OidcError::NotConfigured);
+    }
+
+    let pending = cache
+        .take_oidc_login_state(state)
+        .await
+        .map_err(|err| OidcError::Internal(err.to_string()))?
+        .ok_or(OidcError::InvalidState)?;
+    let pending: PendingOidcLogin =
+        serde_json::from_str(&pending).map_err(|_| OidcError::InvalidState)?;
+
+    let identity = provider
+        .exchange_code(code, &pending.code_verifier, &pending.nonce)
+        .await
+        .map_err(|err| OidcError::Rejected(format!("{err:#}")))?;
+
+    let internal = |err: anyhow::Error| OidcError::Internal(err.to_string());
+
+    if let Some(user) = repo
+        .find_by_identity(&identity.issuer, &identity.subject)
+        .await
+        .map_err(internal)?
+    {
+        if !user.is_active() {
+            return Err(OidcError::AccountDisabled(user.username));
+        }
+        return Ok(user);
+    }
+
+    let username = provisioned_username(&identity);
+
+    let user = match repo.find_by_username(&username).await {
+        Ok(existing) if identity.email_verified && identity.email.as_ref() == Some(&username) => {
+            // A disabled account must not gain a way in through its IdP identity
+            if !existing.is_active() {
+                return Err(OidcError::AccountDisabled(existing.username));
+            }
+            tracing::info!("🔗 Linking user id {} to its IdP identity", existing.id);
+            existing
+        }
+        Ok(_) => return Err(OidcError::UsernameTaken(username)),
+        Err(_) => {
+            // ---
+            let password = crate::auth::create_password_hasher()
+                .and_then(|hasher| hasher.hash_password(&random_param()))
+                .map_err(internal)?;
+
+            let new_user = NewUser {
+                username: username.clone(),
+                password,
+            };
+            let user = repo
+                .create(new_user, vec![provider.default_role()])
+                .await
+                .map_err(internal)?;
+
+            tracing::info!("👤 Provisioned user id {} on first single sign-on", user.id);
+            user
+        }
+    };
+
+    repo.link_identity(user.id, &identity.issuer, &identity.subject)
+        .await
+        .map_err(internal)?;
+
+    Ok(user)
+}
+
+/// Username for an IdP identity: its email address if the IdP verified it, otherwise
+/// `<issuer host>/<preferred_username or subject>`.
+///
+/// Unverified names are scoped to the issuer so they can never equal an email
+/// address or a local account, and so cannot be used to take one over.
+fn provisioned_username(identity: &OidcIdentity) -> String {
+    // ---
+    if identity.email_verified {
+        if let Some(email) = &identity.email {
+            return email.clone();
+        }
+    }
+
+    let issuer = reqwest::Url::parse(&identity.issuer)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_else(|| identity.issuer.clone());
+    let name = identity
+        .preferred_username
+        .as_deref()
+        .unwrap_or(&identity.subject);
+    format!("{issuer}/{name}")
+}
+
+#[cfg(test)]
+mod tests {
+    // ---
+    use super::*;
+    use crate::test_support::{MockAppUserRepo, MockCacheContext};
+    use chrono::Utc;
+    use std::sync::Mutex;
+
+    /// IdP that redeems `"good-code"` for `identity` after checking the PKCE pair.
+    struct MockProvider {
+        identity: OidcIdentity,
+        challenge: Mutex<Option<String>>,
+    }
+
+    impl MockProvider {
+        fn new(email: Option<&str>, email_verified: bool) -> Self {
+            // ---
+            Self {
+                identity: OidcIdentity {
+                    issuer: "https://idp.example.com".into(),
+                    subject: "sub-123".into(),
+                    email: email.map(Into::into),
+                    email_verified,
+                    preferred_username: Some("alice".into()),
+                },
+                challenge: Mutex::new(None),
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl OidcProviderTrait for MockProvider {
+        // ---
+        fn authorization_url(&self, state: &str, nonce: &str, challenge: &str) -> Result<String> {
+            // ---
+            *self.challenge.lock().unwrap() = Some(challenge.into());
+            Ok(format!(
+                "https://idp.example.com/auth?state={state}&nonce={nonce}"
+            ))
+        }
+
+        async fn exchange_code(
+            &self,
+            code: &str,
+            code_verifier: &str,
+            _nonce: &str,
+        ) -> Result<OidcIdentity> {
+            // ---
+            anyhow::ensure!(code == "good-code", "invalid_grant");
+            anyhow::ensure!(
+                self.challenge.lock().unwrap().as_deref() == Some(&pkce_challenge(code_verifier)),
+                "PKCE verification failed"
+            );
+            Ok(self.identity.clone())
+        }
+
+        fn default_role(&self) -> RoleCode {
+            RoleCode::Viewer
+        }
+    }
+
+    fn state_of(url: &str) -> String {
+        // ---
+        url.split("state=")
+            .nth(1)
+            .unwrap()
+            .split('&')
+            .next()
+            .unwrap()
+            .into()
+    }
+
+    #[test]
+    fn test_pkce_challenge_matches_rfc_7636_example() {
+        // ---
+        assert_eq!(
+            pkce_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_first_login_provisions_user_then_reuses_link() {
+        // ---
+        let mock_repo = Arc::new(MockAppUserRepo::default());
+        let repo: AppUserTableTraitPtr = mock_repo.clone();
+        let cache: CacheContextTraitPtr = Arc::new(MockCacheContext::default());
+        let provider: OidcProviderTraitPtr =
+            Arc::new(MockProvider::new(Some("alice@example.com"), true));
+
+        for _ in 0..2 {
+            let url = start_oidc_login(&provider, &cache).await.unwrap();
+            let user = complete_oidc_login(
+                repo.clone(),
+                cache.clone(),
+                provider.clone(),
+                "good-code",
+                &state_of(&url),
+            )
+            .await
+            .unwrap();
+
+            assert_eq!(user.id, 1);
+            assert_eq!(user.username, "alice@example.com");
+            assert_eq!(
+                repo.find_roles_by_user(&user).await.unwrap(),
+                vec![RoleCode::Viewer]
+            );
+        }
+        assert_eq!(mock_repo.users.lock().unwrap().len(), 1);
+        assert_eq!(mock_repo.identities.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_state_is_single_use_and_code_is_checked() {
+        // ---
+        let repo: AppUserTableTraitPtr = Arc::new(MockAppUserRepo::default());
+        let cache: CacheContextTraitPtr = Arc::new(MockCacheContext::default());
+        let provider: OidcProviderTraitPtr = Arc::new(MockProvider::new(None, false));
+
+        let url = start_oidc_login(&provider, &cache).await.unwrap();
+        let state = state_of(&url);
+
+        let result =
+            complete_oidc_login(repo.clone(), cache.clone(), provider.clone(), "bad", &state).await;
+        assert!(matches!(result, Err(OidcError::Rejected(_))));
+
+        // The failed attempt consumed the state
+        let result = complete_oidc_login(repo, cache, provider, "good-code", &state).await;
+        assert!(matches!(result, Err(OidcError::InvalidState)));
+    }
+
+    #[test]
+    fn test_unverified_identities_get_issuer_scoped_usernames() {
+        // ---
+        let mut identity = MockProvider::new(Some("alice@example.com"), true).identity;
+        assert_eq!(provisioned_username(&identity), "alice@example.com");
+
+        identity.email_verified = false;
+        assert_eq!(provisioned_username(&identity), "idp.example.com/alice");
+
+        identity.preferred_username = None;
+        assert_eq!(provisioned_username(&identity), "idp.example.com/sub-123");
+    }
+
+    #[tokio::test]
+    async fn test_existing_username_is_linked_only_when_email_verified() {
+        // ---
+        let mock_repo = Arc::new(MockAppUserRepo::default());
+        let local_user = mock_repo.add_user("alice@example.com", "local-hash", vec![]);
+        let repo: AppUserTableTraitPtr = mock_repo.clone();
+        let cache: CacheContextTraitPtr = Arc::new(MockCacheContext::default());
+
+        let unverified: OidcProviderTraitPtr =
+            Arc::new(MockProvider::new(Some("alice@example.com"), false));
+        let url = start_oidc_login(&unverified, &cache).await.unwrap();
+        let provisioned = complete_oidc_login(
+            repo.clone(),
+            cache.clone(),
+            unverified,
+            "good-code",
+            &state_of(&url),
+        )
+        .await
+        .unwrap();
+        assert_ne!(provisioned.id, local_user.id);
+        assert_eq!(provisioned.username, "idp.example.com/alice");
+
+        // Forget that link so the same subject signs in afresh, now with a verified email
+        mock_repo.identities.lock().unwrap().clear();
+        let verified: OidcProviderTraitPtr =
+            Arc::new(MockProvider::new(Some("alice@example.com"), true));
+        let url = start_oidc_login(&verified, &cache).await.unwrap();
+        let user = complete_oidc_login(repo, cache, verified, "good-code", &state_of(&url))
+            .await
+            .unwrap();
+        assert_eq!(user.id, local_user.id);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_user_is_not_linked_by_verified_email() {
+        // ---
+        let mock_repo = Arc::new(MockAppUserRepo::default());
+        let local_user = mock_repo.add_user("alice@example.com", "local-hash", vec![]);
+        mock_repo.edit_user(local_user.id, |user| {
+            user.disabled_at = Some(Utc::now().naive_utc())
+        });
+        let repo: AppUserTableTraitPtr = mock_repo.clone();
+        let cache: CacheContextTraitPtr = Arc::new(MockCacheContext::default());
+        let provider: OidcProviderTraitPtr =
+            Arc::new(MockProvider::new(Some("alice@example.com"), true));
+
+        let url = start_oidc_login(&provider, &cache).await.unwrap();
+        let result = complete_oidc_login(repo, cache, provider, "good-code", &state_of(&url)).await;
+
+        assert!(matches!(result, Err(OidcError::AccountDisabled(_))));
+        assert!(
+            mock_repo.identities.lock().unwrap().is_empty(),
+            "no identity may be linked to a disabled account"
+        );
+    }
+}
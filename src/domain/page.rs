@@ -0,0 +1,207 @@
+// src/domain/page.rs
+//! Cursor-based pagination shared by the list queries.
+//!
+//! Pages are read with keyset pagination: the cursor names the sort key and holds the
+//! sort value and id of the last item returned, so the next page starts strictly after
+//! it and rows inserted meanwhile never shift items between pages.
+
+use chrono::{NaiveDate, NaiveDateTime};
+use data_encoding::BASE64URL_NOPAD;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Items returned when a query does not ask for a limit.
+pub const DEFAULT_PAGE_LIMIT: i64 = 100;
+
+/// Most items a single page may hold.
+pub const MAX_PAGE_LIMIT: i64 = 500;
+
+/// The requested limit, defaulted and clamped to `1..=MAX_PAGE_LIMIT`.
+pub fn effective_page_limit(limit: Option<i64>) -> i64 {
+    limit.unwrap_or(DEFAULT_PAGE_LIMIT).clamp(1, MAX_PAGE_LIMIT)
+}
+
+/// One page of a list, with the cursor of the next page and the number of items
+/// matching the filters across all pages.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Pass as `cursor` to get the next page; `None` on the last page.
+    pub next_cursor: Option<String>,
+    pub total: i64,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl fmt::Display for SortOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SortOrder::Asc => "asc",
+            SortOrder::Desc => "desc",
+        })
+    }
+}
+
+impl FromStr for SortOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "asc" => Ok(SortOrder::Asc),
+            "desc" => Ok(SortOrder::Desc),
+            _ => Err(format!("Invalid order: '{s}'. Valid orders: asc, desc")),
+        }
+    }
+}
+
+/// Position just after the last item of a page.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Cursor {
+    /// Sort key the page was read with, e.g. `name`.
+    pub sort: String,
+    /// Sort value of the last item, as text.
+    pub key: String,
+    /// Id of the last item, breaking ties between equal sort values.
+    pub id: i32,
+}
+
+impl Cursor {
+    // ---
+    pub fn new(sort: impl fmt::Display, key: String, id: i32) -> Self {
+        Self {
+            sort: sort.to_string(),
+            key,
+            id,
+        }
+    }
+
+    /// Opaque, URL-safe form handed out as `next_cursor`.
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_vec(self).unwrap_or_default();
+        BASE64URL_NOPAD.encode(&json)
+    }
+
+    /// Decode a cursor and check it was issued for the same sort key.
+    pub fn decode(encoded: &str, sort: impl fmt::Display) -> Result<Self, String> {
+        // ---
+        let cursor: Cursor = BASE64URL_NOPAD
+            .decode(encoded.trim().as_bytes())
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .ok_or_else(|| "Invalid cursor".to_string())?;
+
+        let sort = sort.to_string();
+        if cursor.sort != sort {
+            return Err(format!(
+                "Cursor was issued for sort '{}', not '{sort}'",
+                cursor.sort
+            ));
+        }
+        Ok(cursor)
+    }
+}
+
+/// Cursor text for a timestamp sort value, precise enough to compare equal in SQL.
+pub fn timestamp_cursor_key(timestamp: &NaiveDateTime) -> String {
+    timestamp.format("%Y-%m-%d %H:%M:%S%.6f").to_string()
+}
+
+/// Parse a `since` filter: a date (`2025-06-01`, midnight) or a date and time
+/// (`2025-06-01T12:00:00`, optionally with a UTC offset, converted to UTC).
+pub fn parse_since(since: &str) -> Result<NaiveDateTime, String> {
+    // ---
+    let since = since.trim();
+    if let Ok(date_time) = chrono::DateTime::parse_from_rfc3339(since) {
+        return Ok(date_time.naive_utc());
+    }
+    if let Ok(date_time) = NaiveDateTime::parse_from_str(since, "%Y-%m-%dT%H:%M:%S%.f") {
+        return Ok(date_time);
+    }
+    NaiveDate::parse_from_str(since, "%Y-%m-%d")
+        .map(|date| date.and_hms_opt(0, 0, 0).unwrap_or_default())
+        .map_err(|_| format!("Invalid date: '{since}'. Use YYYY-MM-DD or YYYY-MM-DDTHH:MM:SS"))
+}
+
+/// Split one extra row off the end of a result read with `limit + 1` rows, and build
+/// the cursor for the next page from the last row kept.
+pub fn into_page<T>(
+    mut items: Vec<T>,
+    limit: i64,
+    total: i64,
+    cursor_for: impl Fn(&T) -> Cursor,
+) -> Page<T> {
+    // ---
+    let has_more = items.len() as i64 > limit;
+    items.truncate(limit.max(0) as usize);
+
+    let next_cursor = match items.last() {
+        Some(last) if has_more => Some(cursor_for(last).encode()),
+        _ => None,
+    };
+    Page {
+        items,
+        next_cursor,
+        total,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // ---
+    use super::*;
+
+    #[test]
+    fn test_cursor_round_trip_checks_sort() {
+        // ---
+        let cursor = Cursor::new("name", "serde".into(), 7);
+        let encoded = cursor.encode();
+        assert!(encoded
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+
+        assert_eq!(Cursor::decode(&encoded, "name"), Ok(cursor));
+        assert!(Cursor::decode(&encoded, "created_at").is_err());
+        assert!(Cursor::decode("not-a-cursor", "name").is_err());
+    }
+
+    #[test]
+    fn test_into_page_and_limits() {
+        // ---
+        let page = into_page(vec![1, 2, 3], 2, 10, |n| {
+            Cursor::new("id", n.to_string(), *n)
+        });
+        assert_eq!(page.items, vec![1, 2]);
+        assert_eq!(page.total, 10);
+        let next = Cursor::decode(page.next_cursor.as_deref().unwrap(), "id").unwrap();
+        assert_eq!(next.id, 2);
+
+        let last = into_page(vec![1, 2], 2, 2, |n| Cursor::new("id", n.to_string(), *n));
+        assert_eq!(last.next_cursor, None);
+
+        assert_eq!(effective_page_limit(None), DEFAULT_PAGE_LIMIT);
+        assert_eq!(effective_page_limit(Some(0)), 1);
+        assert_eq!(effective_page_limit(Some(10_000)), MAX_PAGE_LIMIT);
+    }
+
+    #[test]
+    fn test_parse_since() {
+        // ---
+        let midnight = parse_since("2025-06-01").unwrap();
+        assert_eq!(
+            timestamp_cursor_key(&midnight),
+            "2025-06-01 00:00:00.000000"
+        );
+        assert_eq!(
+            parse_since("2025-06-01T12:30:00+02:00").unwrap(),
+            parse_since("2025-06-01T10:30:00").unwrap()
+        );
+        assert!(parse_since("yesterday").is_err());
+    }
+}
@@ -18,6 +18,12 @@ pub trait PasswordHasherTrait: Send + Sync {
     /// Verify a candidate password against a stored hash.
     fn verify_password(&self, hashed: &str, candidate: &str) -> Result<()>;
 
+    /// `true` if a stored hash was made with weaker settings than the hasher's
+    /// current ones and should be replaced after the next successful verification.
+    fn needs_rehash(&self, _hashed: &str) -> bool {
+        false
+    }
+
     /// Generate a random session token (e.g. for login state).
     fn generate_session_token(&self) -> String;
 }
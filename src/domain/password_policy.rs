@@ -0,0 +1,347 @@
+// src/domain/password_policy.rs
+//! Rules a new password must satisfy before it is hashed and stored.
+//!
+//! The policy checks length, required character classes, similarity to the username,
+//! and optionally an offline list of breached password hashes. The list is read in
+//! the SHA-1 format published by Have I Been Pwned, either as one file of full hashes
+//! or, k-anonymity style, as a directory of range files named by the first five hex
+//! digits of the hash and holding the remaining 35 (`SUFFIX[:COUNT]` per line).
+
+use sha1::{Digest, Sha1};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+const HASH_PREFIX_LEN: usize = 5;
+
+/// Shortest username fragment that counts as "contained" in a password.
+const MIN_USERNAME_FRAGMENT: usize = 3;
+
+/// A kind of character a policy can require.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharacterClass {
+    Lowercase,
+    Uppercase,
+    Digit,
+    Symbol,
+}
+
+impl CharacterClass {
+    // ---
+    fn matches(self, c: char) -> bool {
+        match self {
+            CharacterClass::Lowercase => c.is_lowercase(),
+            CharacterClass::Uppercase => c.is_uppercase(),
+            CharacterClass::Digit => c.is_ascii_digit(),
+            CharacterClass::Symbol => !c.is_alphanumeric() && !c.is_whitespace(),
+        }
+    }
+}
+
+impl fmt::Display for CharacterClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CharacterClass::Lowercase => "a lowercase letter",
+            CharacterClass::Uppercase => "an uppercase letter",
+            CharacterClass::Digit => "a digit",
+            CharacterClass::Symbol => "a symbol",
+        })
+    }
+}
+
+impl std::str::FromStr for CharacterClass {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "lowercase" | "lower" => Ok(CharacterClass::Lowercase),
+            "uppercase" | "upper" => Ok(CharacterClass::Uppercase),
+            "digit" | "digits" => Ok(CharacterClass::Digit),
+            "symbol" | "symbols" => Ok(CharacterClass::Symbol),
+            other => Err(format!("unknown character class: '{other}'")),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PasswordPolicyError {
+    #[error("password must be at least {0} characters")]
+    TooShort(usize),
+
+    #[error("password must be at most {0} characters")]
+    TooLong(usize),
+
+    #[error("password must contain {0}")]
+    MissingClass(CharacterClass),
+
+    #[error("password must not contain the username")]
+    ContainsUsername,
+
+    #[error("password appears in a list of breached passwords")]
+    Breached,
+
+    #[error("breached password list unavailable: {0}")]
+    ListUnavailable(String),
+}
+
+/// Password rules, read from the environment.
+#[derive(Debug, Clone)]
+pub struct PasswordPolicy {
+    /// Fewest characters (not bytes) a password may have.
+    pub min_length: usize,
+    /// Most characters a password may have, bounding the cost of hashing it.
+    pub max_length: usize,
+    /// Character classes that must each appear at least once.
+    pub required_classes: Vec<CharacterClass>,
+    /// Reject passwords containing the username or its part before `@`.
+    pub reject_username: bool,
+    /// Breached password hash file or range directory; `None` skips the check.
+    pub breached_list: Option<PathBuf>,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            max_length: 128,
+            required_classes: vec![],
+            reject_username: true,
+            breached_list: None,
+        }
+    }
+}
+
+impl PasswordPolicy {
+    // ---
+    /// Respects env vars:
+    /// - `CR8S_PASSWORD_MIN_LENGTH` (default: 8)
+    /// - `CR8S_PASSWORD_MAX_LENGTH` (default: 128)
+    /// - `CR8S_PASSWORD_REQUIRED_CLASSES` (default: none), comma-separated from
+    ///   `lowercase`, `uppercase`, `digit`, `symbol`; unknown entries are ignored
+    /// - `CR8S_PASSWORD_REJECT_USERNAME` (default: true)
+    /// - `CR8S_BREACHED_PASSWORDS_PATH` (default: unset)
+    pub fn from_env() -> Self {
+        // ---
+        let defaults = Self::default();
+        let min_length =
+            crate::get_env_with_default!(usize, "CR8S_PASSWORD_MIN_LENGTH", defaults.min_length)
+                .max(1);
+
+        let required_classes = std::env::var("CR8S_PASSWORD_REQUIRED_CLASSES")
+            .unwrap_or_default()
+            .split(',')
+            .filter(|class| !class.trim().is_empty())
+            .filter_map(|class| match class.parse() {
+                Ok(class) => Some(class),
+                Err(err) => {
+                    tracing::warn!("CR8S_PASSWORD_REQUIRED_CLASSES: {err}");
+                    None
+                }
+            })
+            .collect();
+
+        Self {
+            min_length,
+            max_length: crate::get_env_with_default!(
+                usize,
+                "CR8S_PASSWORD_MAX_LENGTH",
+                defaults.max_length
+            )
+            .max(min_length),
+            required_classes,
+            reject_username: crate::get_env_with_default!(
+                bool,
+                "CR8S_PASSWORD_REJECT_USERNAME",
+                defaults.reject_username
+            ),
+            breached_list: std::env::var("CR8S_BREACHED_PASSWORDS_PATH")
+                .ok()
+                .filter(|path| !path.trim().is_empty())
+                .map(PathBuf::from),
+        }
+    }
+
+    /// Check `password` against every rule. Pass the username it is for when known;
+    /// `None` skips the username rule.
+    ///
+    /// The breached list is read synchronously, so large single-file lists make this
+    /// slow; prefer the range directory layout for those.
+    pub fn check(&self, username: Option<&str>, password: &str) -> Result<(), PasswordPolicyError> {
+        // ---
+        let length = password.chars().count();
+        if length < self.min_length {
+            return Err(PasswordPolicyError::TooShort(self.min_length));
+        }
+        if length > self.max_length {
+            return Err(PasswordPolicyError::TooLong(self.max_length));
+        }
+
+        if let Some(class) = self
+            .required_classes
+            .iter()
+            .find(|class| !password.chars().any(|c| class.matches(c)))
+        {
+            return Err(PasswordPolicyError::MissingClass(*class));
+        }
+
+        if self.reject_username && username.is_some_and(|name| contains_username(password, name)) {
+            return Err(PasswordPolicyError::ContainsUsername);
+        }
+
+        if let Some(path) = &self.breached_list {
+            if is_breached(path, password)
+                .map_err(|err| PasswordPolicyError::ListUnavailable(format!("{err:#}")))?
+            {
+                return Err(PasswordPolicyError::Breached);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `true` if the password contains the username, or its part before `@`, ignoring case.
+fn contains_username(password: &str, username: &str) -> bool {
+    // ---
+    let password = password.to_lowercase();
+    let username = username.trim().to_lowercase();
+    let local_part = username.split('@').next().unwrap_or_default();
+
+    [username.as_str(), local_part]
+        .iter()
+        .any(|name| name.chars().count() >= MIN_USERNAME_FRAGMENT && password.contains(name))
+}
+
+/// Look the password's SHA-1 up in a breached hash file or range directory.
+fn is_breached(path: &Path, password: &str) -> anyhow::Result<bool> {
+    // ---
+    let hash = hex::encode_upper(Sha1::digest(password.as_bytes()));
+
+    let (file, wanted) = if path.is_dir() {
+        let (prefix, suffix) = hash.split_at(HASH_PREFIX_LEN);
+        match File::open(path.join(prefix)) {
+            Ok(file) => (file, suffix),
+            // No range file means no breached hash starts with this prefix
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+            Err(err) => return Err(err.into()),
+        }
+    } else {
+        (File::open(path)?, hash.as_str())
+    };
+
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        let listed = line.split(':').next().unwrap_or_default().trim();
+        if listed.eq_ignore_ascii_case(wanted) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    // ---
+    use super::*;
+
+    fn sha1_upper(password: &str) -> String {
+        hex::encode_upper(Sha1::digest(password.as_bytes()))
+    }
+
+    #[test]
+    fn test_length_and_character_classes() {
+        // ---
+        let policy = PasswordPolicy {
+            required_classes: vec![CharacterClass::Uppercase, CharacterClass::Digit],
+            ..PasswordPolicy::default()
+        };
+
+        assert!(matches!(
+            policy.check(None, ""),
+            Err(PasswordPolicyError::TooShort(8))
+        ));
+        assert!(matches!(
+            policy.check(None, &"A1".repeat(65)),
+            Err(PasswordPolicyError::TooLong(128))
+        ));
+        assert!(matches!(
+            policy.check(None, "lowercase-only"),
+            Err(PasswordPolicyError::MissingClass(CharacterClass::Uppercase))
+        ));
+        assert!(matches!(
+            policy.check(None, "Uppercase-no-digit"),
+            Err(PasswordPolicyError::MissingClass(CharacterClass::Digit))
+        ));
+        assert!(policy.check(None, "Correct-Horse-1").is_ok());
+
+        // Length counts characters, not bytes
+        assert!(PasswordPolicy::default().check(None, "ééééééé").is_err());
+        assert!(PasswordPolicy::default().check(None, "éééééééé").is_ok());
+    }
+
+    #[test]
+    fn test_username_similarity() {
+        // ---
+        let policy = PasswordPolicy::default();
+
+        assert!(matches!(
+            policy.check(Some("alice@example.com"), "ALICE-2024!"),
+            Err(PasswordPolicyError::ContainsUsername)
+        ));
+        assert!(policy
+            .check(Some("alice@example.com"), "wonderland-2024")
+            .is_ok());
+        assert!(
+            policy.check(None, "alice-2024!").is_ok(),
+            "no username, no similarity rule"
+        );
+
+        let lenient = PasswordPolicy {
+            reject_username: false,
+            ..PasswordPolicy::default()
+        };
+        assert!(lenient.check(Some("alice"), "alice-2024!").is_ok());
+    }
+
+    #[test]
+    fn test_breached_hash_file_and_range_directory() -> anyhow::Result<()> {
+        // ---
+        let dir = std::env::temp_dir().join(format!("cr8s-breached-{}", std::process::id()));
+        let ranges = dir.join("ranges");
+        std::fs::create_dir_all(&ranges)?;
+
+        let hash = sha1_upper("password123");
+        let file = dir.join("hashes.txt");
+        std::fs::write(&file, format!("{}:9\n{}:42\n", sha1_upper("other"), hash))?;
+
+        let (prefix, suffix) = hash.split_at(HASH_PREFIX_LEN);
+        std::fs::write(
+            ranges.join(prefix),
+            format!("{}:42\n", suffix.to_lowercase()),
+        )?;
+
+        for path in [file, ranges] {
+            let policy = PasswordPolicy {
+                breached_list: Some(path),
+                ..PasswordPolicy::default()
+            };
+            assert!(matches!(
+                policy.check(None, "password123"),
+                Err(PasswordPolicyError::Breached)
+            ));
+            assert!(policy.check(None, "not-in-the-list").is_ok());
+        }
+
+        let missing = PasswordPolicy {
+            breached_list: Some(dir.join("missing.txt")),
+            ..PasswordPolicy::default()
+        };
+        assert!(matches!(
+            missing.check(None, "password123"),
+            Err(PasswordPolicyError::ListUnavailable(_))
+        ));
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+// src/domain/password_reset.rs
+//! Self-service password reset with single-use tokens delivered by email.
+//!
+//! A reset is a two-step flow: `request_password_reset` stores a short-lived token in
+//! the cache and emails it to the user (usernames are email addresses), then
+//! `confirm_password_reset` consumes the token and stores a new password hash.
+
+use super::{
+    set_password, AppUserTableTraitPtr, CacheContextTraitPtr, MailerTraitPtr, PasswordPolicy,
+};
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::json;
+
+/// Tera template rendered for the reset email.
+const PASSWORD_RESET_TEMPLATE: &str = "email/password_reset.html";
+
+/// Request body asking for a reset email.
+#[derive(Debug, Deserialize, Clone)]
+pub struct PasswordResetRequest {
+    pub username: String,
+}
+
+/// Request body completing a reset with the emailed token.
+#[derive(Debug, Deserialize, Clone)]
+pub struct PasswordResetConfirm {
+    pub token: String,
+    pub new_password: String,
+}
+
+/// Issues a reset token for the user and emails it to them.
+///
+/// Returns `Ok(false)` without sending anything if the user does not exist; callers
+/// must not reveal the difference to the client.
+///
+/// Respects env vars:
+/// - `CR8S_PASSWORD_RESET_TTL_SECS` (default: 900)
+/// - `CR8S_PASSWORD_RESET_URL` (default: none), the front-end page that lets the user
+///   choose a new password and posts it with the token to `/password-reset/confirm`.
+///   The email links to it with a `token` query parameter; without it the email only
+///   carries the token.
+pub async fn request_password_reset(
+    repo: AppUserTableTraitPtr,
+    cache: CacheContextTraitPtr,
+    mailer: MailerTraitPtr,
+    request: PasswordResetRequest,
+) -> Result<bool> {
+    // ---
+    let Ok(user) = repo.find_by_username(&request.username).await else {
+        tracing::info!("Password reset requested for unknown user [user redacted]");
+        return Ok(false);
+    };
+
+    let ttl_secs = crate::get_env_with_default!(u64, "CR8S_PASSWORD_RESET_TTL_SECS", 900);
+    let page_url = std::env::var("CR8S_PASSWORD_RESET_URL")
+        .ok()
+        .filter(|url| !url.trim().is_empty());
+
+    let token = crate::auth::create_password_hasher()?.generate_session_token();
+    cache
+        .set_password_reset_token(&token, user.id, ttl_secs)
+        .await?;
+
+    let context = json!({
+        "username": user.username,
+        "token": token,
+        "link": page_url.map(|url| reset_link(&url, &token)),
+        "expires_minutes": ttl_secs.div_ceil(60),
+    });
+
+    mailer
+        .send_templated(
+            &user.username,
+            "🔑 Reset your cr8s password",
+            PASSWORD_RESET_TEMPLATE,
+            context,
+        )
+        .await?;
+
+    Ok(true)
+}
+
+/// Consumes a reset token and sets the new password.
+///
+/// Returns the id of the user whose password changed, or `None` if the token is
+/// unknown, expired, or already used. Revoking the user's sessions is left to the
+/// caller.
+///
+/// A password breaking the `PasswordPolicy` fails with a `PasswordPolicyError` and
+/// leaves the token usable, so the user can retry with another password.
+pub async fn confirm_password_reset(
+    repo: AppUserTableTraitPtr,
+    cache: CacheContextTraitPtr,
+    confirm: PasswordResetConfirm,
+) -> Result<Option<i32>> {
+    // ---
+    let Some(user_id) = cache.find_password_reset_token(&confirm.token).await? else {
+        return Ok(None);
+    };
+
+    // The whole policy, username rule included, runs before the single-use token is
+    // consumed
+    let user = repo.find(user_id).await?;
+    PasswordPolicy::from_env().check(Some(&user.username), &confirm.new_password)?;
+
+    // A concurrent confirm may have used the token since it was read
+    if cache.take_password_reset_token(&confirm.token).await? != Some(user_id) {
+        return Ok(None);
+    }
+
+    set_password(repo, user_id, &confirm.new_password).await?;
+
+    Ok(Some(user_id))
+}
+
+/// `page_url` with the reset token added as a `token` query parameter.
+fn reset_link(page_url: &str, token: &str) -> String {
+    // ---
+    let separator = if page_url.contains('?') { '&' } else { '?' };
+    format!("{}{separator}token={token}", page_url.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    // ---
+    use super::*;
+
+    #[test]
+    fn test_reset_link_appends_token() {
+        // ---
+        assert_eq!(
+            reset_link("https://cr8s.example.com/reset", "abc"),
+            "https://cr8s.example.com/reset?token=abc"
+        );
+        assert_eq!(
+            reset_link("https://cr8s.example.com/#/account?view=reset", "abc"),
+            "https://cr8s.example.com/#/account?view=reset&token=abc"
+        );
+    }
+}
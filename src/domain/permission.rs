@@ -0,0 +1,201 @@
+// src/domain/permission.rs
+//! Fine-grained permissions checked by route guards.
+//!
+//! Routes declare the permission they need (see `RequirePermission` in
+//! `rocket_routes`) instead of checking role codes themselves. Which role grants
+//! which permission is stored in the `role_permission` table and managed through
+//! `RoleCodeTableTrait`; only Admin is hardwired to hold every permission.
+
+use super::{RoleCode, RoleCodeTableTraitPtr};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// A single action a user may be allowed to perform.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Hash)]
+pub enum Permission {
+    #[serde(rename = "crate:read")]
+    CrateRead,
+    #[serde(rename = "crate:write")]
+    CrateWrite,
+    /// Update crates of the author profile linked to the user (`AppUser::author_id`).
+    #[serde(rename = "crate:write:own")]
+    CrateWriteOwn,
+    #[serde(rename = "crate:delete")]
+    CrateDelete,
+    #[serde(rename = "author:read")]
+    AuthorRead,
+    #[serde(rename = "author:write")]
+    AuthorWrite,
+    #[serde(rename = "author:delete")]
+    AuthorDelete,
+    #[serde(rename = "user:manage")]
+    UserManage,
+    #[serde(rename = "role:manage")]
+    RoleManage,
+    #[serde(rename = "audit:read")]
+    AuditRead,
+}
+
+impl Permission {
+    pub const ALL: [Permission; 10] = [
+        Permission::CrateRead,
+        Permission::CrateWrite,
+        Permission::CrateWriteOwn,
+        Permission::CrateDelete,
+        Permission::AuthorRead,
+        Permission::AuthorWrite,
+        Permission::AuthorDelete,
+        Permission::UserManage,
+        Permission::RoleManage,
+        Permission::AuditRead,
+    ];
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Permission::CrateRead => "crate:read",
+            Permission::CrateWrite => "crate:write",
+            Permission::CrateWriteOwn => "crate:write:own",
+            Permission::CrateDelete => "crate:delete",
+            Permission::AuthorRead => "author:read",
+            Permission::AuthorWrite => "author:write",
+            Permission::AuthorDelete => "author:delete",
+            Permission::UserManage => "user:manage",
+            Permission::RoleManage => "role:manage",
+            Permission::AuditRead => "audit:read",
+        };
+        write!(f, "{s}")
+    }
+}
+
+impl FromStr for Permission {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Permission::ALL
+            .into_iter()
+            .find(|permission| permission.to_string() == s.trim())
+            .ok_or_else(|| format!("Invalid permission: '{s}'"))
+    }
+}
+
+/// Whether any of `roles` grants `permission`. Admin grants everything without a
+/// lookup; other roles are resolved through the role repository.
+pub async fn roles_grant(
+    role_repo: &RoleCodeTableTraitPtr,
+    roles: &[RoleCode],
+    permission: Permission,
+) -> Result<bool> {
+    // ---
+    if roles.contains(&RoleCode::Admin) {
+        return Ok(true);
+    }
+    if roles.is_empty() {
+        return Ok(false);
+    }
+
+    let granted = role_repo.find_permissions_by_roles(roles).await?;
+    Ok(granted.contains(&permission))
+}
+
+#[cfg(test)]
+mod tests {
+    // ---
+    use super::*;
+    use crate::domain::{NewRole, Role, RoleCodeTableTrait};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Viewer grants `crate:read`; counts permission lookups.
+    #[derive(Default)]
+    struct MockRoleRepo {
+        lookups: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl RoleCodeTableTrait for MockRoleRepo {
+        // ---
+        async fn find_role_codes_by_user(&self, _user_id: i32) -> Result<Vec<RoleCode>> {
+            unreachable!()
+        }
+
+        async fn find_all(&self) -> Result<Vec<RoleCode>> {
+            unreachable!()
+        }
+
+        async fn find_role_name_by_code(&self, _code: RoleCode) -> Result<Role> {
+            unreachable!()
+        }
+
+        async fn find_roles(&self) -> Result<Vec<Role>> {
+            unreachable!()
+        }
+
+        async fn find_role(&self, _code: &RoleCode) -> Result<Option<Role>> {
+            unreachable!()
+        }
+
+        async fn create_role(&self, _new_role: &NewRole) -> Result<Role> {
+            unreachable!()
+        }
+
+        async fn rename_role(&self, _code: &RoleCode, _name: &str) -> Result<bool> {
+            unreachable!()
+        }
+
+        async fn set_role_permissions(
+            &self,
+            _code: &RoleCode,
+            _permissions: &[Permission],
+        ) -> Result<bool> {
+            unreachable!()
+        }
+
+        async fn delete_role(&self, _code: &RoleCode) -> Result<bool> {
+            unreachable!()
+        }
+
+        async fn find_permissions_by_roles(&self, roles: &[RoleCode]) -> Result<Vec<Permission>> {
+            // ---
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            Ok(match roles.contains(&RoleCode::Viewer) {
+                true => vec![Permission::CrateRead],
+                false => vec![],
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_roles_grant() -> Result<()> {
+        // ---
+        let mock = Arc::new(MockRoleRepo::default());
+        let repo: RoleCodeTableTraitPtr = mock.clone();
+
+        assert!(roles_grant(&repo, &[RoleCode::Viewer], Permission::CrateRead).await?);
+        assert!(!roles_grant(&repo, &[RoleCode::Viewer], Permission::CrateWrite).await?);
+        assert_eq!(mock.lookups.load(Ordering::SeqCst), 2);
+
+        for permission in Permission::ALL {
+            assert!(roles_grant(&repo, &[RoleCode::Admin], permission).await?);
+        }
+        assert!(!roles_grant(&repo, &[], Permission::CrateRead).await?);
+        assert_eq!(
+            mock.lookups.load(Ordering::SeqCst),
+            2,
+            "Admin and role-less users need no lookup"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_permission_round_trips_through_string() {
+        // ---
+        for permission in Permission::ALL {
+            assert_eq!(permission.to_string().parse::<Permission>(), Ok(permission));
+        }
+        assert!("crate:publish".parse::<Permission>().is_err());
+    }
+}
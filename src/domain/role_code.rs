@@ -1,8 +1,13 @@
 // domain/role_code.rs
 
 //! Contains a trait for querying roles and mapping them to users.
-//! Contains a domain-level enum for user role classification.
+//! Contains a domain-level type for user role classification.
+//!
+//! Roles and the permissions they grant live in the database. `Admin`, `Editor` and
+//! `Viewer` are seeded by the schema and cannot be deleted; any other role is a
+//! `RoleCode::Custom` created at runtime (REST or `cr8s-cli role`).
 
+use super::Permission;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::fmt;
@@ -14,14 +19,29 @@ pub struct Role {
     pub id: i32,
     pub code: RoleCode,
     pub name: String,
+    pub permissions: Vec<Permission>,
 }
 
-/// Distinct access roles available to application users.
-#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+/// Access roles available to application users, stored as their code string.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Hash)]
+#[serde(into = "String", try_from = "String")]
 pub enum RoleCode {
     Admin,
     Editor,
     Viewer,
+    /// A role created at runtime, e.g. `Publisher`.
+    Custom(String),
+}
+
+/// Longest role code the `role` table accepts.
+const MAX_ROLE_CODE_LEN: usize = 64;
+
+impl RoleCode {
+    // ---
+    /// Roles created by the schema; they cannot be deleted.
+    pub fn is_seeded(&self) -> bool {
+        !matches!(self, RoleCode::Custom(_))
+    }
 }
 
 impl fmt::Display for RoleCode {
@@ -30,11 +50,14 @@ impl fmt::Display for RoleCode {
             RoleCode::Admin => "Admin",
             RoleCode::Editor => "Editor",
             RoleCode::Viewer => "Viewer",
+            RoleCode::Custom(code) => code,
         };
         write!(f, "{s}")
     }
 }
 
+/// Accepts the seeded codes, or any other code of 1 to 64 ASCII letters, digits,
+/// `_` or `-` starting with a letter.
 impl FromStr for RoleCode {
     type Err = ();
 
@@ -43,19 +66,385 @@ impl FromStr for RoleCode {
             "Admin" => Ok(RoleCode::Admin),
             "Editor" => Ok(RoleCode::Editor),
             "Viewer" => Ok(RoleCode::Viewer),
+            _ if s.len() <= MAX_ROLE_CODE_LEN
+                && s.starts_with(|c: char| c.is_ascii_alphabetic())
+                && s.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') =>
+            {
+                Ok(RoleCode::Custom(s.to_string()))
+            }
             _ => Err(()),
         }
     }
 }
 
+impl From<RoleCode> for String {
+    fn from(code: RoleCode) -> Self {
+        code.to_string()
+    }
+}
+
+impl TryFrom<String> for RoleCode {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse().map_err(|_| format!("Invalid role code: '{s}'"))
+    }
+}
+
+/// Request body for creating a role.
+#[derive(Debug, Deserialize, Clone)]
+pub struct NewRole {
+    pub code: RoleCode,
+    pub name: String,
+    #[serde(default)]
+    pub permissions: Vec<Permission>,
+}
+
+/// Request body for changing a role; omitted fields are left unchanged.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct RoleUpdate {
+    pub name: Option<String>,
+    pub permissions: Option<Vec<Permission>>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RoleError {
+    #[error("invalid role request: {0}")]
+    Invalid(String),
+
+    #[error("role not found: {0}")]
+    NotFound(RoleCode),
+
+    #[error("role already exists: {0}")]
+    AlreadyExists(RoleCode),
+
+    #[error("role {0} cannot be changed this way: {1}")]
+    Protected(RoleCode, String),
+
+    #[error("internal error: {0}")]
+    Internal(String),
+}
+
+impl From<anyhow::Error> for RoleError {
+    fn from(err: anyhow::Error) -> Self {
+        RoleError::Internal(format!("{err:#}"))
+    }
+}
+
 #[async_trait::async_trait]
 pub trait RoleCodeTableTrait: Send + Sync {
     // ---
     async fn find_role_codes_by_user(&self, user_id: i32) -> Result<Vec<RoleCode>>;
     async fn find_all(&self) -> Result<Vec<RoleCode>>;
     async fn find_role_name_by_code(&self, code: RoleCode) -> Result<Role>;
+
+    // Role management
+
+    /// All roles with their permissions, in creation order.
+    async fn find_roles(&self) -> Result<Vec<Role>>;
+
+    /// The role with this code, if it exists.
+    async fn find_role(&self, code: &RoleCode) -> Result<Option<Role>>;
+
+    /// Insert a role and its permissions.
+    async fn create_role(&self, new_role: &NewRole) -> Result<Role>;
+
+    /// Change a role's display name, return `false` if the role does not exist.
+    async fn rename_role(&self, code: &RoleCode, name: &str) -> Result<bool>;
+
+    /// Replace the permissions a role grants, return `false` if it does not exist.
+    async fn set_role_permissions(
+        &self,
+        code: &RoleCode,
+        permissions: &[Permission],
+    ) -> Result<bool>;
+
+    /// Delete a role and its user assignments, return `false` if it did not exist.
+    async fn delete_role(&self, code: &RoleCode) -> Result<bool>;
+
+    /// The union of the permissions granted by `roles`.
+    async fn find_permissions_by_roles(&self, roles: &[RoleCode]) -> Result<Vec<Permission>>;
 }
 
 pub type RoleCodeTableTraitPtr = Arc<dyn RoleCodeTableTrait + Send + Sync>;
 
 pub use crate::repository::create_role_code_repo;
+
+// ---
+
+fn validate_role_name(name: &str) -> Result<(), RoleError> {
+    // ---
+    match name.trim().len() {
+        0 => Err(RoleError::Invalid("role name must not be empty".into())),
+        1..=128 => Ok(()),
+        _ => Err(RoleError::Invalid(
+            "role name must be at most 128 characters".into(),
+        )),
+    }
+}
+
+/// Create a role; its code must not exist yet.
+pub async fn create_role(
+    role_repo: &RoleCodeTableTraitPtr,
+    new_role: NewRole,
+) -> Result<Role, RoleError> {
+    // ---
+    validate_role_name(&new_role.name)?;
+
+    if role_repo.find_role(&new_role.code).await?.is_some() {
+        return Err(RoleError::AlreadyExists(new_role.code));
+    }
+
+    Ok(role_repo.create_role(&new_role).await?)
+}
+
+/// Rename a role and/or replace its permissions.
+///
+/// Admin always holds every permission, so its permissions cannot be changed.
+pub async fn update_role(
+    role_repo: &RoleCodeTableTraitPtr,
+    code: &RoleCode,
+    update: RoleUpdate,
+) -> Result<Role, RoleError> {
+    // ---
+    if let Some(name) = &update.name {
+        validate_role_name(name)?;
+    }
+    if update.permissions.is_some() && *code == RoleCode::Admin {
+        return Err(RoleError::Protected(
+            code.clone(),
+            "Admin always has every permission".into(),
+        ));
+    }
+
+    let found = match &update.name {
+        Some(name) => role_repo.rename_role(code, name.trim()).await?,
+        None => role_repo.find_role(code).await?.is_some(),
+    };
+    if !found {
+        return Err(RoleError::NotFound(code.clone()));
+    }
+
+    if let Some(permissions) = &update.permissions {
+        role_repo.set_role_permissions(code, permissions).await?;
+    }
+
+    role_repo
+        .find_role(code)
+        .await?
+        .ok_or_else(|| RoleError::NotFound(code.clone()))
+}
+
+/// Delete a runtime-created role; the seeded roles cannot be deleted.
+pub async fn delete_role(
+    role_repo: &RoleCodeTableTraitPtr,
+    code: &RoleCode,
+) -> Result<(), RoleError> {
+    // ---
+    if code.is_seeded() {
+        return Err(RoleError::Protected(
+            code.clone(),
+            "seeded roles cannot be deleted".into(),
+        ));
+    }
+
+    match role_repo.delete_role(code).await? {
+        true => Ok(()),
+        false => Err(RoleError::NotFound(code.clone())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // ---
+    use super::*;
+    use std::sync::Mutex;
+
+    /// In-memory role table seeded with the three default roles.
+    struct MockRoleRepo {
+        roles: Mutex<Vec<Role>>,
+    }
+
+    impl MockRoleRepo {
+        fn seeded() -> Self {
+            let roles = [RoleCode::Admin, RoleCode::Editor, RoleCode::Viewer]
+                .into_iter()
+                .enumerate()
+                .map(|(i, code)| Role {
+                    id: i as i32 + 1,
+                    name: code.to_string(),
+                    code,
+                    permissions: vec![Permission::CrateRead],
+                })
+                .collect();
+            Self {
+                roles: Mutex::new(roles),
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl RoleCodeTableTrait for MockRoleRepo {
+        // ---
+        async fn find_role_codes_by_user(&self, _user_id: i32) -> Result<Vec<RoleCode>> {
+            unreachable!()
+        }
+
+        async fn find_all(&self) -> Result<Vec<RoleCode>> {
+            unreachable!()
+        }
+
+        async fn find_role_name_by_code(&self, _code: RoleCode) -> Result<Role> {
+            unreachable!()
+        }
+
+        async fn find_roles(&self) -> Result<Vec<Role>> {
+            Ok(self.roles.lock().unwrap().clone())
+        }
+
+        async fn find_role(&self, code: &RoleCode) -> Result<Option<Role>> {
+            let roles = self.roles.lock().unwrap();
+            Ok(roles.iter().find(|role| role.code == *code).cloned())
+        }
+
+        async fn create_role(&self, new_role: &NewRole) -> Result<Role> {
+            // ---
+            let mut roles = self.roles.lock().unwrap();
+            let role = Role {
+                id: roles.len() as i32 + 1,
+                code: new_role.code.clone(),
+                name: new_role.name.clone(),
+                permissions: new_role.permissions.clone(),
+            };
+            roles.push(role.clone());
+            Ok(role)
+        }
+
+        async fn rename_role(&self, code: &RoleCode, name: &str) -> Result<bool> {
+            // ---
+            let mut roles = self.roles.lock().unwrap();
+            let role = roles.iter_mut().find(|role| role.code == *code);
+            Ok(role.map(|role| role.name = name.into()).is_some())
+        }
+
+        async fn set_role_permissions(
+            &self,
+            code: &RoleCode,
+            permissions: &[Permission],
+        ) -> Result<bool> {
+            // ---
+            let mut roles = self.roles.lock().unwrap();
+            let role = roles.iter_mut().find(|role| role.code == *code);
+            Ok(role
+                .map(|role| role.permissions = permissions.to_vec())
+                .is_some())
+        }
+
+        async fn delete_role(&self, code: &RoleCode) -> Result<bool> {
+            // ---
+            let mut roles = self.roles.lock().unwrap();
+            let before = roles.len();
+            roles.retain(|role| role.code != *code);
+            Ok(roles.len() < before)
+        }
+
+        async fn find_permissions_by_roles(&self, _roles: &[RoleCode]) -> Result<Vec<Permission>> {
+            unreachable!()
+        }
+    }
+
+    fn repo() -> RoleCodeTableTraitPtr {
+        Arc::new(MockRoleRepo::seeded())
+    }
+
+    #[test]
+    fn test_role_code_parsing() {
+        // ---
+        assert_eq!("Editor".parse::<RoleCode>(), Ok(RoleCode::Editor));
+        assert_eq!(
+            "Publisher".parse::<RoleCode>(),
+            Ok(RoleCode::Custom("Publisher".into()))
+        );
+        assert!("".parse::<RoleCode>().is_err());
+        assert!("1st".parse::<RoleCode>().is_err());
+        assert!("crate admin".parse::<RoleCode>().is_err());
+        assert!("x".repeat(65).parse::<RoleCode>().is_err());
+
+        let json = serde_json::to_string(&RoleCode::Custom("Publisher".into())).unwrap();
+        assert_eq!(json, r#""Publisher""#);
+        let parsed: Vec<RoleCode> = serde_json::from_str(r#"["Admin","Publisher"]"#).unwrap();
+        assert_eq!(
+            parsed,
+            vec![RoleCode::Admin, RoleCode::Custom("Publisher".into())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_and_update_role() {
+        // ---
+        let repo = repo();
+        let publisher = RoleCode::Custom("Publisher".into());
+        let new_role = NewRole {
+            code: publisher.clone(),
+            name: "Publisher".into(),
+            permissions: vec![Permission::CrateWrite],
+        };
+
+        create_role(&repo, new_role.clone()).await.unwrap();
+        assert!(matches!(
+            create_role(&repo, new_role).await,
+            Err(RoleError::AlreadyExists(_))
+        ));
+
+        let update = RoleUpdate {
+            name: Some("Crate publisher".into()),
+            permissions: Some(vec![Permission::CrateRead, Permission::CrateWrite]),
+        };
+        let role = update_role(&repo, &publisher, update).await.unwrap();
+        assert_eq!(role.name, "Crate publisher");
+        assert_eq!(role.permissions.len(), 2);
+
+        let missing = RoleCode::Custom("Missing".into());
+        assert!(matches!(
+            update_role(&repo, &missing, RoleUpdate::default()).await,
+            Err(RoleError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_seeded_roles_are_protected() {
+        // ---
+        let repo = repo();
+        let update = RoleUpdate {
+            name: None,
+            permissions: Some(vec![]),
+        };
+
+        assert!(matches!(
+            update_role(&repo, &RoleCode::Admin, update.clone()).await,
+            Err(RoleError::Protected(..))
+        ));
+        assert!(update_role(&repo, &RoleCode::Editor, update).await.is_ok());
+        assert!(matches!(
+            delete_role(&repo, &RoleCode::Viewer).await,
+            Err(RoleError::Protected(..))
+        ));
+        assert!(matches!(
+            delete_role(&repo, &RoleCode::Custom("Missing".into())).await,
+            Err(RoleError::NotFound(_))
+        ));
+        assert!(matches!(
+            create_role(
+                &repo,
+                NewRole {
+                    code: RoleCode::Custom("Blank".into()),
+                    name: " ".into(),
+                    permissions: vec![],
+                }
+            )
+            .await,
+            Err(RoleError::Invalid(_))
+        ));
+    }
+}
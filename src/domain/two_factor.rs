@@ -0,0 +1,262 @@
+// src/domain/two_factor.rs
+//! Optional TOTP (RFC 6238) two-factor authentication.
+//!
+//! Enrollment is two steps: `enroll_totp` stores an unconfirmed secret and returns the
+//! `otpauth://` URI for an authenticator app, then `confirm_totp` checks a first code,
+//! enables 2FA and hands out one-time recovery codes (stored only as password hashes).
+//! At login, a correct password yields a challenge token which `complete_second_factor`
+//! exchanges, together with a current TOTP code or an unused recovery code, for a
+//! session token.
+
+use super::login_throttle::{
+    login_lockout_remaining, login_throttle_keys, record_login_failure, LockoutPolicy,
+};
+use super::{AppUser, AppUserTableTraitPtr, CacheContextTraitPtr, LoginError};
+use anyhow::Result;
+use rand::distr::{Alphanumeric, SampleString};
+use serde::{Deserialize, Serialize};
+
+/// Issuer shown by authenticator apps.
+const TOTP_ISSUER: &str = "cr8s";
+
+/// Number of recovery codes handed out when 2FA is enabled.
+const RECOVERY_CODE_COUNT: usize = 10;
+
+/// Characters per recovery code, shown as two dash-separated halves.
+const RECOVERY_CODE_LEN: usize = 10;
+
+/// Secret and provisioning URI returned by `enroll_totp`.
+#[derive(Debug, Serialize, Clone)]
+pub struct TotpEnrollment {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+/// Request body carrying a TOTP or recovery code.
+#[derive(Debug, Deserialize, Clone)]
+pub struct TotpCode {
+    pub code: String,
+}
+
+/// Request body completing a login that requires a second factor.
+#[derive(Debug, Deserialize, Clone)]
+pub struct SecondFactorLogin {
+    pub challenge_token: String,
+    pub code: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TwoFactorError {
+    #[error("two-factor authentication is already enabled")]
+    AlreadyEnabled,
+
+    #[error("no pending two-factor enrollment")]
+    NotEnrolled,
+
+    #[error("invalid two-factor code")]
+    InvalidCode,
+
+    #[error("internal error: {0}")]
+    Internal(String),
+}
+
+impl From<anyhow::Error> for TwoFactorError {
+    fn from(err: anyhow::Error) -> Self {
+        TwoFactorError::Internal(err.to_string())
+    }
+}
+
+/// Starts (or restarts) TOTP enrollment with a fresh secret.
+///
+/// Refused once 2FA is enabled, so a stolen session cannot silently replace the secret.
+pub async fn enroll_totp(
+    repo: AppUserTableTraitPtr,
+    user: &AppUser,
+) -> Result<TotpEnrollment, TwoFactorError> {
+    // ---
+    if repo
+        .find_totp(user.id)
+        .await?
+        .is_some_and(|totp| totp.enabled)
+    {
+        return Err(TwoFactorError::AlreadyEnabled);
+    }
+
+    let secret = crate::auth::generate_totp_secret();
+    repo.set_pending_totp(user.id, &secret).await?;
+
+    Ok(TotpEnrollment {
+        otpauth_uri: crate::auth::totp_uri(TOTP_ISSUER, &user.username, &secret),
+        secret,
+    })
+}
+
+/// Confirms enrollment with a first TOTP code and enables 2FA.
+///
+/// Returns the plaintext recovery codes; they are shown once and only their hashes are
+/// kept.
+pub async fn confirm_totp(
+    repo: AppUserTableTraitPtr,
+    user: &AppUser,
+    code: &str,
+) -> Result<Vec<String>, TwoFactorError> {
+    // ---
+    let totp = match repo.find_totp(user.id).await? {
+        Some(totp) if totp.enabled => return Err(TwoFactorError::AlreadyEnabled),
+        Some(totp) => totp,
+        None => return Err(TwoFactorError::NotEnrolled),
+    };
+
+    let Some(step) = crate::auth::matching_totp_step(&totp.secret, code, unix_now()) else {
+        return Err(TwoFactorError::InvalidCode);
+    };
+    if !repo.use_totp_step(user.id, step).await? {
+        return Err(TwoFactorError::InvalidCode);
+    }
+
+    let hasher = crate::auth::create_password_hasher()?;
+    let recovery_codes: Vec<String> = (0..RECOVERY_CODE_COUNT)
+        .map(|_| new_recovery_code())
+        .collect();
+    let code_hashes = recovery_codes
+        .iter()
+        .map(|code| hasher.hash_password(&normalize_recovery_code(code)))
+        .collect::<Result<Vec<_>>>()?;
+
+    repo.enable_totp(user.id, &code_hashes).await?;
+
+    Ok(recovery_codes)
+}
+
+/// `true` if 2FA is enabled for the user, i.e. a password alone must not log them in.
+pub async fn second_factor_enabled(repo: &AppUserTableTraitPtr, user_id: i32) -> Result<bool> {
+    // ---
+    Ok(repo
+        .find_totp(user_id)
+        .await?
+        .is_some_and(|totp| totp.enabled))
+}
+
+/// Exchanges a login challenge plus a TOTP or recovery code for a session token.
+///
+/// The challenge is consumed whether or not the code is right, so a wrong guess sends
+/// the user back to the password step. Wrong codes count as failed logins against the
+/// username and `client_ip`, like wrong passwords (see `login_throttle`), and the
+/// username counter is only reset here, once both factors were accepted.
+///
+/// Returns `Ok((user, token))` on success; storing the session is left to the caller.
+pub async fn complete_second_factor(
+    repo: AppUserTableTraitPtr,
+    cache: CacheContextTraitPtr,
+    login: SecondFactorLogin,
+    client_ip: Option<&str>,
+) -> Result<(AppUser, String), LoginError> {
+    // ---
+    let internal = |err: anyhow::Error| LoginError::Internal(err.to_string());
+
+    let Some(user_id) = cache
+        .take_login_challenge(&login.challenge_token)
+        .await
+        .map_err(internal)?
+    else {
+        tracing::warn!("Unknown or expired login challenge");
+        return Err(LoginError::InvalidCredentials);
+    };
+
+    let user = repo.find(user_id).await.map_err(internal)?;
+    let throttle_keys = login_throttle_keys(&user.username, client_ip);
+    if let Some(retry_after_secs) = login_lockout_remaining(&cache, &throttle_keys)
+        .await
+        .map_err(internal)?
+    {
+        tracing::warn!("Second factor for user id {user_id} locked out for {retry_after_secs}s");
+        return Err(LoginError::LockedOut { retry_after_secs });
+    }
+
+    if !verify_second_factor(&repo, user_id, &login.code)
+        .await
+        .map_err(internal)?
+    {
+        tracing::warn!("Invalid second factor for user id {user_id}");
+        let policy = LockoutPolicy::from_env();
+        return match record_login_failure(&cache, &policy, &throttle_keys)
+            .await
+            .map_err(internal)?
+        {
+            Some(retry_after_secs) => Err(LoginError::LockedOut { retry_after_secs }),
+            None => Err(LoginError::InvalidCredentials),
+        };
+    }
+
+    cache
+        .clear_login_failures(&throttle_keys[0])
+        .await
+        .map_err(internal)?;
+
+    if !user.is_active() {
+        tracing::warn!("Second factor for disabled user id {user_id}");
+        return Err(LoginError::AccountDisabled);
+    }
+    let token = crate::auth::create_password_hasher()
+        .map_err(internal)?
+        .generate_session_token();
+
+    Ok((user, token))
+}
+
+/// Checks a TOTP code, falling back to the user's unused recovery codes; a matching
+/// recovery code is consumed.
+async fn verify_second_factor(
+    repo: &AppUserTableTraitPtr,
+    user_id: i32,
+    code: &str,
+) -> Result<bool> {
+    // ---
+    let Some(totp) = repo.find_totp(user_id).await?.filter(|totp| totp.enabled) else {
+        return Ok(false);
+    };
+
+    // A TOTP code is good once; replaying it within its drift window fails
+    if let Some(step) = crate::auth::matching_totp_step(&totp.secret, code, unix_now()) {
+        return repo.use_totp_step(user_id, step).await;
+    }
+
+    let candidate = normalize_recovery_code(code);
+    let hasher = crate::auth::create_password_hasher()?;
+
+    for recovery_code in repo.find_recovery_codes(user_id).await? {
+        if hasher
+            .verify_password(&recovery_code.code_hash, &candidate)
+            .is_ok()
+        {
+            return repo.consume_recovery_code(recovery_code.id).await;
+        }
+    }
+
+    Ok(false)
+}
+
+/// A random recovery code such as `k3j9x-p2m4q`.
+fn new_recovery_code() -> String {
+    // ---
+    let raw = Alphanumeric
+        .sample_string(&mut rand::rng(), RECOVERY_CODE_LEN)
+        .to_lowercase();
+    let (head, tail) = raw.split_at(RECOVERY_CODE_LEN / 2);
+    format!("{head}-{tail}")
+}
+
+/// Recovery codes are compared case-insensitively and without separators.
+fn normalize_recovery_code(code: &str) -> String {
+    // ---
+    code.trim()
+        .chars()
+        .filter(|c| *c != '-')
+        .collect::<String>()
+        .to_lowercase()
+}
+
+fn unix_now() -> u64 {
+    // ---
+    chrono::Utc::now().timestamp().max(0) as u64
+}
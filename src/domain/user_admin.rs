@@ -0,0 +1,351 @@
+// domain/user_admin.rs
+//! Administrative creation, lookup, disabling and deletion of user accounts.
+//!
+//! Responses use `UserSummary`, which carries the user's roles but never the stored
+//! password hash. Disabling keeps the account and its history; deleting removes it,
+//! and is only allowed once the account is disabled.
+//!
+//! `user:manage` alone does not let a caller reach above their own roles: see
+//! `check_can_manage`.
+
+use super::{
+    roles_grant, user_role::count_admins, AppUser, AppUserTableTraitPtr, AppUserWithRoleCodes,
+    CacheContextTraitPtr, NewUser, PasswordPolicy, PasswordPolicyError, Permission, RoleCode,
+    RoleCodeTableTraitPtr,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+/// A user as shown to administrators: everything but the password hash.
+#[derive(Debug, Clone, Serialize)]
+pub struct UserSummary {
+    pub id: i32,
+    pub username: String,
+    pub created_at: NaiveDateTime,
+    pub author_id: Option<i32>,
+    pub disabled_at: Option<NaiveDateTime>,
+    pub roles: Vec<RoleCode>,
+}
+
+impl From<AppUserWithRoleCodes> for UserSummary {
+    fn from((user, roles): AppUserWithRoleCodes) -> Self {
+        UserSummary {
+            id: user.id,
+            username: user.username,
+            created_at: user.created_at,
+            author_id: user.author_id,
+            disabled_at: user.disabled_at,
+            roles,
+        }
+    }
+}
+
+/// Request body for `POST /users`; `password` is the plain-text initial password.
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewUserAccount {
+    pub username: String,
+    pub password: String,
+    #[serde(default)]
+    pub roles: Vec<RoleCode>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum UserAdminError {
+    #[error("invalid user request: {0}")]
+    Invalid(String),
+
+    #[error("user not found: {0}")]
+    NotFound(i32),
+
+    #[error("user already exists: {0}")]
+    AlreadyExists(String),
+
+    #[error("role not found: {0}")]
+    RoleNotFound(RoleCode),
+
+    #[error("user {0} cannot be changed this way: {1}")]
+    Protected(i32, String),
+
+    #[error("not allowed: {0}")]
+    Forbidden(String),
+
+    #[error("internal error: {0}")]
+    Internal(String),
+}
+
+impl From<anyhow::Error> for UserAdminError {
+    fn from(err: anyhow::Error) -> Self {
+        UserAdminError::Internal(format!("{err:#}"))
+    }
+}
+
+impl From<PasswordPolicyError> for UserAdminError {
+    fn from(err: PasswordPolicyError) -> Self {
+        match err {
+            PasswordPolicyError::ListUnavailable(_) => UserAdminError::Internal(err.to_string()),
+            err => UserAdminError::Invalid(err.to_string()),
+        }
+    }
+}
+
+/// Find a user together with the roles they hold.
+pub async fn find_user(
+    app_user_repo: &AppUserTableTraitPtr,
+    user_id: i32,
+) -> Result<UserSummary, UserAdminError> {
+    // ---
+    let Ok(user) = app_user_repo.find(user_id).await else {
+        return Err(UserAdminError::NotFound(user_id));
+    };
+    let roles = app_user_repo.find_roles_by_user(&user).await?;
+    Ok((user, roles).into())
+}
+
+/// Fails with `Forbidden` unless `actor` may act on a user holding `target_roles`.
+///
+/// Admins may act on anyone. Anyone else may not act on an Admin, nor on a user whose
+/// roles grant a permission the actor's own roles do not.
+pub async fn check_can_manage(
+    app_user_repo: &AppUserTableTraitPtr,
+    role_repo: &RoleCodeTableTraitPtr,
+    actor: &AppUser,
+    target_roles: &[RoleCode],
+) -> Result<(), UserAdminError> {
+    // ---
+    let actor_roles = app_user_repo.find_roles_by_user(actor).await?;
+    if actor_roles.contains(&RoleCode::Admin) {
+        return Ok(());
+    }
+    if target_roles.contains(&RoleCode::Admin) {
+        return Err(UserAdminError::Forbidden(
+            "only Admins can manage Admins".into(),
+        ));
+    }
+    if target_roles.is_empty() {
+        return Ok(());
+    }
+
+    let held = role_repo.find_permissions_by_roles(&actor_roles).await?;
+    let needed = role_repo.find_permissions_by_roles(target_roles).await?;
+    match needed.iter().find(|permission| !held.contains(permission)) {
+        Some(missing) => Err(UserAdminError::Forbidden(format!(
+            "your roles do not grant {missing}"
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Create a user with a hashed password and the given roles, every one of which must
+/// exist. The password must satisfy the `PasswordPolicy`.
+///
+/// Assigning roles takes `role:manage`, and `actor` must be allowed to manage a user
+/// holding them (see `check_can_manage`).
+pub async fn create_user_account(
+    app_user_repo: &AppUserTableTraitPtr,
+    role_repo: &RoleCodeTableTraitPtr,
+    actor: &AppUser,
+    account: NewUserAccount,
+) -> Result<UserSummary, UserAdminError> {
+    // ---
+    let username = account.username.trim();
+    if username.is_empty() {
+        return Err(UserAdminError::Invalid("username must not be empty".into()));
+    }
+    PasswordPolicy::from_env().check(Some(username), &account.password)?;
+
+    let known = role_repo.find_all().await?;
+    if let Some(unknown) = account.roles.iter().find(|code| !known.contains(code)) {
+        return Err(UserAdminError::RoleNotFound(unknown.clone()));
+    }
+    if !account.roles.is_empty() {
+        let actor_roles = app_user_repo.find_roles_by_user(actor).await?;
+        if !roles_grant(role_repo, &actor_roles, Permission::RoleManage).await? {
+            return Err(UserAdminError::Forbidden(
+                "assigning roles requires role:manage".into(),
+            ));
+        }
+        check_can_manage(app_user_repo, role_repo, actor, &account.roles).await?;
+    }
+    if app_user_repo.find_by_username(username).await.is_ok() {
+        return Err(UserAdminError::AlreadyExists(username.to_string()));
+    }
+
+    let password_hash = crate::auth::create_password_hasher()?
+        .hash_password(&account.password)
+        .map_err(|e| anyhow::anyhow!("Password hashing failed: {e}"))?;
+
+    let new_user = NewUser {
+        username: username.to_string(),
+        password: password_hash,
+    };
+    let user: AppUser = app_user_repo.create(new_user, account.roles).await?;
+    let roles = app_user_repo.find_roles_by_user(&user).await?;
+    Ok((user, roles).into())
+}
+
+/// Delete a disabled user and revoke their sessions.
+///
+/// Active accounts must be disabled first, so a deletion is always preceded by a
+/// separate, audited step. Administrators cannot delete themselves.
+pub async fn delete_user_account(
+    app_user_repo: &AppUserTableTraitPtr,
+    cache_context: &CacheContextTraitPtr,
+    acting_user_id: i32,
+    user_id: i32,
+) -> Result<(), UserAdminError> {
+    // ---
+    if user_id == acting_user_id {
+        return Err(UserAdminError::Protected(
+            user_id,
+            "administrators cannot delete themselves".into(),
+        ));
+    }
+
+    // The last active Admin cannot be disabled, so it cannot be deleted either
+    let user = find_user(app_user_repo, user_id).await?;
+    if user.disabled_at.is_none() {
+        return Err(UserAdminError::Protected(
+            user_id,
+            "disable the account before deleting it".into(),
+        ));
+    }
+
+    app_user_repo.delete_by_id(user_id).await?;
+    cache_context.clear_all_session_tokens(user_id).await?;
+    Ok(())
+}
+
+/// Disable an account and revoke its sessions, or re-enable it.
+///
+/// `acting_user_id` is the administrator making the change, or `None` from the CLI.
+/// Administrators cannot disable themselves, and the last active Admin cannot be
+/// disabled. In JWT auth mode, access tokens already issued stay valid until they
+/// expire; refreshing them fails.
+pub async fn set_account_disabled(
+    app_user_repo: &AppUserTableTraitPtr,
+    cache_context: &CacheContextTraitPtr,
+    acting_user_id: Option<i32>,
+    user_id: i32,
+    disabled: bool,
+) -> Result<UserSummary, UserAdminError> {
+    // ---
+    let user = find_user(app_user_repo, user_id).await?;
+
+    if disabled && user.disabled_at.is_none() {
+        if Some(user_id) == acting_user_id {
+            return Err(UserAdminError::Protected(
+                user_id,
+                "administrators cannot disable themselves".into(),
+            ));
+        }
+        if user.roles.contains(&RoleCode::Admin) && count_admins(app_user_repo).await? <= 1 {
+            return Err(UserAdminError::Protected(
+                user_id,
+                "the last Admin cannot be disabled".into(),
+            ));
+        }
+    }
+
+    if !app_user_repo.set_disabled(user_id, disabled).await? {
+        return Err(UserAdminError::NotFound(user_id));
+    }
+    if disabled {
+        cache_context.clear_all_session_tokens(user_id).await?;
+    }
+    find_user(app_user_repo, user_id).await
+}
+
+#[cfg(test)]
+mod tests {
+    // ---
+    use super::*;
+    use crate::domain::AppUserTableTrait;
+    use crate::test_support::{MockAppUserRepo, MockCacheContext};
+    use anyhow::Result;
+    use std::sync::Arc;
+
+    /// Two users: id 1 is the only Admin, id 2 an Editor with one session.
+    fn fixture() -> (Arc<MockAppUserRepo>, Arc<MockCacheContext>) {
+        // ---
+        let repo = MockAppUserRepo::default()
+            .with_user("admin@example.com", vec![RoleCode::Admin])
+            .with_user("editor@example.com", vec![RoleCode::Editor]);
+        let cache = MockCacheContext::default();
+        cache.add_session("editor-session", 2);
+        (Arc::new(repo), Arc::new(cache))
+    }
+
+    #[tokio::test]
+    async fn test_delete_user_account() -> Result<()> {
+        // ---
+        let (repo, cache) = fixture();
+        let repo_ptr: AppUserTableTraitPtr = repo.clone();
+        let cache_ptr: CacheContextTraitPtr = cache.clone();
+
+        let result = delete_user_account(&repo_ptr, &cache_ptr, 1, 1).await;
+        assert!(matches!(result, Err(UserAdminError::Protected(1, _))));
+
+        let result = delete_user_account(&repo_ptr, &cache_ptr, 2, 1).await;
+        assert!(
+            matches!(result, Err(UserAdminError::Protected(1, _))),
+            "the last Admin must not be deleted"
+        );
+
+        let result = delete_user_account(&repo_ptr, &cache_ptr, 1, 99).await;
+        assert!(matches!(result, Err(UserAdminError::NotFound(99))));
+
+        let result = delete_user_account(&repo_ptr, &cache_ptr, 1, 2).await;
+        assert!(
+            matches!(result, Err(UserAdminError::Protected(2, _))),
+            "active accounts must be disabled before they are deleted"
+        );
+        assert!(repo.find(2).await.is_ok());
+
+        set_account_disabled(&repo_ptr, &cache_ptr, Some(1), 2, true).await?;
+        delete_user_account(&repo_ptr, &cache_ptr, 1, 2).await?;
+        assert!(repo.find(2).await.is_err());
+        assert!(cache.session_tokens(2).is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_disable_and_enable_account() -> Result<()> {
+        // ---
+        let (mock_repo, cache) = fixture();
+        let repo: AppUserTableTraitPtr = mock_repo;
+        let cache_ptr: CacheContextTraitPtr = cache.clone();
+
+        let result = set_account_disabled(&repo, &cache_ptr, Some(1), 1, true).await;
+        assert!(matches!(result, Err(UserAdminError::Protected(1, _))));
+
+        let result = set_account_disabled(&repo, &cache_ptr, Some(2), 1, true).await;
+        assert!(
+            matches!(result, Err(UserAdminError::Protected(1, _))),
+            "the last Admin must not be disabled"
+        );
+
+        let disabled = set_account_disabled(&repo, &cache_ptr, Some(1), 2, true).await?;
+        assert!(disabled.disabled_at.is_some());
+        assert!(cache.session_tokens(2).is_empty());
+
+        let enabled = set_account_disabled(&repo, &cache_ptr, Some(1), 2, false).await?;
+        assert!(enabled.disabled_at.is_none());
+
+        let result = set_account_disabled(&repo, &cache_ptr, Some(1), 99, true).await;
+        assert!(matches!(result, Err(UserAdminError::NotFound(99))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_user_summary_has_no_password() -> Result<()> {
+        // ---
+        let (repo, _) = fixture();
+        let summary = UserSummary::from((repo.user(1), repo.roles(1)));
+        let value = serde_json::to_value(&summary)?;
+
+        assert_eq!(value["username"], "admin@example.com");
+        assert_eq!(value["roles"], serde_json::json!(["Admin"]));
+        assert!(value.get("password").is_none());
+        Ok(())
+    }
+}
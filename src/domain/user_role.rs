@@ -0,0 +1,302 @@
+// domain/user_role.rs
+//! Granting and revoking the roles of existing users.
+//!
+//! Role changes are written through `AppUserTableTrait` and then copied onto the
+//! user's live sessions. Guards on session tokens resolve roles on every request, so
+//! a change applies to the next request; in JWT auth mode an access token keeps the
+//! roles it was signed with until it expires, and the next refresh picks them up.
+
+use super::{
+    AppUser, AppUserTableTraitPtr, CacheContextTraitPtr, RoleCode, RoleCodeTableTraitPtr, RoleError,
+};
+use serde::{Deserialize, Serialize};
+
+/// Request body for `PUT /users/<id>/roles`: every role the user should hold.
+#[derive(Debug, Deserialize, Clone)]
+pub struct UserRoles {
+    pub roles: Vec<RoleCode>,
+}
+
+/// How to change the roles a user holds.
+#[derive(Debug, Clone)]
+pub enum RoleAssignment {
+    /// Add these roles, keeping the others.
+    Grant(Vec<RoleCode>),
+    /// Remove these roles, keeping the others.
+    Revoke(Vec<RoleCode>),
+    /// Hold exactly these roles.
+    Replace(Vec<RoleCode>),
+}
+
+/// The roles a user holds after an assignment, and how many live sessions saw it.
+#[derive(Debug, Clone, Serialize)]
+pub struct AssignedRoles {
+    pub roles: Vec<RoleCode>,
+    pub sessions_updated: u64,
+}
+
+/// Change the roles of `user` and record the result on their live sessions.
+///
+/// Every role named must exist. Admin cannot be taken from the last user holding it,
+/// so an installation always keeps someone able to manage roles.
+pub async fn assign_roles(
+    app_user_repo: &AppUserTableTraitPtr,
+    role_repo: &RoleCodeTableTraitPtr,
+    cache_context: &CacheContextTraitPtr,
+    user: &AppUser,
+    assignment: RoleAssignment,
+) -> Result<AssignedRoles, RoleError> {
+    // ---
+    let requested = match &assignment {
+        RoleAssignment::Grant(roles)
+        | RoleAssignment::Revoke(roles)
+        | RoleAssignment::Replace(roles) => roles,
+    };
+    let known = role_repo.find_all().await?;
+    if let Some(unknown) = requested.iter().find(|code| !known.contains(code)) {
+        return Err(RoleError::NotFound(unknown.clone()));
+    }
+
+    let current = app_user_repo.find_roles_by_user(user).await?;
+    let (grant, revoke) = match &assignment {
+        RoleAssignment::Grant(roles) => (missing(&current, roles), vec![]),
+        RoleAssignment::Revoke(roles) => (vec![], held(&current, |code| roles.contains(code))),
+        RoleAssignment::Replace(roles) => (
+            missing(&current, roles),
+            held(&current, |code| !roles.contains(code)),
+        ),
+    };
+
+    if user.is_active()
+        && revoke.contains(&RoleCode::Admin)
+        && count_admins(app_user_repo).await? <= 1
+    {
+        return Err(RoleError::Protected(
+            RoleCode::Admin,
+            "the last Admin cannot lose the role".into(),
+        ));
+    }
+
+    if !grant.is_empty() {
+        app_user_repo.grant_roles(user.id, &grant).await?;
+    }
+    if !revoke.is_empty() {
+        app_user_repo.revoke_roles(user.id, &revoke).await?;
+    }
+
+    let roles = app_user_repo.find_roles_by_user(user).await?;
+    let sessions_updated = cache_context.update_session_roles(user.id, &roles).await?;
+
+    Ok(AssignedRoles {
+        roles,
+        sessions_updated,
+    })
+}
+
+/// The roles in `requested` that are not in `current`, without duplicates.
+fn missing(current: &[RoleCode], requested: &[RoleCode]) -> Vec<RoleCode> {
+    // ---
+    let mut missing: Vec<RoleCode> = Vec::new();
+    for code in requested {
+        if !current.contains(code) && !missing.contains(code) {
+            missing.push(code.clone());
+        }
+    }
+    missing
+}
+
+/// The roles in `current` matching `keep`.
+fn held(current: &[RoleCode], keep: impl Fn(&RoleCode) -> bool) -> Vec<RoleCode> {
+    // ---
+    current.iter().filter(|code| keep(code)).cloned().collect()
+}
+
+/// Number of active (not disabled) users holding the Admin role.
+pub(super) async fn count_admins(app_user_repo: &AppUserTableTraitPtr) -> anyhow::Result<usize> {
+    // ---
+    let users = app_user_repo.find_with_roles().await?;
+    Ok(users
+        .iter()
+        .filter(|(user, roles)| user.is_active() && roles.contains(&RoleCode::Admin))
+        .count())
+}
+
+#[cfg(test)]
+mod tests {
+    // ---
+    use super::*;
+    use crate::domain::{
+        //
+        NewRole,
+        Permission,
+        Role,
+        RoleCodeTableTrait,
+    };
+    use crate::test_support::{MockAppUserRepo, MockCacheContext};
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use std::sync::Arc;
+
+    /// Knows the seeded roles only.
+    struct MockRoleRepo;
+
+    #[async_trait]
+    impl RoleCodeTableTrait for MockRoleRepo {
+        // ---
+        async fn find_role_codes_by_user(&self, _user_id: i32) -> Result<Vec<RoleCode>> {
+            unreachable!()
+        }
+
+        async fn find_all(&self) -> Result<Vec<RoleCode>> {
+            Ok(vec![RoleCode::Admin, RoleCode::Editor, RoleCode::Viewer])
+        }
+
+        async fn find_role_name_by_code(&self, _code: RoleCode) -> Result<Role> {
+            unreachable!()
+        }
+
+        async fn find_roles(&self) -> Result<Vec<Role>> {
+            unreachable!()
+        }
+
+        async fn find_role(&self, _code: &RoleCode) -> Result<Option<Role>> {
+            unreachable!()
+        }
+
+        async fn create_role(&self, _new_role: &NewRole) -> Result<Role> {
+            unreachable!()
+        }
+
+        async fn rename_role(&self, _code: &RoleCode, _name: &str) -> Result<bool> {
+            unreachable!()
+        }
+
+        async fn set_role_permissions(
+            &self,
+            _code: &RoleCode,
+            _permissions: &[Permission],
+        ) -> Result<bool> {
+            unreachable!()
+        }
+
+        async fn delete_role(&self, _code: &RoleCode) -> Result<bool> {
+            unreachable!()
+        }
+
+        async fn find_permissions_by_roles(&self, _roles: &[RoleCode]) -> Result<Vec<Permission>> {
+            unreachable!()
+        }
+    }
+
+    /// Users 1 and 2, each with one session.
+    struct Fixture {
+        mock_users: Arc<MockAppUserRepo>,
+        users: AppUserTableTraitPtr,
+        roles: RoleCodeTableTraitPtr,
+        cache: Arc<MockCacheContext>,
+        cache_ptr: CacheContextTraitPtr,
+    }
+
+    impl Fixture {
+        fn new(user1: Vec<RoleCode>, user2: Vec<RoleCode>) -> Self {
+            // ---
+            let mock_users = Arc::new(
+                MockAppUserRepo::default()
+                    .with_user("user1", user1)
+                    .with_user("user2", user2),
+            );
+            let cache = Arc::new(MockCacheContext::default());
+            cache.add_session("session-1", 1);
+            cache.add_session("session-2", 2);
+            Self {
+                users: mock_users.clone(),
+                mock_users,
+                roles: Arc::new(MockRoleRepo),
+                cache_ptr: cache.clone(),
+                cache,
+            }
+        }
+
+        /// The roles recorded on the session of `user_id`.
+        fn session_roles(&self, user_id: i32) -> Vec<RoleCode> {
+            let sessions = self.cache.sessions.lock().unwrap();
+            let (_, session) = sessions.iter().find(|(_, s)| s.user_id == user_id).unwrap();
+            session.role_codes.clone()
+        }
+
+        async fn assign(
+            &self,
+            user_id: i32,
+            assignment: RoleAssignment,
+        ) -> Result<AssignedRoles, RoleError> {
+            assign_roles(
+                &self.users,
+                &self.roles,
+                &self.cache_ptr,
+                &self.mock_users.user(user_id),
+                assignment,
+            )
+            .await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_grant_revoke_and_replace_update_sessions() {
+        // ---
+        let fixture = Fixture::new(vec![RoleCode::Admin], vec![RoleCode::Viewer]);
+
+        let assigned = fixture
+            .assign(
+                2,
+                RoleAssignment::Grant(vec![RoleCode::Editor, RoleCode::Viewer]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(assigned.roles, [RoleCode::Viewer, RoleCode::Editor]);
+        assert_eq!(assigned.sessions_updated, 1);
+
+        let assigned = fixture
+            .assign(2, RoleAssignment::Revoke(vec![RoleCode::Viewer]))
+            .await
+            .unwrap();
+        assert_eq!(assigned.roles, [RoleCode::Editor]);
+
+        let assigned = fixture
+            .assign(2, RoleAssignment::Replace(vec![RoleCode::Viewer]))
+            .await
+            .unwrap();
+        assert_eq!(assigned.roles, [RoleCode::Viewer]);
+        assert_eq!(fixture.session_roles(2), [RoleCode::Viewer]);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_role_and_last_admin_are_rejected() {
+        // ---
+        let fixture = Fixture::new(vec![RoleCode::Admin], vec![RoleCode::Viewer]);
+
+        let publisher = RoleCode::Custom("Publisher".into());
+        let err = fixture
+            .assign(2, RoleAssignment::Grant(vec![publisher]))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RoleError::NotFound(_)));
+
+        let err = fixture
+            .assign(1, RoleAssignment::Replace(vec![RoleCode::Viewer]))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RoleError::Protected(RoleCode::Admin, _)));
+
+        // With a second Admin, the first may step down
+        fixture
+            .assign(2, RoleAssignment::Grant(vec![RoleCode::Admin]))
+            .await
+            .unwrap();
+        let assigned = fixture
+            .assign(1, RoleAssignment::Revoke(vec![RoleCode::Admin]))
+            .await
+            .unwrap();
+        assert!(assigned.roles.is_empty());
+        assert!(fixture.session_roles(1).is_empty());
+    }
+}
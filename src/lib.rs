@@ -9,6 +9,7 @@
 //! - `repository/` – SQLx-backed implementations for Postgres and Redis
 //! - `auth.rs`     – Password hashing and authentication helpers
 //! - `mail/`       – Email formatting and delivery logic
+//! - `oidc/`       – OpenID Connect client for single sign-on
 //!
 //! ## Consumers
 //! - `bin/server/` – Rocket-based HTTP API
@@ -24,8 +25,15 @@ pub mod domain;
 // --- Mail delivery mechanisms ---
 pub mod mail;
 
+// --- OpenID Connect identity provider client ---
+pub mod oidc;
+
 // --- Repository: Diesel-backed data layer ---
 pub mod repository;
 
 // Contains Diesel-backed implementations and models.
 pub mod rocket_routes;
+
+// --- In-memory repository and cache fakes for unit tests ---
+#[cfg(test)]
+pub(crate) mod test_support;
@@ -8,6 +8,7 @@
 
 use crate::domain::{CrateSummary, MailerTrait, MailerTraitPtr};
 use anyhow::{anyhow, Context as AnyhowContext, Result};
+use chrono::Datelike;
 use lettre::{message::Mailbox, Message, SmtpTransport, Transport};
 use tera::{Context, Tera};
 
@@ -37,13 +38,41 @@ impl HtmlMailer {
         // ---
         let mut context = Context::new();
         context.insert("crates", crates); // Make sure `CrateSummary: Serialize`
+        context.insert("year", &chrono::Utc::now().year());
 
-        let html = self.tera.render("digest.html", &context)?;
+        let html = self
+            .tera
+            .render("email/digest.html", &context)
+            .context("Failed to render template: email/digest.html")?;
 
+        self.send_html_blocking(to, "📦 Crate Digest", html)
+    }
+
+    fn send_templated_blocking(
+        &self,
+        to: &str,
+        subject: &str,
+        template: &str,
+        context: serde_json::Value,
+    ) -> Result<()> {
+        // ---
+        let context = Context::from_value(context)
+            .with_context(|| format!("Invalid context for template: {template}"))?;
+
+        let html = self
+            .tera
+            .render(template, &context)
+            .with_context(|| format!("Failed to render template: {template}"))?;
+
+        self.send_html_blocking(to, subject, html)
+    }
+
+    fn send_html_blocking(&self, to: &str, subject: &str, html: String) -> Result<()> {
+        // ---
         let email = Message::builder()
             .from(self.smtp_user.parse::<Mailbox>()?)
             .to(to.parse::<Mailbox>()?)
-            .subject("📦 Crate Digest")
+            .subject(subject)
             .header(lettre::message::header::ContentType::TEXT_HTML)
             .body(html)?;
 
@@ -77,6 +106,27 @@ impl MailerTrait for HtmlMailer {
 
         Ok(())
     }
+
+    async fn send_templated(
+        &self,
+        dest: &str,
+        subject: &str,
+        template: &str,
+        context: serde_json::Value,
+    ) -> Result<()> {
+        // --
+        let this = self.clone();
+        let dest = dest.to_string();
+        let subject = subject.to_string();
+        let template = template.to_string();
+
+        tokio::task::spawn_blocking(move || {
+            this.send_templated_blocking(&dest, &subject, &template, context)
+        })
+        .await??;
+
+        Ok(())
+    }
 }
 
 /// Constructs the default mailer using Tera templates and SMTP credentials.
@@ -6,5 +6,7 @@
 //! the default mail delivery implementation.
 
 mod html_mailer;
+mod unconfigured_mailer;
 pub use html_mailer::create_mailer;
 pub use html_mailer::HtmlMailer as Mailer;
+pub use unconfigured_mailer::create_unconfigured_mailer;
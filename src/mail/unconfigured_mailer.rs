@@ -0,0 +1,37 @@
+//! Placeholder mailer used when SMTP settings are missing.
+//!
+//! Lets the server start without mail configured; every send fails with the reason
+//! the real mailer could not be built. Access via `crate::mail::create_unconfigured_mailer()`.
+
+use crate::domain::{CrateSummary, MailerTrait, MailerTraitPtr};
+use anyhow::{anyhow, Result};
+
+struct UnconfiguredMailer {
+    reason: String,
+}
+
+#[async_trait::async_trait]
+impl MailerTrait for UnconfiguredMailer {
+    // --
+    async fn send_digest(&self, _dest: &str, _crates: &[CrateSummary]) -> Result<()> {
+        Err(anyhow!("mail is not configured: {}", self.reason))
+    }
+
+    async fn send_templated(
+        &self,
+        _dest: &str,
+        _subject: &str,
+        _template: &str,
+        _context: serde_json::Value,
+    ) -> Result<()> {
+        Err(anyhow!("mail is not configured: {}", self.reason))
+    }
+}
+
+/// Constructs a mailer whose sends always fail with `reason`.
+pub fn create_unconfigured_mailer(reason: impl Into<String>) -> MailerTraitPtr {
+    // ---
+    std::sync::Arc::new(UnconfiguredMailer {
+        reason: reason.into(),
+    })
+}
@@ -0,0 +1,475 @@
+//! OpenID Connect client for a discovered identity provider.
+//!
+//! Do not use directly — access via `crate::oidc::create_oidc_provider()`.
+
+use crate::domain::{OidcIdentity, OidcProviderTrait, OidcProviderTraitPtr, RoleCode};
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use data_encoding::BASE64URL_NOPAD;
+use reqwest::Url;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// Plain `http://` IdP endpoints are only accepted by the unit tests' local mock IdP.
+const ALLOW_PLAIN_HTTP: bool = cfg!(test);
+
+/// Client registration and behavior, read from the environment.
+#[derive(Debug, Clone)]
+struct OidcConfig {
+    issuer: String,
+    client_id: String,
+    client_secret: Option<String>,
+    redirect_url: String,
+    scopes: String,
+    default_role: RoleCode,
+}
+
+impl OidcConfig {
+    // ---
+    /// Respects env vars:
+    /// - `CR8S_OIDC_ISSUER` (required)
+    /// - `CR8S_OIDC_CLIENT_ID` (required)
+    /// - `CR8S_OIDC_CLIENT_SECRET` (optional, omit for a public client)
+    /// - `CR8S_OIDC_REDIRECT_URL` (default: `<CR8S_PUBLIC_URL>/cr8s/auth/oidc/callback`)
+    /// - `CR8S_OIDC_SCOPES` (default: `openid email profile`)
+    /// - `CR8S_OIDC_DEFAULT_ROLE` (default: `Viewer`)
+    fn from_env() -> Result<Self> {
+        // ---
+        let issuer =
+            std::env::var("CR8S_OIDC_ISSUER").context("Missing CR8S_OIDC_ISSUER env var")?;
+        let client_id =
+            std::env::var("CR8S_OIDC_CLIENT_ID").context("Missing CR8S_OIDC_CLIENT_ID env var")?;
+        let client_secret = std::env::var("CR8S_OIDC_CLIENT_SECRET")
+            .ok()
+            .filter(|secret| !secret.is_empty());
+
+        let public_url = crate::get_env_with_default!(
+            String,
+            "CR8S_PUBLIC_URL",
+            "http://localhost:8000".to_owned()
+        );
+        let redirect_url = crate::get_env_with_default!(
+            String,
+            "CR8S_OIDC_REDIRECT_URL",
+            format!(
+                "{}/cr8s/auth/oidc/callback",
+                public_url.trim_end_matches('/')
+            )
+        );
+        let scopes = crate::get_env_with_default!(
+            String,
+            "CR8S_OIDC_SCOPES",
+            "openid email profile".to_owned()
+        );
+
+        let default_role =
+            crate::get_env_with_default!(String, "CR8S_OIDC_DEFAULT_ROLE", "Viewer".to_owned());
+        let default_role = default_role
+            .parse()
+            .map_err(|_| anyhow!("Invalid CR8S_OIDC_DEFAULT_ROLE: {default_role}"))?;
+
+        Ok(Self {
+            issuer,
+            client_id,
+            client_secret,
+            redirect_url,
+            scopes,
+            default_role,
+        })
+    }
+}
+
+/// The parts of the IdP's discovery document this client uses.
+#[derive(Debug, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Audience {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Audience {
+    fn contains(&self, client_id: &str) -> bool {
+        match self {
+            Audience::One(aud) => aud == client_id,
+            Audience::Many(auds) => auds.iter().any(|aud| aud == client_id),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct IdTokenClaims {
+    iss: String,
+    sub: String,
+    aud: Audience,
+    exp: i64,
+    nonce: Option<String>,
+    email: Option<String>,
+    #[serde(default)]
+    email_verified: bool,
+    preferred_username: Option<String>,
+}
+
+/// Authorization code + PKCE client for one IdP.
+struct HttpOidcProvider {
+    config: OidcConfig,
+    client: reqwest::Client,
+    authorization_endpoint: Url,
+    token_endpoint: String,
+}
+
+impl HttpOidcProvider {
+    // ---
+    /// Fetch the IdP's discovery document and build a client for it.
+    async fn discover(config: OidcConfig) -> Result<Self> {
+        // ---
+        ensure_tls(&config.issuer, "CR8S_OIDC_ISSUER", ALLOW_PLAIN_HTTP)?;
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .https_only(!ALLOW_PLAIN_HTTP)
+            .build()
+            .context("Failed to build OIDC HTTP client")?;
+
+        let discovery_url = format!(
+            "{}/.well-known/openid-configuration",
+            config.issuer.trim_end_matches('/')
+        );
+        let metadata: ProviderMetadata = client
+            .get(&discovery_url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .with_context(|| format!("Failed to fetch OIDC discovery: {discovery_url}"))?
+            .json()
+            .await
+            .with_context(|| format!("Invalid OIDC discovery document: {discovery_url}"))?;
+
+        ensure!(
+            metadata.issuer == config.issuer,
+            "OIDC discovery issuer {} does not match CR8S_OIDC_ISSUER {}",
+            metadata.issuer,
+            config.issuer
+        );
+
+        let authorization_endpoint = Url::parse(&metadata.authorization_endpoint)
+            .context("Invalid OIDC authorization_endpoint")?;
+        ensure_tls(
+            &metadata.token_endpoint,
+            "OIDC token_endpoint",
+            ALLOW_PLAIN_HTTP,
+        )?;
+
+        Ok(Self {
+            config,
+            client,
+            authorization_endpoint,
+            token_endpoint: metadata.token_endpoint,
+        })
+    }
+
+    /// Decode and validate an ID token from the token endpoint.
+    ///
+    /// The signature is not checked: the token came straight from the token endpoint
+    /// over a TLS connection we opened, which OpenID Connect Core (section 3.1.3.7)
+    /// allows in place of signature validation. `discover` refuses endpoints that
+    /// would not give that guarantee.
+    fn validate_id_token(&self, id_token: &str, nonce: &str, now: i64) -> Result<OidcIdentity> {
+        // ---
+        let payload = id_token
+            .split('.')
+            .nth(1)
+            .ok_or_else(|| anyhow!("malformed ID token"))?;
+        let claims: IdTokenClaims = serde_json::from_slice(
+            &BASE64URL_NOPAD
+                .decode(payload.as_bytes())
+                .context("malformed ID token payload")?,
+        )
+        .context("invalid ID token claims")?;
+
+        ensure!(claims.iss == self.config.issuer, "ID token issuer mismatch");
+        ensure!(
+            claims.aud.contains(&self.config.client_id),
+            "ID token audience mismatch"
+        );
+        ensure!(claims.exp > now, "ID token expired");
+        ensure!(
+            claims.nonce.as_deref() == Some(nonce),
+            "ID token nonce mismatch"
+        );
+
+        Ok(OidcIdentity {
+            issuer: claims.iss,
+            subject: claims.sub,
+            email: claims.email,
+            email_verified: claims.email_verified,
+            preferred_username: claims.preferred_username,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl OidcProviderTrait for HttpOidcProvider {
+    // ---
+    fn authorization_url(&self, state: &str, nonce: &str, code_challenge: &str) -> Result<String> {
+        // ---
+        let mut url = self.authorization_endpoint.clone();
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.config.client_id)
+            .append_pair("redirect_uri", &self.config.redirect_url)
+            .append_pair("scope", &self.config.scopes)
+            .append_pair("state", state)
+            .append_pair("nonce", nonce)
+            .append_pair("code_challenge", code_challenge)
+            .append_pair("code_challenge_method", "S256");
+
+        Ok(url.into())
+    }
+
+    async fn exchange_code(
+        &self,
+        code: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> Result<OidcIdentity> {
+        // ---
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", &self.config.redirect_url),
+            ("client_id", &self.config.client_id),
+            ("code_verifier", code_verifier),
+        ];
+        if let Some(secret) = &self.config.client_secret {
+            form.push(("client_secret", secret));
+        }
+
+        let response = self
+            .client
+            .post(&self.token_endpoint)
+            .form(&form)
+            .send()
+            .await
+            .context("OIDC token request failed")?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            bail!("OIDC token endpoint returned {status}: {body}");
+        }
+
+        let tokens: TokenResponse = response
+            .json()
+            .await
+            .context("Invalid OIDC token response")?;
+
+        self.validate_id_token(&tokens.id_token, nonce, chrono::Utc::now().timestamp())
+    }
+
+    fn default_role(&self) -> RoleCode {
+        self.config.default_role.clone()
+    }
+}
+
+/// Refuse an IdP URL that is not `https://`; ID tokens are trusted because the token
+/// endpoint is authenticated by TLS.
+fn ensure_tls(url: &str, name: &str, allow_plain_http: bool) -> Result<()> {
+    // ---
+    let url = Url::parse(url).with_context(|| format!("Invalid {name}: {url}"))?;
+    ensure!(
+        url.scheme() == "https" || (allow_plain_http && url.scheme() == "http"),
+        "{name} must use https, got {url}"
+    );
+    Ok(())
+}
+
+/// Constructs the OpenID Connect provider from `CR8S_OIDC_*` env vars (see
+/// `OidcConfig::from_env`), fetching the IdP's discovery document.
+///
+/// # Errors
+/// Returns an error if required env vars are missing or discovery fails.
+pub async fn create_oidc_provider() -> Result<OidcProviderTraitPtr> {
+    // ---
+    let config = OidcConfig::from_env()?;
+    Ok(std::sync::Arc::new(
+        HttpOidcProvider::discover(config).await?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    /// Minimal local IdP: serves discovery and a token endpoint that issues an ID
+    /// token with `claims`, recording every token request body.
+    fn spawn_mock_idp(
+        claims: impl Fn(&str) -> serde_json::Value + Send + 'static,
+    ) -> (String, Arc<Mutex<Vec<String>>>) {
+        // ---
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let issuer = format!("http://{}", listener.local_addr().unwrap());
+        let token_requests = Arc::new(Mutex::new(Vec::new()));
+
+        let base = issuer.clone();
+        let requests = token_requests.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut content_length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                let body = String::from_utf8(body).unwrap();
+
+                let response = if request_line.starts_with("GET /.well-known/openid-configuration")
+                {
+                    serde_json::json!({
+                        "issuer": base,
+                        "authorization_endpoint": format!("{base}/authorize"),
+                        "token_endpoint": format!("{base}/token"),
+                    })
+                } else {
+                    requests.lock().unwrap().push(body.clone());
+                    let payload = BASE64URL_NOPAD.encode(claims(&base).to_string().as_bytes());
+                    serde_json::json!({ "id_token": format!("eyJhbGciOiJSUzI1NiJ9.{payload}.sig") })
+                };
+
+                let response = response.to_string();
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{response}",
+                    response.len()
+                )
+                .unwrap();
+            }
+        });
+
+        (issuer, token_requests)
+    }
+
+    fn config(issuer: &str) -> OidcConfig {
+        // ---
+        OidcConfig {
+            issuer: issuer.into(),
+            client_id: "cr8s".into(),
+            client_secret: Some("s3cret".into()),
+            redirect_url: "http://localhost:8000/cr8s/auth/oidc/callback".into(),
+            scopes: "openid email".into(),
+            default_role: RoleCode::Viewer,
+        }
+    }
+
+    fn id_token_claims(issuer: &str, aud: &str) -> serde_json::Value {
+        // ---
+        serde_json::json!({
+            "iss": issuer,
+            "sub": "user-42",
+            "aud": [aud, "other-client"],
+            "exp": chrono::Utc::now().timestamp() + 60,
+            "nonce": "nonce-1",
+            "email": "sso@example.com",
+            "email_verified": true,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_code_exchange_against_mock_idp() {
+        // ---
+        let (issuer, token_requests) = spawn_mock_idp(|iss| id_token_claims(iss, "cr8s"));
+        let provider = HttpOidcProvider::discover(config(&issuer)).await.unwrap();
+
+        let url = Url::parse(&provider.authorization_url("st", "nonce-1", "ch").unwrap()).unwrap();
+        assert_eq!(
+            url.as_str().split('?').next(),
+            Some(&*format!("{issuer}/authorize"))
+        );
+        let params: std::collections::HashMap<_, _> = url.query_pairs().into_owned().collect();
+        assert_eq!(
+            params["redirect_uri"],
+            "http://localhost:8000/cr8s/auth/oidc/callback"
+        );
+        assert_eq!(params["code_challenge"], "ch");
+        assert_eq!(params["code_challenge_method"], "S256");
+        assert_eq!(params["state"], "st");
+
+        let identity = provider
+            .exchange_code("the-code", "the-verifier", "nonce-1")
+            .await
+            .unwrap();
+        assert_eq!(identity.issuer, issuer);
+        assert_eq!(identity.subject, "user-42");
+        assert_eq!(identity.email.as_deref(), Some("sso@example.com"));
+        assert!(identity.email_verified);
+
+        let request = token_requests.lock().unwrap()[0].clone();
+        assert!(request.contains("code=the-code"));
+        assert!(request.contains("code_verifier=the-verifier"));
+        assert!(request.contains("client_secret=s3cret"));
+
+        // A replayed or foreign ID token is refused
+        assert!(provider
+            .exchange_code("the-code", "the-verifier", "nonce-2")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_id_token_for_other_client_is_rejected() {
+        // ---
+        let (issuer, _) = spawn_mock_idp(|iss| id_token_claims(iss, "someone-else"));
+        let provider = HttpOidcProvider::discover(config(&issuer)).await.unwrap();
+
+        let err = provider
+            .exchange_code("the-code", "the-verifier", "nonce-1")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("audience"));
+    }
+
+    #[test]
+    fn test_idp_endpoints_require_https() {
+        // ---
+        assert!(ensure_tls("https://idp.example.com", "CR8S_OIDC_ISSUER", false).is_ok());
+        let err =
+            ensure_tls("http://idp.example.com/token", "OIDC token_endpoint", false).unwrap_err();
+        assert!(err.to_string().contains("must use https"));
+        assert!(ensure_tls("idp.example.com", "CR8S_OIDC_ISSUER", false).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_discovery_issuer_must_match() {
+        // ---
+        let (issuer, _) = spawn_mock_idp(|iss| id_token_claims(iss, "cr8s"));
+
+        let result = HttpOidcProvider::discover(config(&format!("{issuer}/"))).await;
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,9 @@
+//! OpenID Connect client for single sign-on.
+//!
+//! Provides the `OidcProviderTrait` implementation used by the server: an HTTP client
+//! for a discovered IdP, and a placeholder for when single sign-on is not configured.
+
+mod http_provider;
+mod unconfigured_provider;
+pub use http_provider::create_oidc_provider;
+pub use unconfigured_provider::create_unconfigured_oidc_provider;
@@ -0,0 +1,44 @@
+//! Placeholder provider used when single sign-on is not configured.
+//!
+//! Lets the server start without an IdP; the SSO routes then answer 404. Access via
+//! `crate::oidc::create_unconfigured_oidc_provider()`.
+
+use crate::domain::{OidcIdentity, OidcProviderTrait, OidcProviderTraitPtr, RoleCode};
+use anyhow::{anyhow, Result};
+
+struct UnconfiguredOidcProvider {
+    reason: String,
+}
+
+#[async_trait::async_trait]
+impl OidcProviderTrait for UnconfiguredOidcProvider {
+    // --
+    fn is_configured(&self) -> bool {
+        false
+    }
+
+    fn authorization_url(&self, _state: &str, _nonce: &str, _challenge: &str) -> Result<String> {
+        Err(anyhow!("single sign-on is not configured: {}", self.reason))
+    }
+
+    async fn exchange_code(
+        &self,
+        _code: &str,
+        _code_verifier: &str,
+        _nonce: &str,
+    ) -> Result<OidcIdentity> {
+        Err(anyhow!("single sign-on is not configured: {}", self.reason))
+    }
+
+    fn default_role(&self) -> RoleCode {
+        RoleCode::Viewer
+    }
+}
+
+/// Constructs a provider that rejects every login with `reason`.
+pub fn create_unconfigured_oidc_provider(reason: impl Into<String>) -> OidcProviderTraitPtr {
+    // ---
+    std::sync::Arc::new(UnconfiguredOidcProvider {
+        reason: reason.into(),
+    })
+}
@@ -0,0 +1,172 @@
+// cr8s/src/repository/api_token_sqlx.rs
+
+use crate::domain::{ApiScope, ApiToken, ApiTokenTableTrait, ApiTokenTableTraitPtr};
+use crate::repository::get_pool;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sqlx::FromRow;
+use std::sync::Arc;
+
+// ---
+
+#[derive(Debug, Clone)]
+pub struct ApiTokenRepo {
+    pool: sqlx::PgPool,
+}
+
+// ---
+
+impl ApiTokenRepo {
+    pub fn new(pool: sqlx::PgPool) -> Self {
+        // ---
+        Self { pool }
+    }
+}
+
+// ---
+
+pub fn create_api_token_repo() -> ApiTokenTableTraitPtr {
+    // --
+    let pool = get_pool().clone();
+    Arc::new(ApiTokenRepo::new(pool))
+}
+
+// ---
+
+#[derive(Debug, FromRow)]
+struct ApiTokenRow {
+    id: i32,
+    user_id: i32,
+    name: String,
+    scopes: Vec<String>,
+    created_at: NaiveDateTime,
+    expires_at: Option<NaiveDateTime>,
+    last_used_at: Option<NaiveDateTime>,
+}
+
+impl From<ApiTokenRow> for ApiToken {
+    fn from(row: ApiTokenRow) -> Self {
+        // Unknown scopes (e.g. from a newer release) are dropped rather than granted
+        let scopes = row
+            .scopes
+            .iter()
+            .filter_map(|scope| match scope.parse::<ApiScope>() {
+                Ok(scope) => Some(scope),
+                Err(err) => {
+                    tracing::warn!("API token {}: {err}", row.id);
+                    None
+                }
+            })
+            .collect();
+
+        ApiToken {
+            id: row.id,
+            user_id: row.user_id,
+            name: row.name,
+            scopes,
+            created_at: row.created_at,
+            expires_at: row.expires_at,
+            last_used_at: row.last_used_at,
+        }
+    }
+}
+
+// ---
+
+#[async_trait]
+impl ApiTokenTableTrait for ApiTokenRepo {
+    // ---
+
+    async fn create(
+        &self,
+        user_id: i32,
+        name: &str,
+        token_hash: &str,
+        scopes: &[ApiScope],
+        expires_at: Option<NaiveDateTime>,
+    ) -> Result<ApiToken> {
+        // ---
+        let scopes: Vec<String> = scopes.iter().map(ToString::to_string).collect();
+
+        let row = sqlx::query_as::<_, ApiTokenRow>(
+            r#"
+            INSERT INTO api_token (user_id, name, token_hash, scopes, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, user_id, name, scopes, created_at, expires_at, last_used_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(name)
+        .bind(token_hash)
+        .bind(&scopes)
+        .bind(expires_at)
+        .fetch_one(&self.pool)
+        .await
+        .with_context(|| format!("ApiTokenTableTrait::create failed for user: {user_id}"))?;
+
+        Ok(row.into())
+    }
+
+    // ---
+    async fn find_by_user(&self, user_id: i32) -> Result<Vec<ApiToken>> {
+        // ---
+        let rows = sqlx::query_as::<_, ApiTokenRow>(
+            r#"
+            SELECT id, user_id, name, scopes, created_at, expires_at, last_used_at
+            FROM api_token
+            WHERE user_id = $1
+            ORDER BY created_at DESC, id DESC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .with_context(|| format!("ApiTokenTableTrait::find_by_user failed for: {user_id}"))?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    // ---
+    async fn find_by_hash(&self, token_hash: &str) -> Result<Option<ApiToken>> {
+        // ---
+        let row = sqlx::query_as::<_, ApiTokenRow>(
+            r#"
+            SELECT id, user_id, name, scopes, created_at, expires_at, last_used_at
+            FROM api_token
+            WHERE token_hash = $1
+            "#,
+        )
+        .bind(token_hash)
+        .fetch_optional(&self.pool)
+        .await
+        .context("ApiTokenTableTrait::find_by_hash")?;
+
+        Ok(row.map(Into::into))
+    }
+
+    // ---
+    async fn revoke(&self, user_id: i32, token_id: i32) -> Result<bool> {
+        // ---
+        let result = sqlx::query(r#"DELETE FROM api_token WHERE id = $1 AND user_id = $2"#)
+            .bind(token_id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await
+            .with_context(|| format!("ApiTokenTableTrait::revoke failed for: {token_id}"))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    // ---
+    async fn touch(&self, token_id: i32) -> Result<()> {
+        // ---
+        sqlx::query(r#"UPDATE api_token SET last_used_at = NOW() WHERE id = $1"#)
+            .bind(token_id)
+            .execute(&self.pool)
+            .await
+            .with_context(|| format!("ApiTokenTableTrait::touch failed for: {token_id}"))?;
+
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
 use crate::domain::{
-    AppUser, AppUserTableTrait, AppUserTableTraitPtr, AppUserWithRoleCodes, NewUser, RoleCode,
+    AppUser, AppUserTableTrait, AppUserTableTraitPtr, AppUserWithRoleCodes, NewUser, RecoveryCode,
+    RoleCode, TotpSecret,
 };
 use crate::repository::RoleCodeMapping;
 use anyhow::{Context, Result};
@@ -26,6 +27,8 @@ struct AppUserRow {
     username: String,
     password: String,
     created_at: chrono::NaiveDateTime,
+    author_id: Option<i32>,
+    disabled_at: Option<chrono::NaiveDateTime>,
 }
 
 impl From<AppUserRow> for AppUser {
@@ -35,6 +38,8 @@ impl From<AppUserRow> for AppUser {
             username: row.username,
             password: row.password,
             created_at: row.created_at,
+            author_id: row.author_id,
+            disabled_at: row.disabled_at,
         }
     }
 }
@@ -50,7 +55,7 @@ impl AppUserTableTrait for AppUserRepo {
             r#"
             INSERT INTO app_user (username, password)
             VALUES ($1, $2)
-            RETURNING id, username, password, created_at
+            RETURNING id, username, password, created_at, author_id, disabled_at
             "#,
         )
         .bind(&new_user.username)
@@ -62,7 +67,7 @@ impl AppUserTableTrait for AppUserRepo {
             sqlx::query(
                 r#"
                 INSERT INTO user_roles (user_id, role_id)
-                SELECT $1, id FROM role WHERE code = $2::"RoleCodeMapping"
+                SELECT $1, id FROM role WHERE code = $2
                 "#,
             )
             .bind(rec.id)
@@ -81,7 +86,7 @@ impl AppUserTableTrait for AppUserRepo {
         // ---
         let rec = sqlx::query_as::<_, AppUserRow>(
             r#"
-            SELECT id, username, password, created_at
+            SELECT id, username, password, created_at, author_id, disabled_at
             FROM app_user
             WHERE id = $1
             "#,
@@ -105,6 +110,7 @@ impl AppUserTableTrait for AppUserRepo {
             FROM user_roles ur
             JOIN role r ON r.id = ur.role_id
             WHERE ur.user_id = $1
+            ORDER BY r.id
             "#,
         )
         .bind(user.id)
@@ -152,7 +158,7 @@ impl AppUserTableTrait for AppUserRepo {
         // ---
         let user = sqlx::query_as::<_, AppUserRow>(
             r#"
-            SELECT id, username, password, created_at
+            SELECT id, username, password, created_at, author_id, disabled_at
             FROM app_user
             WHERE username = $1
             "#,
@@ -167,6 +173,281 @@ impl AppUserTableTrait for AppUserRepo {
         Ok(user.into())
     }
 
+    // ---
+    async fn update_password(&self, user_id: i32, password_hash: &str) -> Result<()> {
+        // ---
+        let result = sqlx::query(r#"UPDATE app_user SET password = $2 WHERE id = $1"#)
+            .bind(user_id)
+            .bind(password_hash)
+            .execute(&self.pool)
+            .await
+            .with_context(|| format!("AppUserRepo::update_password failed for: {user_id}"))?;
+
+        if result.rows_affected() == 0 {
+            anyhow::bail!("AppUserRepo::update_password: no user found with id {user_id}");
+        }
+
+        Ok(())
+    }
+
+    // ---
+    async fn find_totp(&self, user_id: i32) -> Result<Option<TotpSecret>> {
+        // ---
+        let row = sqlx::query_as::<_, (Option<String>, bool)>(
+            r#"SELECT totp_secret, totp_enabled FROM app_user WHERE id = $1"#,
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await
+        .with_context(|| format!("AppUserRepo::find_totp failed for: {user_id}"))?;
+
+        Ok(row.and_then(|(secret, enabled)| secret.map(|secret| TotpSecret { secret, enabled })))
+    }
+
+    // ---
+    async fn set_pending_totp(&self, user_id: i32, secret: &str) -> Result<()> {
+        // ---
+        let result = sqlx::query(
+            r#"
+            UPDATE app_user
+            SET totp_secret = $2, totp_enabled = FALSE, totp_last_step = NULL
+            WHERE id = $1
+            "#,
+        )
+        .bind(user_id)
+        .bind(secret)
+        .execute(&self.pool)
+        .await
+        .with_context(|| format!("AppUserRepo::set_pending_totp failed for: {user_id}"))?;
+
+        if result.rows_affected() == 0 {
+            anyhow::bail!("AppUserRepo::set_pending_totp: no user found with id {user_id}");
+        }
+
+        Ok(())
+    }
+
+    // ---
+    async fn enable_totp(&self, user_id: i32, recovery_code_hashes: &[String]) -> Result<()> {
+        // ---
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"UPDATE app_user SET totp_enabled = TRUE WHERE id = $1 AND totp_secret IS NOT NULL"#,
+        )
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await
+        .with_context(|| format!("AppUserRepo::enable_totp failed for: {user_id}"))?;
+
+        sqlx::query(r#"DELETE FROM recovery_code WHERE user_id = $1"#)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .with_context(|| format!("AppUserRepo::enable_totp: clearing codes for: {user_id}"))?;
+
+        for code_hash in recovery_code_hashes {
+            sqlx::query(r#"INSERT INTO recovery_code (user_id, code_hash) VALUES ($1, $2)"#)
+                .bind(user_id)
+                .bind(code_hash)
+                .execute(&mut *tx)
+                .await
+                .with_context(|| {
+                    format!("AppUserRepo::enable_totp: storing codes for: {user_id}")
+                })?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    // ---
+    async fn find_recovery_codes(&self, user_id: i32) -> Result<Vec<RecoveryCode>> {
+        // ---
+        let rows = sqlx::query_as::<_, (i32, String)>(
+            r#"
+            SELECT id, code_hash
+            FROM recovery_code
+            WHERE user_id = $1 AND used_at IS NULL
+            ORDER BY id
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .with_context(|| format!("AppUserRepo::find_recovery_codes failed for: {user_id}"))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(id, code_hash)| RecoveryCode { id, code_hash })
+            .collect())
+    }
+
+    // ---
+    async fn consume_recovery_code(&self, code_id: i32) -> Result<bool> {
+        // ---
+        let result = sqlx::query(
+            r#"UPDATE recovery_code SET used_at = NOW() WHERE id = $1 AND used_at IS NULL"#,
+        )
+        .bind(code_id)
+        .execute(&self.pool)
+        .await
+        .with_context(|| format!("AppUserRepo::consume_recovery_code failed for: {code_id}"))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    // ---
+    async fn use_totp_step(&self, user_id: i32, step: u64) -> Result<bool> {
+        // ---
+        let result = sqlx::query(
+            r#"
+            UPDATE app_user SET totp_last_step = $2
+            WHERE id = $1 AND (totp_last_step IS NULL OR totp_last_step < $2)
+            "#,
+        )
+        .bind(user_id)
+        .bind(step as i64)
+        .execute(&self.pool)
+        .await
+        .with_context(|| format!("AppUserRepo::use_totp_step failed for: {user_id}"))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    // ---
+    async fn find_by_identity(&self, issuer: &str, subject: &str) -> Result<Option<AppUser>> {
+        // ---
+        let user = sqlx::query_as::<_, AppUserRow>(
+            r#"
+            SELECT u.id, u.username, u.password, u.created_at, u.author_id, u.disabled_at
+            FROM user_identity i
+            JOIN app_user u ON u.id = i.user_id
+            WHERE i.issuer = $1 AND i.subject = $2
+            "#,
+        )
+        .bind(issuer)
+        .bind(subject)
+        .fetch_optional(&self.pool)
+        .await
+        .with_context(|| format!("AppUserRepo::find_by_identity failed for: {issuer}"))?;
+
+        Ok(user.map(Into::into))
+    }
+
+    // ---
+    async fn link_identity(&self, user_id: i32, issuer: &str, subject: &str) -> Result<()> {
+        // ---
+        sqlx::query(
+            r#"
+            INSERT INTO user_identity (user_id, issuer, subject)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (issuer, subject) DO NOTHING
+            "#,
+        )
+        .bind(user_id)
+        .bind(issuer)
+        .bind(subject)
+        .execute(&self.pool)
+        .await
+        .with_context(|| format!("AppUserRepo::link_identity failed for: {user_id}"))?;
+
+        Ok(())
+    }
+
+    // ---
+    async fn find_by_author(&self, author_id: i32) -> Result<Option<AppUser>> {
+        // ---
+        let user = sqlx::query_as::<_, AppUserRow>(
+            r#"
+            SELECT id, username, password, created_at, author_id, disabled_at
+            FROM app_user
+            WHERE author_id = $1
+            "#,
+        )
+        .bind(author_id)
+        .fetch_optional(&self.pool)
+        .await
+        .with_context(|| format!("AppUserRepo::find_by_author failed for: {author_id}"))?;
+
+        Ok(user.map(Into::into))
+    }
+
+    // ---
+    async fn set_author(&self, user_id: i32, author_id: Option<i32>) -> Result<()> {
+        // ---
+        let result = sqlx::query(r#"UPDATE app_user SET author_id = $2 WHERE id = $1"#)
+            .bind(user_id)
+            .bind(author_id)
+            .execute(&self.pool)
+            .await
+            .with_context(|| format!("AppUserRepo::set_author failed for: {user_id}"))?;
+
+        anyhow::ensure!(result.rows_affected() > 0, "No user with id {user_id}");
+        Ok(())
+    }
+
+    // ---
+    async fn set_disabled(&self, user_id: i32, disabled: bool) -> Result<bool> {
+        // ---
+        let result = sqlx::query(
+            r#"
+            UPDATE app_user
+            SET disabled_at = CASE WHEN $2 THEN COALESCE(disabled_at, NOW()) ELSE NULL END
+            WHERE id = $1
+            "#,
+        )
+        .bind(user_id)
+        .bind(disabled)
+        .execute(&self.pool)
+        .await
+        .with_context(|| format!("AppUserRepo::set_disabled failed for: {user_id}"))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    // ---
+    async fn grant_roles(&self, user_id: i32, role_codes: &[RoleCode]) -> Result<()> {
+        // ---
+        let codes: Vec<String> = role_codes.iter().map(RoleCode::to_string).collect();
+
+        sqlx::query(
+            r#"
+            INSERT INTO user_roles (user_id, role_id)
+            SELECT $1, id FROM role WHERE code = ANY($2)
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(user_id)
+        .bind(&codes)
+        .execute(&self.pool)
+        .await
+        .with_context(|| format!("AppUserRepo::grant_roles failed for: {user_id}"))?;
+
+        Ok(())
+    }
+
+    // ---
+    async fn revoke_roles(&self, user_id: i32, role_codes: &[RoleCode]) -> Result<()> {
+        // ---
+        let codes: Vec<String> = role_codes.iter().map(RoleCode::to_string).collect();
+
+        sqlx::query(
+            r#"
+            DELETE FROM user_roles
+            WHERE user_id = $1
+              AND role_id IN (SELECT id FROM role WHERE code = ANY($2))
+            "#,
+        )
+        .bind(user_id)
+        .bind(&codes)
+        .execute(&self.pool)
+        .await
+        .with_context(|| format!("AppUserRepo::revoke_roles failed for: {user_id}"))?;
+
+        Ok(())
+    }
+
     // ---
     async fn find_with_roles(&self) -> Result<Vec<AppUserWithRoleCodes>> {
         // ---
@@ -176,6 +457,8 @@ impl AppUserTableTrait for AppUserRepo {
             username: String,
             password: String,
             created_at: chrono::NaiveDateTime,
+            author_id: Option<i32>,
+            disabled_at: Option<chrono::NaiveDateTime>,
             code: Option<RoleCodeMapping>,
         }
 
@@ -186,6 +469,8 @@ impl AppUserTableTrait for AppUserRepo {
               u.username,
               u.password,
               u.created_at,
+              u.author_id,
+              u.disabled_at,
               r.code as code
             FROM app_user u
             LEFT JOIN user_roles ur ON u.id = ur.user_id
@@ -208,6 +493,8 @@ impl AppUserTableTrait for AppUserRepo {
                         username: row.username.clone(),
                         password: row.password.clone(),
                         created_at: row.created_at,
+                        author_id: row.author_id,
+                        disabled_at: row.disabled_at,
                     },
                     Vec::new(),
                 )
@@ -0,0 +1,140 @@
+// cr8s/src/repository/audit_log_sqlx.rs
+
+use crate::domain::{AuditEvent, AuditFilter, AuditLogTrait, AuditLogTraitPtr, NewAuditEvent};
+use crate::repository::get_pool;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sqlx::FromRow;
+use std::sync::Arc;
+
+// ---
+
+#[derive(Debug, Clone)]
+pub struct AuditLogRepo {
+    pool: sqlx::PgPool,
+}
+
+// ---
+
+impl AuditLogRepo {
+    pub fn new(pool: sqlx::PgPool) -> Self {
+        // ---
+        Self { pool }
+    }
+}
+
+// ---
+
+pub fn create_audit_log_repo() -> AuditLogTraitPtr {
+    // --
+    let pool = get_pool().clone();
+    Arc::new(AuditLogRepo::new(pool))
+}
+
+// ---
+
+/// Snapshots are bound and read as text so no SQLx JSON support is needed.
+#[derive(Debug, FromRow)]
+struct AuditEventRow {
+    id: i32,
+    actor_user_id: Option<i32>,
+    action: String,
+    entity_type: String,
+    entity_id: i32,
+    before: Option<String>,
+    after: Option<String>,
+    created_at: NaiveDateTime,
+}
+
+impl TryFrom<AuditEventRow> for AuditEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(row: AuditEventRow) -> Result<Self> {
+        let parse_json = |json: Option<String>| {
+            json.map(|json| serde_json::from_str(&json))
+                .transpose()
+                .with_context(|| format!("audit event {}: invalid snapshot", row.id))
+        };
+
+        Ok(AuditEvent {
+            id: row.id,
+            actor_user_id: row.actor_user_id,
+            action: row.action.parse().map_err(anyhow::Error::msg)?,
+            entity_type: row.entity_type.parse().map_err(anyhow::Error::msg)?,
+            entity_id: row.entity_id,
+            before: parse_json(row.before)?,
+            after: parse_json(row.after)?,
+            created_at: row.created_at,
+        })
+    }
+}
+
+// ---
+
+#[async_trait]
+impl AuditLogTrait for AuditLogRepo {
+    // ---
+
+    async fn record(&self, event: NewAuditEvent) -> Result<AuditEvent> {
+        // ---
+        let row = sqlx::query_as::<_, AuditEventRow>(
+            r#"
+            INSERT INTO audit_event (actor_user_id, action, entity_type, entity_id, before, after)
+            VALUES ($1, $2, $3, $4, $5::jsonb, $6::jsonb)
+            RETURNING id, actor_user_id, action, entity_type, entity_id,
+                      before::text, after::text, created_at
+            "#,
+        )
+        .bind(event.actor_user_id)
+        .bind(event.action.to_string())
+        .bind(event.entity_type.to_string())
+        .bind(event.entity_id)
+        .bind(event.before.as_ref().map(ToString::to_string))
+        .bind(event.after.as_ref().map(ToString::to_string))
+        .fetch_one(&self.pool)
+        .await
+        .with_context(|| {
+            format!(
+                "AuditLogTrait::record failed for: {} {} {}",
+                event.action, event.entity_type, event.entity_id
+            )
+        })?;
+
+        row.try_into()
+    }
+
+    // ---
+    async fn find(&self, filter: &AuditFilter) -> Result<Vec<AuditEvent>> {
+        // ---
+        let rows = sqlx::query_as::<_, AuditEventRow>(
+            r#"
+            SELECT id, actor_user_id, action, entity_type, entity_id,
+                   before::text, after::text, created_at
+            FROM audit_event
+            WHERE ($1::int IS NULL OR actor_user_id = $1)
+              AND ($2::text IS NULL OR action = $2)
+              AND ($3::text IS NULL OR entity_type = $3)
+              AND ($4::int IS NULL OR entity_id = $4)
+              AND ($5::int IS NULL OR id > $5)
+            ORDER BY id DESC
+            LIMIT $6
+            "#,
+        )
+        .bind(filter.actor_user_id)
+        .bind(filter.action.map(|action| action.to_string()))
+        .bind(
+            filter
+                .entity_type
+                .map(|entity_type| entity_type.to_string()),
+        )
+        .bind(filter.entity_id)
+        .bind(filter.after_id)
+        .bind(filter.effective_limit())
+        .fetch_all(&self.pool)
+        .await
+        .with_context(|| format!("AuditLogTrait::find failed for: {filter:?}"))?;
+
+        rows.into_iter().map(TryInto::try_into).collect()
+    }
+}
@@ -1,4 +1,17 @@
-use crate::domain::{Author, AuthorTableTrait, AuthorTableTraitPtr, NewAuthor};
+use super::like_prefix_pattern;
+use crate::domain::{
+    //
+    effective_page_limit,
+    into_page,
+    Author,
+    AuthorQuery,
+    AuthorSort,
+    AuthorTableTrait,
+    AuthorTableTraitPtr,
+    NewAuthor,
+    Page,
+    SortOrder,
+};
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use sqlx::{FromRow, PgPool};
@@ -115,6 +128,60 @@ impl AuthorTableTrait for AuthorRepo {
         Ok(authors.into_iter().map(Into::into).collect())
     }
 
+    // ---
+    async fn find_page(&self, query: &AuthorQuery) -> Result<Page<Author>> {
+        // ---
+        // Filters shared by the count and the page query, binding $1..$2
+        const FILTERS: &str = r#"
+            ($1::text IS NULL OR name ILIKE $1)
+            AND ($2::timestamp IS NULL OR created_at >= $2)
+        "#;
+
+        // Column names come from the enum, never from the request
+        let (column, key_type) = match query.sort {
+            AuthorSort::Name => ("name", "text"),
+            AuthorSort::CreatedAt => ("created_at", "timestamp"),
+        };
+        let (direction, after) = match query.order {
+            SortOrder::Asc => ("ASC", ">"),
+            SortOrder::Desc => ("DESC", "<"),
+        };
+        let limit = effective_page_limit(query.limit);
+        let name_pattern = query.name_prefix.as_deref().map(like_prefix_pattern);
+
+        let total: i64 =
+            sqlx::query_scalar(&format!("SELECT COUNT(*) FROM author WHERE {FILTERS}"))
+                .bind(&name_pattern)
+                .bind(query.created_since)
+                .fetch_one(&self.pool)
+                .await
+                .context("AuthorRepo::find_page count query failed")?;
+
+        let authors = sqlx::query_as::<_, AuthorRow>(&format!(
+            r#"
+            SELECT id, name, email, created_at
+            FROM author
+            WHERE {FILTERS}
+              AND ($3::text IS NULL OR ({column}, id) {after} ($3::{key_type}, $4))
+            ORDER BY {column} {direction}, id {direction}
+            LIMIT $5
+            "#
+        ))
+        .bind(&name_pattern)
+        .bind(query.created_since)
+        .bind(query.cursor.as_ref().map(|cursor| cursor.key.clone()))
+        .bind(query.cursor.as_ref().map(|cursor| cursor.id))
+        .bind(limit + 1)
+        .fetch_all(&self.pool)
+        .await
+        .with_context(|| format!("AuthorRepo::find_page failed for: {query:?}"))?;
+
+        let authors = authors.into_iter().map(Into::into).collect();
+        Ok(into_page(authors, limit, total, |author| {
+            query.sort.cursor_for(author)
+        }))
+    }
+
     // ---
     async fn delete(&self, id: i32) -> Result<()> {
         // ---
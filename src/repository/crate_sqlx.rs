@@ -1,13 +1,40 @@
 // cr8s/src/repository/crate.rs
 
-use crate::domain::{Crate, CrateSummary, CrateTableTrait, CrateTableTraitPtr, NewCrate};
-use crate::repository::get_pool;
+use crate::domain::{
+    //
+    current_version,
+    effective_page_limit,
+    into_page,
+    version_sort_key,
+    Crate,
+    CrateQuery,
+    CrateSearchHit,
+    CrateSort,
+    CrateSummary,
+    CrateTableTrait,
+    CrateTableTraitPtr,
+    CrateVersion,
+    NewCrate,
+    NewCrateVersion,
+    Page,
+    ReleaseKind,
+    SortOrder,
+};
+use crate::repository::{get_pool, like_prefix_pattern};
 use anyhow::{Context, Result};
 use async_trait::async_trait;
+use sqlx::PgConnection;
 use std::sync::Arc;
 
 // ---
 
+/// Marks `ts_headline` puts around matched words; control characters cannot clash
+/// with crate text and survive HTML escaping untouched.
+const HIGHLIGHT_START: char = '\u{2}';
+const HIGHLIGHT_END: char = '\u{3}';
+
+// ---
+
 #[derive(Debug, Clone)]
 pub struct CrateRepo {
     pool: sqlx::PgPool,
@@ -40,7 +67,7 @@ impl CrateTableTrait for CrateRepo {
         // ---
         let rows = sqlx::query_as::<_, CrateRow>(
             r#"
-        SELECT id, author_id, code, name, version, description, created_at
+        SELECT id, author_id, code, name, version, yanked, description, created_at
         FROM crate
         ORDER BY created_at DESC
         LIMIT $1
@@ -55,12 +82,97 @@ impl CrateTableTrait for CrateRepo {
         Ok(crates)
     }
 
+    // ---
+    async fn find_page(&self, query: &CrateQuery) -> Result<Page<Crate>> {
+        // ---
+        // Filters shared by the count and the page query, binding $1..$3
+        const FILTERS: &str = r#"
+            ($1::int IS NULL OR author_id = $1)
+            AND ($2::text IS NULL OR name ILIKE $2)
+            AND ($3::timestamp IS NULL OR created_at >= $3)
+        "#;
+
+        // Column names come from the enum, never from the request
+        let (column, key_type) = match query.sort {
+            CrateSort::Name => ("name", "text"),
+            CrateSort::CreatedAt => ("created_at", "timestamp"),
+            CrateSort::Version => ("version_sort_key", "text"),
+        };
+        let (direction, after) = match query.order {
+            SortOrder::Asc => ("ASC", ">"),
+            SortOrder::Desc => ("DESC", "<"),
+        };
+        let limit = effective_page_limit(query.limit);
+        let name_pattern = query.name_prefix.as_deref().map(like_prefix_pattern);
+
+        let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM crate WHERE {FILTERS}"))
+            .bind(query.author_id)
+            .bind(&name_pattern)
+            .bind(query.created_since)
+            .fetch_one(&self.pool)
+            .await
+            .context("CrateTableTrait::find_page count")?;
+
+        let rows = sqlx::query_as::<_, CrateRow>(&format!(
+            r#"
+            SELECT id, author_id, code, name, version, yanked, description, created_at
+            FROM crate
+            WHERE {FILTERS}
+              AND ($4::text IS NULL OR ({column}, id) {after} ($4::{key_type}, $5))
+            ORDER BY {column} {direction}, id {direction}
+            LIMIT $6
+            "#
+        ))
+        .bind(query.author_id)
+        .bind(&name_pattern)
+        .bind(query.created_since)
+        .bind(query.cursor.as_ref().map(|cursor| cursor.key.clone()))
+        .bind(query.cursor.as_ref().map(|cursor| cursor.id))
+        .bind(limit + 1)
+        .fetch_all(&self.pool)
+        .await
+        .with_context(|| format!("CrateTableTrait::find_page failed for: {query:?}"))?;
+
+        let crates = rows.into_iter().map(Into::into).collect();
+        Ok(into_page(crates, limit, total, |krate| {
+            query.sort.cursor_for(krate)
+        }))
+    }
+
+    // ---
+    async fn search(&self, query: &str, limit: i64) -> Result<Vec<CrateSearchHit>> {
+        // ---
+        let headline_options = format!(
+            "StartSel={HIGHLIGHT_START}, StopSel={HIGHLIGHT_END}, MaxWords=30, MinWords=10, MaxFragments=2"
+        );
+
+        let rows = sqlx::query_as::<_, CrateSearchRow>(
+            r#"
+            SELECT id, author_id, code, name, version, yanked, description, created_at,
+                   (ts_rank(search_vector, query) + similarity(name, $1))::real AS rank,
+                   ts_headline('english', coalesce(description, name), query, $3) AS snippet
+            FROM crate, websearch_to_tsquery('english', $1) AS query
+            WHERE search_vector @@ query OR name % $1
+            ORDER BY rank DESC, id
+            LIMIT $2
+            "#,
+        )
+        .bind(query)
+        .bind(limit)
+        .bind(headline_options)
+        .fetch_all(&self.pool)
+        .await
+        .with_context(|| format!("CrateTableTrait::search failed for: {query:?}"))?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
     // ---
     async fn find(&self, id: i32) -> Result<Crate> {
         // ---
         let row = sqlx::query_as::<_, CrateRow>(
             r#"
-        SELECT id, author_id, code, name, version, description, created_at
+        SELECT id, author_id, code, name, version, yanked, description, created_at
         FROM crate
         WHERE id = $1
         "#,
@@ -77,22 +189,41 @@ impl CrateTableTrait for CrateRepo {
 
     async fn create(&self, new_crate: NewCrate) -> Result<Crate> {
         // ---
+        let mut tx = self.pool.begin().await?;
+        let sort_key = version_sort_key(&new_crate.version);
+
         let rec = sqlx::query_as::<_, CrateRow>(
             r#"
-            INSERT INTO crate (author_id, code, name, version, description)
-            VALUES ($1, $2, $3, $4, $5)
-            RETURNING id, author_id, code, name, version, description, created_at
+            INSERT INTO crate (author_id, code, name, version, version_sort_key, description)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, author_id, code, name, version, yanked, description, created_at
             "#,
         )
         .bind(new_crate.author_id)
         .bind(new_crate.code)
         .bind(new_crate.name)
         .bind(new_crate.version)
+        .bind(sort_key)
         .bind(new_crate.description)
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *tx)
         .await
         .context("CrateTableTrait::create")?;
 
+        // The first version carries no release description of its own
+        sqlx::query(
+            r#"
+            INSERT INTO crate_version (crate_id, version, published_at)
+            VALUES ($1, $2, $3)
+            "#,
+        )
+        .bind(rec.id)
+        .bind(&rec.version)
+        .bind(rec.created_at)
+        .execute(&mut *tx)
+        .await
+        .context("CrateTableTrait::create first version")?;
+
+        tx.commit().await?;
         Ok(rec.into())
     }
 
@@ -101,29 +232,55 @@ impl CrateTableTrait for CrateRepo {
     /// Update an existing crate by ID.
     async fn update(&self, id: i32, updated: NewCrate) -> Result<Crate> {
         // ---
+        let mut tx = self.pool.begin().await?;
+        let sort_key = version_sort_key(&updated.version);
+
         let rec = sqlx::query_as::<_, CrateRow>(
             r#"
         UPDATE crate
-        SET author_id   = $1,
-            code        = $2,
-            name        = $3,
-            version     = $4,
-            description = $5
-        WHERE id = $6
-        RETURNING id, author_id, code, name, version, description, created_at
+        SET author_id        = $1,
+            code             = $2,
+            name             = $3,
+            version          = $4,
+            version_sort_key = $5,
+            description      = $6
+        WHERE id = $7
+        RETURNING id, author_id, code, name, version, yanked, description, created_at
         "#,
         )
         .bind(updated.author_id)
         .bind(updated.code)
         .bind(updated.name)
         .bind(updated.version)
+        .bind(sort_key)
         .bind(updated.description)
         .bind(id)
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *tx)
         .await
         .context("CrateTableTrait::update")?;
 
-        Ok(rec.into())
+        // Keep the history when the update changed the version
+        sqlx::query(
+            r#"
+            INSERT INTO crate_version (crate_id, version)
+            VALUES ($1, $2)
+            ON CONFLICT (crate_id, version) DO NOTHING
+            "#,
+        )
+        .bind(id)
+        .bind(&rec.version)
+        .execute(&mut *tx)
+        .await
+        .context("CrateTableTrait::update version")?;
+
+        let (version, yanked) = sync_current_version(&mut tx, id).await?;
+
+        tx.commit().await?;
+        Ok(Crate {
+            version,
+            yanked,
+            ..rec.into()
+        })
     }
 
     // ---
@@ -146,14 +303,23 @@ impl CrateTableTrait for CrateRepo {
 
     // ---
 
-    /// Return crate summaries modified within the last N hours.
+    /// Return summaries of the versions published within the last N hours, except
+    /// yanked ones.
     async fn find_since(&self, hours_since: i32) -> Result<Vec<CrateSummary>> {
         // ---
+        // A crate's first version is the one that introduced it
         let records = sqlx::query_as::<_, CrateSummaryRow>(
             r#"
-            SELECT name, version
-            FROM crate
-            WHERE created_at >= NOW() - ($1 * INTERVAL '1 hour')::INTERVAL
+            SELECT c.name, c.code, v.version,
+                   coalesce(v.description, c.description) AS description,
+                   v.published_at,
+                   v.id = (SELECT MIN(first.id) FROM crate_version first
+                           WHERE first.crate_id = v.crate_id) AS new_crate
+            FROM crate_version v
+            JOIN crate c ON c.id = v.crate_id
+            WHERE v.published_at >= NOW() - ($1 * INTERVAL '1 hour')::INTERVAL
+              AND NOT v.yanked
+            ORDER BY v.published_at, v.id
             "#,
         )
         .bind(hours_since as f64)
@@ -163,6 +329,140 @@ impl CrateTableTrait for CrateRepo {
 
         Ok(records.into_iter().map(Into::into).collect())
     }
+
+    // ---
+    async fn find_versions(&self, crate_id: i32) -> Result<Vec<CrateVersion>> {
+        // ---
+        let rows = sqlx::query_as::<_, CrateVersionRow>(
+            r#"
+            SELECT id, crate_id, version, description, yanked, published_at
+            FROM crate_version
+            WHERE crate_id = $1
+            ORDER BY published_at DESC, id DESC
+            "#,
+        )
+        .bind(crate_id)
+        .fetch_all(&self.pool)
+        .await
+        .with_context(|| format!("CrateTableTrait::find_versions failed for crate {crate_id}"))?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    // ---
+    async fn publish_version(
+        &self,
+        crate_id: i32,
+        new_version: NewCrateVersion,
+    ) -> Result<CrateVersion> {
+        // ---
+        let mut tx = self.pool.begin().await?;
+
+        let row = sqlx::query_as::<_, CrateVersionRow>(
+            r#"
+            INSERT INTO crate_version (crate_id, version, description)
+            VALUES ($1, $2, $3)
+            RETURNING id, crate_id, version, description, yanked, published_at
+            "#,
+        )
+        .bind(crate_id)
+        .bind(&new_version.version)
+        .bind(&new_version.description)
+        .fetch_one(&mut *tx)
+        .await
+        .with_context(|| {
+            format!(
+                "CrateTableTrait::publish_version failed for crate {crate_id} {}",
+                new_version.version
+            )
+        })?;
+
+        sync_current_version(&mut tx, crate_id).await?;
+
+        tx.commit().await?;
+        Ok(row.into())
+    }
+
+    // ---
+    async fn set_version_yanked(
+        &self,
+        crate_id: i32,
+        version: &str,
+        yanked: bool,
+    ) -> Result<Option<CrateVersion>> {
+        // ---
+        let mut tx = self.pool.begin().await?;
+
+        let row = sqlx::query_as::<_, CrateVersionRow>(
+            r#"
+            UPDATE crate_version
+            SET yanked = $3
+            WHERE crate_id = $1 AND version = $2
+            RETURNING id, crate_id, version, description, yanked, published_at
+            "#,
+        )
+        .bind(crate_id)
+        .bind(version)
+        .bind(yanked)
+        .fetch_optional(&mut *tx)
+        .await
+        .with_context(|| {
+            format!("CrateTableTrait::set_version_yanked failed for crate {crate_id} {version}")
+        })?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        sync_current_version(&mut tx, crate_id).await?;
+
+        tx.commit().await?;
+        Ok(Some(row.into()))
+    }
+}
+
+/// Point `crate.version` at the latest version by SemVer precedence, which is not
+/// necessarily the one just written, and return it with the crate's `yanked` flag.
+///
+/// When every version is yanked the crate is flagged instead, keeping the last
+/// version that counted so it still sorts and pages by version.
+async fn sync_current_version(conn: &mut PgConnection, crate_id: i32) -> Result<(String, bool)> {
+    // ---
+    let versions: Vec<CrateVersion> = sqlx::query_as::<_, CrateVersionRow>(
+        r#"
+        SELECT id, crate_id, version, description, yanked, published_at
+        FROM crate_version
+        WHERE crate_id = $1
+        "#,
+    )
+    .bind(crate_id)
+    .fetch_all(&mut *conn)
+    .await
+    .context("CrateTableTrait current version lookup")?
+    .into_iter()
+    .map(Into::into)
+    .collect();
+
+    let Some(current) = current_version(&versions) else {
+        let version =
+            sqlx::query_scalar("UPDATE crate SET yanked = TRUE WHERE id = $1 RETURNING version")
+                .bind(crate_id)
+                .fetch_one(&mut *conn)
+                .await
+                .context("CrateTableTrait current version update")?;
+        return Ok((version, true));
+    };
+
+    sqlx::query(
+        "UPDATE crate SET version = $1, version_sort_key = $2, yanked = FALSE WHERE id = $3",
+    )
+    .bind(&current.version)
+    .bind(version_sort_key(&current.version))
+    .bind(crate_id)
+    .execute(&mut *conn)
+    .await
+    .context("CrateTableTrait current version update")?;
+
+    Ok((current.version.clone(), false))
 }
 
 #[derive(Debug, sqlx::FromRow)]
@@ -172,6 +472,7 @@ struct CrateRow {
     code: String,
     name: String,
     version: String,
+    yanked: bool,
     description: Option<String>,
     created_at: chrono::NaiveDateTime,
 }
@@ -184,23 +485,112 @@ impl From<CrateRow> for Crate {
             code: row.code,
             name: row.name,
             version: row.version,
+            yanked: row.yanked,
             description: row.description,
             created_at: row.created_at,
         }
     }
 }
 
+#[derive(Debug, sqlx::FromRow)]
+struct CrateSearchRow {
+    #[sqlx(flatten)]
+    krate: CrateRow,
+    rank: f32,
+    snippet: String,
+}
+
+impl From<CrateSearchRow> for CrateSearchHit {
+    fn from(row: CrateSearchRow) -> Self {
+        CrateSearchHit {
+            krate: row.krate.into(),
+            rank: row.rank,
+            snippet: highlight_snippet(&row.snippet),
+        }
+    }
+}
+
+/// HTML-escape a `ts_headline` excerpt and turn its highlight marks into `<mark>`.
+fn highlight_snippet(headline: &str) -> String {
+    // ---
+    let mut html = String::with_capacity(headline.len());
+    for c in headline.chars() {
+        match c {
+            HIGHLIGHT_START => html.push_str("<mark>"),
+            HIGHLIGHT_END => html.push_str("</mark>"),
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            c => html.push(c),
+        }
+    }
+    html
+}
+
 #[derive(Debug, sqlx::FromRow)]
 struct CrateSummaryRow {
     name: String,
+    code: String,
     version: String,
+    description: Option<String>,
+    published_at: chrono::NaiveDateTime,
+    new_crate: bool,
 }
 
 impl From<CrateSummaryRow> for CrateSummary {
     fn from(row: CrateSummaryRow) -> Self {
         CrateSummary {
             name: row.name,
+            code: row.code,
+            version: row.version,
+            description: row.description,
+            published_at: row.published_at,
+            kind: if row.new_crate {
+                ReleaseKind::NewCrate
+            } else {
+                ReleaseKind::NewVersion
+            },
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct CrateVersionRow {
+    id: i32,
+    crate_id: i32,
+    version: String,
+    description: Option<String>,
+    yanked: bool,
+    published_at: chrono::NaiveDateTime,
+}
+
+impl From<CrateVersionRow> for CrateVersion {
+    fn from(row: CrateVersionRow) -> Self {
+        CrateVersion {
+            id: row.id,
+            crate_id: row.crate_id,
             version: row.version,
+            description: row.description,
+            yanked: row.yanked,
+            published_at: row.published_at,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    // ---
+    use super::*;
+
+    #[test]
+    fn test_highlight_snippet_escapes_crate_text() {
+        // ---
+        let headline = format!("Fast {HIGHLIGHT_START}serde{HIGHLIGHT_END} for <script> & co");
+        assert_eq!(
+            highlight_snippet(&headline),
+            "Fast <mark>serde</mark> for &lt;script&gt; &amp; co"
+        );
+    }
+}
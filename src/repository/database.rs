@@ -73,6 +73,17 @@ pub(crate) fn get_pool() -> &'static PgPool {
         .expect("Pool not initialized. Call init_pool_with_retry() first.")
 }
 
+/// `LIKE` pattern matching values that start with `prefix`, with `%`, `_` and `\`
+/// in the prefix matched literally.
+pub(crate) fn like_prefix_pattern(prefix: &str) -> String {
+    // ---
+    let escaped = prefix
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("{escaped}%")
+}
+
 /// Initialize cr8s schema and default roles, called from cli load-schema command
 ///
 pub async fn load_schema_from_sql_file() -> Result<()> {
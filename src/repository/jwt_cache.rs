@@ -0,0 +1,600 @@
+// repository/jwt_cache.rs
+//! Stateless access tokens (JWT auth mode).
+//!
+//! With `CR8S_AUTH_MODE=jwt` a login yields a short-lived HS256 JWT carrying the
+//! user id and role codes, plus an opaque refresh token. The refresh token is an
+//! ordinary Redis session; the JWT is verified from the signing keys alone, so
+//! requests carrying it touch neither Redis nor Postgres.
+//!
+//! Signing keys come from a JWKS-style file (`CR8S_JWT_KEYS_FILE`):
+//!
+//! ```json
+//! { "keys": [ { "kty": "oct", "alg": "HS256", "kid": "2025-07", "k": "<base64url secret>" } ] }
+//! ```
+//!
+//! The first key signs, every key verifies. To rotate, put a new key first, restart,
+//! and drop the old key once the access token TTL has passed.
+
+use crate::domain::{
+    //
+    AccessClaims,
+    AccessToken,
+    AppUser,
+    CacheContextTrait,
+    CacheContextTraitPtr,
+    NewSession,
+    RoleCode,
+    SessionRecord,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use data_encoding::BASE64URL_NOPAD;
+use hmac::{Hmac, Mac};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Issuer written to and required in every access token.
+const ISSUER: &str = "cr8s";
+
+/// Shortest accepted signing key, in bytes (the HS256 output size).
+const MIN_KEY_LEN: usize = 32;
+
+static JWT_KEYS: OnceCell<Option<Arc<JwtKeySet>>> = OnceCell::new();
+
+/// How bearer tokens are issued and checked, from `CR8S_AUTH_MODE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AuthMode {
+    /// Opaque session tokens looked up in Redis on every request (default).
+    Session,
+    /// Signed access tokens plus Redis-backed refresh tokens.
+    Jwt,
+}
+
+impl AuthMode {
+    // ---
+    /// Respects env var `CR8S_AUTH_MODE` (`session` or `jwt`, default: `session`).
+    fn from_env() -> Result<Self> {
+        // ---
+        let mode = get_env_with_default!(String, "CR8S_AUTH_MODE", "session".to_owned());
+
+        match mode.trim().to_ascii_lowercase().as_str() {
+            "session" => Ok(Self::Session),
+            "jwt" => Ok(Self::Jwt),
+            other => bail!("Invalid CR8S_AUTH_MODE '{other}', expected 'session' or 'jwt'"),
+        }
+    }
+}
+
+/// One JWKS entry; only symmetric HS256 keys are supported.
+#[derive(Deserialize)]
+struct Jwk {
+    kty: String,
+    kid: String,
+    k: String,
+    #[serde(default)]
+    alg: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Jwks {
+    keys: Vec<Jwk>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JwtHeader {
+    alg: String,
+    typ: String,
+    kid: String,
+}
+
+/// Registered and private claims of an access token.
+#[derive(Serialize, Deserialize)]
+struct JwtClaims {
+    iss: String,
+    sub: String,
+    name: String,
+    /// User creation time, unix seconds.
+    uca: i64,
+    /// Linked author id, omitted when the user has none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    aid: Option<i32>,
+    roles: Vec<RoleCode>,
+    sid: String,
+    iat: i64,
+    exp: i64,
+}
+
+/// Access token signing keys, in order of preference.
+struct JwtKeySet {
+    keys: Vec<(String, Vec<u8>)>,
+}
+
+impl JwtKeySet {
+    // ---
+    /// Parse and validate a JWKS document.
+    fn from_jwks_json(json: &str) -> Result<Self> {
+        // ---
+        let jwks: Jwks = serde_json::from_str(json).context("invalid JWKS document")?;
+
+        let mut seen = HashSet::new();
+        let mut keys = Vec::with_capacity(jwks.keys.len());
+
+        for jwk in jwks.keys {
+            // ---
+            if jwk.kty != "oct" {
+                bail!("JWT key '{}': unsupported kty '{}'", jwk.kid, jwk.kty);
+            }
+            if jwk.alg.as_deref().is_some_and(|alg| alg != "HS256") {
+                bail!("JWT key '{}': only HS256 is supported", jwk.kid);
+            }
+            if jwk.kid.is_empty() || !seen.insert(jwk.kid.clone()) {
+                bail!("JWT key ids must be non-empty and unique: '{}'", jwk.kid);
+            }
+
+            let secret = BASE64URL_NOPAD
+                .decode(jwk.k.trim_end_matches('=').as_bytes())
+                .with_context(|| format!("JWT key '{}': k is not base64url", jwk.kid))?;
+
+            if secret.len() < MIN_KEY_LEN {
+                bail!(
+                    "JWT key '{}': must be at least {MIN_KEY_LEN} bytes",
+                    jwk.kid
+                );
+            }
+
+            keys.push((jwk.kid, secret));
+        }
+
+        if keys.is_empty() {
+            bail!("JWKS document contains no keys");
+        }
+
+        Ok(Self { keys })
+    }
+
+    fn from_file(path: &Path) -> Result<Self> {
+        // ---
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read JWT keys file: {}", path.display()))?;
+
+        Self::from_jwks_json(&json)
+            .with_context(|| format!("Failed to load JWT keys file: {}", path.display()))
+    }
+
+    fn mac(secret: &[u8], signing_input: &str) -> HmacSha256 {
+        // ---
+        let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any length");
+        mac.update(signing_input.as_bytes());
+        mac
+    }
+
+    /// Sign `claims` with the first (current) key.
+    fn sign(&self, claims: &JwtClaims) -> Result<String> {
+        // ---
+        let (kid, secret) = &self.keys[0];
+        let header = JwtHeader {
+            alg: "HS256".into(),
+            typ: "JWT".into(),
+            kid: kid.clone(),
+        };
+
+        let signing_input = format!(
+            "{}.{}",
+            BASE64URL_NOPAD.encode(&serde_json::to_vec(&header)?),
+            BASE64URL_NOPAD.encode(&serde_json::to_vec(claims)?)
+        );
+        let signature = Self::mac(secret, &signing_input).finalize().into_bytes();
+
+        Ok(format!(
+            "{signing_input}.{}",
+            BASE64URL_NOPAD.encode(&signature)
+        ))
+    }
+
+    /// Check the signature, issuer and expiry of a token, returning its claims.
+    fn verify(&self, token: &str, now: i64) -> Option<JwtClaims> {
+        // ---
+        let (signing_input, signature) = token.rsplit_once('.')?;
+        let (header, claims) = signing_input.split_once('.')?;
+
+        let header: JwtHeader =
+            serde_json::from_slice(&BASE64URL_NOPAD.decode(header.as_bytes()).ok()?).ok()?;
+        if header.alg != "HS256" {
+            return None;
+        }
+
+        let (_, secret) = self.keys.iter().find(|(kid, _)| *kid == header.kid)?;
+        let signature = BASE64URL_NOPAD.decode(signature.as_bytes()).ok()?;
+        Self::mac(secret, signing_input)
+            .verify_slice(&signature)
+            .ok()?;
+
+        let claims: JwtClaims =
+            serde_json::from_slice(&BASE64URL_NOPAD.decode(claims.as_bytes()).ok()?).ok()?;
+
+        (claims.iss == ISSUER && claims.exp > now).then_some(claims)
+    }
+}
+
+/// Loads the access token signing keys when `CR8S_AUTH_MODE=jwt`.
+///
+/// Respects env vars:
+/// - `CR8S_AUTH_MODE` (default: `session`)
+/// - `CR8S_JWT_KEYS_FILE` (required in `jwt` mode)
+///
+/// Fails on an unknown mode, or a missing or invalid keys file, so a misconfigured
+/// instance never starts. Must run before `create_cache_context`.
+pub fn init_jwt_keys_from_env() -> Result<()> {
+    // ---
+    let keys = match AuthMode::from_env()? {
+        AuthMode::Session => None,
+        AuthMode::Jwt => {
+            // ---
+            let path = get_env_with_default!(String, "CR8S_JWT_KEYS_FILE", String::new());
+            if path.is_empty() {
+                bail!("CR8S_JWT_KEYS_FILE must be set when CR8S_AUTH_MODE=jwt");
+            }
+
+            let keys = JwtKeySet::from_file(Path::new(&path))?;
+            tracing::info!(
+                "🔐 JWT auth mode: {} key(s) loaded, signing with '{}'",
+                keys.keys.len(),
+                keys.keys[0].0
+            );
+            Some(Arc::new(keys))
+        }
+    };
+
+    JWT_KEYS
+        .set(keys)
+        .map_err(|_| anyhow!("JWT keys already initialized"))
+}
+
+/// Wrap `sessions` for JWT auth mode if it is configured, otherwise return it as is.
+pub fn with_access_tokens(sessions: CacheContextTraitPtr) -> CacheContextTraitPtr {
+    // ---
+    match JWT_KEYS.get() {
+        Some(Some(keys)) => Arc::new(JwtCacheContext {
+            sessions,
+            keys: keys.clone(),
+            // Respects env var `CR8S_JWT_ACCESS_TTL_SECS` (default: 300)
+            access_ttl_secs: get_env_with_default!(u64, "CR8S_JWT_ACCESS_TTL_SECS", 300),
+        }),
+        _ => sessions,
+    }
+}
+
+/// Cache context for JWT auth mode.
+///
+/// Session tokens become refresh tokens and are handled by the wrapped context.
+/// Operations that identify "the current session" by its bearer token also accept an
+/// access token, and resolve it through the session id it carries.
+struct JwtCacheContext {
+    sessions: CacheContextTraitPtr,
+    keys: Arc<JwtKeySet>,
+    access_ttl_secs: u64,
+}
+
+#[async_trait]
+impl CacheContextTrait for JwtCacheContext {
+    // ---
+    async fn get_user_id_by_session_token(&self, token: &str) -> Result<Option<i32>> {
+        // ---
+        self.sessions.get_user_id_by_session_token(token).await
+    }
+
+    async fn set_user_session_token(
+        &self,
+        token: &str,
+        new_session: NewSession,
+    ) -> Result<SessionRecord> {
+        // ---
+        self.sessions
+            .set_user_session_token(token, new_session)
+            .await
+    }
+
+    async fn touch_session_token(&self, token: &str) -> Result<()> {
+        // ---
+        self.sessions.touch_session_token(token).await
+    }
+
+    async fn clear_session_token(&self, token: &str) -> Result<bool> {
+        // ---
+        match self.verify_access_token(token) {
+            Some(claims) => {
+                self.sessions
+                    .clear_user_session(claims.user_id, &claims.session_id)
+                    .await
+            }
+            None => self.sessions.clear_session_token(token).await,
+        }
+    }
+
+    async fn clear_all_session_tokens(&self, user_id: i32) -> Result<u64> {
+        // ---
+        self.sessions.clear_all_session_tokens(user_id).await
+    }
+
+    async fn list_user_sessions(&self, user_id: i32) -> Result<Vec<SessionRecord>> {
+        // ---
+        self.sessions.list_user_sessions(user_id).await
+    }
+
+    async fn clear_user_session(&self, user_id: i32, session_id: &str) -> Result<bool> {
+        // ---
+        self.sessions.clear_user_session(user_id, session_id).await
+    }
+
+    /// Access tokens already issued keep their roles until they expire; the next
+    /// refresh re-reads roles from the database.
+    async fn update_session_roles(&self, user_id: i32, role_codes: &[RoleCode]) -> Result<u64> {
+        // ---
+        self.sessions
+            .update_session_roles(user_id, role_codes)
+            .await
+    }
+
+    async fn clear_other_session_tokens(&self, user_id: i32, keep_token: &str) -> Result<u64> {
+        // ---
+        let Some(claims) = self.verify_access_token(keep_token) else {
+            return self
+                .sessions
+                .clear_other_session_tokens(user_id, keep_token)
+                .await;
+        };
+
+        let mut cleared = 0;
+        for session in self.sessions.list_user_sessions(user_id).await? {
+            if session.id != claims.session_id
+                && self
+                    .sessions
+                    .clear_user_session(user_id, &session.id)
+                    .await?
+            {
+                cleared += 1;
+            }
+        }
+        Ok(cleared)
+    }
+
+    async fn set_password_reset_token(
+        &self,
+        token: &str,
+        user_id: i32,
+        ttl_secs: u64,
+    ) -> Result<()> {
+        // ---
+        self.sessions
+            .set_password_reset_token(token, user_id, ttl_secs)
+            .await
+    }
+
+    async fn find_password_reset_token(&self, token: &str) -> Result<Option<i32>> {
+        // ---
+        self.sessions.find_password_reset_token(token).await
+    }
+
+    async fn take_password_reset_token(&self, token: &str) -> Result<Option<i32>> {
+        // ---
+        self.sessions.take_password_reset_token(token).await
+    }
+
+    async fn set_login_challenge(&self, token: &str, user_id: i32, ttl_secs: u64) -> Result<()> {
+        // ---
+        self.sessions
+            .set_login_challenge(token, user_id, ttl_secs)
+            .await
+    }
+
+    async fn take_login_challenge(&self, token: &str) -> Result<Option<i32>> {
+        // ---
+        self.sessions.take_login_challenge(token).await
+    }
+
+    async fn set_oidc_login_state(&self, state: &str, value: &str, ttl_secs: u64) -> Result<()> {
+        // ---
+        self.sessions
+            .set_oidc_login_state(state, value, ttl_secs)
+            .await
+    }
+
+    async fn take_oidc_login_state(&self, state: &str) -> Result<Option<String>> {
+        // ---
+        self.sessions.take_oidc_login_state(state).await
+    }
+
+    async fn set_author_claim(&self, token: &str, value: &str, ttl_secs: u64) -> Result<()> {
+        // ---
+        self.sessions.set_author_claim(token, value, ttl_secs).await
+    }
+
+    async fn take_author_claim(&self, token: &str) -> Result<Option<String>> {
+        // ---
+        self.sessions.take_author_claim(token).await
+    }
+
+    async fn incr_login_failures(&self, key: &str, window_secs: u64) -> Result<u64> {
+        // ---
+        self.sessions.incr_login_failures(key, window_secs).await
+    }
+
+    async fn lock_login(&self, key: &str, secs: u64) -> Result<()> {
+        // ---
+        self.sessions.lock_login(key, secs).await
+    }
+
+    async fn login_lock_ttl(&self, key: &str) -> Result<Option<u64>> {
+        // ---
+        self.sessions.login_lock_ttl(key).await
+    }
+
+    async fn clear_login_failures(&self, key: &str) -> Result<()> {
+        // ---
+        self.sessions.clear_login_failures(key).await
+    }
+
+    async fn issue_access_token(
+        &self,
+        user: &AppUser,
+        session: &SessionRecord,
+    ) -> Result<Option<AccessToken>> {
+        // ---
+        let now = chrono::Utc::now().timestamp();
+        let claims = JwtClaims {
+            iss: ISSUER.into(),
+            sub: user.id.to_string(),
+            name: user.username.clone(),
+            uca: user.created_at.and_utc().timestamp(),
+            aid: user.author_id,
+            roles: session.role_codes.clone(),
+            sid: session.id.clone(),
+            iat: now,
+            exp: now + self.access_ttl_secs as i64,
+        };
+
+        Ok(Some(AccessToken {
+            token: self.keys.sign(&claims)?,
+            expires_in: self.access_ttl_secs,
+        }))
+    }
+
+    fn verify_access_token(&self, token: &str) -> Option<AccessClaims> {
+        // ---
+        let claims = self.keys.verify(token, chrono::Utc::now().timestamp())?;
+
+        Some(AccessClaims {
+            user_id: claims.sub.parse().ok()?,
+            username: claims.name,
+            user_created_at: chrono::DateTime::from_timestamp(claims.uca, 0)?.naive_utc(),
+            author_id: claims.aid,
+            role_codes: claims.roles,
+            session_id: claims.sid,
+        })
+    }
+
+    fn requires_access_tokens(&self) -> bool {
+        // ---
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jwks(keys: &[(&str, &[u8])]) -> String {
+        // ---
+        let keys = keys
+            .iter()
+            .map(|(kid, secret)| {
+                serde_json::json!({
+                    "kty": "oct",
+                    "alg": "HS256",
+                    "kid": kid,
+                    "k": BASE64URL_NOPAD.encode(secret),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        serde_json::json!({ "keys": keys }).to_string()
+    }
+
+    fn claims(exp: i64) -> JwtClaims {
+        // ---
+        JwtClaims {
+            iss: ISSUER.into(),
+            sub: "42".into(),
+            name: "alice@example.com".into(),
+            uca: 1_700_000_000,
+            aid: Some(7),
+            roles: vec![RoleCode::Editor],
+            sid: "session-1".into(),
+            iat: 1_000,
+            exp,
+        }
+    }
+
+    #[test]
+    fn test_sign_and_verify_round_trip() {
+        // ---
+        let keys = JwtKeySet::from_jwks_json(&jwks(&[("k1", &[1; 32])])).unwrap();
+        let token = keys.sign(&claims(2_000)).unwrap();
+
+        let verified = keys.verify(&token, 1_500).expect("token should verify");
+        assert_eq!(verified.sub, "42");
+        assert_eq!(verified.roles, vec![RoleCode::Editor]);
+        assert_eq!(verified.sid, "session-1");
+        assert_eq!(verified.aid, Some(7));
+
+        // Expired
+        assert!(keys.verify(&token, 2_000).is_none());
+    }
+
+    #[test]
+    fn test_verify_rejects_tampering() {
+        // ---
+        let keys = JwtKeySet::from_jwks_json(&jwks(&[("k1", &[1; 32])])).unwrap();
+        let token = keys.sign(&claims(2_000)).unwrap();
+        let (header, rest) = token.split_once('.').unwrap();
+        let (_, signature) = rest.split_once('.').unwrap();
+
+        let mut elevated = claims(2_000);
+        elevated.roles = vec![RoleCode::Admin];
+        let forged_claims = BASE64URL_NOPAD.encode(&serde_json::to_vec(&elevated).unwrap());
+        assert!(keys
+            .verify(&format!("{header}.{forged_claims}.{signature}"), 1_500)
+            .is_none());
+
+        let none_header = BASE64URL_NOPAD.encode(br#"{"alg":"none","typ":"JWT","kid":"k1"}"#);
+        assert!(keys
+            .verify(&format!("{none_header}.{forged_claims}."), 1_500)
+            .is_none());
+
+        let other = JwtKeySet::from_jwks_json(&jwks(&[("k1", &[2; 32])])).unwrap();
+        assert!(other.verify(&token, 1_500).is_none());
+        assert!(keys.verify("not-a-jwt", 1_500).is_none());
+    }
+
+    #[test]
+    fn test_key_rotation() {
+        // ---
+        let old = JwtKeySet::from_jwks_json(&jwks(&[("old", &[1; 32])])).unwrap();
+        let old_token = old.sign(&claims(2_000)).unwrap();
+
+        // New key signs, old key still verifies
+        let rotating =
+            JwtKeySet::from_jwks_json(&jwks(&[("new", &[2; 32]), ("old", &[1; 32])])).unwrap();
+        let new_token = rotating.sign(&claims(2_000)).unwrap();
+        assert!(rotating.verify(&old_token, 1_500).is_some());
+        assert!(rotating.verify(&new_token, 1_500).is_some());
+        assert!(old.verify(&new_token, 1_500).is_none());
+
+        // Old key retired
+        let rotated = JwtKeySet::from_jwks_json(&jwks(&[("new", &[2; 32])])).unwrap();
+        assert!(rotated.verify(&old_token, 1_500).is_none());
+        assert!(rotated.verify(&new_token, 1_500).is_some());
+    }
+
+    #[test]
+    fn test_invalid_jwks_is_rejected() {
+        // ---
+        for json in [
+            "not json".to_owned(),
+            r#"{"keys":[]}"#.to_owned(),
+            jwks(&[("short", &[1; 16])]),
+            jwks(&[("dup", &[1; 32]), ("dup", &[2; 32])]),
+            r#"{"keys":[{"kty":"RSA","kid":"k","k":"AAAA"}]}"#.to_owned(),
+            r#"{"keys":[{"kty":"oct","alg":"HS512","kid":"k","k":"AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE"}]}"#
+                .to_owned(),
+        ] {
+            assert!(JwtKeySet::from_jwks_json(&json).is_err(), "accepted: {json}");
+        }
+    }
+}
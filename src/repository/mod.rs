@@ -4,6 +4,8 @@
 //! This module forms the infrastructure layer beneath the domain traits.
 //!
 //! ## Public interface (used by CLI, server, and tests)
+//! - `create_api_token_repo`
+//! - `create_audit_log_repo`
 //! - `create_app_user_repo`
 //! - `create_author_repo`
 //! - `create_crate_repo`
@@ -19,19 +21,24 @@
 //! - individual `*Repo` structs
 //! - `RoleCodeMapping` lookup logic
 
+mod api_token_sqlx;
 mod app_user_sqlx;
+mod audit_log_sqlx;
 mod author_sqlx;
 mod crate_sqlx;
 mod database;
 #[macro_use]
 mod env;
 mod health_check;
+mod jwt_cache;
 mod redis_cache;
 mod role_code_mapping;
 mod role_code_sqlx;
 
 // --- Public interface
+pub use api_token_sqlx::create_api_token_repo;
 pub use app_user_sqlx::create_app_user_repo;
+pub use audit_log_sqlx::create_audit_log_repo;
 pub use author_sqlx::create_author_repo;
 pub use crate_sqlx::create_crate_repo;
 pub use database::init_database_with_retry_from_env;
@@ -44,5 +51,6 @@ pub use role_code_sqlx::create_role_code_repo;
 
 // --- Package scope interface
 use database::get_pool;
+use database::like_prefix_pattern;
 use redis_cache::get_redis_pool;
 use role_code_mapping::RoleCodeMapping;
@@ -1,10 +1,14 @@
 // repository/redis_cache.rs
 //! Internal Redis connection pool for use inside `repository/` only.
 
-use crate::domain::{CacheContextTrait, CacheContextTraitPtr};
+use super::jwt_cache::{init_jwt_keys_from_env, with_access_tokens};
+use crate::domain::{CacheContextTrait, CacheContextTraitPtr, NewSession, RoleCode, SessionRecord};
 use anyhow::{anyhow, Context, Result};
 use deadpool_redis::{redis::AsyncCommands, Connection, Pool}; // from explicit `redis = "0.25.4"` dependency
+use hmac::{Hmac, Mac};
 use once_cell::sync::OnceCell;
+use sha2::Sha256;
+use std::collections::HashMap;
 use std::time::Duration;
 use tokio::time::sleep;
 
@@ -30,11 +34,159 @@ pub async fn get_redis_connection() -> Result<Connection> {
         .context("Failed to get Redis connection from pool")
 }
 
-struct RedisCacheContext;
+/// Session expiry settings, read once from the environment.
+#[derive(Debug, Clone, Copy)]
+struct SessionTtl {
+    /// Idle timeout, pushed forward every time the session is used.
+    idle_secs: u64,
+    /// Hard upper bound on session age, regardless of activity.
+    max_lifetime_secs: u64,
+}
+
+impl SessionTtl {
+    // ---
+    /// Respects env vars:
+    /// - `CR8S_SESSION_IDLE_TTL_SECS` (default: 3600)
+    /// - `CR8S_SESSION_MAX_LIFETIME_SECS` (default: 86400)
+    fn from_env() -> Self {
+        // ---
+        let idle_secs = get_env_with_default!(u64, "CR8S_SESSION_IDLE_TTL_SECS", 3600);
+        let max_lifetime_secs = get_env_with_default!(u64, "CR8S_SESSION_MAX_LIFETIME_SECS", 86400);
+
+        Self {
+            // An idle timeout longer than the absolute lifetime would never apply.
+            idle_secs: std::cmp::min(idle_secs, max_lifetime_secs),
+            max_lifetime_secs,
+        }
+    }
+}
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Keyed hash applied to session tokens before they are used as Redis keys, so
+/// a dump of the cache does not hand out usable bearer tokens.
+#[derive(Clone)]
+struct TokenHasher {
+    key: Vec<u8>,
+}
+
+impl TokenHasher {
+    // ---
+    /// Respects env var `CR8S_SESSION_SECRET`.
+    ///
+    /// Without it a random per-process key is used, which means sessions do not
+    /// survive a server restart and are not shared between server instances.
+    fn from_env() -> Self {
+        // ---
+        let secret = get_env_with_default!(String, "CR8S_SESSION_SECRET", String::new());
+
+        if secret.is_empty() {
+            tracing::warn!(
+                "CR8S_SESSION_SECRET is not set, using a random key: sessions will not survive a restart"
+            );
+            return Self::new(rand::random::<[u8; 32]>().to_vec());
+        }
+
+        Self::new(secret.into_bytes())
+    }
+
+    fn new(key: Vec<u8>) -> Self {
+        // ---
+        Self { key }
+    }
+
+    /// Hex-encoded HMAC-SHA256 of `token`.
+    fn hash(&self, token: &str) -> String {
+        // ---
+        let mut mac =
+            HmacSha256::new_from_slice(&self.key).expect("HMAC accepts keys of any length");
+        mac.update(token.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+}
+
+/// Session cache backed by Redis.
+///
+/// Raw session tokens never reach Redis: every key and index entry is derived from
+/// the token's HMAC (see `TokenHasher`), and lookups hash the presented token first.
+struct RedisCacheContext {
+    ttl: SessionTtl,
+    hasher: TokenHasher,
+}
+
 impl Default for RedisCacheContext {
     // ---
     fn default() -> Self {
-        RedisCacheContext
+        RedisCacheContext {
+            ttl: SessionTtl::from_env(),
+            hasher: TokenHasher::from_env(),
+        }
+    }
+}
+
+impl RedisCacheContext {
+    // ---
+    /// Load a session record and its absolute deadline (unix seconds).
+    ///
+    /// Returns `None` if the token hash is unknown, its absolute lifetime has
+    /// elapsed, or the stored record cannot be parsed.
+    async fn load_session(
+        &self,
+        conn: &mut Connection,
+        token_hash: &str,
+    ) -> Result<Option<(SessionRecord, i64)>> {
+        // ---
+        let session_key = session_key(token_hash);
+
+        let (record, deadline): (Option<String>, Option<i64>) = redis::pipe()
+            .get(&session_key)
+            .get(session_deadline_key(token_hash))
+            .query_async(conn)
+            .await
+            .context("failed to get session token from Redis")?;
+
+        let Some(record) = record else {
+            return Ok(None);
+        };
+
+        let Some(deadline) = deadline else {
+            // Absolute lifetime elapsed before the idle TTL did.
+            conn.del::<_, ()>(&session_key)
+                .await
+                .context("failed to delete expired session token from Redis")?;
+            return Ok(None);
+        };
+
+        match serde_json::from_str::<SessionRecord>(&record) {
+            Ok(session) => Ok(Some((session, deadline))),
+            Err(err) => {
+                tracing::warn!("Ignoring unreadable session record: {err}");
+                Ok(None)
+            }
+        }
+    }
+
+    /// Delete a session by token hash, return `true` if found else `false`.
+    async fn clear_session_by_hash(&self, token_hash: &str) -> Result<bool> {
+        // ---
+        let mut conn = get_redis_connection().await?;
+
+        let session = self.load_session(&mut conn, token_hash).await?;
+
+        let (delete_count, _): (u64, u64) = redis::pipe()
+            .del(session_key(token_hash))
+            .del(session_deadline_key(token_hash))
+            .query_async(&mut conn)
+            .await
+            .context("failed to delete session token from Redis")?;
+
+        if let Some((session, _)) = session {
+            conn.hdel::<_, _, ()>(user_sessions_key(session.user_id), &session.id)
+                .await
+                .context("failed to remove session token from user index")?;
+        }
+
+        Ok(delete_count > 0)
     }
 }
 
@@ -42,54 +194,570 @@ impl Default for RedisCacheContext {
 impl CacheContextTrait for RedisCacheContext {
     // --
     /// Validate a session token and return the associated user ID, or `None` if invalid.
+    ///
+    /// A session is invalid once either its idle TTL or its absolute lifetime has
+    /// elapsed; the latter is tracked by a companion deadline key.
     async fn get_user_id_by_session_token(&self, token: &str) -> Result<Option<i32>> {
         // ---
 
         let mut conn = get_redis_connection().await?;
 
-        let val: Option<i32> = conn
-            .get(token)
-            .await
-            .context("failed to get session token from Redis")?;
+        let session = self
+            .load_session(&mut conn, &self.hasher.hash(token))
+            .await?;
 
-        Ok(val)
+        Ok(session.map(|(session, _)| session.user_id))
     }
 
-    /// Write a new session token for a user (or update existing one).
-    async fn set_user_session_token(&self, user_id: i32, token: &str) -> Result<()> {
+    /// Write a new session record under the hash of the given token.
+    ///
+    /// The token hash is also added to the user's session index (public session id
+    /// -> token hash) so that it can be listed and revoked later.
+    async fn set_user_session_token(
+        &self,
+        token: &str,
+        new_session: NewSession,
+    ) -> Result<SessionRecord> {
         // ---
 
         let mut conn = get_redis_connection().await?;
 
-        match conn.set::<_, _, ()>(token, user_id).await {
-            Ok(_) => Ok(()),
+        let now = chrono::Utc::now();
+        let deadline = now.timestamp() + self.ttl.max_lifetime_secs as i64;
+
+        let session = SessionRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: new_session.user_id,
+            created_at: now.naive_utc(),
+            last_seen: now.naive_utc(),
+            client_ip: new_session.client_ip,
+            user_agent: new_session.user_agent,
+            role_codes: new_session.role_codes,
+        };
+        let record = serde_json::to_string(&session)?;
+        let token_hash = self.hasher.hash(token);
+        let index_key = user_sessions_key(session.user_id);
+
+        let result = redis::pipe()
+            .atomic()
+            .set_ex(session_key(&token_hash), record, self.ttl.idle_secs)
+            .ignore()
+            .set_ex(
+                session_deadline_key(&token_hash),
+                deadline,
+                self.ttl.max_lifetime_secs,
+            )
+            .ignore()
+            .hset(&index_key, &session.id, &token_hash)
+            .ignore()
+            .expire(&index_key, self.ttl.max_lifetime_secs as i64)
+            .ignore()
+            .query_async::<_, ()>(&mut conn)
+            .await;
+
+        match result {
+            Ok(_) => Ok(session),
             Err(err) => Err(anyhow!("failed to set session token in Redis:{err}")),
         }
     }
 
+    /// Slide the idle TTL of a session forward, capped by its absolute deadline, and
+    /// record the time it was last used.
+    async fn touch_session_token(&self, token: &str) -> Result<()> {
+        // ---
+        let mut conn = get_redis_connection().await?;
+        let token_hash = self.hasher.hash(token);
+
+        let Some((mut session, deadline)) = self.load_session(&mut conn, &token_hash).await? else {
+            return Ok(());
+        };
+
+        let now = chrono::Utc::now();
+        let remaining = deadline - now.timestamp();
+        let ttl = std::cmp::min(remaining, self.ttl.idle_secs as i64);
+
+        if ttl > 0 {
+            session.last_seen = now.naive_utc();
+            let record = serde_json::to_string(&session)?;
+
+            conn.set_ex::<_, _, ()>(session_key(&token_hash), record, ttl as u64)
+                .await
+                .context("failed to refresh session TTL in Redis")?;
+        }
+
+        Ok(())
+    }
+
     /// Delete a session token (e.g., logout), return `true` if found else `false`.
     async fn clear_session_token(&self, token: &str) -> Result<bool> {
+        // ---
+        self.clear_session_by_hash(&self.hasher.hash(token)).await
+    }
+
+    /// Delete every session token issued to a user, return the number revoked.
+    async fn clear_all_session_tokens(&self, user_id: i32) -> Result<u64> {
+        // ---
+        let mut conn = get_redis_connection().await?;
+        let index_key = user_sessions_key(user_id);
+
+        let index: HashMap<String, String> = conn
+            .hgetall(&index_key)
+            .await
+            .context("failed to read user session index from Redis")?;
+
+        let token_hashes: Vec<String> = index.into_values().collect();
+
+        // Index entries may outlive their token, so only count keys actually deleted.
+        let delete_count: u64 = if token_hashes.is_empty() {
+            0
+        } else {
+            let deadline_keys: Vec<String> = token_hashes
+                .iter()
+                .map(|h| session_deadline_key(h))
+                .collect();
+            let session_keys: Vec<String> = token_hashes.iter().map(|h| session_key(h)).collect();
+
+            conn.del::<_, ()>(&deadline_keys)
+                .await
+                .context("failed to delete session deadlines from Redis")?;
+
+            conn.del(&session_keys)
+                .await
+                .context("failed to delete session tokens from Redis")?
+        };
+
+        conn.del::<_, ()>(&index_key)
+            .await
+            .context("failed to delete user session index from Redis")?;
+
+        Ok(delete_count)
+    }
+
+    /// Delete every session of a user except the one behind `keep_token`, return the
+    /// number revoked.
+    async fn clear_other_session_tokens(&self, user_id: i32, keep_token: &str) -> Result<u64> {
         // ---
         let mut conn = get_redis_connection().await?;
+        let index_key = user_sessions_key(user_id);
+        let keep_hash = self.hasher.hash(keep_token);
+
+        let index: HashMap<String, String> = conn
+            .hgetall(&index_key)
+            .await
+            .context("failed to read user session index from Redis")?;
+
+        let (session_ids, token_hashes): (Vec<String>, Vec<String>) = index
+            .into_iter()
+            .filter(|(_, token_hash)| *token_hash != keep_hash)
+            .unzip();
+
+        if token_hashes.is_empty() {
+            return Ok(0);
+        }
+
+        let deadline_keys: Vec<String> = token_hashes
+            .iter()
+            .map(|h| session_deadline_key(h))
+            .collect();
+        let session_keys: Vec<String> = token_hashes.iter().map(|h| session_key(h)).collect();
+
+        conn.del::<_, ()>(&deadline_keys)
+            .await
+            .context("failed to delete session deadlines from Redis")?;
 
+        // Index entries may outlive their token, so only count keys actually deleted.
         let delete_count: u64 = conn
-            .del(token)
+            .del(&session_keys)
             .await
-            .context("failed to delete session token from Redis")?;
+            .context("failed to delete session tokens from Redis")?;
 
-        Ok(delete_count > 0)
+        conn.hdel::<_, _, ()>(&index_key, &session_ids)
+            .await
+            .context("failed to prune user session index in Redis")?;
+
+        Ok(delete_count)
+    }
+
+    /// Store a password reset token under its hash, expiring after `ttl_secs`.
+    async fn set_password_reset_token(
+        &self,
+        token: &str,
+        user_id: i32,
+        ttl_secs: u64,
+    ) -> Result<()> {
+        // ---
+        let mut conn = get_redis_connection().await?;
+
+        conn.set_ex::<_, _, ()>(
+            password_reset_key(&self.hasher.hash(token)),
+            user_id,
+            ttl_secs,
+        )
+        .await
+        .context("failed to set password reset token in Redis")
+    }
+
+    /// Read a password reset token, leaving it in place.
+    async fn find_password_reset_token(&self, token: &str) -> Result<Option<i32>> {
+        // ---
+        let mut conn = get_redis_connection().await?;
+
+        conn.get(password_reset_key(&self.hasher.hash(token)))
+            .await
+            .context("failed to read password reset token from Redis")
+    }
+
+    /// Atomically read and delete a password reset token so it can only be used once.
+    async fn take_password_reset_token(&self, token: &str) -> Result<Option<i32>> {
+        // ---
+        let mut conn = get_redis_connection().await?;
+
+        conn.get_del(password_reset_key(&self.hasher.hash(token)))
+            .await
+            .context("failed to take password reset token from Redis")
+    }
+
+    /// Store a login challenge under its hash, expiring after `ttl_secs`.
+    async fn set_login_challenge(&self, token: &str, user_id: i32, ttl_secs: u64) -> Result<()> {
+        // ---
+        let mut conn = get_redis_connection().await?;
+
+        conn.set_ex::<_, _, ()>(
+            login_challenge_key(&self.hasher.hash(token)),
+            user_id,
+            ttl_secs,
+        )
+        .await
+        .context("failed to set login challenge in Redis")
+    }
+
+    /// Atomically read and delete a login challenge so it can only be used once.
+    async fn take_login_challenge(&self, token: &str) -> Result<Option<i32>> {
+        // ---
+        let mut conn = get_redis_connection().await?;
+
+        conn.get_del(login_challenge_key(&self.hasher.hash(token)))
+            .await
+            .context("failed to take login challenge from Redis")
+    }
+
+    /// Store a pending OIDC login under the hash of its `state` parameter.
+    async fn set_oidc_login_state(&self, state: &str, value: &str, ttl_secs: u64) -> Result<()> {
+        // ---
+        let mut conn = get_redis_connection().await?;
+
+        conn.set_ex::<_, _, ()>(oidc_state_key(&self.hasher.hash(state)), value, ttl_secs)
+            .await
+            .context("failed to set OIDC login state in Redis")
+    }
+
+    /// Atomically read and delete a pending OIDC login so a callback is only accepted once.
+    async fn take_oidc_login_state(&self, state: &str) -> Result<Option<String>> {
+        // ---
+        let mut conn = get_redis_connection().await?;
+
+        conn.get_del(oidc_state_key(&self.hasher.hash(state)))
+            .await
+            .context("failed to take OIDC login state from Redis")
+    }
+
+    /// Store a pending author claim under the hash of its token.
+    async fn set_author_claim(&self, token: &str, value: &str, ttl_secs: u64) -> Result<()> {
+        // ---
+        let mut conn = get_redis_connection().await?;
+
+        conn.set_ex::<_, _, ()>(author_claim_key(&self.hasher.hash(token)), value, ttl_secs)
+            .await
+            .context("failed to set author claim in Redis")
+    }
+
+    /// Atomically read and delete a pending author claim so its token is only used once.
+    async fn take_author_claim(&self, token: &str) -> Result<Option<String>> {
+        // ---
+        let mut conn = get_redis_connection().await?;
+
+        conn.get_del(author_claim_key(&self.hasher.hash(token)))
+            .await
+            .context("failed to take author claim from Redis")
+    }
+
+    /// Increment a login failure counter and (re)start its expiry window.
+    async fn incr_login_failures(&self, key: &str, window_secs: u64) -> Result<u64> {
+        // ---
+        let mut conn = get_redis_connection().await?;
+        let failures_key = login_failures_key(key);
+
+        let (failures,): (u64,) = redis::pipe()
+            .atomic()
+            .incr(&failures_key, 1)
+            .expire(&failures_key, window_secs as i64)
+            .ignore()
+            .query_async(&mut conn)
+            .await
+            .context("failed to count login failure in Redis")?;
+
+        Ok(failures)
+    }
+
+    /// Lock a throttle key for `secs` seconds.
+    async fn lock_login(&self, key: &str, secs: u64) -> Result<()> {
+        // ---
+        let mut conn = get_redis_connection().await?;
+
+        conn.set_ex::<_, _, ()>(login_lock_key(key), 1, secs)
+            .await
+            .context("failed to set login lockout in Redis")
+    }
+
+    /// Remaining lockout of a throttle key, from the TTL of its lock key.
+    async fn login_lock_ttl(&self, key: &str) -> Result<Option<u64>> {
+        // ---
+        let mut conn = get_redis_connection().await?;
+
+        // TTL is -2 for a missing key and -1 for a key without expiry
+        let ttl: i64 = conn
+            .ttl(login_lock_key(key))
+            .await
+            .context("failed to read login lockout from Redis")?;
+
+        Ok((ttl > 0).then_some(ttl as u64))
+    }
+
+    /// Delete the failure counter and lock of a throttle key.
+    async fn clear_login_failures(&self, key: &str) -> Result<()> {
+        // ---
+        let mut conn = get_redis_connection().await?;
+
+        conn.del::<_, ()>(&[login_failures_key(key), login_lock_key(key)])
+            .await
+            .context("failed to clear login failures in Redis")
+    }
+
+    /// List the active sessions of a user, pruning index entries whose token expired.
+    async fn list_user_sessions(&self, user_id: i32) -> Result<Vec<SessionRecord>> {
+        // ---
+        let mut conn = get_redis_connection().await?;
+        let index_key = user_sessions_key(user_id);
+
+        let index: HashMap<String, String> = conn
+            .hgetall(&index_key)
+            .await
+            .context("failed to read user session index from Redis")?;
+
+        let mut sessions = Vec::with_capacity(index.len());
+        let mut stale = Vec::new();
+
+        for (session_id, token_hash) in index {
+            match self.load_session(&mut conn, &token_hash).await? {
+                Some((session, _)) => sessions.push(session),
+                None => stale.push(session_id),
+            }
+        }
+
+        if !stale.is_empty() {
+            conn.hdel::<_, _, ()>(&index_key, &stale)
+                .await
+                .context("failed to prune user session index in Redis")?;
+        }
+
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.last_seen));
+        Ok(sessions)
+    }
+
+    /// Rewrite the roles of each of the user's sessions, keeping their idle TTL.
+    async fn update_session_roles(&self, user_id: i32, role_codes: &[RoleCode]) -> Result<u64> {
+        // ---
+        let mut conn = get_redis_connection().await?;
+
+        let index: HashMap<String, String> = conn
+            .hgetall(user_sessions_key(user_id))
+            .await
+            .context("failed to read user session index from Redis")?;
+
+        let mut update_count = 0;
+        for token_hash in index.values() {
+            let Some((mut session, _)) = self.load_session(&mut conn, token_hash).await? else {
+                continue;
+            };
+            session.role_codes = role_codes.to_vec();
+            let record = serde_json::to_string(&session)?;
+
+            // KEEPTTL, and XX so a session that expired meanwhile is not resurrected.
+            let updated: Option<String> = redis::cmd("SET")
+                .arg(session_key(token_hash))
+                .arg(record)
+                .arg("KEEPTTL")
+                .arg("XX")
+                .query_async(&mut conn)
+                .await
+                .context("failed to update session roles in Redis")?;
+            update_count += updated.is_some() as u64;
+        }
+
+        Ok(update_count)
+    }
+
+    /// Delete one session of a user by its public id, return `true` if found.
+    async fn clear_user_session(&self, user_id: i32, session_id: &str) -> Result<bool> {
+        // ---
+        let mut conn = get_redis_connection().await?;
+
+        let token_hash: Option<String> = conn
+            .hget(user_sessions_key(user_id), session_id)
+            .await
+            .context("failed to read user session index from Redis")?;
+
+        match token_hash {
+            Some(token_hash) => self.clear_session_by_hash(&token_hash).await,
+            None => Ok(false),
+        }
     }
 }
 
+/// Redis key holding the JSON session record for a token hash.
+fn session_key(token_hash: &str) -> String {
+    // ---
+    format!("cr8s:session:{token_hash}")
+}
+
+/// Redis key of the hash mapping public session id -> token hash for `user_id`.
+fn user_sessions_key(user_id: i32) -> String {
+    // ---
+    format!("cr8s:user_sessions:{user_id}")
+}
+
+/// Redis key holding the absolute expiry (unix seconds) of a session.
+fn session_deadline_key(token_hash: &str) -> String {
+    // ---
+    format!("cr8s:session_deadline:{token_hash}")
+}
+
+/// Redis key holding the user id a password reset token was issued for.
+fn password_reset_key(token_hash: &str) -> String {
+    // ---
+    format!("cr8s:password_reset:{token_hash}")
+}
+
+/// Redis key holding the user id a two-factor login challenge was issued for.
+fn login_challenge_key(token_hash: &str) -> String {
+    // ---
+    format!("cr8s:login_challenge:{token_hash}")
+}
+
+/// Redis key holding a pending OIDC login, by the hash of its `state` parameter.
+fn oidc_state_key(state_hash: &str) -> String {
+    // ---
+    format!("cr8s:oidc_state:{state_hash}")
+}
+
+/// Redis key holding a pending author claim, by the hash of its emailed token.
+fn author_claim_key(token_hash: &str) -> String {
+    // ---
+    format!("cr8s:author_claim:{token_hash}")
+}
+
+/// Redis key counting recent failed logins for a throttle key.
+fn login_failures_key(key: &str) -> String {
+    // ---
+    format!("cr8s:login_failures:{key}")
+}
+
+/// Redis key whose TTL is the remaining lockout of a throttle key.
+fn login_lock_key(key: &str) -> String {
+    // ---
+    format!("cr8s:login_lock:{key}")
+}
+
+/// Build the Redis-backed cache context, reading session TTL settings and the token
+/// hashing secret from the environment (see `SessionTtl::from_env` and
+/// `TokenHasher::from_env`).
+///
+/// In JWT auth mode the context is wrapped to issue and verify access tokens (see
+/// `jwt_cache`).
 pub fn create_cache_context() -> CacheContextTraitPtr {
     // ---
-    std::sync::Arc::new(RedisCacheContext)
+    with_access_tokens(std::sync::Arc::new(RedisCacheContext::default()))
+}
+
+/// Version of the session key layout; bump when stored session keys change shape.
+const SESSION_KEY_VERSION: i32 = 2;
+
+/// Redis key recording which session key layout the stored data uses.
+const SESSION_KEY_VERSION_KEY: &str = "cr8s:session_key_version";
+
+/// Length of the raw session tokens older releases used directly as Redis keys.
+const LEGACY_TOKEN_LEN: usize = 128;
+
+/// One-time removal of session keys written before tokens were hashed at rest.
+///
+/// Older releases stored the raw bearer token as a Redis key (with a
+/// `cr8s:session_deadline:<token>` companion) and raw tokens in the per-user session
+/// index. Those sessions can no longer be resolved, and are deleted so the raw tokens
+/// do not linger in the cache. Everyone with a legacy session has to log in again.
+async fn purge_legacy_session_keys(pool: &Pool) -> Result<()> {
+    // ---
+    let mut conn = pool
+        .get()
+        .await
+        .context("purge_legacy_session_keys: failed to get Redis connection")?;
+
+    let version: Option<i32> = conn
+        .get(SESSION_KEY_VERSION_KEY)
+        .await
+        .context("failed to read session key version from Redis")?;
+
+    if version.unwrap_or(0) >= SESSION_KEY_VERSION {
+        return Ok(());
+    }
+
+    let mut legacy_keys = Vec::new();
+    {
+        let mut iter = conn
+            .scan::<String>()
+            .await
+            .context("failed to scan Redis keys")?;
+
+        while let Some(key) = iter.next_item().await {
+            if is_legacy_session_key(&key) {
+                legacy_keys.push(key);
+            }
+        }
+    }
+
+    if !legacy_keys.is_empty() {
+        conn.del::<_, ()>(&legacy_keys)
+            .await
+            .context("failed to delete legacy session keys from Redis")?;
+    }
+
+    conn.set::<_, _, ()>(SESSION_KEY_VERSION_KEY, SESSION_KEY_VERSION)
+        .await
+        .context("failed to record session key version in Redis")?;
+
+    tracing::info!("🧹 Removed {} legacy session key(s)", legacy_keys.len());
+    Ok(())
+}
+
+/// `true` for keys only written by the pre-hashing session layout.
+fn is_legacy_session_key(key: &str) -> bool {
+    // ---
+    let is_raw_token =
+        |s: &str| s.len() == LEGACY_TOKEN_LEN && s.chars().all(|c| c.is_ascii_alphanumeric());
+
+    is_raw_token(key)
+        || key
+            .strip_prefix("cr8s:session_deadline:")
+            .is_some_and(is_raw_token)
+        || key.starts_with("cr8s:user_sessions:")
 }
 
 /// Attempt to create and verify a Redis connection pool with retry logic.
 pub async fn init_redis_cache_with_retry_from_env() -> Result<()> {
     // ---
 
+    // Configuration errors are not worth retrying for.
+    init_jwt_keys_from_env()?;
+
     let redis_url = get_env_with_default!(String, "REDIS_URL", "redis://127.0.0.1/".to_owned());
     let retry_max = get_env_with_default!(u32, "CR8S_REDIS_RETRY_COUNT", 20); // Reduced from 50
     let base_delay_secs = get_env_with_default!(u64, "CR8S_REDIS_RETRY_DELAY_SECS", 2); // Increased from 1
@@ -104,6 +772,11 @@ pub async fn init_redis_cache_with_retry_from_env() -> Result<()> {
             Ok(pool) => {
                 tracing::info!("✅ Redis pool created, verifying:{}...", redis_url);
                 if verify_redis_pool(&pool).await.is_ok() {
+                    // Sessions from older releases were keyed by the raw token.
+                    if let Err(e) = purge_legacy_session_keys(&pool).await {
+                        tracing::warn!("⚠️ Failed to purge legacy session keys: {e:#}");
+                    }
+
                     // Store the verified pool for use by the rest of the application
                     REDIS_POOL
                         .set(pool)
@@ -192,3 +865,40 @@ async fn verify_redis_pool(pool: &Pool) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_hash_is_keyed_and_hides_token() {
+        // ---
+        let token = "a".repeat(LEGACY_TOKEN_LEN);
+        let hasher = TokenHasher::new(b"secret-one".to_vec());
+
+        let hash = hasher.hash(&token);
+
+        assert_eq!(hash.len(), 64);
+        assert!(!hash.contains(&token));
+        assert_eq!(hash, hasher.hash(&token));
+        assert_ne!(hash, TokenHasher::new(b"secret-two".to_vec()).hash(&token));
+    }
+
+    #[test]
+    fn test_is_legacy_session_key() {
+        // ---
+        let token = "Ab1".repeat(LEGACY_TOKEN_LEN / 3) + "xy";
+        let hash = TokenHasher::new(b"secret".to_vec()).hash(&token);
+
+        assert!(is_legacy_session_key(&token));
+        assert!(is_legacy_session_key(&format!(
+            "cr8s:session_deadline:{token}"
+        )));
+        assert!(is_legacy_session_key("cr8s:user_sessions:42"));
+
+        assert!(!is_legacy_session_key(&session_key(&hash)));
+        assert!(!is_legacy_session_key(&session_deadline_key(&hash)));
+        assert!(!is_legacy_session_key(SESSION_KEY_VERSION_KEY));
+        assert!(!is_legacy_session_key("__cr8s_redis_ping"));
+    }
+}
@@ -1,31 +1,21 @@
 use crate::domain::RoleCode;
 
+/// Database representation of a role code: the `role.code` column as text.
 #[derive(Debug, serde::Deserialize, sqlx::Type)]
-#[sqlx(type_name = "RoleCodeMapping", rename_all = "PascalCase")]
-pub enum RoleCodeMapping {
-    Admin,
-    Editor,
-    Viewer,
-}
+#[sqlx(transparent)]
+pub struct RoleCodeMapping(String);
 
 impl From<RoleCodeMapping> for RoleCode {
     // ---
     fn from(m: RoleCodeMapping) -> Self {
-        match m {
-            RoleCodeMapping::Admin => RoleCode::Admin,
-            RoleCodeMapping::Editor => RoleCode::Editor,
-            RoleCodeMapping::Viewer => RoleCode::Viewer,
-        }
+        // The `role.code` CHECK constraint only admits codes `RoleCode` can parse
+        m.0.parse().unwrap_or(RoleCode::Custom(m.0))
     }
 }
 
 impl From<RoleCode> for RoleCodeMapping {
     // ---
     fn from(m: RoleCode) -> Self {
-        match m {
-            RoleCode::Admin => RoleCodeMapping::Admin,
-            RoleCode::Editor => RoleCodeMapping::Editor,
-            RoleCode::Viewer => RoleCodeMapping::Viewer,
-        }
+        RoleCodeMapping(m.to_string())
     }
 }
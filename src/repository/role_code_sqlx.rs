@@ -1,6 +1,8 @@
-use crate::domain::{Role, RoleCode, RoleCodeTableTrait, RoleCodeTableTraitPtr};
+use crate::domain::{
+    NewRole, Permission, Role, RoleCode, RoleCodeTableTrait, RoleCodeTableTraitPtr,
+};
 use crate::repository::{get_pool, RoleCodeMapping};
-use anyhow::Result;
+use anyhow::{Context, Result};
 use async_trait::async_trait;
 use sqlx::PgPool;
 use std::sync::Arc;
@@ -31,19 +33,27 @@ pub fn create_role_code_repo() -> Result<RoleCodeTableTraitPtr> {
 
 // ---
 
+/// Roles joined with their permissions, one row per role.
+const SELECT_ROLES: &str = r#"
+    SELECT r.id, r.code, r.name,
+           COALESCE(
+             array_agg(rp.permission ORDER BY rp.permission)
+               FILTER (WHERE rp.permission IS NOT NULL),
+             '{}'
+           ) AS permissions
+    FROM role r
+    LEFT JOIN role_permission rp ON rp.role_id = r.id
+"#;
+
 #[async_trait]
 impl RoleCodeTableTrait for RoleCodeRepo {
     // ---
     #[instrument(skip(self))]
     async fn find_all(&self) -> Result<Vec<RoleCode>> {
         // ---
-        // TODO I think this method is not used. -jbasrai
-        // ---
-        // ORDER BY id for stable ordering; id is not included in SELECT
-
-        let rows = sqlx::query_as::<_, RoleRow>(
+        let rows = sqlx::query_scalar::<_, RoleCodeMapping>(
             r#"
-            SELECT code AS "code: RoleCodeMapping"
+            SELECT code
             FROM role
             ORDER BY id
             "#,
@@ -51,7 +61,7 @@ impl RoleCodeTableTrait for RoleCodeRepo {
         .fetch_all(&self.pool)
         .await?;
 
-        Ok(rows.into_iter().map(|r| RoleCode::from(r.code)).collect())
+        Ok(rows.into_iter().map(RoleCode::from).collect())
     }
 
     // ---
@@ -59,22 +69,9 @@ impl RoleCodeTableTrait for RoleCodeRepo {
     #[instrument(skip(self))]
     async fn find_role_name_by_code(&self, code: RoleCode) -> Result<Role> {
         // ---
-        let row: RoleRow = sqlx::query_as::<_, RoleRow>(
-            r#"
-            SELECT id, code as "code: RoleCodeMapping", name
-            FROM role
-            WHERE code = $1::"RoleCodeMapping"
-            "#,
-        )
-        .bind(RoleCodeMapping::from(code))
-        .fetch_one(&self.pool)
-        .await?;
-
-        Ok(Role {
-            id: row.id,
-            code: row.code.into(),
-            name: row.name,
-        })
+        self.find_role(&code)
+            .await?
+            .with_context(|| format!("RoleCodeRepo: no role with code {code}"))
     }
 
     // ---
@@ -82,10 +79,9 @@ impl RoleCodeTableTrait for RoleCodeRepo {
     #[instrument(skip(self))]
     async fn find_role_codes_by_user(&self, user_id: i32) -> Result<Vec<RoleCode>> {
         // ---
-
-        let rows: Vec<RoleRow> = sqlx::query_as(
+        let rows = sqlx::query_scalar::<_, RoleCodeMapping>(
             r#"
-            SELECT r.code AS "code: RoleCodeMapping"
+            SELECT r.code
             FROM role r
             JOIN user_roles ur ON ur.role_id = r.id
             WHERE ur.user_id = $1
@@ -96,15 +92,193 @@ impl RoleCodeTableTrait for RoleCodeRepo {
         .fetch_all(&self.pool)
         .await?;
 
-        Ok(rows.into_iter().map(|r| RoleCode::from(r.code)).collect())
+        Ok(rows.into_iter().map(RoleCode::from).collect())
+    }
+
+    // ---
+
+    #[instrument(skip(self))]
+    async fn find_roles(&self) -> Result<Vec<Role>> {
+        // ---
+        let rows =
+            sqlx::query_as::<_, RoleRow>(&format!("{SELECT_ROLES} GROUP BY r.id ORDER BY r.id"))
+                .fetch_all(&self.pool)
+                .await
+                .context("RoleCodeRepo::find_roles failed")?;
+
+        Ok(rows.into_iter().map(Role::from).collect())
+    }
+
+    // ---
+
+    #[instrument(skip(self))]
+    async fn find_role(&self, code: &RoleCode) -> Result<Option<Role>> {
+        // ---
+        let row = sqlx::query_as::<_, RoleRow>(&format!(
+            "{SELECT_ROLES} WHERE r.code = $1 GROUP BY r.id"
+        ))
+        .bind(code.to_string())
+        .fetch_optional(&self.pool)
+        .await
+        .with_context(|| format!("RoleCodeRepo::find_role failed for: {code}"))?;
+
+        Ok(row.map(Role::from))
+    }
+
+    // ---
+
+    #[instrument(skip(self))]
+    async fn create_role(&self, new_role: &NewRole) -> Result<Role> {
+        // ---
+        let mut tx = self.pool.begin().await?;
+
+        let role_id: i32 = sqlx::query_scalar(
+            r#"
+            INSERT INTO role (code, name)
+            VALUES ($1, $2)
+            RETURNING id
+            "#,
+        )
+        .bind(new_role.code.to_string())
+        .bind(new_role.name.trim())
+        .fetch_one(&mut *tx)
+        .await
+        .with_context(|| format!("RoleCodeRepo::create_role failed for: {}", new_role.code))?;
+
+        insert_permissions(&mut tx, role_id, &new_role.permissions).await?;
+        tx.commit().await?;
+
+        self.find_role_name_by_code(new_role.code.clone()).await
+    }
+
+    // ---
+
+    #[instrument(skip(self))]
+    async fn rename_role(&self, code: &RoleCode, name: &str) -> Result<bool> {
+        // ---
+        let result = sqlx::query(r#"UPDATE role SET name = $2 WHERE code = $1"#)
+            .bind(code.to_string())
+            .bind(name)
+            .execute(&self.pool)
+            .await
+            .with_context(|| format!("RoleCodeRepo::rename_role failed for: {code}"))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    // ---
+
+    #[instrument(skip(self))]
+    async fn set_role_permissions(
+        &self,
+        code: &RoleCode,
+        permissions: &[Permission],
+    ) -> Result<bool> {
+        // ---
+        let mut tx = self.pool.begin().await?;
+
+        let role_id: Option<i32> = sqlx::query_scalar(r#"SELECT id FROM role WHERE code = $1"#)
+            .bind(code.to_string())
+            .fetch_optional(&mut *tx)
+            .await?;
+        let Some(role_id) = role_id else {
+            return Ok(false);
+        };
+
+        sqlx::query(r#"DELETE FROM role_permission WHERE role_id = $1"#)
+            .bind(role_id)
+            .execute(&mut *tx)
+            .await?;
+        insert_permissions(&mut tx, role_id, permissions).await?;
+
+        tx.commit()
+            .await
+            .with_context(|| format!("RoleCodeRepo::set_role_permissions failed for: {code}"))?;
+        Ok(true)
+    }
+
+    // ---
+
+    #[instrument(skip(self))]
+    async fn delete_role(&self, code: &RoleCode) -> Result<bool> {
+        // ---
+        let result = sqlx::query(r#"DELETE FROM role WHERE code = $1"#)
+            .bind(code.to_string())
+            .execute(&self.pool)
+            .await
+            .with_context(|| format!("RoleCodeRepo::delete_role failed for: {code}"))?;
+
+        Ok(result.rows_affected() > 0)
     }
+
+    // ---
+
+    #[instrument(skip(self))]
+    async fn find_permissions_by_roles(&self, roles: &[RoleCode]) -> Result<Vec<Permission>> {
+        // ---
+        let codes: Vec<String> = roles.iter().map(RoleCode::to_string).collect();
+
+        let rows: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT DISTINCT rp.permission
+            FROM role_permission rp
+            JOIN role r ON r.id = rp.role_id
+            WHERE r.code = ANY($1)
+            "#,
+        )
+        .bind(&codes)
+        .fetch_all(&self.pool)
+        .await
+        .context("RoleCodeRepo::find_permissions_by_roles failed")?;
+
+        Ok(parse_permissions(&rows))
+    }
+}
+
+async fn insert_permissions(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    role_id: i32,
+    permissions: &[Permission],
+) -> Result<()> {
+    // ---
+    let permissions: Vec<String> = permissions.iter().map(Permission::to_string).collect();
+
+    sqlx::query(
+        r#"
+        INSERT INTO role_permission (role_id, permission)
+        SELECT $1, permission FROM UNNEST($2::varchar[]) AS permission
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(role_id)
+    .bind(&permissions)
+    .execute(&mut **tx)
+    .await
+    .with_context(|| format!("Failed to assign permissions to role id {role_id}"))?;
+
+    Ok(())
+}
+
+/// Unknown permissions (e.g. from a newer release) are dropped rather than granted.
+fn parse_permissions(rows: &[String]) -> Vec<Permission> {
+    // ---
+    rows.iter()
+        .filter_map(|permission| match permission.parse::<Permission>() {
+            Ok(permission) => Some(permission),
+            Err(err) => {
+                tracing::warn!("role_permission: {err}");
+                None
+            }
+        })
+        .collect()
 }
 
-#[derive(Debug, serde::Deserialize, sqlx::FromRow)]
+#[derive(Debug, sqlx::FromRow)]
 struct RoleRow {
     id: i32,
     code: RoleCodeMapping,
     name: String,
+    permissions: Vec<String>,
 }
 
 impl From<RoleRow> for Role {
@@ -113,6 +287,7 @@ impl From<RoleRow> for Role {
             id: row.id,
             code: row.code.into(),
             name: row.name,
+            permissions: parse_permissions(&row.permissions),
         }
     }
 }
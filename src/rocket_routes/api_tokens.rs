@@ -0,0 +1,274 @@
+// rocket_routes/api_tokens.rs
+use super::{server_error, GuardedAppUser};
+use crate::domain::{
+    //
+    issue_api_token,
+    ApiTokenError,
+    ApiTokenTableTraitPtr,
+    NewApiToken,
+};
+use rocket::http::Status;
+use rocket::response::status::{Custom, NoContent};
+use rocket::serde::json::{json, Json, Value};
+use rocket::State;
+
+/// List the calling user's API tokens (metadata only, never the secrets).
+#[rocket::get("/me/tokens")]
+pub async fn list_my_api_tokens(
+    api_token_repo: &State<ApiTokenTableTraitPtr>,
+    user: GuardedAppUser,
+) -> Result<Value, Custom<Value>> {
+    // ---
+    api_token_repo
+        .find_by_user(user.0.id)
+        .await
+        .map(|tokens| json!(tokens))
+        .map_err(server_error)
+}
+
+/// Create an API token for the calling user.
+///
+/// The secret is only part of this response; store it right away.
+#[rocket::post("/me/tokens", format = "json", data = "<new_token>")]
+pub async fn create_my_api_token(
+    api_token_repo: &State<ApiTokenTableTraitPtr>,
+    user: GuardedAppUser,
+    new_token: Json<NewApiToken>,
+) -> Result<Value, Custom<Value>> {
+    // ---
+    let (api_token, secret) = issue_api_token(
+        api_token_repo.inner().clone(),
+        user.0.id,
+        new_token.into_inner(),
+    )
+    .await
+    .map_err(|err| match err {
+        ApiTokenError::Invalid(reason) => Custom(Status::BadRequest, json!({ "error": reason })),
+        ApiTokenError::Internal(err) => server_error(err),
+    })?;
+
+    tracing::info!(
+        "🔑 API token {} created for user id {}",
+        api_token.id,
+        user.0.id
+    );
+    Ok(json!({ "token": secret, "api_token": api_token }))
+}
+
+/// Revoke one of the calling user's API tokens.
+#[rocket::delete("/me/tokens/<id>")]
+pub async fn revoke_my_api_token(
+    api_token_repo: &State<ApiTokenTableTraitPtr>,
+    user: GuardedAppUser,
+    id: i32,
+) -> Result<NoContent, Custom<Value>> {
+    // ---
+    let found = api_token_repo
+        .revoke(user.0.id, id)
+        .await
+        .map_err(server_error)?;
+
+    if found {
+        Ok(NoContent)
+    } else {
+        Err(Custom(
+            Status::NotFound,
+            json!({ "error": "API token not found" }),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // ---
+    use super::*;
+    use crate::domain::{
+        //
+        authenticate_api_token,
+        ApiScope,
+        ApiToken,
+        ApiTokenTableTrait,
+        AppUser,
+    };
+
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use chrono::{NaiveDateTime, Utc};
+    use std::sync::{Arc, Mutex};
+
+    /// In-memory tokens as `(hash, token)` pairs.
+    #[derive(Default)]
+    struct MockApiTokenRepo {
+        tokens: Mutex<Vec<(String, ApiToken)>>,
+    }
+
+    #[async_trait]
+    impl ApiTokenTableTrait for MockApiTokenRepo {
+        // ---
+        async fn create(
+            &self,
+            user_id: i32,
+            name: &str,
+            token_hash: &str,
+            scopes: &[ApiScope],
+            expires_at: Option<NaiveDateTime>,
+        ) -> Result<ApiToken> {
+            // ---
+            let mut tokens = self.tokens.lock().unwrap();
+            let token = ApiToken {
+                id: tokens.len() as i32 + 1,
+                user_id,
+                name: name.into(),
+                scopes: scopes.to_vec(),
+                created_at: Utc::now().naive_utc(),
+                expires_at,
+                last_used_at: None,
+            };
+            tokens.push((token_hash.into(), token.clone()));
+            Ok(token)
+        }
+
+        async fn find_by_user(&self, user_id: i32) -> Result<Vec<ApiToken>> {
+            // ---
+            Ok(self
+                .tokens
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(_, token)| token.user_id == user_id)
+                .map(|(_, token)| token.clone())
+                .collect())
+        }
+
+        async fn find_by_hash(&self, token_hash: &str) -> Result<Option<ApiToken>> {
+            // ---
+            Ok(self
+                .tokens
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|(hash, _)| hash == token_hash)
+                .map(|(_, token)| token.clone()))
+        }
+
+        async fn revoke(&self, user_id: i32, token_id: i32) -> Result<bool> {
+            // ---
+            let mut tokens = self.tokens.lock().unwrap();
+            let before = tokens.len();
+            tokens.retain(|(_, token)| !(token.id == token_id && token.user_id == user_id));
+            Ok(tokens.len() < before)
+        }
+    }
+
+    fn guarded_user(id: i32) -> GuardedAppUser {
+        // ---
+        GuardedAppUser(AppUser {
+            id,
+            username: "ci@example.com".into(),
+            password: "hashed".into(),
+            created_at: Utc::now().naive_utc(),
+            author_id: None,
+            disabled_at: None,
+        })
+    }
+
+    fn new_token(scopes: Vec<ApiScope>, expires_in_days: Option<u32>) -> Json<NewApiToken> {
+        Json(NewApiToken {
+            name: "ci".into(),
+            scopes,
+            expires_in_days,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_create_list_and_revoke_api_token() {
+        // ---
+        let repo: ApiTokenTableTraitPtr = Arc::new(MockApiTokenRepo::default());
+
+        let created = create_my_api_token(
+            State::from(&repo),
+            guarded_user(5),
+            new_token(vec![ApiScope::CratesWrite, ApiScope::CratesRead], Some(30)),
+        )
+        .await
+        .expect("token creation should succeed");
+
+        let secret = created["token"].as_str().unwrap();
+        assert!(secret.starts_with("cr8s_pat_"));
+        assert_eq!(
+            created["api_token"]["scopes"],
+            json!(["crates:read", "crates:write"])
+        );
+        assert!(created["api_token"]["expires_at"].is_string());
+
+        // The secret resolves to the stored token, which is never listed with it
+        let resolved = authenticate_api_token(&repo, secret)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(resolved.user_id, 5);
+        assert!(resolved.allows(ApiScope::CratesRead));
+        assert!(!resolved.allows(ApiScope::AuthorsWrite));
+
+        let listed = list_my_api_tokens(State::from(&repo), guarded_user(5))
+            .await
+            .unwrap();
+        assert_eq!(listed.as_array().unwrap().len(), 1);
+        assert!(!listed.to_string().contains(secret));
+
+        // Other users cannot revoke it
+        let id = resolved.id;
+        match revoke_my_api_token(State::from(&repo), guarded_user(6), id).await {
+            Err(Custom(status, _)) => assert_eq!(status.code, 404),
+            Ok(_) => panic!("Expected 404 for another user's token"),
+        }
+
+        assert!(revoke_my_api_token(State::from(&repo), guarded_user(5), id)
+            .await
+            .is_ok());
+        assert!(authenticate_api_token(&repo, secret)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_create_api_token_validation() {
+        // ---
+        let repo: ApiTokenTableTraitPtr = Arc::new(MockApiTokenRepo::default());
+
+        for body in [
+            new_token(vec![], None),
+            new_token(vec![ApiScope::CratesRead], Some(0)),
+        ] {
+            match create_my_api_token(State::from(&repo), guarded_user(5), body).await {
+                Err(Custom(status, _)) => assert_eq!(status.code, 400),
+                Ok(_) => panic!("Expected invalid token request to be rejected"),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_expired_api_token_is_rejected() {
+        // ---
+        let mock_repo = Arc::new(MockApiTokenRepo::default());
+        let repo: ApiTokenTableTraitPtr = mock_repo.clone();
+
+        let created = create_my_api_token(
+            State::from(&repo),
+            guarded_user(5),
+            new_token(vec![ApiScope::CratesRead], Some(1)),
+        )
+        .await
+        .unwrap();
+        let secret = created["token"].as_str().unwrap();
+
+        mock_repo.tokens.lock().unwrap()[0].1.expires_at =
+            Some(Utc::now().naive_utc() - chrono::Duration::seconds(1));
+
+        assert!(authenticate_api_token(&repo, secret)
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
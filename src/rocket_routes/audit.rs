@@ -0,0 +1,151 @@
+// rocket_routes/audit.rs
+//! Admin-only read access to the audit log.
+use super::{bad_request, server_error, AuditRead, RequirePermission};
+use crate::domain::{
+    //
+    AuditFilter,
+    AuditLogTraitPtr,
+};
+use rocket::response::status::Custom;
+use rocket::serde::json::{json, Value};
+use rocket::State;
+
+/// Audit events, newest first, e.g. `/audit?entity_type=crate&entity_id=7&limit=20`.
+///
+/// Filters: `actor` (user id), `action` (`create`, `update`, `delete`, `yank`,
+/// `unyank`), `entity_type` (`crate`, `author`, `user`), `entity_id` and `after_id`
+/// (only newer events).
+/// `limit` defaults to 50 and is capped at 500.
+#[allow(clippy::too_many_arguments)]
+#[rocket::get("/audit?<actor>&<action>&<entity_type>&<entity_id>&<after_id>&<limit>")]
+pub async fn list_audit_events(
+    audit_log: &State<AuditLogTraitPtr>,
+    _admin: RequirePermission<AuditRead>,
+    actor: Option<i32>,
+    action: Option<&str>,
+    entity_type: Option<&str>,
+    entity_id: Option<i32>,
+    after_id: Option<i32>,
+    limit: Option<i64>,
+) -> Result<Value, Custom<Value>> {
+    // ---
+    let filter = AuditFilter {
+        actor_user_id: actor,
+        action: action.map(str::parse).transpose().map_err(bad_request)?,
+        entity_type: entity_type
+            .map(str::parse)
+            .transpose()
+            .map_err(bad_request)?,
+        entity_id,
+        after_id,
+        limit,
+    };
+
+    audit_log
+        .find(&filter)
+        .await
+        .map(|events| json!(events))
+        .map_err(server_error)
+}
+
+#[cfg(test)]
+mod tests {
+    // ---
+    use super::*;
+    use crate::domain::{
+        //
+        AppUser,
+        AuditAction,
+        AuditEntity,
+        AuditEvent,
+        AuditLogTrait,
+        NewAuditEvent,
+    };
+    use crate::rocket_routes::GuardedAppUser;
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use chrono::Utc;
+    use rocket::http::Status;
+    use std::sync::{Arc, Mutex};
+
+    /// Returns one crate event and remembers the last filter it was asked for.
+    #[derive(Default)]
+    struct MockAuditLog {
+        last_filter: Mutex<Option<AuditFilter>>,
+    }
+
+    #[async_trait]
+    impl AuditLogTrait for MockAuditLog {
+        // ---
+        async fn record(&self, _event: NewAuditEvent) -> Result<AuditEvent> {
+            unreachable!()
+        }
+
+        async fn find(&self, filter: &AuditFilter) -> Result<Vec<AuditEvent>> {
+            // ---
+            *self.last_filter.lock().unwrap() = Some(filter.clone());
+            Ok(vec![AuditEvent {
+                id: 1,
+                actor_user_id: Some(1),
+                action: AuditAction::Delete,
+                entity_type: AuditEntity::Crate,
+                entity_id: 7,
+                before: Some(json!({ "name": "serde" })),
+                after: None,
+                created_at: Utc::now().naive_utc(),
+            }])
+        }
+    }
+
+    fn admin() -> RequirePermission<AuditRead> {
+        RequirePermission::new(GuardedAppUser(AppUser {
+            id: 1,
+            username: "admin@example.com".into(),
+            password: "admin-hash".into(),
+            created_at: Utc::now().naive_utc(),
+            author_id: None,
+            disabled_at: None,
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_list_audit_events_filters() {
+        // ---
+        let mock_log = Arc::new(MockAuditLog::default());
+        let audit_log: AuditLogTraitPtr = mock_log.clone();
+
+        let events = list_audit_events(
+            State::from(&audit_log),
+            admin(),
+            None,
+            Some("delete"),
+            Some("crate"),
+            Some(7),
+            None,
+            Some(20),
+        )
+        .await
+        .unwrap();
+        assert_eq!(events[0]["action"], "delete");
+        assert_eq!(events[0]["entity_type"], "crate");
+        assert_eq!(events[0]["before"]["name"], "serde");
+
+        let filter = mock_log.last_filter.lock().unwrap().clone().unwrap();
+        assert_eq!(filter.action, Some(AuditAction::Delete));
+        assert_eq!(filter.entity_type, Some(AuditEntity::Crate));
+        assert_eq!((filter.entity_id, filter.limit), (Some(7), Some(20)));
+
+        let invalid = list_audit_events(
+            State::from(&audit_log),
+            admin(),
+            None,
+            None,
+            Some("role"),
+            None,
+            None,
+            None,
+        )
+        .await;
+        assert_eq!(invalid.unwrap_err().0, Status::BadRequest);
+    }
+}
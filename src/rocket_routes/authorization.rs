@@ -1,42 +1,242 @@
-use super::{server_error, GuardedAppUser};
+use super::users::user_admin_error;
+use super::{
+    password_error, server_error, ClientInfo, GuardedAppUser, RequirePermission, SessionToken,
+    UserManage,
+};
 use crate::domain::{
     //
     authenticate_user,
+    change_password,
+    check_can_manage,
+    record_audit_event,
+    set_password,
+    AppUser,
     AppUserTableTraitPtr,
+    AuditEntity,
+    AuditLogTraitPtr,
     CacheContextTraitPtr,
     Credentials,
+    LoginError,
+    LoginOutcome,
+    NewAuditEvent,
+    NewSession,
+    PasswordChange,
+    PasswordReset,
+    RoleCodeTableTraitPtr,
+    UserSummary,
 };
-use rocket::http::Status;
-use rocket::response::status::Custom;
+use rocket::http::{Header, Status};
+use rocket::response::status::{Custom, NoContent};
 use rocket::serde::json::{json, Json, Value};
 use rocket::State;
 
+/// Rejected login: plain error responses, or 429 with `Retry-After` while locked out.
+#[derive(Debug, rocket::Responder)]
+pub enum LoginRejection {
+    Denied(Custom<Value>),
+    #[response(status = 429)]
+    LockedOut(Value, Header<'static>),
+}
+
+impl From<Custom<Value>> for LoginRejection {
+    fn from(custom: Custom<Value>) -> Self {
+        LoginRejection::Denied(custom)
+    }
+}
+
 #[rocket::post("/login", format = "json", data = "<credentials>")]
 pub async fn login(
     app_user_repo: &State<AppUserTableTraitPtr>,
     cache_context: &State<CacheContextTraitPtr>,
+    client: ClientInfo,
     credentials: Json<Credentials>,
-) -> Result<Value, Custom<Value>> {
+) -> Result<Value, LoginRejection> {
     // --
-    let (user, session_id) =
-        match authenticate_user(app_user_repo.inner().clone(), credentials.into_inner()).await {
-            // ---
-            Ok(result) => result,
-            Err(err) => {
-                tracing::warn!("❌ Login failed: {err}");
-                return Err(Custom(
-                    Status::Unauthorized,
-                    json!("error: Invalid credentials"),
-                ));
-            }
-        };
+    let result = authenticate_user(
+        app_user_repo.inner().clone(),
+        cache_context.inner().clone(),
+        credentials.into_inner(),
+        client.ip.as_deref(),
+    )
+    .await;
+
+    let (user, session_id) = match result {
+        // ---
+        Ok(LoginOutcome::Authenticated(user, session_id)) => (user, session_id),
+        Ok(LoginOutcome::SecondFactorRequired { challenge_token }) => {
+            return Ok(json!({
+                "second_factor_required": true,
+                "challenge_token": challenge_token,
+            }));
+        }
+        Err(LoginError::LockedOut { retry_after_secs }) => {
+            tracing::warn!("❌ Login locked out for {retry_after_secs}s");
+            return Err(locked_out(retry_after_secs));
+        }
+        Err(LoginError::AccountDisabled) => {
+            tracing::warn!("❌ Login refused, account is disabled");
+            return Err(LoginRejection::Denied(account_disabled()));
+        }
+        Err(err) => {
+            tracing::warn!("❌ Login failed: {err}");
+            return Err(LoginRejection::Denied(Custom(
+                Status::Unauthorized,
+                json!("error: Invalid credentials"),
+            )));
+        }
+    };
+
+    Ok(start_session(app_user_repo, cache_context, client, &user, &session_id).await?)
+}
 
+/// 429 response for a locked-out login, telling the client when to retry.
+pub(super) fn locked_out(retry_after_secs: u64) -> LoginRejection {
+    // ---
+    LoginRejection::LockedOut(
+        json!({ "error": "Too many failed login attempts", "retry_after": retry_after_secs }),
+        Header::new("Retry-After", retry_after_secs.to_string()),
+    )
+}
+
+/// Response for a login by a disabled account.
+pub(super) fn account_disabled() -> Custom<Value> {
+    // ---
+    Custom(Status::Forbidden, json!({ "error": "Account is disabled" }))
+}
+
+/// Store a freshly minted session token for `user` and build the login response.
+///
+/// The response is `{"token": <session token>}`, or in JWT auth mode an access token
+/// with the session token as its refresh token.
+pub(super) async fn start_session(
+    app_user_repo: &AppUserTableTraitPtr,
+    cache_context: &CacheContextTraitPtr,
+    client: ClientInfo,
+    user: &AppUser,
+    session_id: &str,
+) -> Result<Value, Custom<Value>> {
+    // ---
+    // Snapshot the roles held at login so the session record shows what was granted
+    let role_codes = app_user_repo
+        .find_roles_by_user(user)
+        .await
+        .map_err(server_error)?;
+
+    let new_session = NewSession {
+        user_id: user.id,
+        client_ip: client.ip,
+        user_agent: client.user_agent,
+        role_codes,
+    };
+
+    let session = cache_context
+        .set_user_session_token(session_id, new_session)
+        .await
+        .map_err(server_error)?;
+
+    let access_token = cache_context
+        .issue_access_token(user, &session)
+        .await
+        .map_err(server_error)?;
+
+    Ok(match access_token {
+        Some(access_token) => json!({
+            "token": access_token.token,
+            "token_type": "Bearer",
+            "expires_in": access_token.expires_in,
+            "refresh_token": session_id,
+        }),
+        None => json!({ "token": session_id }),
+    })
+}
+
+/// Exchange a refresh token for a new access token (JWT auth mode only).
+///
+/// The refresh token is single use: it is replaced by a new one, and roles are
+/// re-read so grants and revocations show up in the new access token.
+#[rocket::post("/token/refresh", format = "json", data = "<refresh>")]
+pub async fn refresh_access_token(
+    app_user_repo: &State<AppUserTableTraitPtr>,
+    cache_context: &State<CacheContextTraitPtr>,
+    client: ClientInfo,
+    refresh: Json<RefreshRequest>,
+) -> Result<Value, Custom<Value>> {
+    // ---
+    if !cache_context.requires_access_tokens() {
+        return Err(Custom(
+            Status::NotFound,
+            json!({ "error": "Access tokens are not enabled" }),
+        ));
+    }
+
+    let invalid = || {
+        Custom(
+            Status::Unauthorized,
+            json!({ "error": "Invalid refresh token" }),
+        )
+    };
+
+    let user_id = cache_context
+        .get_user_id_by_session_token(&refresh.refresh_token)
+        .await
+        .map_err(server_error)?
+        .ok_or_else(invalid)?;
+
+    // Losing a race against a concurrent refresh (or logout) of the same token
+    if !cache_context
+        .clear_session_token(&refresh.refresh_token)
+        .await
+        .map_err(server_error)?
+    {
+        return Err(invalid());
+    }
+
+    let user = app_user_repo.find(user_id).await.map_err(|_| invalid())?;
+    if !user.is_active() {
+        return Err(account_disabled());
+    }
+    let session_id = crate::auth::create_password_hasher()
+        .map_err(server_error)?
+        .generate_session_token();
+
+    start_session(app_user_repo, cache_context, client, &user, &session_id).await
+}
+
+/// Body of `POST /token/refresh`.
+#[derive(Debug, serde::Deserialize)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+/// Revoke the session token presented with this request.
+#[rocket::post("/logout")]
+pub async fn logout(
+    cache_context: &State<CacheContextTraitPtr>,
+    _user: GuardedAppUser,
+    token: SessionToken,
+) -> Result<NoContent, Custom<Value>> {
+    // ---
     cache_context
-        .set_user_session_token(user.id, &session_id)
+        .clear_session_token(&token.0)
+        .await
+        .map(|_| NoContent)
+        .map_err(server_error)
+}
+
+/// Revoke every session token issued to the calling user.
+#[rocket::post("/logout-all")]
+pub async fn logout_all(
+    cache_context: &State<CacheContextTraitPtr>,
+    user: GuardedAppUser,
+) -> Result<Value, Custom<Value>> {
+    // ---
+    let revoked = cache_context
+        .clear_all_session_tokens(user.0.id)
         .await
         .map_err(server_error)?;
 
-    Ok(json!({ "token": session_id }))
+    tracing::info!("🔒 Revoked {revoked} session(s) for user id {}", user.0.id);
+    Ok(json!({ "revoked": revoked }))
 }
 
 #[rocket::get("/me")]
@@ -46,201 +246,759 @@ pub fn me(user: GuardedAppUser) -> Value {
     json!(user)
 }
 
+/// List the calling user's active sessions.
+#[rocket::get("/me/sessions")]
+pub async fn list_my_sessions(
+    cache_context: &State<CacheContextTraitPtr>,
+    user: GuardedAppUser,
+) -> Result<Value, Custom<Value>> {
+    // ---
+    cache_context
+        .list_user_sessions(user.0.id)
+        .await
+        .map(|sessions| json!(sessions))
+        .map_err(server_error)
+}
+
+/// Revoke one of the calling user's sessions by its public id.
+#[rocket::delete("/me/sessions/<id>")]
+pub async fn delete_my_session(
+    cache_context: &State<CacheContextTraitPtr>,
+    user: GuardedAppUser,
+    id: &str,
+) -> Result<NoContent, Custom<Value>> {
+    // ---
+    let found = cache_context
+        .clear_user_session(user.0.id, id)
+        .await
+        .map_err(server_error)?;
+
+    if found {
+        Ok(NoContent)
+    } else {
+        Err(Custom(
+            Status::NotFound,
+            json!({ "error": "Session not found" }),
+        ))
+    }
+}
+
+/// Change the caller's password and revoke every other session they hold.
+///
+/// The session used for this request stays valid. Wrong current passwords are
+/// throttled like failed logins, answering 429 with `Retry-After` once locked out.
+#[rocket::put("/me/password", format = "json", data = "<change>")]
+pub async fn change_my_password(
+    app_user_repo: &State<AppUserTableTraitPtr>,
+    cache_context: &State<CacheContextTraitPtr>,
+    user: GuardedAppUser,
+    token: SessionToken,
+    client: ClientInfo,
+    change: Json<PasswordChange>,
+) -> Result<Value, LoginRejection> {
+    // ---
+    // Users authenticated by an access token carry no password hash
+    let stored_user = app_user_repo.find(user.0.id).await.map_err(server_error)?;
+
+    match change_password(
+        app_user_repo.inner().clone(),
+        cache_context.inner().clone(),
+        &stored_user,
+        change.into_inner(),
+        client.ip.as_deref(),
+    )
+    .await
+    {
+        Ok(()) => {}
+        Err(LoginError::InvalidCredentials) => {
+            return Err(Custom(
+                Status::Unauthorized,
+                json!({ "error": "Current password is incorrect" }),
+            )
+            .into());
+        }
+        Err(LoginError::LockedOut { retry_after_secs }) => {
+            return Err(locked_out(retry_after_secs));
+        }
+        Err(LoginError::WeakPassword(err)) => {
+            return Err(Custom(Status::BadRequest, json!({ "error": err.to_string() })).into());
+        }
+        Err(err) => return Err(server_error(err).into()),
+    }
+
+    let revoked = cache_context
+        .clear_other_session_tokens(user.0.id, &token.0)
+        .await
+        .map_err(server_error)?;
+
+    tracing::info!(
+        "🔑 Password changed for user id {}, revoked {revoked} other session(s)",
+        user.0.id
+    );
+    Ok(json!({ "revoked": revoked }))
+}
+
+/// Admin only: set a user's password and revoke all of that user's sessions.
+///
+/// The caller must be allowed to manage the user (see `check_can_manage`), so
+/// `user:manage` cannot take over more privileged accounts.
+#[rocket::put("/users/<id>/password", format = "json", data = "<reset>")]
+pub async fn reset_user_password(
+    app_user_repo: &State<AppUserTableTraitPtr>,
+    role_repo: &State<RoleCodeTableTraitPtr>,
+    cache_context: &State<CacheContextTraitPtr>,
+    audit_log: &State<AuditLogTraitPtr>,
+    admin: RequirePermission<UserManage>,
+    id: i32,
+    reset: Json<PasswordReset>,
+) -> Result<Value, Custom<Value>> {
+    // ---
+    let Ok(user) = app_user_repo.find(id).await else {
+        return Err(Custom(
+            Status::NotFound,
+            json!({ "error": "User not found" }),
+        ));
+    };
+    let roles = app_user_repo
+        .find_roles_by_user(&user)
+        .await
+        .map_err(server_error)?;
+    check_can_manage(
+        app_user_repo.inner(),
+        role_repo.inner(),
+        &admin.0 .0,
+        &roles,
+    )
+    .await
+    .map_err(user_admin_error)?;
+    let before = UserSummary::from((user, roles));
+
+    set_password(app_user_repo.inner().clone(), id, &reset.new_password)
+        .await
+        .map_err(password_error)?;
+
+    let revoked = cache_context
+        .clear_all_session_tokens(id)
+        .await
+        .map_err(server_error)?;
+
+    // The hash is never audited; mark the change instead
+    let mut after = json!(before);
+    after["password_changed"] = json!(true);
+    let event = NewAuditEvent::updated(AuditEntity::User, id, Some(&before), &after);
+    record_audit_event(audit_log, event.by(admin.0 .0.id)).await;
+
+    tracing::info!(
+        "🔑 Password reset for user id {id} by admin id {}, revoked {revoked} session(s)",
+        admin.0 .0.id
+    );
+    Ok(json!({ "revoked": revoked }))
+}
+
 #[cfg(test)]
 mod tests {
     // ---
     use super::*;
+    use crate::domain::unlock_login;
     use crate::domain::{
         //
         AppUser as DomainAppUser,
-        AppUser,
         AppUserTableTrait,
-        AppUserWithRoleCodes,
+        AuditAction,
         CacheContextTrait,
         Credentials,
+        LockoutPolicy,
+        Permission,
         RoleCode,
     };
+    use crate::test_support::{MockAppUserRepo, MockAuditLog, MockCacheContext, MockRoleRepo};
 
-    use anyhow::Result;
-    use async_trait::async_trait;
     use chrono::Utc;
     use rocket::State;
-    use std::collections::HashMap;
     use std::sync::Arc;
 
-    struct MockAppUserRepo {
-        users: HashMap<String, DomainAppUser>,
+    /// User 1 is alice, who logs in with `password`.
+    fn alice(password: &str) -> Arc<MockAppUserRepo> {
+        Arc::new(MockAppUserRepo::default().with_login("alice", password, vec![RoleCode::Viewer]))
     }
 
-    impl MockAppUserRepo {
+    fn guarded_user(id: i32) -> GuardedAppUser {
         // ---
+        GuardedAppUser(DomainAppUser {
+            id,
+            username: "test@example.com".into(),
+            password: "test_password".into(),
+            created_at: Utc::now().naive_utc(),
+            author_id: None,
+            disabled_at: None,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_login_success() {
+        // ---
+        let mock_repo = alice("password");
+        let repo: Arc<dyn AppUserTableTrait + Send + Sync> = mock_repo.clone();
+        let stored_hash = mock_repo.user(1).password;
+
+        let mock_cache = Arc::new(MockCacheContext::default());
+        let cache: Arc<dyn CacheContextTrait> = mock_cache.clone();
 
-        fn with_user(username: &str, password: &str) -> Self {
-            // ---
-
-            let mut users = HashMap::new();
-
-            // Hash the password like the real system would
-            let hasher = crate::auth::create_password_hasher().unwrap();
-            let hashed_password = hasher.hash_password(password).unwrap();
-
-            users.insert(
-                username.to_string(),
-                DomainAppUser {
-                    id: 1,
-                    username: username.to_string(),
-                    password: hashed_password,
-                    created_at: Utc::now().naive_utc(),
-                },
-            );
-            Self { users }
+        let repo_state = State::from(&repo);
+        let cache_state = State::from(&cache);
+
+        let creds = Credentials {
+            username: "alice".into(),
+            password: "password".into(),
+        };
+
+        // Debug: Check what user is stored
+        let stored_user = repo.find_by_username("alice").await.unwrap();
+        println!("Stored user: {:?}", stored_user);
+        println!("Stored password: {}", stored_user.password);
+        println!("Input password: {}", creds.password);
+
+        let result = login(repo_state, cache_state, ClientInfo::default(), Json(creds)).await;
+
+        match result {
+            Ok(value) => {
+                assert!(value["token"].is_string()); // Just verify a token was returned
+                assert_eq!(
+                    mock_cache.session_tokens(1),
+                    [value["token"].as_str().unwrap()]
+                );
+            }
+            Err(e) => panic!("Expected success but got error: {:?}", e),
         }
+
+        // A hash made with the current settings is not rewritten
+        assert_eq!(mock_repo.user(1).password, stored_hash);
     }
 
-    #[async_trait]
-    impl AppUserTableTrait for MockAppUserRepo {
-        // --
-        async fn find_with_roles(&self) -> Result<Vec<AppUserWithRoleCodes>> {
-            // ---
-            unreachable!()
+    #[tokio::test]
+    async fn test_login_invalid_password() {
+        // ---
+        let repo: Arc<dyn AppUserTableTrait + Send + Sync> = alice("correct");
+        let cache: Arc<dyn CacheContextTrait> = Arc::new(MockCacheContext::default());
+        let repo_state = State::from(&repo);
+        let cache_state = State::from(&cache);
+
+        let creds = Credentials {
+            username: "alice".into(),
+            password: "wrong".into(),
+        };
+
+        let result = login(repo_state, cache_state, ClientInfo::default(), Json(creds)).await;
+
+        // Check if it's an Unauthorized error without using Status::Unauthorized in pattern
+        match result {
+            Err(LoginRejection::Denied(Custom(status, _))) => {
+                assert_eq!(status.code, 401); // Unauthorized status code
+            }
+            Err(other) => panic!("Expected unauthorized error but got {:?}", other),
+            Ok(_) => panic!("Expected unauthorized error but got success"),
         }
+    }
 
-        async fn find(&self, _id: i32) -> Result<AppUser> {
-            // ---
-            unreachable!()
+    #[tokio::test]
+    async fn test_login_upgrades_weak_password_hash() {
+        // ---
+        let weak_hasher = crate::auth::create_password_hasher_with(&crate::auth::Argon2Config {
+            memory_kib: 64,
+            iterations: 1,
+            parallelism: 1,
+        })
+        .unwrap();
+
+        let mock_repo = alice("password");
+        let weak_hash = weak_hasher.hash_password("password").unwrap();
+        mock_repo.edit_user(1, |user| user.password = weak_hash.clone());
+        let repo: Arc<dyn AppUserTableTrait + Send + Sync> = mock_repo.clone();
+        let cache: Arc<dyn CacheContextTrait> = Arc::new(MockCacheContext::default());
+
+        let result = login(
+            State::from(&repo),
+            State::from(&cache),
+            ClientInfo::default(),
+            Json(Credentials::new("alice", "password")),
+        )
+        .await;
+        assert!(result.is_ok(), "Expected login to succeed: {result:?}");
+
+        let stored_hash = mock_repo.user(1).password;
+        assert_ne!(stored_hash, weak_hash, "weak hash was not upgraded");
+
+        let hasher = crate::auth::create_password_hasher().unwrap();
+        assert!(hasher.verify_password(&stored_hash, "password").is_ok());
+        assert!(!hasher.needs_rehash(&stored_hash));
+    }
+
+    #[tokio::test]
+    async fn test_login_disabled_account() {
+        // ---
+        let mock_repo = alice("password");
+        mock_repo.edit_user(1, |user| user.disabled_at = Some(Utc::now().naive_utc()));
+        let repo: Arc<dyn AppUserTableTrait + Send + Sync> = mock_repo;
+        let cache: Arc<dyn CacheContextTrait> = Arc::new(MockCacheContext::default());
+
+        let creds = Credentials::new("alice", "password");
+        let result = login(
+            State::from(&repo),
+            State::from(&cache),
+            ClientInfo::default(),
+            Json(creds),
+        )
+        .await;
+
+        match result {
+            Err(LoginRejection::Denied(Custom(status, body))) => {
+                assert_eq!(status, Status::Forbidden);
+                assert_eq!(body["error"], "Account is disabled");
+            }
+            other => panic!("Expected a disabled account to be refused, got {:?}", other),
         }
+    }
 
-        async fn find_by_username(&self, username: &str) -> Result<DomainAppUser> {
-            // ---
-            self.users
-                .get(username)
-                .cloned()
-                .ok_or_else(|| anyhow::anyhow!("not found"))
+    #[tokio::test]
+    async fn test_login_lockout_after_repeated_failures() {
+        // ---
+        let repo: Arc<dyn AppUserTableTrait + Send + Sync> = alice("correct");
+        let cache: Arc<dyn CacheContextTrait> = Arc::new(MockCacheContext::default());
+        let policy = LockoutPolicy::from_env();
+
+        let attempt = |password: &str| {
+            let creds = Credentials::new("alice", password);
+            let client = ClientInfo {
+                ip: Some("10.0.0.1".into()),
+                user_agent: None,
+            };
+            login(State::from(&repo), State::from(&cache), client, Json(creds))
+        };
+
+        for _ in 1..policy.max_failures {
+            match attempt("wrong").await {
+                Err(LoginRejection::Denied(Custom(status, _))) => assert_eq!(status.code, 401),
+                other => panic!("Expected unauthorized error but got {:?}", other),
+            }
         }
 
-        async fn create(
-            // ---
-            &self,
-            _new_user: crate::domain::NewUser,
-            _roles: Vec<RoleCode>,
-        ) -> Result<DomainAppUser> {
-            unreachable!()
+        // The failure that reaches the threshold locks the account out
+        match attempt("wrong").await {
+            Err(LoginRejection::LockedOut(body, header)) => {
+                assert_eq!(header.name(), "Retry-After");
+                assert_eq!(header.value(), policy.base_lockout_secs.to_string());
+                assert_eq!(body["retry_after"], policy.base_lockout_secs);
+            }
+            other => panic!("Expected lockout but got {:?}", other),
         }
 
-        async fn find_roles_by_user(&self, _user: &DomainAppUser) -> Result<Vec<RoleCode>> {
-            // ---
-            unreachable!()
+        // Even the right password is refused while locked out
+        assert!(matches!(
+            attempt("correct").await,
+            Err(LoginRejection::LockedOut(..))
+        ));
+
+        unlock_login(&cache, "alice", Some("10.0.0.1"))
+            .await
+            .unwrap();
+        assert!(
+            attempt("correct").await.is_ok(),
+            "Expected login after unlock"
+        );
+    }
+
+    #[test]
+    fn test_lockout_policy_doubles_and_caps() {
+        // ---
+        let policy = LockoutPolicy {
+            max_failures: 3,
+            max_failures_per_ip: 10,
+            base_lockout_secs: 60,
+            max_lockout_secs: 300,
+            failure_window_secs: 900,
+        };
+
+        assert_eq!(policy.lockout_secs("user:alice", 2), None);
+        assert_eq!(policy.lockout_secs("user:alice", 3), Some(60));
+        assert_eq!(policy.lockout_secs("user:alice", 4), Some(120));
+        assert_eq!(policy.lockout_secs("user:alice", 5), Some(240));
+        assert_eq!(policy.lockout_secs("user:alice", 6), Some(300));
+        assert_eq!(policy.lockout_secs("user:alice", 500), Some(300));
+
+        assert_eq!(policy.lockout_secs("ip:10.0.0.1", 9), None);
+        assert_eq!(policy.lockout_secs("ip:10.0.0.1", 10), Some(60));
+    }
+
+    #[tokio::test]
+    async fn test_logout_revokes_presented_token() {
+        // ---
+        let mock_cache = Arc::new(MockCacheContext::default());
+        mock_cache.add_session("session-abc", 7);
+        mock_cache.add_session("session-other", 7);
+        let cache: Arc<dyn CacheContextTrait> = mock_cache.clone();
+        let cache_state = State::from(&cache);
+
+        let result = logout(
+            cache_state,
+            guarded_user(7),
+            SessionToken("session-abc".into()),
+        )
+        .await;
+
+        assert!(result.is_ok(), "Expected logout to succeed");
+        assert_eq!(mock_cache.session_tokens(7), ["session-other"]);
+    }
+
+    #[tokio::test]
+    async fn test_logout_cache_failure_returns_500() {
+        // ---
+        let cache: Arc<dyn CacheContextTrait> = Arc::new(MockCacheContext::failing());
+        let cache_state = State::from(&cache);
+
+        let result = logout(
+            cache_state,
+            guarded_user(7),
+            SessionToken("session-abc".into()),
+        )
+        .await;
+
+        match result {
+            Err(Custom(status, _)) => assert_eq!(status.code, 500),
+            Ok(_) => panic!("Expected server error but got success"),
         }
+    }
 
-        async fn delete_by_id(&self, _user_id: i32) -> Result<()> {
-            // ---
-            unreachable!()
+    #[tokio::test]
+    async fn test_logout_all_reports_revoked_count() {
+        // ---
+        let mock_cache = MockCacheContext::default();
+        for token in ["session-1", "session-2", "session-3"] {
+            mock_cache.add_session(token, 7);
         }
+        mock_cache.add_session("someone-else", 8);
+        let cache: Arc<dyn CacheContextTrait> = Arc::new(mock_cache);
+        let cache_state = State::from(&cache);
 
-        async fn delete_by_username(&self, _username: &str) -> Result<()> {
-            // ---
-            unreachable!()
+        match logout_all(cache_state, guarded_user(7)).await {
+            Ok(value) => assert_eq!(value["revoked"], 3),
+            Err(e) => panic!("Expected success but got error: {:?}", e),
         }
     }
 
-    struct MockCacheContext {
-        expected_token: String,
-        expected_user_id: i32,
-        fail: bool,
-    }
+    #[tokio::test]
+    async fn test_list_my_sessions_returns_records() {
+        // ---
+        let mock_cache = MockCacheContext::default();
+        mock_cache.add_session("session-1", 7);
+        mock_cache.add_session("session-2", 7);
+        let cache: Arc<dyn CacheContextTrait> = Arc::new(mock_cache);
+        let cache_state = State::from(&cache);
 
-    impl MockCacheContext {
-        fn new(expected_token: &str, expected_user_id: i32, fail: bool) -> Self {
-            Self {
-                expected_token: expected_token.to_string(),
-                expected_user_id,
-                fail,
+        match list_my_sessions(cache_state, guarded_user(7)).await {
+            Ok(value) => {
+                assert_eq!(value.as_array().map(Vec::len), Some(2));
+                assert_eq!(value[0]["id"], "s-1");
+                assert_eq!(value[0]["user_agent"], "cr8s-test");
+                assert!(value[0].get("token").is_none(), "token must not be exposed");
             }
+            Err(e) => panic!("Expected success but got error: {:?}", e),
         }
     }
 
-    #[async_trait]
-    impl CacheContextTrait for MockCacheContext {
+    #[tokio::test]
+    async fn test_delete_my_session() {
         // ---
-        async fn get_user_id_by_session_token(&self, _token: &str) -> Result<Option<i32>> {
-            // ---
-            unreachable!()
+        let mock_cache = MockCacheContext::default();
+        mock_cache.add_session("session-1", 7);
+        mock_cache.add_session("someone-else", 8);
+        let cache: Arc<dyn CacheContextTrait> = Arc::new(mock_cache);
+
+        let result = delete_my_session(State::from(&cache), guarded_user(7), "s-1").await;
+        assert!(result.is_ok(), "Expected known session to be deleted");
+
+        match delete_my_session(State::from(&cache), guarded_user(7), "s-2").await {
+            Err(Custom(status, _)) => assert_eq!(status.code, 404),
+            Ok(_) => panic!("Expected not found but got success"),
         }
+    }
 
-        async fn set_user_session_token(&self, user_id: i32, token: &str) -> Result<()> {
-            // ---
-            assert_eq!(user_id, self.expected_user_id, "user_id mismatch");
-            if !self.expected_token.is_empty() {
-                assert_eq!(token, self.expected_token, "token mismatch");
-            }
-            if self.fail {
-                anyhow::bail!("simulated Redis failure");
-            }
-            Ok(())
+    #[tokio::test]
+    async fn test_change_my_password_revokes_other_sessions() {
+        // ---
+        let mock = alice("old-password");
+        let repo: Arc<dyn AppUserTableTrait + Send + Sync> = mock.clone();
+        let mock_cache = Arc::new(MockCacheContext::default());
+        for token in ["session-abc", "laptop", "phone"] {
+            mock_cache.add_session(token, 1);
         }
+        let cache: Arc<dyn CacheContextTrait> = mock_cache.clone();
+
+        let user = GuardedAppUser(repo.find_by_username("alice").await.unwrap());
+        let change = PasswordChange {
+            current_password: "old-password".into(),
+            new_password: "new-password".into(),
+        };
 
-        async fn clear_session_token(&self, _token: &str) -> Result<bool> {
-            // ---
-            unreachable!()
+        let result = change_my_password(
+            State::from(&repo),
+            State::from(&cache),
+            user,
+            SessionToken("session-abc".into()),
+            ClientInfo::default(),
+            Json(change),
+        )
+        .await;
+
+        match result {
+            Ok(value) => assert_eq!(value["revoked"], 2),
+            Err(e) => panic!("Expected success but got error: {:?}", e),
         }
+
+        assert_eq!(mock_cache.session_tokens(1), ["session-abc"]);
+        let hasher = crate::auth::create_password_hasher().unwrap();
+        assert!(hasher
+            .verify_password(&mock.user(1).password, "new-password")
+            .is_ok());
     }
 
     #[tokio::test]
-    async fn test_login_success() {
+    async fn test_change_my_password_wrong_current_password() {
         // ---
-        let repo: Arc<dyn AppUserTableTrait + Send + Sync> =
-            Arc::new(MockAppUserRepo::with_user("alice", "password"));
+        let mock = alice("old-password");
+        let repo: Arc<dyn AppUserTableTrait + Send + Sync> = mock.clone();
+        let stored_hash = mock.user(1).password;
+        let mock_cache = Arc::new(MockCacheContext::default());
+        let cache: Arc<dyn CacheContextTrait> = mock_cache.clone();
+        let policy = LockoutPolicy::from_env();
+
+        let attempt = |current_password: &str| {
+            let change = PasswordChange {
+                current_password: current_password.into(),
+                new_password: "new-password".into(),
+            };
+            let client = ClientInfo {
+                ip: Some("10.0.0.1".into()),
+                user_agent: None,
+            };
+            change_my_password(
+                State::from(&repo),
+                State::from(&cache),
+                GuardedAppUser(mock.user(1)),
+                SessionToken("session-abc".into()),
+                client,
+                Json(change),
+            )
+        };
 
-        // Accept any tokena
-        let cache: Arc<dyn CacheContextTrait> = Arc::new(MockCacheContext::new("", 1, false));
+        match attempt("wrong").await {
+            Err(LoginRejection::Denied(Custom(status, _))) => assert_eq!(status.code, 401),
+            other => panic!("Expected unauthorized error but got {:?}", other),
+        }
+        assert_eq!(mock.user(1).password, stored_hash);
+        assert_eq!(mock_cache.login_failures.lock().unwrap()["user:alice"], 1);
+        assert_eq!(mock_cache.login_failures.lock().unwrap()["ip:10.0.0.1"], 1);
 
-        let repo_state = State::from(&repo);
-        let cache_state = State::from(&cache);
+        // Guessing through a session is throttled like guessing at /login
+        for _ in 2..policy.max_failures {
+            attempt("wrong").await.unwrap_err();
+        }
+        assert!(matches!(
+            attempt("wrong").await,
+            Err(LoginRejection::LockedOut(..))
+        ));
+        assert!(matches!(
+            attempt("old-password").await,
+            Err(LoginRejection::LockedOut(..))
+        ));
+        assert_eq!(mock.user(1).password, stored_hash);
+    }
 
-        let creds = Credentials {
-            username: "alice".into(),
-            password: "password".into(),
+    #[tokio::test]
+    async fn test_reset_user_password() {
+        // ---
+        let mock = alice("old-password");
+        let admin = mock.add_user("admin", "admin-hash", vec![RoleCode::Admin]);
+        let support = mock.add_user(
+            "support",
+            "support-hash",
+            vec![RoleCode::Custom("Support".into())],
+        );
+        let repo: Arc<dyn AppUserTableTrait + Send + Sync> = mock.clone();
+        let roles: RoleCodeTableTraitPtr = Arc::new(MockRoleRepo::default().with_role(
+            "Support",
+            vec![Permission::UserManage, Permission::CrateRead],
+        ));
+        let stored_hash = mock.user(1).password;
+        let mock_cache = MockCacheContext::default();
+        for token in ["session-1", "session-2", "session-3"] {
+            mock_cache.add_session(token, 1);
+        }
+        let cache: Arc<dyn CacheContextTrait> = Arc::new(mock_cache);
+        let mock_log = Arc::new(MockAuditLog::default());
+        let audit: AuditLogTraitPtr = mock_log.clone();
+
+        let reset = PasswordReset {
+            new_password: "reset-password".into(),
         };
+        let result = reset_user_password(
+            State::from(&repo),
+            State::from(&roles),
+            State::from(&cache),
+            State::from(&audit),
+            RequirePermission::new(GuardedAppUser(admin.clone())),
+            1,
+            Json(reset),
+        )
+        .await;
 
-        // Debug: Check what user is stored
-        let stored_user = repo.find_by_username("alice").await.unwrap();
-        println!("Stored user: {:?}", stored_user);
-        println!("Stored password: {}", stored_user.password);
-        println!("Input password: {}", creds.password);
+        match result {
+            Ok(value) => assert_eq!(value["revoked"], 3),
+            Err(e) => panic!("Expected success but got error: {:?}", e),
+        }
+        assert_ne!(mock.user(1).password, stored_hash);
 
-        let result = login(repo_state, cache_state, Json(creds)).await;
+        let reset = PasswordReset {
+            new_password: "reset-password".into(),
+        };
+        let result = reset_user_password(
+            State::from(&repo),
+            State::from(&roles),
+            State::from(&cache),
+            State::from(&audit),
+            RequirePermission::new(GuardedAppUser(admin.clone())),
+            42,
+            Json(reset),
+        )
+        .await;
 
         match result {
-            Ok(value) => {
-                assert!(value["token"].is_string()); // Just verify a token was returned
-                assert!(!value["token"].as_str().unwrap().is_empty());
+            Err(Custom(status, _)) => assert_eq!(status.code, 404),
+            Ok(_) => panic!("Expected not found but got success"),
+        }
+
+        // `user:manage` alone cannot take over Admins or users whose roles grant more
+        for target in [1, admin.id] {
+            let reset = PasswordReset {
+                new_password: "takeover-password".into(),
+            };
+            let result = reset_user_password(
+                State::from(&repo),
+                State::from(&roles),
+                State::from(&cache),
+                State::from(&audit),
+                RequirePermission::new(GuardedAppUser(support.clone())),
+                target,
+                Json(reset),
+            )
+            .await;
+
+            match result {
+                Err(Custom(status, _)) => assert_eq!(status.code, 403),
+                Ok(_) => panic!("Expected forbidden but got success"),
             }
-            Err(e) => panic!("Expected success but got error: {:?}", e),
         }
+        assert_eq!(mock.user(admin.id).password, "admin-hash");
+
+        // Only the reset that went through is audited, without the hash
+        let events = mock_log.events.lock().unwrap();
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event.action, AuditAction::Update);
+        assert_eq!(event.entity_type, AuditEntity::User);
+        assert_eq!(event.entity_id, 1);
+        assert_eq!(event.actor_user_id, Some(admin.id));
+        let after = event.after.as_ref().unwrap();
+        assert_eq!(after["password_changed"], true);
+        assert!(after.get("password").is_none());
+        assert!(event
+            .before
+            .as_ref()
+            .unwrap()
+            .get("password_changed")
+            .is_none());
     }
 
     #[tokio::test]
-    async fn test_login_invalid_password() {
+    async fn test_login_in_jwt_mode_returns_access_and_refresh_tokens() {
         // ---
-        let repo: Arc<dyn AppUserTableTrait + Send + Sync> =
-            Arc::new(MockAppUserRepo::with_user("alice", "correct"));
-        let cache: Arc<dyn CacheContextTrait> = Arc::new(MockCacheContext::new("x", 1, false));
-        let repo_state = State::from(&repo);
-        let cache_state = State::from(&cache);
+        let repo: Arc<dyn AppUserTableTrait + Send + Sync> = alice("password");
+        let cache: Arc<dyn CacheContextTrait> = Arc::new(MockCacheContext::jwt());
+
+        let creds = Credentials::new("alice", "password");
+        let value = login(
+            State::from(&repo),
+            State::from(&cache),
+            ClientInfo::default(),
+            Json(creds),
+        )
+        .await
+        .expect("login should succeed");
+
+        assert_eq!(value["token"], "access-1-s-1");
+        assert_eq!(value["token_type"], "Bearer");
+        assert_eq!(value["expires_in"], 300);
+        let refresh_token = value["refresh_token"].as_str().unwrap();
+        assert_eq!(
+            cache
+                .get_user_id_by_session_token(refresh_token)
+                .await
+                .unwrap(),
+            Some(1)
+        );
+    }
 
-        let creds = Credentials {
-            username: "alice".into(),
-            password: "wrong".into(),
+    #[tokio::test]
+    async fn test_refresh_access_token_rotates_refresh_token() {
+        // ---
+        let repo: Arc<dyn AppUserTableTrait + Send + Sync> = alice("password");
+        let mock = MockCacheContext::jwt();
+        mock.add_session("refresh-1", 1);
+        let cache: Arc<dyn CacheContextTrait> = Arc::new(mock);
+
+        let refresh = |token: &str| {
+            refresh_access_token(
+                State::from(&repo),
+                State::from(&cache),
+                ClientInfo::default(),
+                Json(RefreshRequest {
+                    refresh_token: token.into(),
+                }),
+            )
         };
 
-        let result = login(repo_state, cache_state, Json(creds)).await;
+        let value = refresh("refresh-1").await.expect("refresh should succeed");
+        assert_eq!(value["token"], "access-1-s-2");
+        let rotated = value["refresh_token"].as_str().unwrap().to_string();
+        assert_ne!(rotated, "refresh-1");
+
+        // The old refresh token is spent
+        match refresh("refresh-1").await {
+            Err(Custom(status, _)) => assert_eq!(status.code, 401),
+            Ok(_) => panic!("Expected reused refresh token to be rejected"),
+        }
+
+        assert!(refresh(&rotated).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_access_token_outside_jwt_mode() {
+        // ---
+        let repo: Arc<dyn AppUserTableTrait + Send + Sync> = alice("password");
+        let cache: Arc<dyn CacheContextTrait> = Arc::new(MockCacheContext::default());
+
+        let result = refresh_access_token(
+            State::from(&repo),
+            State::from(&cache),
+            ClientInfo::default(),
+            Json(RefreshRequest {
+                refresh_token: "session-abc".into(),
+            }),
+        )
+        .await;
 
-        // Check if it's an Unauthorized error without using Status::Unauthorized in pattern
         match result {
-            Err(Custom(status, _)) => {
-                assert_eq!(status.code, 401); // Unauthorized status code
-            }
-            Ok(_) => panic!("Expected unauthorized error but got success"),
+            Err(Custom(status, _)) => assert_eq!(status.code, 404),
+            Ok(_) => panic!("Expected not found outside JWT auth mode"),
         }
     }
 
@@ -251,6 +1009,8 @@ mod tests {
             username: "test@example.com".into(),
             password: "test_password".into(),
             created_at: Utc::now().naive_utc(),
+            author_id: None,
+            disabled_at: None,
         });
 
         let result = me(user);
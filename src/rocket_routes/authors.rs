@@ -2,31 +2,83 @@
 //! backed by the renamed `Author` domain model and traits.  See tracking issue
 //! (#23) in cr8s and (#16) cr8s-fe to rename this endpoint.
 
-use super::{EditorUser, GuardedAppUser};
+use super::{AuthorDelete, AuthorRead, AuthorWrite, GuardedAppUser, RequirePermission, UserManage};
 use rocket::http::Status;
 use rocket::response::status::{Custom, NoContent};
 use rocket::serde::json::{json, Json, Value};
 use rocket::{delete, get, post, put, State};
 
-use super::server_error;
+use super::{bad_request, server_error};
 use crate::domain::{
     //
+    confirm_author_claim,
+    link_author,
+    parse_since,
+    record_audit_event,
+    request_author_claim,
+    AppUserTableTraitPtr,
+    AuditEntity,
+    AuditLogTraitPtr,
     Author,
+    AuthorClaimConfirm,
+    AuthorLink,
+    AuthorLinkError,
+    AuthorQuery,
+    AuthorSort,
     AuthorTableTraitPtr,
+    CacheContextTraitPtr,
+    Cursor,
+    MailerTraitPtr,
+    NewAuditEvent,
     NewAuthor,
+    UserSummary,
 };
 
-#[get("/rustaceans")]
+/// One page of authors, oldest first unless sorted otherwise, e.g.
+/// `/rustaceans?name=jo&sort=name&limit=20`.
+///
+/// Sorts by `name` or `created_at` (`order` is `asc` or `desc`) and filters by name
+/// prefix (`name`) and creation date (`since`). Pass the returned `next_cursor` back
+/// as `cursor`, with the same `sort`, for the next page.
+#[allow(clippy::too_many_arguments)]
+#[get("/rustaceans?<limit>&<cursor>&<sort>&<order>&<name>&<since>")]
 pub async fn get_rustaceans(
     repo: &State<AuthorTableTraitPtr>,
-    _user: GuardedAppUser,
+    _user: RequirePermission<AuthorRead>,
+    limit: Option<i64>,
+    cursor: Option<&str>,
+    sort: Option<&str>,
+    order: Option<&str>,
+    name: Option<&str>,
+    since: Option<&str>,
 ) -> Result<Value, Custom<Value>> {
     // ---
+    let sort: AuthorSort = sort
+        .map(str::parse)
+        .transpose()
+        .map_err(bad_request)?
+        .unwrap_or_default();
+
+    let query = AuthorQuery {
+        name_prefix: name.map(str::to_string),
+        created_since: since.map(parse_since).transpose().map_err(bad_request)?,
+        sort,
+        order: order
+            .map(str::parse)
+            .transpose()
+            .map_err(bad_request)?
+            .unwrap_or_default(),
+        cursor: cursor
+            .map(|cursor| Cursor::decode(cursor, sort))
+            .transpose()
+            .map_err(bad_request)?,
+        limit,
+    };
 
     repo.inner()
-        .find_multiple(100)
+        .find_page(&query)
         .await
-        .map(|authors| json!(authors))
+        .map(|page| json!(page))
         .map_err(server_error)
 }
 
@@ -34,7 +86,7 @@ pub async fn get_rustaceans(
 pub async fn view_rustacean(
     repo: &State<AuthorTableTraitPtr>,
     id: i32,
-    _user: GuardedAppUser,
+    _user: RequirePermission<AuthorRead>,
 ) -> Result<Value, Custom<Value>> {
     // ---
 
@@ -48,53 +100,212 @@ pub async fn view_rustacean(
 #[post("/rustaceans", format = "json", data = "<new_author>")]
 pub async fn create_rustacean(
     repo: &State<AuthorTableTraitPtr>,
+    audit_log: &State<AuditLogTraitPtr>,
     new_author: Json<NewAuthor>,
-    _user: EditorUser,
+    user: RequirePermission<AuthorWrite>,
 ) -> Result<Custom<Value>, Custom<Value>> {
     // ---
 
-    repo.inner()
+    let author = repo
+        .inner()
         .create(new_author.into_inner())
         .await
-        .map(|author| Custom(Status::Created, json!(author)))
-        .map_err(server_error)
+        .map_err(server_error)?;
+
+    let event = NewAuditEvent::created(AuditEntity::Author, author.id, &author);
+    record_audit_event(audit_log, event.by(user.0 .0.id)).await;
+
+    Ok(Custom(Status::Created, json!(author)))
 }
 
 #[put("/rustaceans/<id>", format = "json", data = "<author>")]
 pub async fn update_rustacean(
     repo: &State<AuthorTableTraitPtr>,
+    audit_log: &State<AuditLogTraitPtr>,
     id: i32,
     author: Json<Author>,
-    _user: EditorUser,
+    user: RequirePermission<AuthorWrite>,
 ) -> Result<Value, Custom<Value>> {
     // ---
 
-    repo.inner()
+    let existing = repo.find(id).await.ok();
+    let updated = repo
+        .inner()
         .update(id, author.into_inner())
         .await
-        .map(|author| json!(author))
-        .map_err(server_error)
+        .map_err(server_error)?;
+
+    let event = NewAuditEvent::updated(AuditEntity::Author, id, existing.as_ref(), &updated);
+    record_audit_event(audit_log, event.by(user.0 .0.id)).await;
+
+    Ok(json!(updated))
 }
 
 #[delete("/rustaceans/<id>")]
 pub async fn delete_rustacean(
     repo: &State<AuthorTableTraitPtr>,
+    audit_log: &State<AuditLogTraitPtr>,
     id: i32,
-    _user: EditorUser,
+    user: RequirePermission<AuthorDelete>,
 ) -> Result<NoContent, Custom<Value>> {
     // ---
 
-    repo.inner()
-        .delete(id)
+    let existing = repo.find(id).await.ok();
+    repo.inner().delete(id).await.map_err(server_error)?;
+
+    let event = NewAuditEvent::deleted(AuditEntity::Author, id, existing.as_ref());
+    record_audit_event(audit_log, event.by(user.0 .0.id)).await;
+
+    Ok(NoContent)
+}
+
+fn author_link_error(err: AuthorLinkError) -> Custom<Value> {
+    // ---
+    match err {
+        AuthorLinkError::NotFound(_) => {
+            Custom(Status::NotFound, json!({ "error": "Author not found" }))
+        }
+        AuthorLinkError::AlreadyLinked(_) => {
+            Custom(Status::Conflict, json!({ "error": err.to_string() }))
+        }
+        AuthorLinkError::InvalidClaim => {
+            Custom(Status::BadRequest, json!({ "error": err.to_string() }))
+        }
+        AuthorLinkError::Internal(err) => server_error(err),
+    }
+}
+
+/// Claim an author profile, or unlink with `{"author_id": null}`.
+///
+/// A claim answers 202 and emails a confirmation token to the author's address; the
+/// link is only made once the token is sent to `POST /me/author/confirm`.
+#[put("/me/author", format = "json", data = "<link>")]
+pub async fn link_my_author(
+    app_user_repo: &State<AppUserTableTraitPtr>,
+    author_repo: &State<AuthorTableTraitPtr>,
+    cache_context: &State<CacheContextTraitPtr>,
+    mailer: &State<MailerTraitPtr>,
+    user: GuardedAppUser,
+    link: Json<AuthorLink>,
+) -> Result<Custom<Value>, Custom<Value>> {
+    // ---
+    if let Some(author_id) = link.author_id {
+        request_author_claim(
+            app_user_repo.inner(),
+            author_repo.inner(),
+            cache_context.inner(),
+            mailer.inner(),
+            &user.0,
+            author_id,
+        )
         .await
-        .map(|_| NoContent)
-        .map_err(server_error)
+        .map_err(author_link_error)?;
+
+        tracing::info!("✍️ User id {} asked to claim author {author_id}", user.0.id);
+        return Ok(Custom(
+            Status::Accepted,
+            json!({ "status": "A confirmation email has been sent to the author's address" }),
+        ));
+    }
+
+    let user = link_author(app_user_repo.inner(), author_repo.inner(), &user.0, None)
+        .await
+        .map_err(author_link_error)?;
+
+    tracing::info!("✍️ User id {} unlinked from its author", user.id);
+    Ok(Custom(Status::Ok, json!({ "author_id": user.author_id })))
+}
+
+/// Complete an author claim with the token emailed by `PUT /me/author`.
+#[post("/me/author/confirm", format = "json", data = "<confirm>")]
+pub async fn confirm_my_author(
+    app_user_repo: &State<AppUserTableTraitPtr>,
+    author_repo: &State<AuthorTableTraitPtr>,
+    cache_context: &State<CacheContextTraitPtr>,
+    user: GuardedAppUser,
+    confirm: Json<AuthorClaimConfirm>,
+) -> Result<Value, Custom<Value>> {
+    // ---
+    let user = confirm_author_claim(
+        app_user_repo.inner(),
+        author_repo.inner(),
+        cache_context.inner(),
+        &user.0,
+        &confirm.token,
+    )
+    .await
+    .map_err(author_link_error)?;
+
+    tracing::info!(
+        "✍️ User id {} linked to author {:?}",
+        user.id,
+        user.author_id
+    );
+    Ok(json!({ "author_id": user.author_id }))
+}
+
+/// Admin only: link any user to any author profile not linked to someone else.
+#[put("/users/<id>/author", format = "json", data = "<link>")]
+pub async fn link_user_author(
+    app_user_repo: &State<AppUserTableTraitPtr>,
+    author_repo: &State<AuthorTableTraitPtr>,
+    audit_log: &State<AuditLogTraitPtr>,
+    admin: RequirePermission<UserManage>,
+    id: i32,
+    link: Json<AuthorLink>,
+) -> Result<Value, Custom<Value>> {
+    // ---
+    let Ok(user) = app_user_repo.find(id).await else {
+        return Err(Custom(
+            Status::NotFound,
+            json!({ "error": "User not found" }),
+        ));
+    };
+    let roles = app_user_repo
+        .find_roles_by_user(&user)
+        .await
+        .map_err(server_error)?;
+    let before = UserSummary::from((user.clone(), roles));
+
+    let user = link_author(
+        app_user_repo.inner(),
+        author_repo.inner(),
+        &user,
+        link.author_id,
+    )
+    .await
+    .map_err(author_link_error)?;
+
+    let after = UserSummary {
+        author_id: user.author_id,
+        ..before.clone()
+    };
+    let event = NewAuditEvent::updated(AuditEntity::User, id, Some(&before), &after);
+    record_audit_event(audit_log, event.by(admin.0 .0.id)).await;
+
+    tracing::info!(
+        "✍️ User id {id} linked to author {:?} by admin id {}",
+        user.author_id,
+        admin.0 .0.id
+    );
+    Ok(json!({ "author_id": user.author_id }))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::{Author, AuthorTableTrait, NewAuthor};
+    use crate::domain::{
+        //
+        effective_page_limit,
+        into_page,
+        AuditAction,
+        Author,
+        AuthorTableTrait,
+        NewAuthor,
+        Page,
+        RoleCode,
+    };
+    use crate::test_support::{MockAppUserRepo, MockAuditLog, MockCacheContext, MockMailer};
     use anyhow::Result;
     use async_trait::async_trait;
     use chrono::Utc;
@@ -132,6 +343,24 @@ mod tests {
             Ok(self.authors.values().cloned().collect())
         }
 
+        /// Filters by name prefix only and orders by id.
+        async fn find_page(&self, query: &AuthorQuery) -> Result<Page<Author>> {
+            let prefix = query.name_prefix.clone().unwrap_or_default().to_lowercase();
+            let mut authors: Vec<_> = self
+                .authors
+                .values()
+                .filter(|author| author.name.to_lowercase().starts_with(&prefix))
+                .cloned()
+                .collect();
+            authors.sort_by_key(|author| author.id);
+
+            let total = authors.len() as i64;
+            let limit = effective_page_limit(query.limit);
+            Ok(into_page(authors, limit, total, |author| {
+                query.sort.cursor_for(author)
+            }))
+        }
+
         async fn create(&self, new: NewAuthor) -> Result<Author> {
             Ok(Author {
                 id: 42,
@@ -162,22 +391,81 @@ mod tests {
         let repo: Arc<dyn AuthorTableTrait + Send + Sync> =
             Arc::new(MockAuthorRepo::new().with_author(author.clone()));
         let repo_state = State::from(&repo);
-        let user = GuardedAppUser(crate::domain::AppUser {
+        let user = RequirePermission::new(GuardedAppUser(crate::domain::AppUser {
             id: 123,
             username: "tester".into(),
             password: "password".into(),
             created_at: Utc::now().naive_utc(),
-        });
+            author_id: None,
+            disabled_at: None,
+        }));
 
-        let result = get_rustaceans(repo_state, user).await;
+        let result = get_rustaceans(repo_state, user, None, None, None, None, None, None).await;
         match result {
             Ok(value) => {
-                assert_eq!(value[0]["name"], "Alice");
+                assert_eq!(value["items"][0]["name"], "Alice");
+                assert_eq!(value["total"], 1);
             }
             Err(e) => panic!("Expected success but got error: {:?}", e),
         }
     }
 
+    #[tokio::test]
+    async fn test_get_authors_pages_by_name_prefix() {
+        // ---
+        let author = |id: i32, name: &str| Author {
+            id,
+            name: name.into(),
+            email: format!("author{id}@example.com"),
+            created_at: Utc::now().naive_utc(),
+        };
+        let repo: Arc<dyn AuthorTableTrait + Send + Sync> = Arc::new(
+            MockAuthorRepo::new()
+                .with_author(author(1, "Jo Smith"))
+                .with_author(author(2, "Joan Doe"))
+                .with_author(author(3, "Ferris")),
+        );
+        let user = || {
+            RequirePermission::new(GuardedAppUser(crate::domain::AppUser {
+                id: 123,
+                username: "tester".into(),
+                password: "password".into(),
+                created_at: Utc::now().naive_utc(),
+                author_id: None,
+                disabled_at: None,
+            }))
+        };
+
+        let first = get_rustaceans(
+            State::from(&repo),
+            user(),
+            Some(1),
+            None,
+            Some("name"),
+            None,
+            Some("jo"),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(first["items"][0]["name"], "Jo Smith");
+        assert_eq!(first["total"], 2);
+        assert!(first["next_cursor"].is_string());
+
+        let invalid = get_rustaceans(
+            State::from(&repo),
+            user(),
+            None,
+            None,
+            None,
+            Some("sideways"),
+            None,
+            Some("last tuesday"),
+        )
+        .await;
+        assert_eq!(invalid.unwrap_err().0, Status::BadRequest);
+    }
+
     #[tokio::test]
     async fn test_view_author_success() {
         let author = Author {
@@ -190,12 +478,14 @@ mod tests {
         let repo: Arc<dyn AuthorTableTrait + Send + Sync> =
             Arc::new(MockAuthorRepo::new().with_author(author.clone()));
         let repo_state = State::from(&repo);
-        let user = GuardedAppUser(crate::domain::AppUser {
+        let user = RequirePermission::new(GuardedAppUser(crate::domain::AppUser {
             id: 123,
             username: "tester".into(),
             password: "password".into(),
             created_at: Utc::now().naive_utc(),
-        });
+            author_id: None,
+            disabled_at: None,
+        }));
 
         let result = view_rustacean(repo_state, 5, user).await;
         match result {
@@ -210,11 +500,13 @@ mod tests {
     async fn test_create_author_success() {
         let repo: Arc<dyn AuthorTableTrait + Send + Sync> = Arc::new(MockAuthorRepo::new());
         let repo_state = State::from(&repo);
-        let user = EditorUser(GuardedAppUser(crate::domain::AppUser {
+        let user = RequirePermission::new(GuardedAppUser(crate::domain::AppUser {
             id: 123,
             username: "tester".into(),
             password: "password".into(),
             created_at: Utc::now().naive_utc(),
+            author_id: None,
+            disabled_at: None,
         }));
 
         let new_author = Json(NewAuthor {
@@ -222,7 +514,9 @@ mod tests {
             email: "charlie@example.com".into(),
         });
 
-        let result = create_rustacean(repo_state, new_author, user).await;
+        let audit: AuditLogTraitPtr = Arc::new(MockAuditLog::default());
+
+        let result = create_rustacean(repo_state, State::from(&audit), new_author, user).await;
         match result {
             Ok(custom_response) => {
                 let value = custom_response.1; // Extract the JSON value from Custom<Value>
@@ -244,11 +538,13 @@ mod tests {
         let repo: Arc<dyn AuthorTableTrait + Send + Sync> =
             Arc::new(MockAuthorRepo::new().with_author(existing));
         let repo_state = State::from(&repo);
-        let user = EditorUser(GuardedAppUser(crate::domain::AppUser {
+        let user = RequirePermission::new(GuardedAppUser(crate::domain::AppUser {
             id: 1,
             username: "admin".into(),
             password: "password".into(),
             created_at: Utc::now().naive_utc(),
+            author_id: None,
+            disabled_at: None,
         }));
 
         let updated = Json(Author {
@@ -258,7 +554,10 @@ mod tests {
             created_at: Utc::now().naive_utc(),
         });
 
-        let result = update_rustacean(repo_state, 10, updated, user).await;
+        let mock_audit = Arc::new(MockAuditLog::default());
+        let audit: AuditLogTraitPtr = mock_audit.clone();
+
+        let result = update_rustacean(repo_state, State::from(&audit), 10, updated, user).await;
 
         match result {
             Ok(value) => {
@@ -267,6 +566,13 @@ mod tests {
             }
             Err(e) => panic!("Expected success but got error: {:?}", e),
         }
+
+        let events = mock_audit.events.lock().unwrap().clone();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].action, AuditAction::Update);
+        assert_eq!(events[0].actor_user_id, Some(1));
+        assert_eq!(events[0].before.as_ref().unwrap()["name"], "Old Name");
+        assert_eq!(events[0].after.as_ref().unwrap()["name"], "Updated Name");
     }
 
     #[tokio::test]
@@ -281,14 +587,19 @@ mod tests {
         let repo: Arc<dyn AuthorTableTrait + Send + Sync> =
             Arc::new(MockAuthorRepo::new().with_author(author));
         let repo_state = State::from(&repo);
-        let user = EditorUser(GuardedAppUser(crate::domain::AppUser {
+        let user = RequirePermission::new(GuardedAppUser(crate::domain::AppUser {
             id: 1,
             username: "admin".into(),
             password: "password".into(),
             created_at: Utc::now().naive_utc(),
+            author_id: None,
+            disabled_at: None,
         }));
 
-        let result = delete_rustacean(repo_state, 7, user).await;
+        let mock_audit = Arc::new(MockAuditLog::default());
+        let audit: AuditLogTraitPtr = mock_audit.clone();
+
+        let result = delete_rustacean(repo_state, State::from(&audit), 7, user).await;
         match result {
             Ok(_no_content) => {
                 // NoContent doesn't have indexable content, so we just check that it succeeded
@@ -296,6 +607,11 @@ mod tests {
             }
             Err(e) => panic!("Expected success but got error: {:?}", e),
         }
+
+        let events = mock_audit.events.lock().unwrap().clone();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].action, AuditAction::Delete);
+        assert_eq!(events[0].before.as_ref().unwrap()["name"], "ToDelete");
     }
 
     #[tokio::test]
@@ -303,4 +619,169 @@ mod tests {
     async fn test_view_author_not_found() {
         todo!("Handle error case where view_rustacean is called with unknown ID");
     }
+
+    #[tokio::test]
+    async fn test_link_author() {
+        // ---
+        let author = |id, email: &str| Author {
+            id,
+            name: email.into(),
+            email: email.into(),
+            created_at: Utc::now().naive_utc(),
+        };
+        let authors: AuthorTableTraitPtr = Arc::new(
+            MockAuthorRepo::new()
+                .with_author(author(1, "Alice@Example.com"))
+                .with_author(author(2, "bob@example.com"))
+                .with_author(author(3, "carol@example.com")),
+        );
+        let mock_users = Arc::new(
+            MockAppUserRepo::default()
+                .with_user("alice@example.com", vec![RoleCode::Editor])
+                .with_user("bob@example.com", vec![RoleCode::Editor]),
+        );
+        mock_users.edit_user(2, |bob| bob.author_id = Some(2));
+        let users: AppUserTableTraitPtr = mock_users.clone();
+        let alice = || GuardedAppUser(mock_users.user(1));
+        let link = |author_id| Json(AuthorLink { author_id });
+
+        let mock_cache = Arc::new(MockCacheContext::default());
+        let cache: CacheContextTraitPtr = mock_cache.clone();
+        let mock_mailer = Arc::new(MockMailer::default());
+        let mailer: MailerTraitPtr = mock_mailer.clone();
+        let claim = |user, author_id| {
+            link_my_author(
+                State::from(&users),
+                State::from(&authors),
+                State::from(&cache),
+                State::from(&mailer),
+                user,
+                link(author_id),
+            )
+        };
+        let confirm = |user, token: &str| {
+            confirm_my_author(
+                State::from(&users),
+                State::from(&authors),
+                State::from(&cache),
+                user,
+                Json(AuthorClaimConfirm {
+                    token: token.into(),
+                }),
+            )
+        };
+        let last_token = || {
+            let sent = mock_mailer.sent.lock().unwrap();
+            let (to, template, context) = sent.last().unwrap();
+            assert_eq!(to, "Alice@Example.com");
+            assert_eq!(template, "email/author_claim.html");
+            context["token"].as_str().unwrap().to_string()
+        };
+
+        // Claims of missing or already linked authors fail before any email is sent
+        assert_eq!(
+            claim(alice(), Some(9)).await.unwrap_err().0,
+            Status::NotFound
+        );
+        assert_eq!(
+            claim(alice(), Some(2)).await.unwrap_err().0,
+            Status::Conflict
+        );
+        assert!(mock_mailer.sent.lock().unwrap().is_empty());
+
+        // A claim only emails a token to the author; nothing is linked yet
+        let result = claim(alice(), Some(1)).await.unwrap();
+        assert_eq!(result.0, Status::Accepted);
+        assert_eq!(mock_users.user(1).author_id, None);
+
+        // The token only works for the user who asked for it
+        let bob = GuardedAppUser(mock_users.user(2));
+        let result = confirm(bob, &last_token()).await;
+        assert_eq!(result.unwrap_err().0, Status::BadRequest);
+        assert_eq!(mock_users.user(2).author_id, Some(2));
+
+        claim(alice(), Some(1)).await.unwrap();
+        let token = last_token();
+        let result = confirm(alice(), &token).await;
+        assert_eq!(result.unwrap(), json!({ "author_id": 1 }));
+        assert_eq!(mock_users.user(1).author_id, Some(1));
+
+        // Tokens are single use
+        let result = confirm(alice(), &token).await;
+        assert_eq!(result.unwrap_err().0, Status::BadRequest);
+
+        // Unlinking needs no confirmation
+        let result = claim(alice(), None).await.unwrap();
+        assert_eq!(result.1, json!({ "author_id": null }));
+        assert_eq!(mock_users.user(1).author_id, None);
+
+        // Admins link any author, but not one already linked to someone else
+        let admin = || RequirePermission::new(alice());
+        let mock_log = Arc::new(MockAuditLog::default());
+        let audit: AuditLogTraitPtr = mock_log.clone();
+        let result = link_user_author(
+            State::from(&users),
+            State::from(&authors),
+            State::from(&audit),
+            admin(),
+            1,
+            link(Some(2)),
+        )
+        .await;
+        assert_eq!(result.unwrap_err().0, Status::Conflict);
+
+        let result = link_user_author(
+            State::from(&users),
+            State::from(&authors),
+            State::from(&audit),
+            admin(),
+            1,
+            link(Some(3)),
+        )
+        .await;
+        assert_eq!(result.unwrap(), json!({ "author_id": 3 }));
+
+        let result = link_user_author(
+            State::from(&users),
+            State::from(&authors),
+            State::from(&audit),
+            admin(),
+            2,
+            link(None),
+        )
+        .await;
+        assert_eq!(result.unwrap(), json!({ "author_id": null }));
+        assert_eq!(mock_users.user(2).author_id, None);
+
+        let result = link_user_author(
+            State::from(&users),
+            State::from(&authors),
+            State::from(&audit),
+            admin(),
+            9,
+            link(None),
+        )
+        .await;
+        assert_eq!(result.unwrap_err().0, Status::NotFound);
+
+        // Each link that went through is audited with the author before and after
+        let events = mock_log.events.lock().unwrap();
+        let links: Vec<_> = events
+            .iter()
+            .map(|event| {
+                assert_eq!(event.action, AuditAction::Update);
+                assert_eq!(event.entity_type, AuditEntity::User);
+                assert_eq!(event.actor_user_id, Some(1));
+                (
+                    event.entity_id,
+                    event.before.as_ref().unwrap()["author_id"].clone(),
+                    event.after.as_ref().unwrap()["author_id"].clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            links,
+            vec![(1, json!(null), json!(3)), (2, json!(2), json!(null))]
+        );
+    }
 }
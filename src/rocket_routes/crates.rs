@@ -1,78 +1,355 @@
 // rocket_routes/crates.rs
-use super::GuardedAppUser;
+use super::{CrateDelete, CrateRead, CrateWrite, CrateWriteAccess, RequirePermission};
 use crate::domain::{
     //
+    latest_version,
+    matching_version,
+    parse_since,
+    parse_version,
+    parse_version_req,
+    publish_crate_version,
+    record_audit_event,
+    set_crate_version_yanked,
+    sort_by_precedence,
+    AuditEntity,
+    AuditLogTraitPtr,
+    Crate,
+    CrateQuery,
+    CrateSort,
     CrateTableTraitPtr,
+    CrateVersionError,
+    Cursor,
+    NewAuditEvent,
     NewCrate,
+    NewCrateVersion,
+    SortOrder,
+    DEFAULT_SEARCH_LIMIT,
+    MAX_SEARCH_LIMIT,
+    MAX_SEARCH_QUERY_LEN,
 };
-use crate::rocket_routes::server_error;
+use crate::rocket_routes::{bad_request, invalid_field, server_error};
+use rocket::http::Status;
 use rocket::response::status::Custom;
 use rocket::serde::json::{json, Json, Value};
 use rocket::State;
 
-#[rocket::get("/crates")]
+fn crate_not_found() -> Custom<Value> {
+    Custom(Status::NotFound, json!({ "error": "Crate not found" }))
+}
+
+/// Normalize the crate's version, refusing anything that is not SemVer.
+fn validate_new_crate(mut new_crate: NewCrate) -> Result<NewCrate, Custom<Value>> {
+    // ---
+    new_crate.version = parse_version(&new_crate.version)
+        .map_err(|err| invalid_field("version", err))?
+        .to_string();
+    Ok(new_crate)
+}
+
+fn crate_version_error(err: CrateVersionError) -> Custom<Value> {
+    // ---
+    match err {
+        CrateVersionError::Invalid(err) => invalid_field("version", err),
+        CrateVersionError::AlreadyExists(_) => {
+            Custom(Status::Conflict, json!({ "error": err.to_string() }))
+        }
+        CrateVersionError::NotFound(_) => {
+            Custom(Status::NotFound, json!({ "error": err.to_string() }))
+        }
+        CrateVersionError::Internal(err) => server_error(err),
+    }
+}
+
+/// One page of crates, newest first unless sorted otherwise, e.g.
+/// `/crates?author_id=3&name=ser&sort=name&limit=20`.
+///
+/// Sorts by `name`, `created_at` or `version` (`order` is `asc` or `desc`) and filters
+/// by `author_id`, name prefix (`name`) and creation date (`since`). Pass the returned
+/// `next_cursor` back as `cursor`, with the same `sort`, for the next page.
+#[allow(clippy::too_many_arguments)]
+#[rocket::get("/crates?<limit>&<cursor>&<sort>&<order>&<author_id>&<name>&<since>")]
 pub async fn get_crates(
     repo: &State<crate::domain::CrateTableTraitPtr>,
-    _user: GuardedAppUser,
+    _user: RequirePermission<CrateRead>,
+    limit: Option<i64>,
+    cursor: Option<&str>,
+    sort: Option<&str>,
+    order: Option<&str>,
+    author_id: Option<i32>,
+    name: Option<&str>,
+    since: Option<&str>,
 ) -> Result<Value, Custom<Value>> {
     // ---
-    let crates = repo
-        .inner()
-        .find_multiple(100)
-        .await
-        .map_err(server_error)?;
+    let sort: CrateSort = sort
+        .map(str::parse)
+        .transpose()
+        .map_err(bad_request)?
+        .unwrap_or_default();
+    let order: SortOrder = order
+        .map(str::parse)
+        .transpose()
+        .map_err(bad_request)?
+        .unwrap_or(sort.default_order());
+
+    let query = CrateQuery {
+        author_id,
+        name_prefix: name.map(str::to_string),
+        created_since: since.map(parse_since).transpose().map_err(bad_request)?,
+        sort,
+        order,
+        cursor: cursor
+            .map(|cursor| Cursor::decode(cursor, sort))
+            .transpose()
+            .map_err(bad_request)?,
+        limit,
+    };
+
+    let page = repo.find_page(&query).await.map_err(server_error)?;
 
-    Ok(json!(crates))
+    Ok(json!(page))
+}
+
+/// Crates matching a full-text search of their names and descriptions, best match
+/// first, e.g. `/crates/search?q=json+parser&limit=10`.
+///
+/// `q` takes web search syntax (`"exact phrase"`, `-excluded`, `or`) and names also
+/// match with small typos. `limit` defaults to 20 and is capped at 100.
+#[rocket::get("/crates/search?<q>&<limit>")]
+pub async fn search_crates(
+    repo: &State<CrateTableTraitPtr>,
+    _user: RequirePermission<CrateRead>,
+    q: Option<&str>,
+    limit: Option<i64>,
+) -> Result<Value, Custom<Value>> {
+    // ---
+    let q = q.map(str::trim).unwrap_or_default();
+    if q.is_empty() {
+        return Err(bad_request("Missing search query 'q'".into()));
+    }
+    if q.chars().count() > MAX_SEARCH_QUERY_LEN {
+        return Err(bad_request(format!(
+            "Search query is longer than {MAX_SEARCH_QUERY_LEN} characters"
+        )));
+    }
+
+    let limit = limit
+        .unwrap_or(DEFAULT_SEARCH_LIMIT)
+        .clamp(1, MAX_SEARCH_LIMIT);
+    repo.search(q, limit)
+        .await
+        .map(|hits| json!(hits))
+        .map_err(server_error)
 }
 
 #[rocket::post("/crates", format = "json", data = "<new_crate>")]
 pub async fn create_crate(
     repo: &State<CrateTableTraitPtr>,
-    _user: GuardedAppUser,
+    audit_log: &State<AuditLogTraitPtr>,
+    user: RequirePermission<CrateWrite>,
     new_crate: Json<NewCrate>,
 ) -> Result<Value, Custom<Value>> {
     // ---
-    let result = repo
-        .create(new_crate.into_inner())
-        .await
-        .map_err(server_error)?;
+    let new_crate = validate_new_crate(new_crate.into_inner())?;
+    let result = repo.create(new_crate).await.map_err(server_error)?;
+
+    let event = NewAuditEvent::created(AuditEntity::Crate, result.id, &result);
+    record_audit_event(audit_log, event.by(user.0 .0.id)).await;
 
     Ok(json!(result))
 }
 
-#[rocket::get("/crates/<id>")]
+/// A crate, with `version` its latest release. While every version is yanked the
+/// crate is flagged `yanked` and `version` is the last one that counted.
+///
+/// `version_req` (e.g. `^1.2`) resolves `version` to the highest published version
+/// matching it instead, and `prerelease=true` lets pre-releases count; 404 when no
+/// version qualifies.
+#[rocket::get("/crates/<id>?<version_req>&<prerelease>")]
 pub async fn view_crate(
     repo: &State<CrateTableTraitPtr>,
-    _user: GuardedAppUser,
+    _user: RequirePermission<CrateRead>,
     id: i32,
+    version_req: Option<&str>,
+    prerelease: Option<bool>,
 ) -> Result<Value, Custom<Value>> {
-    repo.find(id).await.map(|c| json!(c)).map_err(server_error)
+    // ---
+    let krate = repo.find(id).await.map_err(server_error)?;
+    let prerelease = prerelease.unwrap_or(false);
+    if version_req.is_none() && !prerelease {
+        return Ok(json!(krate));
+    }
+
+    let req = version_req
+        .map(parse_version_req)
+        .transpose()
+        .map_err(bad_request)?;
+    let versions = repo.find_versions(id).await.map_err(server_error)?;
+    let resolved = match &req {
+        Some(req) => matching_version(&versions, req, prerelease),
+        None => latest_version(&versions, prerelease),
+    };
+    let Some(resolved) = resolved else {
+        return Err(Custom(
+            Status::NotFound,
+            json!({ "error": format!("No version matches '{}'", version_req.unwrap_or("*")) }),
+        ));
+    };
+
+    Ok(json!(Crate {
+        version: resolved.version.clone(),
+        ..krate
+    }))
 }
 
+/// Users holding only `crate:write:own` may update crates of their linked author
+/// profile, and may not hand them to another author.
 #[rocket::put("/crates/<id>", format = "json", data = "<a_crate>")]
 pub async fn update_crate(
     repo: &State<CrateTableTraitPtr>,
-    _user: GuardedAppUser,
+    audit_log: &State<AuditLogTraitPtr>,
+    access: CrateWriteAccess,
     id: i32,
     a_crate: Json<NewCrate>,
 ) -> Result<Value, Custom<Value>> {
     // --
-    let updated = repo
-        .update(id, a_crate.into_inner())
-        .await
-        .map_err(server_error)?;
+    let a_crate = validate_new_crate(a_crate.into_inner())?;
+    let existing = repo.find(id).await.ok();
+
+    if let CrateWriteAccess::Own(user) = &access {
+        let Some(existing) = &existing else {
+            return Err(crate_not_found());
+        };
+        if !existing.is_owned_by(&user.0) || user.0.author_id != Some(a_crate.author_id) {
+            return Err(Custom(
+                Status::Forbidden,
+                json!({ "error": "Only crates of your own author profile can be updated" }),
+            ));
+        }
+    }
+
+    let updated = repo.update(id, a_crate).await.map_err(server_error)?;
+
+    let event = NewAuditEvent::updated(AuditEntity::Crate, id, existing.as_ref(), &updated);
+    record_audit_event(audit_log, event.by(access.user().0.id)).await;
 
     Ok(json!(updated))
 }
 
+/// All published versions of a crate, highest SemVer precedence first.
+#[rocket::get("/crates/<id>/versions")]
+pub async fn list_crate_versions(
+    repo: &State<CrateTableTraitPtr>,
+    _user: RequirePermission<CrateRead>,
+    id: i32,
+) -> Result<Value, Custom<Value>> {
+    // ---
+    if repo.find(id).await.is_err() {
+        return Err(crate_not_found());
+    }
+    let mut versions = repo.find_versions(id).await.map_err(server_error)?;
+    sort_by_precedence(&mut versions);
+    Ok(json!(versions))
+}
+
+/// Publish a new SemVer version of a crate (422 otherwise), which becomes the crate's
+/// current `version` when it is the latest release.
+///
+/// Users holding only `crate:write:own` may publish versions of their own crates.
+#[rocket::post("/crates/<id>/versions", format = "json", data = "<new_version>")]
+pub async fn create_crate_version(
+    repo: &State<CrateTableTraitPtr>,
+    audit_log: &State<AuditLogTraitPtr>,
+    access: CrateWriteAccess,
+    id: i32,
+    new_version: Json<NewCrateVersion>,
+) -> Result<Custom<Value>, Custom<Value>> {
+    // ---
+    let existing = repo.find(id).await.map_err(|_| crate_not_found())?;
+
+    if let CrateWriteAccess::Own(user) = &access {
+        if !existing.is_owned_by(&user.0) {
+            return Err(Custom(
+                Status::Forbidden,
+                json!({ "error": "Only crates of your own author profile can be updated" }),
+            ));
+        }
+    }
+
+    let (version, updated) = publish_crate_version(repo, &existing, new_version.into_inner())
+        .await
+        .map_err(crate_version_error)?;
+
+    let event = NewAuditEvent::updated(AuditEntity::Crate, id, Some(&existing), &updated);
+    record_audit_event(audit_log, event.by(access.user().0.id)).await;
+
+    Ok(Custom(Status::Created, json!(version)))
+}
+
+/// Yank a version: it stays listed and viewable but no longer counts as the crate's
+/// latest version, matches `version_req` lookups or appears in digests. Yanking the
+/// last version that counted flags the crate itself `yanked`.
+#[rocket::put("/crates/<id>/versions/<version>/yank")]
+pub async fn yank_crate_version(
+    repo: &State<CrateTableTraitPtr>,
+    audit_log: &State<AuditLogTraitPtr>,
+    user: RequirePermission<CrateWrite>,
+    id: i32,
+    version: &str,
+) -> Result<Value, Custom<Value>> {
+    set_yanked(repo, audit_log, user, id, version, true).await
+}
+
+/// Undo a yank, so the version counts again.
+#[rocket::put("/crates/<id>/versions/<version>/unyank")]
+pub async fn unyank_crate_version(
+    repo: &State<CrateTableTraitPtr>,
+    audit_log: &State<AuditLogTraitPtr>,
+    user: RequirePermission<CrateWrite>,
+    id: i32,
+    version: &str,
+) -> Result<Value, Custom<Value>> {
+    set_yanked(repo, audit_log, user, id, version, false).await
+}
+
+/// Shared by the yank routes; repeating a yank or un-yank is a no-op and not audited.
+async fn set_yanked(
+    repo: &State<CrateTableTraitPtr>,
+    audit_log: &State<AuditLogTraitPtr>,
+    user: RequirePermission<CrateWrite>,
+    id: i32,
+    version: &str,
+    yanked: bool,
+) -> Result<Value, Custom<Value>> {
+    // ---
+    if repo.find(id).await.is_err() {
+        return Err(crate_not_found());
+    }
+
+    let change = set_crate_version_yanked(repo, id, version, yanked)
+        .await
+        .map_err(crate_version_error)?;
+
+    if change.changed() {
+        let event = NewAuditEvent::yank_changed(id, &change.before, &change.after, yanked);
+        record_audit_event(audit_log, event.by(user.0 .0.id)).await;
+    }
+
+    Ok(json!(change.after))
+}
+
 #[rocket::delete("/crates/<id>")]
 pub async fn delete_crate(
     repo: &State<CrateTableTraitPtr>,
-    _user: GuardedAppUser,
+    audit_log: &State<AuditLogTraitPtr>,
+    user: RequirePermission<CrateDelete>,
     id: i32,
 ) -> Result<Value, Custom<Value>> {
+    let existing = repo.find(id).await.ok();
     repo.delete(id).await.map_err(server_error)?;
 
+    let event = NewAuditEvent::deleted(AuditEntity::Crate, id, existing.as_ref());
+    record_audit_event(audit_log, event.by(user.0 .0.id)).await;
+
     Ok(json!({ "deleted": true }))
 }
 
@@ -82,21 +359,37 @@ mod tests {
     use super::*;
     use crate::domain::{
         //
+        effective_page_limit,
+        into_page,
         AppUser as DomainAppUser,
+        AuditAction,
         Crate as CrateModel,
+        CrateSearchHit,
         CrateSummary,
         CrateTableTrait,
+        CrateVersion,
         NewCrate,
+        Page,
+        ReleaseKind,
     };
+    use crate::rocket_routes::GuardedAppUser;
+    use crate::test_support::MockAuditLog;
     use anyhow::{anyhow, Result};
     use async_trait::async_trait;
     use chrono::Utc;
     use rocket::State;
     use std::collections::HashMap;
-    use std::sync::Arc;
+    use std::sync::{Arc, Mutex};
+
+    fn audit_log() -> (Arc<MockAuditLog>, AuditLogTraitPtr) {
+        let mock = Arc::new(MockAuditLog::default());
+        (mock.clone(), mock)
+    }
 
     pub struct MockCrateRepo {
         crates: HashMap<i32, CrateModel>,
+        last_query: Mutex<Option<CrateQuery>>,
+        versions: Mutex<Vec<CrateVersion>>,
     }
 
     impl MockCrateRepo {
@@ -104,6 +397,8 @@ mod tests {
         pub fn new() -> Self {
             Self {
                 crates: HashMap::new(),
+                last_query: Mutex::new(None),
+                versions: Mutex::new(vec![]),
             }
         }
 
@@ -120,6 +415,44 @@ mod tests {
             Ok(self.crates.values().cloned().collect())
         }
 
+        /// Filters by author only and orders by id, remembering the query.
+        async fn find_page(&self, query: &CrateQuery) -> Result<Page<CrateModel>> {
+            // ---
+            *self.last_query.lock().unwrap() = Some(query.clone());
+            let mut crates: Vec<_> = self
+                .crates
+                .values()
+                .filter(|c| query.author_id.is_none_or(|id| c.author_id == id))
+                .cloned()
+                .collect();
+            crates.sort_by_key(|c| c.id);
+
+            let total = crates.len() as i64;
+            let limit = effective_page_limit(query.limit);
+            Ok(into_page(crates, limit, total, |c| {
+                query.sort.cursor_for(c)
+            }))
+        }
+
+        /// Case-insensitive substring match on names, everything ranked equally.
+        async fn search(&self, query: &str, limit: i64) -> Result<Vec<CrateSearchHit>> {
+            // ---
+            let query = query.to_lowercase();
+            let mut hits: Vec<_> = self
+                .crates
+                .values()
+                .filter(|c| c.name.to_lowercase().contains(&query))
+                .map(|c| CrateSearchHit {
+                    krate: c.clone(),
+                    rank: 1.0,
+                    snippet: c.name.replace(&query, &format!("<mark>{query}</mark>")),
+                })
+                .collect();
+            hits.sort_by_key(|hit| hit.krate.id);
+            hits.truncate(limit as usize);
+            Ok(hits)
+        }
+
         async fn create(&self, new: NewCrate) -> Result<CrateModel> {
             // ---
             Ok(CrateModel {
@@ -128,6 +461,7 @@ mod tests {
                 code: new.code,
                 name: new.name,
                 version: new.version,
+                yanked: false,
                 description: new.description,
                 created_at: Utc::now().naive_utc(),
             })
@@ -141,6 +475,7 @@ mod tests {
                 code: updated.code,
                 name: updated.name,
                 version: updated.version,
+                yanked: false,
                 description: updated.description,
                 created_at: Utc::now().naive_utc(),
             })
@@ -151,12 +486,18 @@ mod tests {
             Ok(())
         }
 
+        /// Flags the crate `yanked`, like the SQLx repo, once all its versions are.
         async fn find(&self, id: i32) -> Result<CrateModel> {
             // ---
-            self.crates
+            let mut krate = self
+                .crates
                 .get(&id)
                 .cloned()
-                .ok_or_else(|| anyhow!("Crate not found"))
+                .ok_or_else(|| anyhow!("Crate not found"))?;
+            let versions = self.versions.lock().unwrap();
+            let mut published = versions.iter().filter(|v| v.crate_id == id).peekable();
+            krate.yanked = published.peek().is_some() && published.all(|v| v.yanked);
+            Ok(krate)
         }
 
         async fn find_since(&self, _hours_since: i32) -> Result<Vec<CrateSummary>> {
@@ -166,11 +507,62 @@ mod tests {
                 .values()
                 .map(|c| CrateSummary {
                     name: c.name.clone(),
+                    code: c.code.clone(),
                     version: c.version.clone(),
+                    description: c.description.clone(),
+                    published_at: c.created_at,
+                    kind: ReleaseKind::NewCrate,
                 })
                 .collect();
             Ok(summaries)
         }
+
+        async fn find_versions(&self, crate_id: i32) -> Result<Vec<CrateVersion>> {
+            // ---
+            let versions = self.versions.lock().unwrap();
+            Ok(versions
+                .iter()
+                .rev()
+                .filter(|v| v.crate_id == crate_id)
+                .cloned()
+                .collect())
+        }
+
+        async fn publish_version(
+            &self,
+            crate_id: i32,
+            new_version: NewCrateVersion,
+        ) -> Result<CrateVersion> {
+            // ---
+            let mut versions = self.versions.lock().unwrap();
+            let version = CrateVersion {
+                id: versions.len() as i32 + 1,
+                crate_id,
+                version: new_version.version,
+                description: new_version.description,
+                yanked: false,
+                published_at: Utc::now().naive_utc(),
+            };
+            versions.push(version.clone());
+            Ok(version)
+        }
+
+        async fn set_version_yanked(
+            &self,
+            crate_id: i32,
+            version: &str,
+            yanked: bool,
+        ) -> Result<Option<CrateVersion>> {
+            // ---
+            let mut versions = self.versions.lock().unwrap();
+            Ok(versions
+                .iter_mut()
+                .find(|v| v.crate_id == crate_id && v.version == version)
+                .map(|v| {
+                    v.yanked = yanked;
+                    v.clone()
+                }))
+        }
     }
 
     #[tokio::test]
@@ -182,6 +574,7 @@ mod tests {
             code: "test_code".into(),
             name: "test_crate".into(),
             version: "1.0.0".into(),
+            yanked: false,
             description: Some("Test description".into()),
             created_at: Utc::now().naive_utc(),
         };
@@ -189,34 +582,174 @@ mod tests {
         let mock_repo = Arc::new(MockCrateRepo::new().with_crate(test_crate));
         let binding = mock_repo as Arc<dyn CrateTableTrait>;
         let repo_state = State::from(&binding);
-        let user = GuardedAppUser(DomainAppUser {
+        let user = RequirePermission::new(GuardedAppUser(DomainAppUser {
             id: 1,
             username: "test".into(),
             password: "password".into(),
             created_at: Utc::now().naive_utc(),
-        });
+            author_id: None,
+            disabled_at: None,
+        }));
 
-        let result = get_crates(repo_state, user).await;
+        let result = get_crates(repo_state, user, None, None, None, None, None, None, None).await;
         match result {
             Ok(value) => {
-                assert_eq!(value[0]["name"], "test_crate");
+                assert_eq!(value["items"][0]["name"], "test_crate");
+                assert_eq!(value["total"], 1);
+                assert!(value["next_cursor"].is_null());
             }
             Err(e) => panic!("Expected success but got error: {:?}", e),
         }
     }
 
+    #[tokio::test]
+    async fn test_get_crates_paginates_and_parses_query() {
+        // ---
+        let make_crate = |id: i32, author_id: i32| CrateModel {
+            id,
+            author_id,
+            code: format!("crate{id}"),
+            name: format!("Crate {id}"),
+            version: "1.0.0".into(),
+            yanked: false,
+            description: None,
+            created_at: Utc::now().naive_utc(),
+        };
+        let mock_repo = Arc::new(
+            MockCrateRepo::new()
+                .with_crate(make_crate(1, 3))
+                .with_crate(make_crate(2, 3))
+                .with_crate(make_crate(3, 4)),
+        );
+        let binding: CrateTableTraitPtr = mock_repo.clone();
+        let user = || {
+            RequirePermission::new(GuardedAppUser(DomainAppUser {
+                id: 1,
+                username: "test".into(),
+                password: "password".into(),
+                created_at: Utc::now().naive_utc(),
+                author_id: None,
+                disabled_at: None,
+            }))
+        };
+
+        let first = get_crates(
+            State::from(&binding),
+            user(),
+            Some(1),
+            None,
+            Some("name"),
+            None,
+            Some(3),
+            Some("Crate"),
+            Some("2025-06-01"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(first["items"][0]["id"], 1);
+        assert_eq!(first["total"], 2);
+        let cursor = first["next_cursor"].as_str().unwrap().to_string();
+
+        let query = mock_repo.last_query.lock().unwrap().clone().unwrap();
+        assert_eq!((query.sort, query.order), (CrateSort::Name, SortOrder::Asc));
+        assert_eq!(query.name_prefix.as_deref(), Some("Crate"));
+        assert!(query.created_since.is_some());
+
+        let second = get_crates(
+            State::from(&binding),
+            user(),
+            Some(1),
+            Some(&cursor),
+            Some("name"),
+            Some("desc"),
+            Some(3),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(second["total"], 2);
+        let query = mock_repo.last_query.lock().unwrap().clone().unwrap();
+        assert_eq!(query.cursor.map(|c| c.id), Some(1));
+        assert_eq!(query.order, SortOrder::Desc);
+
+        // Unknown sort keys and cursors issued for another sort are rejected
+        for (sort, cursor) in [(Some("downloads"), None), (None, Some(cursor.as_str()))] {
+            let rejected = get_crates(
+                State::from(&binding),
+                user(),
+                None,
+                cursor,
+                sort,
+                None,
+                None,
+                None,
+                None,
+            )
+            .await;
+            assert_eq!(rejected.unwrap_err().0, Status::BadRequest);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_crates_validates_query() {
+        // ---
+        let make_crate = |id: i32, name: &str| CrateModel {
+            id,
+            author_id: 1,
+            code: name.into(),
+            name: name.into(),
+            version: "1.0.0".into(),
+            yanked: false,
+            description: None,
+            created_at: Utc::now().naive_utc(),
+        };
+        let binding: CrateTableTraitPtr = Arc::new(
+            MockCrateRepo::new()
+                .with_crate(make_crate(1, "serde"))
+                .with_crate(make_crate(2, "serde_json"))
+                .with_crate(make_crate(3, "tokio")),
+        );
+        let user = || {
+            RequirePermission::new(GuardedAppUser(DomainAppUser {
+                id: 1,
+                username: "test".into(),
+                password: "password".into(),
+                created_at: Utc::now().naive_utc(),
+                author_id: None,
+                disabled_at: None,
+            }))
+        };
+
+        let hits = search_crates(State::from(&binding), user(), Some(" serde "), Some(1))
+            .await
+            .unwrap();
+        let hits = hits.as_array().unwrap();
+        assert_eq!(hits.len(), 1, "limit applies");
+        assert_eq!(hits[0]["name"], "serde", "crate fields are flattened");
+        assert_eq!(hits[0]["snippet"], "<mark>serde</mark>");
+
+        let too_long = "x".repeat(MAX_SEARCH_QUERY_LEN + 1);
+        for q in [None, Some("   "), Some(too_long.as_str())] {
+            let rejected = search_crates(State::from(&binding), user(), q, None).await;
+            assert_eq!(rejected.unwrap_err().0, Status::BadRequest);
+        }
+    }
+
     #[tokio::test]
     async fn test_create_crate_success() {
         // ---
         let repo = Arc::new(MockCrateRepo::new());
         let binding = repo as Arc<dyn CrateTableTrait>;
         let repo_state = State::from(&binding);
-        let user = GuardedAppUser(DomainAppUser {
+        let user = RequirePermission::new(GuardedAppUser(DomainAppUser {
             id: 42,
             username: "alice".into(),
             password: "password".into(),
             created_at: Utc::now().naive_utc(),
-        });
+            author_id: None,
+            disabled_at: None,
+        }));
         let new_crate = Json(NewCrate {
             author_id: 42,
             code: "abc".into(),
@@ -225,13 +758,22 @@ mod tests {
             description: Some("desc".into()),
         });
 
-        let result = create_crate(repo_state, user, new_crate).await;
+        let (mock_audit, audit) = audit_log();
+
+        let result = create_crate(repo_state, State::from(&audit), user, new_crate).await;
         match result {
             Ok(value) => {
                 assert_eq!(value["name"], "test_create");
             }
             Err(e) => panic!("Expected success but got error: {:?}", e),
         }
+
+        let events = mock_audit.events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].action, AuditAction::Create);
+        assert_eq!(events[0].actor_user_id, Some(42));
+        assert_eq!(events[0].entity_id, 999);
+        assert_eq!(events[0].after.as_ref().unwrap()["name"], "test_create");
     }
 
     #[tokio::test]
@@ -243,6 +785,7 @@ mod tests {
             code: "crate10".into(),
             name: "Test Crate".into(),
             version: "1.0.0".into(),
+            yanked: false,
             description: Some("A crate for testing".into()),
             created_at: Utc::now().naive_utc(),
         };
@@ -250,14 +793,16 @@ mod tests {
         let repo = Arc::new(MockCrateRepo::new().with_crate(test_crate.clone()));
         let binding = repo as Arc<dyn CrateTableTrait>;
         let repo_state = State::from(&binding);
-        let user = GuardedAppUser(DomainAppUser {
+        let user = RequirePermission::new(GuardedAppUser(DomainAppUser {
             id: 1,
             username: "test".into(),
             password: "password".into(),
             created_at: Utc::now().naive_utc(),
-        });
+            author_id: None,
+            disabled_at: None,
+        }));
 
-        let result = view_crate(repo_state, user, 10).await;
+        let result = view_crate(repo_state, user, 10, None, None).await;
         match result {
             Ok(value) => {
                 assert_eq!(value["name"], "Test Crate");
@@ -272,12 +817,14 @@ mod tests {
         let repo = Arc::new(MockCrateRepo::new());
         let binding = repo as Arc<dyn CrateTableTrait>;
         let repo_state = State::from(&binding);
-        let user = GuardedAppUser(DomainAppUser {
+        let user = CrateWriteAccess::All(GuardedAppUser(DomainAppUser {
             id: 1,
             username: "bob".into(),
             password: "password".into(),
             created_at: Utc::now().naive_utc(),
-        });
+            author_id: None,
+            disabled_at: None,
+        }));
         let updated = Json(NewCrate {
             author_id: 1,
             code: "upd".into(),
@@ -286,7 +833,9 @@ mod tests {
             description: None,
         });
 
-        let result = update_crate(repo_state, user, 123, updated).await;
+        let (_, audit) = audit_log();
+
+        let result = update_crate(repo_state, State::from(&audit), user, 123, updated).await;
         match result {
             Ok(value) => {
                 assert_eq!(value["name"], "updated_crate");
@@ -295,26 +844,377 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_update_own_crate() {
+        // ---
+        let owned = |id, author_id| CrateModel {
+            id,
+            author_id,
+            code: format!("crate{id}"),
+            name: format!("Crate {id}"),
+            version: "1.0.0".into(),
+            yanked: false,
+            description: None,
+            created_at: Utc::now().naive_utc(),
+        };
+        let repo: CrateTableTraitPtr = Arc::new(
+            MockCrateRepo::new()
+                .with_crate(owned(1, 7))
+                .with_crate(owned(2, 8)),
+        );
+        let viewer = || {
+            CrateWriteAccess::Own(GuardedAppUser(DomainAppUser {
+                id: 3,
+                username: "viewer".into(),
+                password: "password".into(),
+                created_at: Utc::now().naive_utc(),
+                author_id: Some(7),
+                disabled_at: None,
+            }))
+        };
+        let update = |author_id| {
+            Json(NewCrate {
+                author_id,
+                code: "upd".into(),
+                name: "updated".into(),
+                version: "1.0.1".into(),
+                description: None,
+            })
+        };
+
+        let (mock_audit, audit) = audit_log();
+        let audit = State::from(&audit);
+
+        let result = update_crate(State::from(&repo), audit, viewer(), 1, update(7)).await;
+        assert_eq!(result.unwrap()["name"], "updated");
+
+        let result = update_crate(State::from(&repo), audit, viewer(), 2, update(7)).await;
+        assert_eq!(result.unwrap_err().0, Status::Forbidden);
+
+        let result = update_crate(State::from(&repo), audit, viewer(), 1, update(8)).await;
+        assert_eq!(
+            result.unwrap_err().0,
+            Status::Forbidden,
+            "an owned crate cannot be handed to another author"
+        );
+
+        let result = update_crate(State::from(&repo), audit, viewer(), 99, update(7)).await;
+        assert_eq!(result.unwrap_err().0, Status::NotFound);
+
+        // Only the update that went through is audited, with both snapshots
+        let events = mock_audit.events();
+        assert_eq!(events.len(), 1, "refused updates must not be audited");
+        assert_eq!(events[0].action, AuditAction::Update);
+        assert_eq!(events[0].actor_user_id, Some(3));
+        assert_eq!(events[0].before.as_ref().unwrap()["name"], "Crate 1");
+        assert_eq!(events[0].after.as_ref().unwrap()["name"], "updated");
+    }
+
+    #[tokio::test]
+    async fn test_crate_versions() {
+        // ---
+        let owned = |id, author_id| CrateModel {
+            id,
+            author_id,
+            code: format!("crate{id}"),
+            name: format!("Crate {id}"),
+            version: "1.0.0".into(),
+            yanked: false,
+            description: None,
+            created_at: Utc::now().naive_utc(),
+        };
+        let repo: CrateTableTraitPtr = Arc::new(
+            MockCrateRepo::new()
+                .with_crate(owned(1, 7))
+                .with_crate(owned(2, 8)),
+        );
+        let owner_user = || {
+            GuardedAppUser(DomainAppUser {
+                id: 3,
+                username: "owner".into(),
+                password: "password".into(),
+                created_at: Utc::now().naive_utc(),
+                author_id: Some(7),
+                disabled_at: None,
+            })
+        };
+        let owner = || CrateWriteAccess::Own(owner_user());
+        let release = |version: &str| {
+            Json(NewCrateVersion {
+                version: version.into(),
+                description: Some("Bug fixes".into()),
+            })
+        };
+
+        let (mock_audit, audit) = audit_log();
+        let audit = State::from(&audit);
+
+        for version in ["1.1.0", " 1.2.0 ", "2.0.0-beta.1", "1.1.1"] {
+            let created =
+                create_crate_version(State::from(&repo), audit, owner(), 1, release(version))
+                    .await
+                    .unwrap();
+            assert_eq!(created.0, Status::Created);
+        }
+
+        let reader = RequirePermission::new(owner_user());
+        let versions = list_crate_versions(State::from(&repo), reader, 1)
+            .await
+            .unwrap();
+        let order: Vec<_> = versions
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v["version"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            order,
+            ["2.0.0-beta.1", "1.2.0", "1.1.1", "1.1.0"],
+            "SemVer precedence, trimmed"
+        );
+        assert_eq!(versions[0]["yanked"], false);
+
+        // Lookups resolve the version by requirement, pre-releases only on request
+        let view = |version_req: Option<&'static str>, prerelease| {
+            let reader = RequirePermission::new(owner_user());
+            view_crate(State::from(&repo), reader, 1, version_req, prerelease)
+        };
+        assert_eq!(view(Some("~1.1"), None).await.unwrap()["version"], "1.1.1");
+        assert_eq!(view(Some("^1"), None).await.unwrap()["version"], "1.2.0");
+        assert_eq!(
+            view(Some(">=2"), Some(true)).await.unwrap()["version"],
+            "2.0.0-beta.1"
+        );
+        assert_eq!(
+            view(None, Some(true)).await.unwrap()["version"],
+            "2.0.0-beta.1"
+        );
+        assert_eq!(
+            view(Some(">=2"), None).await.unwrap_err().0,
+            Status::NotFound
+        );
+        assert_eq!(
+            view(Some("one"), None).await.unwrap_err().0,
+            Status::BadRequest
+        );
+
+        let duplicate =
+            create_crate_version(State::from(&repo), audit, owner(), 1, release("1.1.0")).await;
+        assert_eq!(duplicate.unwrap_err().0, Status::Conflict);
+
+        for invalid in [" ", "1.3", "v1.3.0"] {
+            let rejected =
+                create_crate_version(State::from(&repo), audit, owner(), 1, release(invalid))
+                    .await
+                    .unwrap_err();
+            assert_eq!(rejected.0, Status::UnprocessableEntity);
+            assert!(rejected.1["fields"]["version"].is_string());
+        }
+
+        let foreign =
+            create_crate_version(State::from(&repo), audit, owner(), 2, release("2.0.0")).await;
+        assert_eq!(foreign.unwrap_err().0, Status::Forbidden);
+
+        let reader = RequirePermission::new(owner_user());
+        let missing = list_crate_versions(State::from(&repo), reader, 99).await;
+        assert_eq!(missing.unwrap_err().0, Status::NotFound);
+
+        // Each release is audited as an update of the crate's current version, which
+        // neither a pre-release nor a lower patch release replaces
+        let events = mock_audit.events();
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[1].before.as_ref().unwrap()["version"], "1.0.0");
+        assert_eq!(events[1].after.as_ref().unwrap()["version"], "1.2.0");
+        assert_eq!(events[2].after.as_ref().unwrap()["version"], "1.2.0");
+        assert_eq!(events[3].after.as_ref().unwrap()["version"], "1.2.0");
+    }
+
+    #[tokio::test]
+    async fn test_yank_crate_version() {
+        // ---
+        let repo: CrateTableTraitPtr = Arc::new(MockCrateRepo::new().with_crate(CrateModel {
+            id: 1,
+            author_id: 7,
+            code: "crate1".into(),
+            name: "Crate 1".into(),
+            version: "1.0.0".into(),
+            yanked: false,
+            description: None,
+            created_at: Utc::now().naive_utc(),
+        }));
+        let editor = || {
+            GuardedAppUser(DomainAppUser {
+                id: 5,
+                username: "editor".into(),
+                password: "password".into(),
+                created_at: Utc::now().naive_utc(),
+                author_id: None,
+                disabled_at: None,
+            })
+        };
+        let writer = || RequirePermission::new(editor());
+        let reader = || RequirePermission::new(editor());
+
+        let (mock_audit, audit) = audit_log();
+        let audit = State::from(&audit);
+        for version in ["1.1.0", "1.2.0"] {
+            let release = Json(NewCrateVersion {
+                version: version.into(),
+                description: None,
+            });
+            create_crate_version(
+                State::from(&repo),
+                audit,
+                CrateWriteAccess::All(editor()),
+                1,
+                release,
+            )
+            .await
+            .unwrap();
+        }
+
+        let yanked = yank_crate_version(State::from(&repo), audit, writer(), 1, "1.2.0")
+            .await
+            .unwrap();
+        assert_eq!(yanked["yanked"], true);
+
+        // Yanked versions stay listed but no longer resolve
+        let versions = list_crate_versions(State::from(&repo), reader(), 1)
+            .await
+            .unwrap();
+        assert_eq!(
+            (&versions[0]["version"], &versions[0]["yanked"]),
+            (&json!("1.2.0"), &json!(true))
+        );
+        let latest = view_crate(State::from(&repo), reader(), 1, Some("^1.2"), None).await;
+        assert_eq!(latest.unwrap_err().0, Status::NotFound);
+        let latest = view_crate(State::from(&repo), reader(), 1, Some("^1"), None).await;
+        assert_eq!(latest.unwrap()["version"], "1.1.0");
+
+        // Repeating a yank changes nothing and is not audited again
+        yank_crate_version(State::from(&repo), audit, writer(), 1, "1.2.0")
+            .await
+            .unwrap();
+        let unyanked = unyank_crate_version(State::from(&repo), audit, writer(), 1, "1.2.0")
+            .await
+            .unwrap();
+        assert_eq!(unyanked["yanked"], false);
+
+        let missing = yank_crate_version(State::from(&repo), audit, writer(), 1, "9.9.9").await;
+        assert_eq!(missing.unwrap_err().0, Status::NotFound);
+        let missing = yank_crate_version(State::from(&repo), audit, writer(), 2, "1.0.0").await;
+        assert_eq!(missing.unwrap_err().0, Status::NotFound);
+
+        let events = mock_audit.events();
+        let actions: Vec<_> = events.iter().skip(2).map(|e| e.action).collect();
+        assert_eq!(actions, [AuditAction::Yank, AuditAction::Unyank]);
+        assert_eq!(events[2].entity_id, 1);
+        assert_eq!(events[2].actor_user_id, Some(5));
+        assert_eq!(events[2].before.as_ref().unwrap()["yanked"], false);
+        assert_eq!(events[2].after.as_ref().unwrap()["yanked"], true);
+    }
+
+    #[tokio::test]
+    async fn test_yank_every_version_flags_crate() {
+        // ---
+        let repo: CrateTableTraitPtr = Arc::new(MockCrateRepo::new().with_crate(CrateModel {
+            id: 1,
+            author_id: 7,
+            code: "crate1".into(),
+            name: "Crate 1".into(),
+            version: "1.0.0".into(),
+            yanked: false,
+            description: None,
+            created_at: Utc::now().naive_utc(),
+        }));
+        let editor = || {
+            GuardedAppUser(DomainAppUser {
+                id: 5,
+                username: "editor".into(),
+                password: "password".into(),
+                created_at: Utc::now().naive_utc(),
+                author_id: None,
+                disabled_at: None,
+            })
+        };
+        let writer = || RequirePermission::new(editor());
+        let reader = || RequirePermission::new(editor());
+
+        let (_, audit) = audit_log();
+        let audit = State::from(&audit);
+        for version in ["1.0.0", "1.1.0"] {
+            let release = Json(NewCrateVersion {
+                version: version.into(),
+                description: None,
+            });
+            create_crate_version(
+                State::from(&repo),
+                audit,
+                CrateWriteAccess::All(editor()),
+                1,
+                release,
+            )
+            .await
+            .unwrap();
+        }
+
+        yank_crate_version(State::from(&repo), audit, writer(), 1, "1.1.0")
+            .await
+            .unwrap();
+        let krate = view_crate(State::from(&repo), reader(), 1, None, None)
+            .await
+            .unwrap();
+        assert_eq!(krate["yanked"], false);
+
+        // With nothing left to count the crate is flagged, and nothing resolves
+        yank_crate_version(State::from(&repo), audit, writer(), 1, "1.0.0")
+            .await
+            .unwrap();
+        let krate = view_crate(State::from(&repo), reader(), 1, None, None)
+            .await
+            .unwrap();
+        assert_eq!(krate["yanked"], true);
+        let latest = view_crate(State::from(&repo), reader(), 1, None, Some(true)).await;
+        assert_eq!(latest.unwrap_err().0, Status::NotFound);
+
+        unyank_crate_version(State::from(&repo), audit, writer(), 1, "1.0.0")
+            .await
+            .unwrap();
+        let krate = view_crate(State::from(&repo), reader(), 1, None, None)
+            .await
+            .unwrap();
+        assert_eq!(krate["yanked"], false);
+    }
+
     #[tokio::test]
     async fn test_delete_crate_success() {
         // ---
         let repo = Arc::new(MockCrateRepo::new());
         let binding = repo as Arc<dyn CrateTableTrait>;
         let repo_state = State::from(&binding);
-        let user = GuardedAppUser(DomainAppUser {
+        let user = RequirePermission::new(GuardedAppUser(DomainAppUser {
             id: 1,
             username: "admin".into(),
             password: "password".into(),
             created_at: Utc::now().naive_utc(),
-        });
+            author_id: None,
+            disabled_at: None,
+        }));
+
+        let (mock_audit, audit) = audit_log();
 
-        let result = delete_crate(repo_state, user, 555).await;
+        let result = delete_crate(repo_state, State::from(&audit), user, 555).await;
         match result {
             Ok(value) => {
                 assert_eq!(value["deleted"], true);
             }
             Err(e) => panic!("Expected success but got error: {:?}", e),
         }
+
+        let events = mock_audit.events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].action, AuditAction::Delete);
+        assert_eq!((events[0].entity_id, events[0].after.clone()), (555, None));
     }
 
     #[tokio::test]
@@ -339,10 +1239,54 @@ mod tests {
     }
 
     #[tokio::test]
-    #[ignore]
     async fn test_create_crate_validation_failure() {
         // ---
-        todo!("Implement input validation failure for create_crate()");
+        let repo: CrateTableTraitPtr = Arc::new(MockCrateRepo::new());
+        let user = || {
+            RequirePermission::new(GuardedAppUser(DomainAppUser {
+                id: 42,
+                username: "alice".into(),
+                password: "password".into(),
+                created_at: Utc::now().naive_utc(),
+                author_id: None,
+                disabled_at: None,
+            }))
+        };
+        let new_crate = |version: &str| {
+            Json(NewCrate {
+                author_id: 42,
+                code: "abc".into(),
+                name: "semver".into(),
+                version: version.into(),
+                description: None,
+            })
+        };
+        let (mock_audit, audit) = audit_log();
+
+        for version in ["1.0", "latest", "1.0.0.0", ""] {
+            let rejected = create_crate(
+                State::from(&repo),
+                State::from(&audit),
+                user(),
+                new_crate(version),
+            )
+            .await
+            .unwrap_err();
+            assert_eq!(rejected.0, Status::UnprocessableEntity, "{version:?}");
+            assert_eq!(rejected.1["error"], "Validation failed");
+            assert!(rejected.1["fields"]["version"].is_string());
+        }
+        assert!(mock_audit.events().is_empty());
+
+        let created = create_crate(
+            State::from(&repo),
+            State::from(&audit),
+            user(),
+            new_crate(" 1.0.0-alpha.1 "),
+        )
+        .await
+        .unwrap();
+        assert_eq!(created["version"], "1.0.0-alpha.1");
     }
 
     #[tokio::test]
@@ -1,16 +1,153 @@
-use crate::domain::{AppUser, AppUserTableTraitPtr, CacheContextTraitPtr, RoleCode};
-use rocket::http::Status;
+use crate::domain::{
+    //
+    authenticate_api_token,
+    is_api_token,
+    roles_grant,
+    ApiScope,
+    ApiTokenTableTraitPtr,
+    AppUser,
+    AppUserTableTraitPtr,
+    CacheContextTraitPtr,
+    Permission,
+    RoleCode,
+    RoleCodeTableTraitPtr,
+};
+use rocket::http::{Method, Status};
 use rocket::outcome::Outcome;
 use rocket::request::FromRequest;
 use rocket::{Request, State};
+use std::marker::PhantomData;
+use std::net::IpAddr;
 
 // ---
 
 #[derive(Debug, serde::Serialize)]
 pub struct GuardedAppUser(pub AppUser);
 
-#[derive(Debug, serde::Serialize)]
-pub struct EditorUser(pub GuardedAppUser);
+/// An authenticated user whose roles grant the permission named by the marker `P`,
+/// e.g. `RequirePermission<CrateWrite>`; fails with 403 otherwise.
+#[derive(Debug)]
+pub struct RequirePermission<P: PermissionMarker>(pub GuardedAppUser, PhantomData<P>);
+
+impl<P: PermissionMarker> RequirePermission<P> {
+    pub fn new(user: GuardedAppUser) -> Self {
+        Self(user, PhantomData)
+    }
+}
+
+/// Type-level name of a `Permission`, used as the parameter of `RequirePermission`.
+pub trait PermissionMarker: Send + Sync + 'static {
+    const PERMISSION: Permission;
+}
+
+macro_rules! permission_markers {
+    ($($marker:ident),* $(,)?) => {
+        $(
+            #[derive(Debug)]
+            pub struct $marker;
+
+            impl PermissionMarker for $marker {
+                const PERMISSION: Permission = Permission::$marker;
+            }
+        )*
+    };
+}
+
+permission_markers!(
+    CrateRead,
+    CrateWrite,
+    CrateWriteOwn,
+    CrateDelete,
+    AuthorRead,
+    AuthorWrite,
+    AuthorDelete,
+    UserManage,
+    RoleManage,
+    AuditRead,
+);
+
+/// Raw bearer token presented in the `Authorization` header (e.g. for logout).
+#[derive(Debug)]
+pub struct SessionToken(pub String);
+
+/// Client details recorded alongside a new session and keying the per-IP login
+/// throttle; never fails.
+///
+/// `ip` is the connection's peer address. Rocket's `ip_header` (`X-Real-IP` unless
+/// `ROCKET_IP_HEADER` says otherwise) is client-controlled, so it is only believed
+/// when the peer is a reverse proxy listed in `CR8S_TRUSTED_PROXIES`
+/// (comma-separated IPs, e.g. `10.0.0.2,10.0.0.3`) that sets the header itself.
+#[derive(Debug, Default, Clone)]
+pub struct ClientInfo {
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ClientInfo {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> rocket::request::Outcome<Self, Self::Error> {
+        let remote = request.remote().map(|addr| addr.ip());
+        let ip = resolve_client_ip(remote, request.real_ip(), &trusted_proxies());
+        Outcome::Success(ClientInfo {
+            ip: ip.map(|ip| ip.to_string()),
+            user_agent: request.headers().get_one("User-Agent").map(str::to_string),
+        })
+    }
+}
+
+/// Reverse proxies allowed to report the client address, from `CR8S_TRUSTED_PROXIES`.
+fn trusted_proxies() -> Vec<IpAddr> {
+    // ---
+    let proxies = std::env::var("CR8S_TRUSTED_PROXIES").unwrap_or_default();
+    proxies
+        .split(',')
+        .map(str::trim)
+        .filter(|proxy| !proxy.is_empty())
+        .filter_map(|proxy| {
+            proxy
+                .parse()
+                .map_err(|_| tracing::warn!("Ignoring invalid CR8S_TRUSTED_PROXIES entry: {proxy}"))
+                .ok()
+        })
+        .collect()
+}
+
+/// The peer address, or the address reported in the IP header when the peer is a
+/// trusted proxy.
+fn resolve_client_ip(
+    remote: Option<IpAddr>,
+    header_ip: Option<IpAddr>,
+    trusted_proxies: &[IpAddr],
+) -> Option<IpAddr> {
+    match remote {
+        Some(peer) if trusted_proxies.contains(&peer) => header_ip.or(Some(peer)),
+        _ => remote,
+    }
+}
+
+/// Extract the token from an `Authorization: Bearer <token>` header.
+fn bearer_token(request: &Request<'_>) -> Option<String> {
+    // ---
+    request
+        .headers()
+        .get_one("Authorization")
+        .and_then(|auth| auth.strip_prefix("Bearer "))
+        .map(|s| s.to_string())
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for SessionToken {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> rocket::request::Outcome<Self, Self::Error> {
+        match bearer_token(request) {
+            Some(token) => Outcome::Success(SessionToken(token)),
+            None => Outcome::Error((Status::Unauthorized, ())),
+        }
+    }
+}
 
 #[rocket::async_trait]
 impl<'r> FromRequest<'r> for GuardedAppUser {
@@ -18,17 +155,15 @@ impl<'r> FromRequest<'r> for GuardedAppUser {
 
     async fn from_request(request: &'r Request<'_>) -> rocket::request::Outcome<Self, Self::Error> {
         // Extract token from Authorization header
-        let token = request
-            .headers()
-            .get_one("Authorization")
-            .and_then(|auth| auth.strip_prefix("Bearer "))
-            .map(|s| s.to_string());
-
-        let token = match token {
+        let token = match bearer_token(request) {
             Some(t) => t,
             None => return Outcome::Error((Status::Unauthorized, ())),
         };
 
+        if is_api_token(&token) {
+            return api_token_user(request, &token).await;
+        }
+
         // Get cache and user repo from Rocket state - FIX: Remove unwrap()
         let cache: &State<CacheContextTraitPtr> = match request.guard().await {
             Outcome::Success(cache) => cache,
@@ -38,6 +173,25 @@ impl<'r> FromRequest<'r> for GuardedAppUser {
             }
         };
 
+        // Access tokens (JWT auth mode) are trusted on their signature alone
+        if let Some(claims) = cache.inner().verify_access_token(&token) {
+            request.local_cache(|| AccessTokenRoles(Some(claims.role_codes.clone())));
+
+            return Outcome::Success(GuardedAppUser(AppUser {
+                id: claims.user_id,
+                username: claims.username,
+                password: String::new(),
+                created_at: claims.user_created_at,
+                author_id: claims.author_id,
+                disabled_at: None,
+            }));
+        }
+
+        // Session tokens are then refresh tokens, only accepted by `/token/refresh`
+        if cache.inner().requires_access_tokens() {
+            return Outcome::Error((Status::Unauthorized, ()));
+        }
+
         let user_repo: &State<AppUserTableTraitPtr> = match request.guard().await {
             Outcome::Success(repo) => repo,
             _ => {
@@ -52,18 +206,144 @@ impl<'r> FromRequest<'r> for GuardedAppUser {
             _ => return Outcome::Error((Status::Unauthorized, ())),
         };
 
-        // Fetch user from DB
+        // Fetch user from DB; sessions of disabled users are no longer honored
         let user = match user_repo.inner().find(user_id).await {
-            Ok(user) => user,
-            Err(_) => return Outcome::Error((Status::Unauthorized, ())),
+            Ok(user) if user.is_active() => user,
+            _ => return Outcome::Error((Status::Unauthorized, ())),
         };
 
+        // Slide the idle timeout forward; a failure here must not reject the request
+        if let Err(err) = cache.inner().touch_session_token(&token).await {
+            tracing::warn!("Failed to refresh session TTL: {err}");
+        }
+
         Outcome::Success(GuardedAppUser(user))
     }
 }
 
+/// Role codes carried by the request's access token, if it was authenticated by one.
+struct AccessTokenRoles(Option<Vec<RoleCode>>);
+
+/// Role codes of the request's user: from its access token when there is one,
+/// otherwise from the database.
+async fn user_roles(
+    request: &Request<'_>,
+    user: &GuardedAppUser,
+    user_repo: &AppUserTableTraitPtr,
+) -> anyhow::Result<Vec<RoleCode>> {
+    // ---
+    match &request.local_cache(|| AccessTokenRoles(None)).0 {
+        Some(roles) => Ok(roles.clone()),
+        None => user_repo.find_roles_by_user(&user.0).await,
+    }
+}
+
+/// Scope an API token needs for this request: derived from the route's first path
+/// segment below its mount point and whether the method is read-only.
+fn required_api_scope(request: &Request<'_>) -> Option<ApiScope> {
+    // ---
+    let route = request.route()?;
+    let resource = route
+        .uri
+        .unmounted_origin
+        .path()
+        .segments()
+        .next()
+        .unwrap_or_default();
+    let read_only = matches!(request.method(), Method::Get | Method::Head);
+
+    ApiScope::for_resource(resource, read_only)
+}
+
+/// Authenticate a request presenting an API token instead of a session token.
+///
+/// Tokens are refused (403) on routes their scopes do not cover, including every
+/// route outside the scoped resources such as `/me/*`.
+async fn api_token_user(
+    request: &Request<'_>,
+    token: &str,
+) -> rocket::request::Outcome<GuardedAppUser, ()> {
+    // ---
+    let token_repo: &State<ApiTokenTableTraitPtr> = match request.guard().await {
+        Outcome::Success(repo) => repo,
+        _ => {
+            tracing::debug!("Failed to get API token repo from Rocket state");
+            return Outcome::Error((Status::InternalServerError, ()));
+        }
+    };
+
+    let user_repo: &State<AppUserTableTraitPtr> = match request.guard().await {
+        Outcome::Success(repo) => repo,
+        _ => {
+            tracing::debug!("Failed to get user repo from Rocket state");
+            return Outcome::Error((Status::InternalServerError, ()));
+        }
+    };
+
+    let api_token = match authenticate_api_token(token_repo.inner(), token).await {
+        Ok(Some(api_token)) => api_token,
+        _ => return Outcome::Error((Status::Unauthorized, ())),
+    };
+
+    match required_api_scope(request) {
+        Some(scope) if api_token.allows(scope) => {}
+        scope => {
+            tracing::debug!(
+                "API token {} lacks scope {:?} for {}",
+                api_token.id,
+                scope.map(|s| s.to_string()),
+                request.uri()
+            );
+            return Outcome::Error((Status::Forbidden, ()));
+        }
+    }
+
+    match user_repo.inner().find(api_token.user_id).await {
+        Ok(user) if user.is_active() => Outcome::Success(GuardedAppUser(user)),
+        _ => Outcome::Error((Status::Unauthorized, ())),
+    }
+}
+
+/// Whether the roles of `user` grant `permission`; `Err` carries the status to fail with.
+async fn has_permission(
+    req: &Request<'_>,
+    user: &GuardedAppUser,
+    permission: Permission,
+) -> Result<bool, Status> {
+    // ---
+    let user_repo: &State<AppUserTableTraitPtr> = match req.guard().await {
+        Outcome::Success(repo) => repo,
+        _ => {
+            tracing::debug!("RequirePermission: Failed to get user repo from state");
+            return Err(Status::InternalServerError);
+        }
+    };
+
+    let role_repo: &State<RoleCodeTableTraitPtr> = match req.guard().await {
+        Outcome::Success(repo) => repo,
+        _ => {
+            tracing::debug!("RequirePermission: Failed to get role repo from state");
+            return Err(Status::InternalServerError);
+        }
+    };
+
+    let granted = match user_roles(req, user, user_repo.inner()).await {
+        Ok(roles) => roles_grant(role_repo.inner(), &roles, permission).await,
+        Err(e) => Err(e),
+    };
+
+    granted.map_err(|e| {
+        tracing::debug!(
+            "RequirePermission: Role check failed for {}: {:?}",
+            user.0.username,
+            e
+        );
+        Status::InternalServerError
+    })
+}
+
 #[rocket::async_trait]
-impl<'r> FromRequest<'r> for EditorUser {
+impl<'r, P: PermissionMarker> FromRequest<'r> for RequirePermission<P> {
     type Error = ();
 
     async fn from_request(req: &'r Request<'_>) -> rocket::request::Outcome<Self, Self::Error> {
@@ -74,206 +354,562 @@ impl<'r> FromRequest<'r> for EditorUser {
             Outcome::Forward(f) => return Outcome::Forward(f),
         };
 
-        // Then get the user repository from managed state
-        let user_repo: &State<AppUserTableTraitPtr> = match req.guard().await {
-            Outcome::Success(repo) => repo,
-            _ => {
-                tracing::debug!("EditorUser: Failed to get user repo from state");
-                return Outcome::Error((Status::InternalServerError, ()));
-            }
-        };
-
-        // Check permissions
-        match user.is_editor(user_repo.inner()).await {
-            Ok(true) => Outcome::Success(EditorUser(user)),
+        // Then check its roles
+        match has_permission(req, &user, P::PERMISSION).await {
+            Ok(true) => Outcome::Success(RequirePermission::new(user)),
             Ok(false) => {
                 tracing::debug!(
-                    "EditorUser: User {} lacks editor privileges",
-                    user.0.username
-                );
-                Outcome::Error((Status::Forbidden, ()))
-            }
-            Err(e) => {
-                tracing::debug!(
-                    "EditorUser: Role check failed for {}: {:?}",
+                    "RequirePermission: User {} lacks {}",
                     user.0.username,
-                    e
+                    P::PERMISSION
                 );
-                Outcome::Error((Status::InternalServerError, ()))
+                Outcome::Error((Status::Forbidden, ()))
             }
+            Err(status) => Outcome::Error((status, ())),
         }
     }
 }
 
-impl GuardedAppUser {
-    pub async fn is_editor(&self, user_repo: &AppUserTableTraitPtr) -> anyhow::Result<bool> {
-        let roles = user_repo.find_roles_by_user(&self.0).await?;
-        Ok(roles
-            .iter()
-            .any(|role| matches!(role, RoleCode::Admin | RoleCode::Editor)))
+/// Permission to update crates: `All` when the user's roles grant `crate:write`, `Own`
+/// when they only grant `crate:write:own`, in which case the route must check that the
+/// crate belongs to the user's author profile. Fails with 403 otherwise.
+#[derive(Debug)]
+pub enum CrateWriteAccess {
+    All(GuardedAppUser),
+    Own(GuardedAppUser),
+}
+
+impl CrateWriteAccess {
+    // ---
+    pub fn user(&self) -> &GuardedAppUser {
+        match self {
+            CrateWriteAccess::All(user) | CrateWriteAccess::Own(user) => user,
+        }
     }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for CrateWriteAccess {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> rocket::request::Outcome<Self, Self::Error> {
+        // ---
+        let user = match req.guard::<GuardedAppUser>().await {
+            Outcome::Success(user) => user,
+            Outcome::Error(e) => return Outcome::Error(e),
+            Outcome::Forward(f) => return Outcome::Forward(f),
+        };
+
+        match has_permission(req, &user, Permission::CrateWrite).await {
+            Ok(true) => return Outcome::Success(CrateWriteAccess::All(user)),
+            Ok(false) => {}
+            Err(status) => return Outcome::Error((status, ())),
+        }
 
-    pub async fn is_admin(&self, user_repo: &AppUserTableTraitPtr) -> anyhow::Result<bool> {
-        let roles = user_repo.find_roles_by_user(&self.0).await?;
-        Ok(roles.iter().any(|role| matches!(role, RoleCode::Admin)))
+        match has_permission(req, &user, Permission::CrateWriteOwn).await {
+            Ok(true) => Outcome::Success(CrateWriteAccess::Own(user)),
+            Ok(false) => {
+                tracing::debug!(
+                    "CrateWriteAccess: User {} lacks crate:write",
+                    user.0.username
+                );
+                Outcome::Error((Status::Forbidden, ()))
+            }
+            Err(status) => Outcome::Error((status, ())),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     // ---
-    // Unit tests for role-based authorization logic (is_editor, is_admin).
+    // Guard flows against a local Rocket client: permission checks, API token
+    // scopes and access token claims. Role permissions come from a mock of the
+    // seeded `role_permission` rows.
     //
     // NOT COVERED (intentionally - would require integration tests):
-    // - Full Rocket guard flow (GuardedAppUser::from_request, EditorUser::from_request)
-    // - Token validation and session management
+    // - Real session storage in Redis
     // - Database role lookups in real repository implementations
     //
     // For full end-to-end testing of guards, see integration tests in tests/ directory.
     // ---
 
     use super::*;
-    use crate::domain::{AppUser, AppUserTableTrait, NewUser, RoleCode};
+    use crate::domain::{
+        //
+        issue_api_token,
+        AccessClaims,
+        ApiToken,
+        ApiTokenTableTrait,
+        CacheContextTrait,
+        NewApiToken,
+        NewRole,
+        NewSession,
+        Role,
+        RoleCode,
+        RoleCodeTableTrait,
+        SessionRecord,
+    };
+    use crate::test_support::{MockAppUserRepo, MockCacheContext};
     use anyhow::Result;
     use async_trait::async_trait;
-    use chrono::Utc;
-    use std::sync::Arc;
+    use chrono::{NaiveDateTime, Utc};
+    use rocket::local::asynchronous::Client;
+    use std::sync::{Arc, Mutex};
 
-    // Much simpler mock - only implement what we need!
-    struct MockAppUserRepo {
-        user_roles: Vec<RoleCode>,
+    // Macro to create a mock repo with specific roles
+    macro_rules! mock_repo {
+        ($($role:expr),*) => {
+            Arc::new(MockAppUserRepo::default().with_user("test_user", vec![$($role),*]))
+        };
     }
 
-    impl MockAppUserRepo {
-        fn with_roles(roles: Vec<RoleCode>) -> Self {
-            Self { user_roles: roles }
-        }
-    }
+    /// The seeded role permissions: Editor reads and writes, Viewer reads.
+    struct MockRoleRepo;
 
     #[async_trait]
-    impl AppUserTableTrait for MockAppUserRepo {
-        // --
-        // Only implement the one method we actually use in guard tests
-        async fn find_roles_by_user(&self, _user: &AppUser) -> Result<Vec<RoleCode>> {
-            Ok(self.user_roles.clone())
+    impl RoleCodeTableTrait for MockRoleRepo {
+        // ---
+        async fn find_permissions_by_roles(&self, roles: &[RoleCode]) -> Result<Vec<Permission>> {
+            // ---
+            let mut permissions = vec![];
+            if roles.contains(&RoleCode::Editor) {
+                permissions.extend([
+                    Permission::CrateRead,
+                    Permission::CrateWrite,
+                    Permission::CrateDelete,
+                    Permission::AuthorRead,
+                    Permission::AuthorWrite,
+                    Permission::AuthorDelete,
+                ]);
+            }
+            if roles.contains(&RoleCode::Viewer) {
+                permissions.extend([Permission::CrateRead, Permission::AuthorRead]);
+            }
+            Ok(permissions)
         }
 
-        // All other methods use the trait defaults, so we don't need to implement them!
+        async fn find_role_codes_by_user(&self, _user_id: i32) -> Result<Vec<RoleCode>> {
+            unreachable!("Not used in guard tests")
+        }
 
-        async fn create(&self, _new_user: NewUser, _role_codes: Vec<RoleCode>) -> Result<AppUser> {
+        async fn find_all(&self) -> Result<Vec<RoleCode>> {
             unreachable!("Not used in guard tests")
         }
 
-        async fn find(&self, _id: i32) -> Result<AppUser> {
+        async fn find_role_name_by_code(&self, _code: RoleCode) -> Result<Role> {
             unreachable!("Not used in guard tests")
         }
 
-        async fn find_by_username(&self, _username: &str) -> Result<AppUser> {
+        async fn find_roles(&self) -> Result<Vec<Role>> {
             unreachable!("Not used in guard tests")
         }
-    }
 
-    // Macro to create a mock repo with specific roles
-    macro_rules! mock_repo {
-        ($($role:expr),*) => {
-            Arc::new(MockAppUserRepo::with_roles(vec![$($role),*]))
-        };
+        async fn find_role(&self, _code: &RoleCode) -> Result<Option<Role>> {
+            unreachable!("Not used in guard tests")
+        }
+
+        async fn create_role(&self, _new_role: &NewRole) -> Result<Role> {
+            unreachable!("Not used in guard tests")
+        }
+
+        async fn rename_role(&self, _code: &RoleCode, _name: &str) -> Result<bool> {
+            unreachable!("Not used in guard tests")
+        }
+
+        async fn set_role_permissions(
+            &self,
+            _code: &RoleCode,
+            _permissions: &[Permission],
+        ) -> Result<bool> {
+            unreachable!("Not used in guard tests")
+        }
+
+        async fn delete_role(&self, _code: &RoleCode) -> Result<bool> {
+            unreachable!("Not used in guard tests")
+        }
     }
 
-    fn create_test_user() -> GuardedAppUser {
-        GuardedAppUser(AppUser {
-            id: 1,
-            username: "test_user".to_string(),
-            password: "hashed_password".to_string(),
-            created_at: Utc::now().naive_utc(),
-        })
+    // Permission guard flow ---
+
+    #[rocket::get("/rustaceans")]
+    fn read_authors(_user: RequirePermission<AuthorRead>) -> &'static str {
+        "ok"
     }
 
-    // Tests begin here ---
+    /// A local Rocket client whose session `s` belongs to user 1 of `user_repo`.
+    async fn client_with_roles(user_repo: AppUserTableTraitPtr) -> Result<Client> {
+        // ---
+        let mock_cache = Arc::new(MockCacheContext::default());
+        mock_cache.add_session("s", 1);
+        let cache: CacheContextTraitPtr = mock_cache;
+
+        let role_repo: RoleCodeTableTraitPtr = Arc::new(MockRoleRepo);
+
+        let rocket = rocket::build()
+            .manage(cache)
+            .manage(user_repo)
+            .manage(role_repo)
+            .mount(
+                "/cr8s",
+                rocket::routes![read_authors, edit_crates, admin_users],
+            );
+        Ok(Client::tracked(rocket).await?)
+    }
 
     #[tokio::test]
-    async fn test_is_editor_with_admin_role() -> anyhow::Result<()> {
-        let user = create_test_user();
-        let repo: AppUserTableTraitPtr = mock_repo!(RoleCode::Admin);
+    async fn test_require_permission_for_viewer() -> anyhow::Result<()> {
+        let client = client_with_roles(mock_repo!(RoleCode::Viewer)).await?;
 
-        let result = user.is_editor(&repo).await?;
-        anyhow::ensure!(result, "Admin should have editor privileges");
+        anyhow::ensure!(status_of(&client, Method::Get, "/cr8s/rustaceans", "s").await == 200);
+        anyhow::ensure!(
+            status_of(&client, Method::Delete, "/cr8s/crates", "s").await == 403,
+            "Viewer must not delete crates"
+        );
+        anyhow::ensure!(status_of(&client, Method::Delete, "/cr8s/users", "s").await == 403);
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_is_editor_with_editor_role() -> anyhow::Result<()> {
-        let user = create_test_user();
-        let repo: AppUserTableTraitPtr = mock_repo!(RoleCode::Editor);
+    async fn test_require_permission_for_editor_and_admin() -> anyhow::Result<()> {
+        let client = client_with_roles(mock_repo!(RoleCode::Viewer, RoleCode::Editor)).await?;
 
-        let result = user.is_editor(&repo).await?;
-        anyhow::ensure!(result, "Editor should have editor privileges");
+        anyhow::ensure!(status_of(&client, Method::Delete, "/cr8s/crates", "s").await == 200);
+        anyhow::ensure!(
+            status_of(&client, Method::Delete, "/cr8s/users", "s").await == 403,
+            "Editor must not manage users"
+        );
+
+        let client = client_with_roles(mock_repo!(RoleCode::Admin)).await?;
+        anyhow::ensure!(status_of(&client, Method::Delete, "/cr8s/users", "s").await == 200);
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_is_editor_with_viewer_role() -> anyhow::Result<()> {
-        let user = create_test_user();
-        let repo: AppUserTableTraitPtr = mock_repo!(RoleCode::Viewer);
+    async fn test_require_permission_without_roles_or_token() -> anyhow::Result<()> {
+        let client = client_with_roles(mock_repo!()).await?;
 
-        let result = user.is_editor(&repo).await?;
-        anyhow::ensure!(!result, "Viewer should not have editor privileges");
+        anyhow::ensure!(
+            status_of(&client, Method::Get, "/cr8s/rustaceans", "s").await == 403,
+            "Users without roles have no permissions"
+        );
+        let response = client.get("/cr8s/rustaceans").dispatch().await;
+        anyhow::ensure!(response.status() == Status::Unauthorized);
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_is_editor_with_multiple_roles() -> anyhow::Result<()> {
-        let user = create_test_user();
-        let repo: AppUserTableTraitPtr = mock_repo!(RoleCode::Viewer, RoleCode::Editor);
+    async fn test_disabled_user_session_is_rejected() -> anyhow::Result<()> {
+        let mock_repo = MockAppUserRepo::default().with_user("test_user", vec![RoleCode::Admin]);
+        mock_repo.edit_user(1, |user| user.disabled_at = Some(Utc::now().naive_utc()));
+        let user_repo: AppUserTableTraitPtr = Arc::new(mock_repo);
+        let client = client_with_roles(user_repo).await?;
 
-        let result = user.is_editor(&repo).await?;
         anyhow::ensure!(
-            result,
-            "User with Editor among multiple roles should have editor privileges"
+            status_of(&client, Method::Get, "/cr8s/rustaceans", "s").await == 401,
+            "Sessions of disabled users must be rejected"
         );
         Ok(())
     }
 
+    // API token guard flow ---
+
+    /// In-memory tokens as `(hash, token)` pairs.
+    #[derive(Default)]
+    struct MockApiTokenRepo {
+        tokens: Mutex<Vec<(String, ApiToken)>>,
+    }
+
+    #[async_trait]
+    impl ApiTokenTableTrait for MockApiTokenRepo {
+        // ---
+        async fn create(
+            &self,
+            user_id: i32,
+            name: &str,
+            token_hash: &str,
+            scopes: &[ApiScope],
+            expires_at: Option<NaiveDateTime>,
+        ) -> Result<ApiToken> {
+            // ---
+            let mut tokens = self.tokens.lock().unwrap();
+            let token = ApiToken {
+                id: tokens.len() as i32 + 1,
+                user_id,
+                name: name.into(),
+                scopes: scopes.to_vec(),
+                created_at: Utc::now().naive_utc(),
+                expires_at,
+                last_used_at: None,
+            };
+            tokens.push((token_hash.into(), token.clone()));
+            Ok(token)
+        }
+
+        async fn find_by_user(&self, _user_id: i32) -> Result<Vec<ApiToken>> {
+            unreachable!("Not used in guard tests")
+        }
+
+        async fn find_by_hash(&self, token_hash: &str) -> Result<Option<ApiToken>> {
+            // ---
+            Ok(self
+                .tokens
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|(hash, _)| hash == token_hash)
+                .map(|(_, token)| token.clone()))
+        }
+
+        async fn revoke(&self, _user_id: i32, _token_id: i32) -> Result<bool> {
+            unreachable!("Not used in guard tests")
+        }
+    }
+
+    #[rocket::get("/crates")]
+    fn read_crates(_user: GuardedAppUser) -> &'static str {
+        "ok"
+    }
+
+    #[rocket::post("/crates")]
+    fn write_crates(_user: GuardedAppUser) -> &'static str {
+        "ok"
+    }
+
+    #[rocket::get("/me")]
+    fn read_me(_user: GuardedAppUser) -> &'static str {
+        "ok"
+    }
+
+    /// A local Rocket client plus an API token holding `scopes`.
+    async fn client_with_token(scopes: Vec<ApiScope>) -> Result<(Client, String)> {
+        // ---
+        let token_repo: ApiTokenTableTraitPtr = Arc::new(MockApiTokenRepo::default());
+        let user_repo: AppUserTableTraitPtr = mock_repo!(RoleCode::Viewer);
+
+        let new_token = NewApiToken {
+            name: "ci".into(),
+            scopes,
+            expires_in_days: None,
+        };
+        let (_, secret) = issue_api_token(token_repo.clone(), 1, new_token).await?;
+
+        let rocket = rocket::build()
+            .manage(token_repo)
+            .manage(user_repo)
+            .mount("/cr8s", rocket::routes![read_crates, write_crates, read_me]);
+
+        Ok((Client::tracked(rocket).await?, secret))
+    }
+
+    async fn status_of(client: &Client, method: Method, path: &str, token: &str) -> u16 {
+        // ---
+        client
+            .req(method, path)
+            .header(rocket::http::Header::new(
+                "Authorization",
+                format!("Bearer {token}"),
+            ))
+            .dispatch()
+            .await
+            .status()
+            .code
+    }
+
     #[tokio::test]
-    async fn test_is_editor_with_no_roles() -> anyhow::Result<()> {
-        let user = create_test_user();
-        let repo: AppUserTableTraitPtr = mock_repo!();
+    async fn test_api_token_scopes_are_enforced() -> anyhow::Result<()> {
+        let (client, token) = client_with_token(vec![ApiScope::CratesRead]).await?;
 
-        let result = user.is_editor(&repo).await?;
+        anyhow::ensure!(status_of(&client, Method::Get, "/cr8s/crates", &token).await == 200);
+        anyhow::ensure!(
+            status_of(&client, Method::Post, "/cr8s/crates", &token).await == 403,
+            "crates:read must not allow writes"
+        );
         anyhow::ensure!(
-            !result,
-            "User with no roles should not have editor privileges"
+            status_of(&client, Method::Get, "/cr8s/me", &token).await == 403,
+            "API tokens must not reach unscoped routes"
         );
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_is_admin_with_admin_role() -> anyhow::Result<()> {
-        let user = create_test_user();
-        let repo: AppUserTableTraitPtr = mock_repo!(RoleCode::Admin);
+    async fn test_api_token_write_scope_and_unknown_token() -> anyhow::Result<()> {
+        let (client, token) = client_with_token(vec![ApiScope::CratesWrite]).await?;
 
-        let result = user.is_admin(&repo).await?;
-        anyhow::ensure!(result, "Admin should have admin privileges");
+        anyhow::ensure!(status_of(&client, Method::Post, "/cr8s/crates", &token).await == 200);
+        anyhow::ensure!(
+            status_of(&client, Method::Get, "/cr8s/crates", &token).await == 403,
+            "crates:write does not imply crates:read"
+        );
+        anyhow::ensure!(
+            status_of(&client, Method::Get, "/cr8s/crates", "cr8s_pat_unknown").await == 401,
+            "unknown API tokens are unauthorized"
+        );
         Ok(())
     }
 
-    #[tokio::test]
-    async fn test_is_admin_with_editor_role() -> anyhow::Result<()> {
-        let user = create_test_user();
-        let repo: AppUserTableTraitPtr = mock_repo!(RoleCode::Editor);
+    // Access token guard flow ---
 
-        let result = user.is_admin(&repo).await?;
-        anyhow::ensure!(!result, "Editor should not have admin privileges");
-        Ok(())
+    /// JWT auth mode cache that only knows one access token; any Redis access panics.
+    struct MockAccessTokenCache;
+
+    #[async_trait]
+    impl CacheContextTrait for MockAccessTokenCache {
+        // ---
+        async fn get_user_id_by_session_token(&self, _token: &str) -> Result<Option<i32>> {
+            unreachable!("Access tokens must not hit the session cache")
+        }
+
+        async fn set_user_session_token(
+            &self,
+            _token: &str,
+            _new_session: NewSession,
+        ) -> Result<SessionRecord> {
+            unreachable!("Not used in guard tests")
+        }
+
+        async fn touch_session_token(&self, _token: &str) -> Result<()> {
+            unreachable!("Access tokens must not hit the session cache")
+        }
+
+        async fn clear_session_token(&self, _token: &str) -> Result<bool> {
+            unreachable!("Not used in guard tests")
+        }
+
+        async fn clear_all_session_tokens(&self, _user_id: i32) -> Result<u64> {
+            unreachable!("Not used in guard tests")
+        }
+
+        fn verify_access_token(&self, token: &str) -> Option<AccessClaims> {
+            (token == "editor-access-token").then(|| AccessClaims {
+                user_id: 7,
+                username: "editor@example.com".into(),
+                user_created_at: Utc::now().naive_utc(),
+                author_id: None,
+                role_codes: vec![RoleCode::Editor],
+                session_id: "s-1".into(),
+            })
+        }
+
+        fn requires_access_tokens(&self) -> bool {
+            true
+        }
+    }
+
+    #[rocket::get("/me/id")]
+    fn read_my_id(user: GuardedAppUser) -> String {
+        user.0.id.to_string()
+    }
+
+    #[rocket::delete("/crates")]
+    fn edit_crates(_user: RequirePermission<CrateDelete>) -> &'static str {
+        "ok"
+    }
+
+    #[rocket::delete("/users")]
+    fn admin_users(_user: RequirePermission<UserManage>) -> &'static str {
+        "ok"
+    }
+
+    /// The address the login route counts per-IP failures against.
+    #[rocket::get("/throttle-key")]
+    fn throttle_key(client: ClientInfo) -> String {
+        client.ip.unwrap_or_default()
     }
 
     #[tokio::test]
-    async fn test_is_admin_with_viewer_role() -> anyhow::Result<()> {
-        let user = create_test_user();
-        let repo: AppUserTableTraitPtr = mock_repo!(RoleCode::Viewer);
+    async fn test_spoofed_ip_header_keeps_the_ip_throttle_key() -> anyhow::Result<()> {
+        // ---
+        let client =
+            Client::tracked(rocket::build().mount("/", rocket::routes![throttle_key])).await?;
+        let peer: std::net::SocketAddr = "203.0.113.5:40000".parse()?;
+
+        let mut keys = vec![];
+        for spoofed in [None, Some("10.0.0.1"), Some("10.0.0.2")] {
+            let mut request = client.get("/throttle-key").remote(peer);
+            if let Some(ip) = spoofed {
+                request = request.header(rocket::http::Header::new("X-Real-IP", ip));
+            }
+            keys.push(
+                request
+                    .dispatch()
+                    .await
+                    .into_string()
+                    .await
+                    .unwrap_or_default(),
+            );
+        }
+        anyhow::ensure!(
+            keys.iter().all(|key| key == "203.0.113.5"),
+            "a rotated X-Real-IP must not start a fresh per-IP counter: {keys:?}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_ip_header_is_believed_only_from_trusted_proxies() {
+        // ---
+        let ip = |s: &str| s.parse::<IpAddr>().unwrap();
+        let proxies = [ip("10.0.0.2")];
+
+        let via_proxy = resolve_client_ip(Some(ip("10.0.0.2")), Some(ip("198.51.100.7")), &proxies);
+        assert_eq!(via_proxy, Some(ip("198.51.100.7")));
+        let spoofed =
+            resolve_client_ip(Some(ip("203.0.113.5")), Some(ip("198.51.100.7")), &proxies);
+        assert_eq!(spoofed, Some(ip("203.0.113.5")));
+        let no_header = resolve_client_ip(Some(ip("10.0.0.2")), None, &proxies);
+        assert_eq!(no_header, Some(ip("10.0.0.2")));
+        assert_eq!(
+            resolve_client_ip(None, Some(ip("198.51.100.7")), &proxies),
+            None
+        );
+    }
 
-        let result = user.is_admin(&repo).await?;
-        anyhow::ensure!(!result, "Viewer should not have admin privileges");
+    #[tokio::test]
+    async fn test_access_token_authorizes_from_claims() -> anyhow::Result<()> {
+        // The database says Viewer; the access token's Editor role must win
+        let cache: CacheContextTraitPtr = Arc::new(MockAccessTokenCache);
+        let user_repo: AppUserTableTraitPtr = mock_repo!(RoleCode::Viewer);
+
+        let role_repo: RoleCodeTableTraitPtr = Arc::new(MockRoleRepo);
+
+        let rocket = rocket::build()
+            .manage(cache)
+            .manage(user_repo)
+            .manage(role_repo)
+            .mount(
+                "/cr8s",
+                rocket::routes![read_my_id, edit_crates, admin_users],
+            );
+        let client = Client::tracked(rocket).await?;
+
+        let response = client
+            .get("/cr8s/me/id")
+            .header(rocket::http::Header::new(
+                "Authorization",
+                "Bearer editor-access-token",
+            ))
+            .dispatch()
+            .await;
+        anyhow::ensure!(response.into_string().await.as_deref() == Some("7"));
+
+        let token = "editor-access-token";
+        anyhow::ensure!(status_of(&client, Method::Delete, "/cr8s/crates", token).await == 200);
+        anyhow::ensure!(
+            status_of(&client, Method::Delete, "/cr8s/users", token).await == 403,
+            "Editor claims must not grant admin"
+        );
+        anyhow::ensure!(
+            status_of(
+                &client,
+                Method::Get,
+                "/cr8s/me/id",
+                "refresh-or-session-token"
+            )
+            .await
+                == 401,
+            "Session tokens are only refresh tokens in JWT auth mode"
+        );
         Ok(())
     }
 }
@@ -12,35 +12,91 @@
 //! It does *not* contain business logic or direct database access.
 //! All logic is delegated to domain traits, ensuring separation of concerns.
 
+mod api_tokens;
+mod audit;
 mod authorization;
 mod authors;
 mod crates;
 mod guards;
 mod health;
+mod oidc;
+mod password_reset;
+mod roles;
 mod support;
+mod two_factor;
+mod users;
 
-pub use support::{options, server_error, Cors};
+pub use support::{bad_request, invalid_field, options, password_error, server_error, Cors};
 
-pub use authorization::{login, me};
+pub use api_tokens::{create_my_api_token, list_my_api_tokens, revoke_my_api_token};
+
+pub use audit::list_audit_events;
+
+pub use authorization::{
+    // ---
+    change_my_password,
+    delete_my_session,
+    list_my_sessions,
+    login,
+    logout,
+    logout_all,
+    me,
+    refresh_access_token,
+    reset_user_password,
+    RefreshRequest,
+};
 
 pub use crates::{
     // ---
     create_crate,
+    create_crate_version,
     delete_crate,
     get_crates,
+    list_crate_versions,
+    search_crates,
+    unyank_crate_version,
     update_crate,
     view_crate,
+    yank_crate_version,
 };
 
-pub use guards::{EditorUser, GuardedAppUser};
+pub use guards::{
+    // ---
+    AuditRead,
+    AuthorDelete,
+    AuthorRead,
+    AuthorWrite,
+    ClientInfo,
+    CrateDelete,
+    CrateRead,
+    CrateWrite,
+    CrateWriteAccess,
+    CrateWriteOwn,
+    GuardedAppUser,
+    PermissionMarker,
+    RequirePermission,
+    RoleManage,
+    SessionToken,
+    UserManage,
+};
 
 pub use health::health_endpoint;
 
+pub use password_reset::{password_reset_confirm, password_reset_request};
+
+pub use oidc::{oidc_callback, oidc_start};
+pub use roles::{create_role, delete_role, list_roles, set_user_roles, update_role};
+pub use two_factor::{confirm_my_totp, enroll_my_totp, login_second_factor};
+pub use users::{create_user, delete_user, disable_user, enable_user, list_users, view_user};
+
 pub use authors::{
     // ---
+    confirm_my_author,
     create_rustacean,
     delete_rustacean,
     get_rustaceans,
+    link_my_author,
+    link_user_author,
     update_rustacean,
     view_rustacean,
 };
@@ -74,6 +130,7 @@ pub fn index() -> rocket::response::content::RawHtml<&'static str> {
             <ul>
                 <li><a href="/cr8s/health" class="api-link">/cr8s/health</a> - Health check</li>
                 <li><a href="/cr8s/login" class="api-link">/cr8s/login</a> - Authentication (POST)</li>
+                <li><a href="/cr8s/logout" class="api-link">/cr8s/logout</a> - Revoke current session (POST)</li>
                 <li><a href="/cr8s/rustaceans" class="api-link">/cr8s/rustaceans</a> - Authors (requires auth)</li>
                 <li><a href="/cr8s/crates" class="api-link">/cr8s/crates</a> - Crates (requires auth)</li>
             </ul>
@@ -0,0 +1,220 @@
+// rocket_routes/oidc.rs
+use super::authorization::start_session;
+use super::{server_error, ClientInfo};
+use crate::domain::{
+    //
+    complete_oidc_login,
+    start_oidc_login,
+    AppUserTableTraitPtr,
+    CacheContextTraitPtr,
+    OidcError,
+    OidcProviderTraitPtr,
+};
+use rocket::http::Status;
+use rocket::response::status::Custom;
+use rocket::response::Redirect;
+use rocket::serde::json::{json, Value};
+use rocket::State;
+
+fn oidc_error(err: OidcError) -> Custom<Value> {
+    // ---
+    match err {
+        OidcError::NotConfigured => Custom(
+            Status::NotFound,
+            json!({ "error": "Single sign-on is not configured" }),
+        ),
+        OidcError::InvalidState | OidcError::Rejected(_) => {
+            tracing::warn!("❌ Single sign-on failed: {err}");
+            Custom(
+                Status::Unauthorized,
+                json!({ "error": "Single sign-on failed" }),
+            )
+        }
+        OidcError::UsernameTaken(_) => {
+            tracing::warn!("❌ Single sign-on refused: {err}");
+            Custom(
+                Status::Conflict,
+                json!({ "error": "An account with this username already exists" }),
+            )
+        }
+        OidcError::AccountDisabled(_) => {
+            tracing::warn!("❌ Single sign-on refused: {err}");
+            Custom(Status::Forbidden, json!({ "error": "Account is disabled" }))
+        }
+        OidcError::Internal(err) => server_error(err),
+    }
+}
+
+/// Start a single sign-on: redirect the browser to the identity provider.
+#[rocket::get("/auth/oidc/start")]
+pub async fn oidc_start(
+    oidc_provider: &State<OidcProviderTraitPtr>,
+    cache_context: &State<CacheContextTraitPtr>,
+) -> Result<Redirect, Custom<Value>> {
+    // ---
+    start_oidc_login(oidc_provider.inner(), cache_context.inner())
+        .await
+        .map(Redirect::to)
+        .map_err(oidc_error)
+}
+
+/// Identity provider callback: finish the login and issue a session, answering like
+/// `/login`. Local two-factor authentication is left to the identity provider.
+#[rocket::get("/auth/oidc/callback?<code>&<state>&<error>")]
+pub async fn oidc_callback(
+    app_user_repo: &State<AppUserTableTraitPtr>,
+    cache_context: &State<CacheContextTraitPtr>,
+    oidc_provider: &State<OidcProviderTraitPtr>,
+    client: ClientInfo,
+    code: Option<&str>,
+    state: Option<&str>,
+    error: Option<&str>,
+) -> Result<Value, Custom<Value>> {
+    // ---
+    let (Some(code), Some(state)) = (code, state) else {
+        tracing::warn!("❌ Single sign-on callback without code: {error:?}");
+        return Err(Custom(
+            Status::Unauthorized,
+            json!({ "error": "Single sign-on failed", "reason": error }),
+        ));
+    };
+
+    let user = complete_oidc_login(
+        app_user_repo.inner().clone(),
+        cache_context.inner().clone(),
+        oidc_provider.inner().clone(),
+        code,
+        state,
+    )
+    .await
+    .map_err(oidc_error)?;
+
+    let session_id = crate::auth::create_password_hasher()
+        .map_err(server_error)?
+        .generate_session_token();
+
+    tracing::info!("✅ Single sign-on for user id {}", user.id);
+    start_session(app_user_repo, cache_context, client, &user, &session_id).await
+}
+
+#[cfg(test)]
+mod tests {
+    // ---
+    use super::*;
+    use crate::domain::{
+        //
+        create_unconfigured_oidc_provider,
+        OidcIdentity,
+        OidcProviderTrait,
+        RoleCode,
+    };
+    use crate::test_support::{MockAppUserRepo, MockCacheContext};
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use rocket::local::asynchronous::Client;
+    use std::sync::Arc;
+
+    /// IdP that accepts the code `"good-code"`.
+    struct MockProvider;
+
+    #[async_trait]
+    impl OidcProviderTrait for MockProvider {
+        // ---
+        fn authorization_url(&self, state: &str, _nonce: &str, _challenge: &str) -> Result<String> {
+            Ok(format!("https://idp.example.com/authorize?state={state}"))
+        }
+
+        async fn exchange_code(
+            &self,
+            code: &str,
+            _code_verifier: &str,
+            _nonce: &str,
+        ) -> Result<OidcIdentity> {
+            // ---
+            anyhow::ensure!(code == "good-code", "invalid_grant");
+            Ok(OidcIdentity {
+                issuer: "https://idp.example.com".into(),
+                subject: "sub-1".into(),
+                email: Some("sso@example.com".into()),
+                email_verified: true,
+                preferred_username: None,
+            })
+        }
+
+        fn default_role(&self) -> RoleCode {
+            RoleCode::Viewer
+        }
+    }
+
+    async fn client_with(
+        provider: OidcProviderTraitPtr,
+    ) -> Result<(Client, Arc<MockCacheContext>)> {
+        // ---
+        let mock_cache = Arc::new(MockCacheContext::default());
+        let cache: CacheContextTraitPtr = mock_cache.clone();
+        let repo: AppUserTableTraitPtr = Arc::new(MockAppUserRepo::default());
+
+        let rocket = rocket::build()
+            .manage(cache)
+            .manage(repo)
+            .manage(provider)
+            .mount("/cr8s", rocket::routes![oidc_start, oidc_callback]);
+
+        Ok((Client::tracked(rocket).await?, mock_cache))
+    }
+
+    #[tokio::test]
+    async fn test_oidc_start_and_callback_issue_session() -> Result<()> {
+        // ---
+        let (client, cache) = client_with(Arc::new(MockProvider)).await?;
+
+        let response = client.get("/cr8s/auth/oidc/start").dispatch().await;
+        assert_eq!(response.status(), Status::SeeOther);
+        let location = response.headers().get_one("Location").unwrap().to_string();
+        let state = location.split("state=").nth(1).unwrap();
+
+        let response = client
+            .get(format!("/cr8s/auth/oidc/callback?code=bad&state={state}"))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Unauthorized);
+
+        // A fresh login succeeds and starts a normal session
+        let response = client.get("/cr8s/auth/oidc/start").dispatch().await;
+        let location = response.headers().get_one("Location").unwrap().to_string();
+        let state = location.split("state=").nth(1).unwrap();
+
+        let response = client
+            .get(format!(
+                "/cr8s/auth/oidc/callback?code=good-code&state={state}"
+            ))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+        let body: Value = response.into_json().await.unwrap();
+        assert_eq!(cache.session_tokens(1), [body["token"].as_str().unwrap()]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_oidc_routes_when_not_configured() -> Result<()> {
+        // ---
+        let (client, _) = client_with(create_unconfigured_oidc_provider("no issuer")).await?;
+
+        let response = client.get("/cr8s/auth/oidc/start").dispatch().await;
+        assert_eq!(response.status(), Status::NotFound);
+
+        let response = client
+            .get("/cr8s/auth/oidc/callback?code=c&state=s")
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::NotFound);
+
+        let response = client
+            .get("/cr8s/auth/oidc/callback?error=access_denied")
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Unauthorized);
+        Ok(())
+    }
+}
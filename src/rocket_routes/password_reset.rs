@@ -0,0 +1,249 @@
+// rocket_routes/password_reset.rs
+use super::{password_error, server_error};
+use crate::domain::{
+    //
+    confirm_password_reset,
+    request_password_reset,
+    AppUserTableTraitPtr,
+    CacheContextTraitPtr,
+    MailerTraitPtr,
+    PasswordResetConfirm,
+    PasswordResetRequest,
+};
+use rocket::http::Status;
+use rocket::response::status::{Accepted, Custom};
+use rocket::serde::json::{json, Json, Value};
+use rocket::State;
+
+/// Email a single-use reset token to the user.
+///
+/// Always answers 202 so the response does not reveal whether the account exists or
+/// whether the mail could be sent; failures are only logged.
+#[rocket::post("/password-reset/request", format = "json", data = "<request>")]
+pub async fn password_reset_request(
+    app_user_repo: &State<AppUserTableTraitPtr>,
+    cache_context: &State<CacheContextTraitPtr>,
+    mailer: &State<MailerTraitPtr>,
+    request: Json<PasswordResetRequest>,
+) -> Accepted<Value> {
+    // ---
+    let result = request_password_reset(
+        app_user_repo.inner().clone(),
+        cache_context.inner().clone(),
+        mailer.inner().clone(),
+        request.into_inner(),
+    )
+    .await;
+
+    if let Err(err) = result {
+        tracing::warn!("❌ Password reset request failed: {err:#}");
+    }
+
+    Accepted(json!({ "status": "If the account exists, a reset email has been sent" }))
+}
+
+/// Set a new password using an emailed reset token, then revoke all of the user's
+/// sessions.
+#[rocket::post("/password-reset/confirm", format = "json", data = "<confirm>")]
+pub async fn password_reset_confirm(
+    app_user_repo: &State<AppUserTableTraitPtr>,
+    cache_context: &State<CacheContextTraitPtr>,
+    confirm: Json<PasswordResetConfirm>,
+) -> Result<Value, Custom<Value>> {
+    // ---
+    let user_id = confirm_password_reset(
+        app_user_repo.inner().clone(),
+        cache_context.inner().clone(),
+        confirm.into_inner(),
+    )
+    .await
+    .map_err(password_error)?;
+
+    let Some(user_id) = user_id else {
+        return Err(Custom(
+            Status::BadRequest,
+            json!({ "error": "Invalid or expired reset token" }),
+        ));
+    };
+
+    let revoked = cache_context
+        .clear_all_session_tokens(user_id)
+        .await
+        .map_err(server_error)?;
+
+    tracing::info!(
+        "🔑 Password reset completed for user id {user_id}, revoked {revoked} session(s)"
+    );
+    Ok(json!({ "revoked": revoked }))
+}
+
+#[cfg(test)]
+mod tests {
+    // ---
+    use super::*;
+    use crate::test_support::{MockAppUserRepo, MockCacheContext, MockMailer};
+    use std::sync::Arc;
+
+    /// User 1 is `alice@example.com`.
+    fn mock_repo() -> Arc<MockAppUserRepo> {
+        Arc::new(MockAppUserRepo::default().with_user("alice@example.com", vec![]))
+    }
+
+    fn reset_request(username: &str) -> Json<PasswordResetRequest> {
+        Json(PasswordResetRequest {
+            username: username.into(),
+        })
+    }
+
+    fn reset_confirm(token: &str) -> Json<PasswordResetConfirm> {
+        reset_confirm_with(token, "brand-new-password")
+    }
+
+    fn reset_confirm_with(token: &str, new_password: &str) -> Json<PasswordResetConfirm> {
+        Json(PasswordResetConfirm {
+            token: token.into(),
+            new_password: new_password.into(),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_password_reset_round_trip() {
+        // ---
+        let mock_repo = mock_repo();
+        let repo: AppUserTableTraitPtr = mock_repo.clone();
+        let mock_cache = Arc::new(MockCacheContext::default());
+        mock_cache.add_session("laptop", 1);
+        mock_cache.add_session("phone", 1);
+        let cache: CacheContextTraitPtr = mock_cache.clone();
+        let mock_mailer = Arc::new(MockMailer::default());
+        let mailer: MailerTraitPtr = mock_mailer.clone();
+
+        let _ = password_reset_request(
+            State::from(&repo),
+            State::from(&cache),
+            State::from(&mailer),
+            reset_request("alice@example.com"),
+        )
+        .await;
+
+        let sent = mock_mailer.sent.lock().unwrap().clone();
+        assert_eq!(sent.len(), 1, "expected one reset email");
+        let (to, template, context) = &sent[0];
+        assert_eq!(to, "alice@example.com");
+        assert_eq!(template, "email/password_reset.html");
+        let token = context["token"].as_str().unwrap().to_string();
+        // No reset page is configured, so there is nothing to link to
+        assert!(context["link"].is_null());
+
+        match password_reset_confirm(
+            State::from(&repo),
+            State::from(&cache),
+            reset_confirm(&token),
+        )
+        .await
+        {
+            Ok(value) => assert_eq!(value["revoked"], 2),
+            Err(e) => panic!("Expected success but got error: {:?}", e),
+        }
+
+        let stored_hash = mock_repo.user(1).password;
+        let hasher = crate::auth::create_password_hasher().unwrap();
+        assert!(hasher
+            .verify_password(&stored_hash, "brand-new-password")
+            .is_ok());
+
+        // Tokens are single-use
+        match password_reset_confirm(
+            State::from(&repo),
+            State::from(&cache),
+            reset_confirm(&token),
+        )
+        .await
+        {
+            Err(Custom(status, _)) => assert_eq!(status.code, 400),
+            Ok(_) => panic!("Expected reused token to be rejected"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_password_reset_request_unknown_user_sends_nothing() {
+        // ---
+        let repo: AppUserTableTraitPtr = mock_repo();
+        let mock_cache = Arc::new(MockCacheContext::default());
+        let cache: CacheContextTraitPtr = mock_cache.clone();
+        let mock_mailer = Arc::new(MockMailer::default());
+        let mailer: MailerTraitPtr = mock_mailer.clone();
+
+        let response = password_reset_request(
+            State::from(&repo),
+            State::from(&cache),
+            State::from(&mailer),
+            reset_request("nobody@example.com"),
+        )
+        .await;
+
+        assert!(response.0["status"].is_string());
+        assert!(mock_mailer.sent.lock().unwrap().is_empty());
+        assert!(mock_cache.reset_tokens.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_password_reset_rejected_password_keeps_token() {
+        // ---
+        let mock_repo = mock_repo();
+        let repo: AppUserTableTraitPtr = mock_repo.clone();
+        let mock_cache = Arc::new(MockCacheContext::default());
+        let cache: CacheContextTraitPtr = mock_cache.clone();
+        cache
+            .set_password_reset_token("reset-token", 1, 900)
+            .await
+            .unwrap();
+
+        // Only the username rule knows this password is too close to alice@example.com
+        match password_reset_confirm(
+            State::from(&repo),
+            State::from(&cache),
+            reset_confirm_with("reset-token", "alice-in-wonderland"),
+        )
+        .await
+        {
+            Err(Custom(status, _)) => assert_eq!(status.code, 400),
+            Ok(_) => panic!("Expected a password containing the username to be rejected"),
+        }
+        assert_eq!(mock_repo.user(1).password, "hash");
+        assert_eq!(
+            mock_cache.reset_tokens.lock().unwrap().get("reset-token"),
+            Some(&1),
+            "a rejected password must not use up the token"
+        );
+
+        let result = password_reset_confirm(
+            State::from(&repo),
+            State::from(&cache),
+            reset_confirm("reset-token"),
+        )
+        .await;
+        assert!(result.is_ok(), "the token still works: {result:?}");
+        assert!(mock_cache.reset_tokens.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_password_reset_confirm_unknown_token() {
+        // ---
+        let mock_repo = mock_repo();
+        let repo: AppUserTableTraitPtr = mock_repo.clone();
+        let cache: CacheContextTraitPtr = Arc::new(MockCacheContext::default());
+
+        match password_reset_confirm(
+            State::from(&repo),
+            State::from(&cache),
+            reset_confirm("bogus"),
+        )
+        .await
+        {
+            Err(Custom(status, _)) => assert_eq!(status.code, 400),
+            Ok(_) => panic!("Expected unknown token to be rejected"),
+        }
+        assert_eq!(mock_repo.user(1).password, "hash");
+    }
+}
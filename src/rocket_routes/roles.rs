@@ -0,0 +1,392 @@
+// rocket_routes/roles.rs
+//! Admin-only management of roles, the permissions they grant, and who holds them.
+use super::{server_error, RequirePermission, RoleManage};
+use crate::domain::{
+    //
+    assign_roles,
+    record_audit_event,
+    AppUserTableTraitPtr,
+    AuditEntity,
+    AuditLogTraitPtr,
+    CacheContextTraitPtr,
+    NewAuditEvent,
+    NewRole,
+    RoleAssignment,
+    RoleCode,
+    RoleCodeTableTraitPtr,
+    RoleError,
+    RoleUpdate,
+    UserRoles,
+    UserSummary,
+};
+use rocket::http::Status;
+use rocket::response::status::{Custom, NoContent};
+use rocket::serde::json::{json, Json, Value};
+use rocket::State;
+
+fn role_error(err: RoleError) -> Custom<Value> {
+    // ---
+    match err {
+        RoleError::Invalid(_) | RoleError::Protected(..) => {
+            Custom(Status::BadRequest, json!({ "error": err.to_string() }))
+        }
+        RoleError::NotFound(_) => Custom(Status::NotFound, json!({ "error": "Role not found" })),
+        RoleError::AlreadyExists(_) => {
+            Custom(Status::Conflict, json!({ "error": err.to_string() }))
+        }
+        RoleError::Internal(err) => server_error(err),
+    }
+}
+
+fn parse_role_code(code: &str) -> Result<RoleCode, Custom<Value>> {
+    // ---
+    code.parse()
+        .map_err(|_| role_error(RoleError::Invalid(format!("invalid role code: '{code}'"))))
+}
+
+/// List all roles with their permissions.
+#[rocket::get("/roles")]
+pub async fn list_roles(
+    role_repo: &State<RoleCodeTableTraitPtr>,
+    _admin: RequirePermission<RoleManage>,
+) -> Result<Value, Custom<Value>> {
+    // ---
+    role_repo
+        .find_roles()
+        .await
+        .map(|roles| json!(roles))
+        .map_err(server_error)
+}
+
+/// Create a role, e.g. `{"code": "Publisher", "name": "Publisher", "permissions": ["crate:write"]}`.
+#[rocket::post("/roles", format = "json", data = "<new_role>")]
+pub async fn create_role(
+    role_repo: &State<RoleCodeTableTraitPtr>,
+    admin: RequirePermission<RoleManage>,
+    new_role: Json<NewRole>,
+) -> Result<Custom<Value>, Custom<Value>> {
+    // ---
+    let role = crate::domain::create_role(role_repo.inner(), new_role.into_inner())
+        .await
+        .map_err(role_error)?;
+
+    tracing::info!(
+        "🛡️ Role {} created by admin id {}",
+        role.code,
+        admin.0 .0.id
+    );
+    Ok(Custom(Status::Created, json!(role)))
+}
+
+/// Rename a role and/or replace its permissions.
+#[rocket::put("/roles/<code>", format = "json", data = "<update>")]
+pub async fn update_role(
+    role_repo: &State<RoleCodeTableTraitPtr>,
+    admin: RequirePermission<RoleManage>,
+    code: &str,
+    update: Json<RoleUpdate>,
+) -> Result<Value, Custom<Value>> {
+    // ---
+    let code = parse_role_code(code)?;
+    let role = crate::domain::update_role(role_repo.inner(), &code, update.into_inner())
+        .await
+        .map_err(role_error)?;
+
+    tracing::info!("🛡️ Role {code} updated by admin id {}", admin.0 .0.id);
+    Ok(json!(role))
+}
+
+/// Delete a runtime-created role; users holding it lose it.
+#[rocket::delete("/roles/<code>")]
+pub async fn delete_role(
+    role_repo: &State<RoleCodeTableTraitPtr>,
+    admin: RequirePermission<RoleManage>,
+    code: &str,
+) -> Result<NoContent, Custom<Value>> {
+    // ---
+    let code = parse_role_code(code)?;
+    crate::domain::delete_role(role_repo.inner(), &code)
+        .await
+        .map_err(role_error)?;
+
+    tracing::info!("🛡️ Role {code} deleted by admin id {}", admin.0 .0.id);
+    Ok(NoContent)
+}
+
+/// Replace the roles a user holds, e.g. `{"roles": ["Editor", "Viewer"]}`; the user's
+/// live sessions pick up the change.
+#[rocket::put("/users/<id>/roles", format = "json", data = "<user_roles>")]
+pub async fn set_user_roles(
+    app_user_repo: &State<AppUserTableTraitPtr>,
+    role_repo: &State<RoleCodeTableTraitPtr>,
+    cache_context: &State<CacheContextTraitPtr>,
+    audit_log: &State<AuditLogTraitPtr>,
+    admin: RequirePermission<RoleManage>,
+    id: i32,
+    user_roles: Json<UserRoles>,
+) -> Result<Value, Custom<Value>> {
+    // ---
+    let Ok(user) = app_user_repo.find(id).await else {
+        return Err(Custom(
+            Status::NotFound,
+            json!({ "error": "User not found" }),
+        ));
+    };
+    let roles = app_user_repo
+        .find_roles_by_user(&user)
+        .await
+        .map_err(server_error)?;
+    let before = UserSummary::from((user.clone(), roles));
+
+    let assigned = assign_roles(
+        app_user_repo.inner(),
+        role_repo.inner(),
+        cache_context.inner(),
+        &user,
+        RoleAssignment::Replace(user_roles.into_inner().roles),
+    )
+    .await
+    .map_err(role_error)?;
+
+    let after = UserSummary::from((user, assigned.roles.clone()));
+    let event = NewAuditEvent::updated(AuditEntity::User, id, Some(&before), &after);
+    record_audit_event(audit_log, event.by(admin.0 .0.id)).await;
+
+    tracing::info!(
+        "🛡️ Roles of user id {id} set to {:?} by admin id {}, {} session(s) updated",
+        assigned.roles,
+        admin.0 .0.id,
+        assigned.sessions_updated
+    );
+    Ok(json!(assigned))
+}
+
+#[cfg(test)]
+mod tests {
+    // ---
+    use super::*;
+    use crate::domain::{
+        //
+        AppUser,
+        AuditAction,
+        Permission,
+        Role,
+        RoleCodeTableTrait,
+    };
+    use crate::rocket_routes::GuardedAppUser;
+    use crate::test_support::{MockAppUserRepo, MockAuditLog, MockCacheContext};
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use chrono::Utc;
+    use std::sync::{Arc, Mutex};
+
+    /// Role table holding only the seeded Viewer role.
+    struct MockRoleRepo {
+        roles: Mutex<Vec<Role>>,
+    }
+
+    impl MockRoleRepo {
+        fn new() -> Self {
+            let viewer = Role {
+                id: 3,
+                code: RoleCode::Viewer,
+                name: "Viewer".into(),
+                permissions: vec![Permission::CrateRead, Permission::AuthorRead],
+            };
+            Self {
+                roles: Mutex::new(vec![viewer]),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl RoleCodeTableTrait for MockRoleRepo {
+        // ---
+        async fn find_role_codes_by_user(&self, _user_id: i32) -> Result<Vec<RoleCode>> {
+            unreachable!()
+        }
+
+        async fn find_all(&self) -> Result<Vec<RoleCode>> {
+            let roles = self.roles.lock().unwrap();
+            Ok(roles.iter().map(|role| role.code.clone()).collect())
+        }
+
+        async fn find_role_name_by_code(&self, _code: RoleCode) -> Result<Role> {
+            unreachable!()
+        }
+
+        async fn find_roles(&self) -> Result<Vec<Role>> {
+            Ok(self.roles.lock().unwrap().clone())
+        }
+
+        async fn find_role(&self, code: &RoleCode) -> Result<Option<Role>> {
+            let roles = self.roles.lock().unwrap();
+            Ok(roles.iter().find(|role| role.code == *code).cloned())
+        }
+
+        async fn create_role(&self, new_role: &NewRole) -> Result<Role> {
+            // ---
+            let role = Role {
+                id: 4,
+                code: new_role.code.clone(),
+                name: new_role.name.clone(),
+                permissions: new_role.permissions.clone(),
+            };
+            self.roles.lock().unwrap().push(role.clone());
+            Ok(role)
+        }
+
+        async fn rename_role(&self, code: &RoleCode, name: &str) -> Result<bool> {
+            // ---
+            let mut roles = self.roles.lock().unwrap();
+            let role = roles.iter_mut().find(|role| role.code == *code);
+            Ok(role.map(|role| role.name = name.into()).is_some())
+        }
+
+        async fn set_role_permissions(
+            &self,
+            code: &RoleCode,
+            permissions: &[Permission],
+        ) -> Result<bool> {
+            // ---
+            let mut roles = self.roles.lock().unwrap();
+            let role = roles.iter_mut().find(|role| role.code == *code);
+            Ok(role
+                .map(|role| role.permissions = permissions.to_vec())
+                .is_some())
+        }
+
+        async fn delete_role(&self, code: &RoleCode) -> Result<bool> {
+            // ---
+            let mut roles = self.roles.lock().unwrap();
+            let before = roles.len();
+            roles.retain(|role| role.code != *code);
+            Ok(roles.len() < before)
+        }
+
+        async fn find_permissions_by_roles(&self, _roles: &[RoleCode]) -> Result<Vec<Permission>> {
+            unreachable!()
+        }
+    }
+
+    fn admin() -> RequirePermission<RoleManage> {
+        RequirePermission::new(GuardedAppUser(AppUser {
+            id: 1,
+            username: "admin".into(),
+            password: "hash".into(),
+            created_at: Utc::now().naive_utc(),
+            author_id: None,
+            disabled_at: None,
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_create_update_and_delete_role() {
+        // ---
+        let repo: RoleCodeTableTraitPtr = Arc::new(MockRoleRepo::new());
+        let new_role = NewRole {
+            code: RoleCode::Custom("Publisher".into()),
+            name: "Publisher".into(),
+            permissions: vec![Permission::CrateWrite],
+        };
+
+        let created = create_role(State::from(&repo), admin(), Json(new_role.clone()))
+            .await
+            .unwrap();
+        assert_eq!(created.0, Status::Created);
+        assert_eq!(created.1["permissions"], json!(["crate:write"]));
+
+        let duplicate = create_role(State::from(&repo), admin(), Json(new_role)).await;
+        assert_eq!(duplicate.unwrap_err().0, Status::Conflict);
+
+        let update = RoleUpdate {
+            name: None,
+            permissions: Some(vec![Permission::CrateRead, Permission::CrateWrite]),
+        };
+        let updated = update_role(State::from(&repo), admin(), "Publisher", Json(update))
+            .await
+            .unwrap();
+        assert_eq!(updated["permissions"], json!(["crate:read", "crate:write"]));
+
+        let listed = list_roles(State::from(&repo), admin()).await.unwrap();
+        assert_eq!(listed.as_array().unwrap().len(), 2);
+
+        assert!(delete_role(State::from(&repo), admin(), "Publisher")
+            .await
+            .is_ok());
+        let missing = delete_role(State::from(&repo), admin(), "Publisher").await;
+        assert_eq!(missing.unwrap_err().0, Status::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_seeded_and_invalid_roles_are_rejected() {
+        // ---
+        let repo: RoleCodeTableTraitPtr = Arc::new(MockRoleRepo::new());
+
+        let seeded = delete_role(State::from(&repo), admin(), "Viewer").await;
+        assert_eq!(seeded.unwrap_err().0, Status::BadRequest);
+
+        let invalid = delete_role(State::from(&repo), admin(), "not a role").await;
+        assert_eq!(invalid.unwrap_err().0, Status::BadRequest);
+
+        let update = RoleUpdate {
+            name: Some("Root".into()),
+            permissions: None,
+        };
+        let missing = update_role(State::from(&repo), admin(), "Root", Json(update)).await;
+        assert_eq!(missing.unwrap_err().0, Status::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_set_user_roles() {
+        // ---
+        let users: AppUserTableTraitPtr = Arc::new(
+            MockAppUserRepo::default()
+                .with_user("admin", vec![RoleCode::Admin])
+                .with_user("editor", vec![RoleCode::Viewer]),
+        );
+        let roles: RoleCodeTableTraitPtr = Arc::new(MockRoleRepo::new());
+        let mock_cache = Arc::new(MockCacheContext::default());
+        mock_cache.add_session("laptop", 2);
+        mock_cache.add_session("phone", 2);
+        let cache: CacheContextTraitPtr = mock_cache.clone();
+        let mock_log = Arc::new(MockAuditLog::default());
+        let audit: AuditLogTraitPtr = mock_log.clone();
+        let body = |roles: &[&str]| {
+            let roles = roles.iter().map(|code| code.parse().unwrap()).collect();
+            Json(UserRoles { roles })
+        };
+
+        let set = |id, body| {
+            set_user_roles(
+                State::from(&users),
+                State::from(&roles),
+                State::from(&cache),
+                State::from(&audit),
+                admin(),
+                id,
+                body,
+            )
+        };
+
+        let missing_user = set(9, body(&["Viewer"])).await;
+        assert_eq!(missing_user.unwrap_err().0, Status::NotFound);
+
+        let unknown_role = set(2, body(&["Publisher"])).await;
+        assert_eq!(unknown_role.unwrap_err().0, Status::NotFound);
+
+        let assigned = set(2, body(&[])).await.unwrap();
+        assert_eq!(assigned, json!({ "roles": [], "sessions_updated": 2 }));
+
+        // Only the change that went through is audited, with the roles before and after
+        let events = mock_log.events.lock().unwrap();
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event.action, AuditAction::Update);
+        assert_eq!(event.entity_type, AuditEntity::User);
+        assert_eq!(event.entity_id, 2);
+        assert_eq!(event.actor_user_id, Some(1));
+        assert_eq!(event.before.as_ref().unwrap()["roles"], json!(["Viewer"]));
+        assert_eq!(event.after.as_ref().unwrap()["roles"], json!([]));
+    }
+}
@@ -1,6 +1,7 @@
 // src/rocket_routes/support.rs
 //! Rocket support utilities: error handler, CORS fairing, and OPTIONS fallback.
 
+use crate::domain::PasswordPolicyError;
 use rocket::fairing::{Fairing, Info, Kind};
 use rocket::http::Status;
 use rocket::response::status::Custom;
@@ -16,6 +17,29 @@ pub fn server_error<E: std::fmt::Display>(e: E) -> Custom<Value> {
     )
 }
 
+/// Rejected request parameter, e.g. an unknown enum value in the query string.
+pub fn bad_request(err: String) -> Custom<Value> {
+    Custom(Status::BadRequest, json!({ "error": err }))
+}
+
+/// Request body that parsed but holds an invalid field (422), e.g.
+/// `{"error": "Validation failed", "fields": {"version": "..."}}`.
+pub fn invalid_field(field: &str, err: String) -> Custom<Value> {
+    Custom(
+        Status::UnprocessableEntity,
+        json!({ "error": "Validation failed", "fields": { field: err } }),
+    )
+}
+
+/// Error handler for operations that set a password: a password rejected by the
+/// password policy is the caller's fault (400), anything else is a server error.
+pub fn password_error(e: anyhow::Error) -> Custom<Value> {
+    match e.downcast_ref::<PasswordPolicyError>() {
+        Some(PasswordPolicyError::ListUnavailable(_)) | None => server_error(format!("{e:#}")),
+        Some(err) => Custom(Status::BadRequest, json!({ "error": err.to_string() })),
+    }
+}
+
 /// Generic fallback handler to allow CORS preflight requests.
 #[rocket::options("/<_route_args..>")]
 pub fn options(_route_args: Option<std::path::PathBuf>) {
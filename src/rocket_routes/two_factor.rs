@@ -0,0 +1,374 @@
+// rocket_routes/two_factor.rs
+use super::authorization::{account_disabled, locked_out, start_session, LoginRejection};
+use super::{server_error, ClientInfo, GuardedAppUser};
+use crate::domain::{
+    //
+    complete_second_factor,
+    confirm_totp,
+    enroll_totp,
+    AppUserTableTraitPtr,
+    CacheContextTraitPtr,
+    LoginError,
+    SecondFactorLogin,
+    TotpCode,
+    TwoFactorError,
+};
+use rocket::http::Status;
+use rocket::response::status::Custom;
+use rocket::serde::json::{json, Json, Value};
+use rocket::State;
+
+/// Map enrollment errors to client responses.
+fn two_factor_error(err: TwoFactorError) -> Custom<Value> {
+    // ---
+    match err {
+        TwoFactorError::AlreadyEnabled => Custom(
+            Status::Conflict,
+            json!({ "error": "Two-factor authentication is already enabled" }),
+        ),
+        TwoFactorError::NotEnrolled => Custom(
+            Status::BadRequest,
+            json!({ "error": "No pending two-factor enrollment" }),
+        ),
+        TwoFactorError::InvalidCode => Custom(
+            Status::BadRequest,
+            json!({ "error": "Invalid two-factor code" }),
+        ),
+        TwoFactorError::Internal(err) => server_error(err),
+    }
+}
+
+/// Start TOTP enrollment: returns the secret and an `otpauth://` URI for an
+/// authenticator app. Two-factor login is not active until confirmed.
+#[rocket::post("/me/2fa/enroll")]
+pub async fn enroll_my_totp(
+    app_user_repo: &State<AppUserTableTraitPtr>,
+    user: GuardedAppUser,
+) -> Result<Value, Custom<Value>> {
+    // ---
+    enroll_totp(app_user_repo.inner().clone(), &user.0)
+        .await
+        .map(|enrollment| json!(enrollment))
+        .map_err(two_factor_error)
+}
+
+/// Confirm TOTP enrollment with a current code and enable two-factor login.
+///
+/// The returned recovery codes are shown only this once.
+#[rocket::post("/me/2fa/confirm", format = "json", data = "<code>")]
+pub async fn confirm_my_totp(
+    app_user_repo: &State<AppUserTableTraitPtr>,
+    user: GuardedAppUser,
+    code: Json<TotpCode>,
+) -> Result<Value, Custom<Value>> {
+    // ---
+    let recovery_codes = confirm_totp(app_user_repo.inner().clone(), &user.0, &code.code)
+        .await
+        .map_err(two_factor_error)?;
+
+    tracing::info!(
+        "🔐 Two-factor authentication enabled for user id {}",
+        user.0.id
+    );
+    Ok(json!({ "recovery_codes": recovery_codes }))
+}
+
+/// Second login step: exchange the challenge token from `/login` and a TOTP or
+/// recovery code for a session token.
+#[rocket::post("/login/2fa", format = "json", data = "<second_factor>")]
+pub async fn login_second_factor(
+    app_user_repo: &State<AppUserTableTraitPtr>,
+    cache_context: &State<CacheContextTraitPtr>,
+    client: ClientInfo,
+    second_factor: Json<SecondFactorLogin>,
+) -> Result<Value, LoginRejection> {
+    // ---
+    let result = complete_second_factor(
+        app_user_repo.inner().clone(),
+        cache_context.inner().clone(),
+        second_factor.into_inner(),
+        client.ip.as_deref(),
+    )
+    .await;
+
+    let (user, session_id) = match result {
+        Ok(result) => result,
+        Err(LoginError::Internal(err)) => return Err(server_error(err).into()),
+        Err(LoginError::AccountDisabled) => return Err(account_disabled().into()),
+        Err(LoginError::LockedOut { retry_after_secs }) => {
+            tracing::warn!("❌ Second factor locked out for {retry_after_secs}s");
+            return Err(locked_out(retry_after_secs));
+        }
+        Err(err) => {
+            tracing::warn!("❌ Second factor rejected: {err}");
+            return Err(Custom(
+                Status::Unauthorized,
+                json!({ "error": "Invalid or expired two-factor code" }),
+            )
+            .into());
+        }
+    };
+
+    Ok(start_session(app_user_repo, cache_context, client, &user, &session_id).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    // ---
+    use super::*;
+    use crate::domain::{
+        //
+        unlock_login,
+        Credentials,
+        LockoutPolicy,
+        RoleCode,
+    };
+    use crate::rocket_routes::login;
+    use crate::test_support::{MockAppUserRepo, MockCacheContext};
+
+    use chrono::Utc;
+    use std::sync::Arc;
+
+    /// User 1 is alice, who logs in with `password`.
+    fn mock_repo() -> Arc<MockAppUserRepo> {
+        Arc::new(MockAppUserRepo::default().with_login("alice", "password", vec![RoleCode::Viewer]))
+    }
+
+    fn current_code(secret: &str) -> String {
+        // ---
+        let now = Utc::now().timestamp() as u64;
+        crate::auth::totp_code_at(secret, now).unwrap()
+    }
+
+    /// The code of the next time step, still accepted as clock drift. Logins use it
+    /// because the current one was spent confirming the enrollment.
+    fn next_code(secret: &str) -> String {
+        // ---
+        let now = Utc::now().timestamp() as u64;
+        crate::auth::totp_code_at(secret, now + 30).unwrap()
+    }
+
+    fn guarded_user(repo: &MockAppUserRepo) -> GuardedAppUser {
+        GuardedAppUser(repo.user(1))
+    }
+
+    /// Log in with the password and return the challenge token.
+    async fn password_step(repo: &AppUserTableTraitPtr, cache: &CacheContextTraitPtr) -> String {
+        // ---
+        let creds = Credentials::new("alice", "password");
+        let value = login(
+            State::from(repo),
+            State::from(cache),
+            ClientInfo::default(),
+            Json(creds),
+        )
+        .await
+        .expect("password step should succeed");
+
+        assert_eq!(value["second_factor_required"], true);
+        assert!(value.get("token").is_none(), "no session before 2FA");
+        value["challenge_token"].as_str().unwrap().to_string()
+    }
+
+    async fn second_step(
+        repo: &AppUserTableTraitPtr,
+        cache: &CacheContextTraitPtr,
+        challenge_token: &str,
+        code: &str,
+    ) -> Result<Value, LoginRejection> {
+        // ---
+        login_second_factor(
+            State::from(repo),
+            State::from(cache),
+            ClientInfo::default(),
+            Json(SecondFactorLogin {
+                challenge_token: challenge_token.into(),
+                code: code.into(),
+            }),
+        )
+        .await
+    }
+
+    async fn enroll_and_confirm(
+        mock_repo: &MockAppUserRepo,
+        repo: &AppUserTableTraitPtr,
+    ) -> (String, Vec<String>) {
+        // ---
+        let enrollment = enroll_my_totp(State::from(repo), guarded_user(mock_repo))
+            .await
+            .unwrap();
+        let secret = enrollment["secret"].as_str().unwrap().to_string();
+        assert!(enrollment["otpauth_uri"]
+            .as_str()
+            .unwrap()
+            .starts_with("otpauth://totp/"));
+
+        let confirmed = confirm_my_totp(
+            State::from(repo),
+            guarded_user(mock_repo),
+            Json(TotpCode {
+                code: current_code(&secret),
+            }),
+        )
+        .await
+        .unwrap();
+
+        let recovery_codes = confirmed["recovery_codes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|code| code.as_str().unwrap().to_string())
+            .collect();
+        (secret, recovery_codes)
+    }
+
+    #[tokio::test]
+    async fn test_confirm_rejects_wrong_code_and_missing_enrollment() {
+        // ---
+        let mock_repo = mock_repo();
+        let repo: AppUserTableTraitPtr = mock_repo.clone();
+        let wrong = || {
+            Json(TotpCode {
+                code: "000000".into(),
+            })
+        };
+
+        match confirm_my_totp(State::from(&repo), guarded_user(&mock_repo), wrong()).await {
+            Err(Custom(status, body)) => {
+                assert_eq!(status.code, 400);
+                assert_eq!(body["error"], "No pending two-factor enrollment");
+            }
+            Ok(_) => panic!("Expected confirm without enrollment to fail"),
+        }
+
+        enroll_my_totp(State::from(&repo), guarded_user(&mock_repo))
+            .await
+            .unwrap();
+        let secret = mock_repo.totp.lock().unwrap()[&1].secret.clone();
+        if current_code(&secret) != "000000" {
+            match confirm_my_totp(State::from(&repo), guarded_user(&mock_repo), wrong()).await {
+                Err(Custom(status, _)) => assert_eq!(status.code, 400),
+                Ok(_) => panic!("Expected wrong code to be rejected"),
+            }
+        }
+        assert!(!mock_repo.totp.lock().unwrap()[&1].enabled);
+    }
+
+    #[tokio::test]
+    async fn test_two_factor_login_with_totp_code() {
+        // ---
+        let mock_repo = mock_repo();
+        let repo: AppUserTableTraitPtr = mock_repo.clone();
+        let mock_cache = Arc::new(MockCacheContext::default());
+        let cache: CacheContextTraitPtr = mock_cache.clone();
+
+        let (secret, recovery_codes) = enroll_and_confirm(&mock_repo, &repo).await;
+        assert_eq!(recovery_codes.len(), 10);
+
+        // Re-enrolling would replace the secret, so it is refused once enabled
+        match enroll_my_totp(State::from(&repo), guarded_user(&mock_repo)).await {
+            Err(Custom(status, _)) => assert_eq!(status.code, 409),
+            Ok(_) => panic!("Expected re-enrollment to be refused"),
+        }
+
+        let challenge = password_step(&repo, &cache).await;
+        assert!(mock_cache.sessions.lock().unwrap().is_empty());
+
+        // The code that confirmed the enrollment is already spent
+        let spent_step = mock_repo.totp_last_step.lock().unwrap()[&1];
+        let code = crate::auth::totp_code_at(&secret, spent_step * 30).unwrap();
+        match second_step(&repo, &cache, &challenge, &code).await {
+            Err(LoginRejection::Denied(Custom(status, _))) => assert_eq!(status.code, 401),
+            other => panic!("Expected the enrollment code to be rejected, got {other:?}"),
+        }
+
+        let code = next_code(&secret);
+        let challenge = password_step(&repo, &cache).await;
+        let value = second_step(&repo, &cache, &challenge, &code)
+            .await
+            .expect("valid TOTP code should log in");
+        let token = value["token"].as_str().unwrap();
+        assert_eq!(mock_cache.session_tokens(1), [token]);
+
+        // Challenges are single-use
+        match second_step(&repo, &cache, &challenge, &code).await {
+            Err(LoginRejection::Denied(Custom(status, _))) => assert_eq!(status.code, 401),
+            other => panic!("Expected reused challenge to be rejected, got {other:?}"),
+        }
+
+        // ...and so are codes, even while still inside their drift window
+        let challenge = password_step(&repo, &cache).await;
+        match second_step(&repo, &cache, &challenge, &code).await {
+            Err(LoginRejection::Denied(Custom(status, _))) => assert_eq!(status.code, 401),
+            other => panic!("Expected replayed code to be rejected, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_two_factor_login_with_recovery_code() {
+        // ---
+        let mock_repo = mock_repo();
+        let repo: AppUserTableTraitPtr = mock_repo.clone();
+        let cache: CacheContextTraitPtr = Arc::new(MockCacheContext::default());
+
+        let (_, recovery_codes) = enroll_and_confirm(&mock_repo, &repo).await;
+
+        // Recovery codes are accepted regardless of case and separator
+        let code = recovery_codes[3].replace('-', "").to_uppercase();
+        let challenge = password_step(&repo, &cache).await;
+        assert!(second_step(&repo, &cache, &challenge, &code).await.is_ok());
+
+        // ...but only once
+        let challenge = password_step(&repo, &cache).await;
+        match second_step(&repo, &cache, &challenge, &code).await {
+            Err(LoginRejection::Denied(Custom(status, _))) => assert_eq!(status.code, 401),
+            other => panic!("Expected used recovery code to be rejected, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_repeated_wrong_codes_lock_the_account_out() {
+        // ---
+        let mock_repo = mock_repo();
+        let repo: AppUserTableTraitPtr = mock_repo.clone();
+        let mock_cache = Arc::new(MockCacheContext::default());
+        let cache: CacheContextTraitPtr = mock_cache.clone();
+        let policy = LockoutPolicy::from_env();
+
+        let (secret, _) = enroll_and_confirm(&mock_repo, &repo).await;
+
+        // The right password in between must not reset the count of wrong codes
+        for _ in 1..policy.max_failures {
+            let challenge = password_step(&repo, &cache).await;
+            match second_step(&repo, &cache, &challenge, "wrong-code").await {
+                Err(LoginRejection::Denied(Custom(status, _))) => assert_eq!(status.code, 401),
+                other => panic!("Expected wrong code to be rejected, got {other:?}"),
+            }
+        }
+
+        let challenge = password_step(&repo, &cache).await;
+        match second_step(&repo, &cache, &challenge, "wrong-code").await {
+            Err(LoginRejection::LockedOut(_, header)) => {
+                assert_eq!(header.value(), policy.base_lockout_secs.to_string())
+            }
+            other => panic!("Expected lockout but got {other:?}"),
+        }
+
+        // Locked out at the password step too, until an admin unlocks the account
+        let result = login(
+            State::from(&repo),
+            State::from(&cache),
+            ClientInfo::default(),
+            Json(Credentials::new("alice", "password")),
+        )
+        .await;
+        assert!(matches!(result, Err(LoginRejection::LockedOut(..))));
+
+        unlock_login(&cache, "alice", None).await.unwrap();
+        let challenge = password_step(&repo, &cache).await;
+        second_step(&repo, &cache, &challenge, &next_code(&secret))
+            .await
+            .expect("the right code logs in after the unlock");
+        assert!(mock_cache.login_failures.lock().unwrap().is_empty());
+    }
+}
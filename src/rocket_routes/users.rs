@@ -0,0 +1,544 @@
+// rocket_routes/users.rs
+//! Admin-only management of user accounts; responses never include password hashes.
+//!
+//! Callers holding `user:manage` but not Admin may only act on users whose roles grant
+//! nothing beyond their own (see `check_can_manage`).
+use super::{server_error, RequirePermission, UserManage};
+use crate::domain::{
+    //
+    check_can_manage,
+    create_user_account,
+    delete_user_account,
+    find_user,
+    record_audit_event,
+    set_account_disabled,
+    AppUserTableTraitPtr,
+    AuditEntity,
+    AuditLogTraitPtr,
+    CacheContextTraitPtr,
+    NewAuditEvent,
+    NewUserAccount,
+    RoleCodeTableTraitPtr,
+    UserAdminError,
+    UserSummary,
+};
+use rocket::http::Status;
+use rocket::response::status::{Custom, NoContent};
+use rocket::serde::json::{json, Json, Value};
+use rocket::State;
+
+pub(super) fn user_admin_error(err: UserAdminError) -> Custom<Value> {
+    // ---
+    match err {
+        UserAdminError::Invalid(_) | UserAdminError::Protected(..) => {
+            Custom(Status::BadRequest, json!({ "error": err.to_string() }))
+        }
+        UserAdminError::NotFound(_) => {
+            Custom(Status::NotFound, json!({ "error": "User not found" }))
+        }
+        UserAdminError::RoleNotFound(_) => {
+            Custom(Status::NotFound, json!({ "error": "Role not found" }))
+        }
+        UserAdminError::AlreadyExists(_) => {
+            Custom(Status::Conflict, json!({ "error": err.to_string() }))
+        }
+        UserAdminError::Forbidden(_) => {
+            Custom(Status::Forbidden, json!({ "error": err.to_string() }))
+        }
+        UserAdminError::Internal(err) => server_error(err),
+    }
+}
+
+/// List all users with their roles.
+#[rocket::get("/users")]
+pub async fn list_users(
+    app_user_repo: &State<AppUserTableTraitPtr>,
+    _admin: RequirePermission<UserManage>,
+) -> Result<Value, Custom<Value>> {
+    // ---
+    let users = app_user_repo
+        .find_with_roles()
+        .await
+        .map_err(server_error)?;
+    let users: Vec<UserSummary> = users.into_iter().map(UserSummary::from).collect();
+    Ok(json!(users))
+}
+
+/// View one user with their roles.
+#[rocket::get("/users/<id>")]
+pub async fn view_user(
+    app_user_repo: &State<AppUserTableTraitPtr>,
+    _admin: RequirePermission<UserManage>,
+    id: i32,
+) -> Result<Value, Custom<Value>> {
+    // ---
+    find_user(app_user_repo.inner(), id)
+        .await
+        .map(|user| json!(user))
+        .map_err(user_admin_error)
+}
+
+/// Create a user, e.g. `{"username": "ann@example.com", "password": "...", "roles": ["Editor"]}`.
+#[rocket::post("/users", format = "json", data = "<account>")]
+pub async fn create_user(
+    app_user_repo: &State<AppUserTableTraitPtr>,
+    role_repo: &State<RoleCodeTableTraitPtr>,
+    audit_log: &State<AuditLogTraitPtr>,
+    admin: RequirePermission<UserManage>,
+    account: Json<NewUserAccount>,
+) -> Result<Custom<Value>, Custom<Value>> {
+    // ---
+    let user = create_user_account(
+        app_user_repo.inner(),
+        role_repo.inner(),
+        &admin.0 .0,
+        account.into_inner(),
+    )
+    .await
+    .map_err(user_admin_error)?;
+
+    let event = NewAuditEvent::created(AuditEntity::User, user.id, &user);
+    record_audit_event(audit_log, event.by(admin.0 .0.id)).await;
+
+    tracing::info!(
+        "👤 User id {} created by admin id {}",
+        user.id,
+        admin.0 .0.id
+    );
+    Ok(Custom(Status::Created, json!(user)))
+}
+
+/// Delete a user and revoke all of their sessions.
+#[rocket::delete("/users/<id>")]
+pub async fn delete_user(
+    app_user_repo: &State<AppUserTableTraitPtr>,
+    role_repo: &State<RoleCodeTableTraitPtr>,
+    cache_context: &State<CacheContextTraitPtr>,
+    audit_log: &State<AuditLogTraitPtr>,
+    admin: RequirePermission<UserManage>,
+    id: i32,
+) -> Result<NoContent, Custom<Value>> {
+    // ---
+    let existing = find_user(app_user_repo.inner(), id)
+        .await
+        .map_err(user_admin_error)?;
+    check_can_manage(
+        app_user_repo.inner(),
+        role_repo.inner(),
+        &admin.0 .0,
+        &existing.roles,
+    )
+    .await
+    .map_err(user_admin_error)?;
+
+    delete_user_account(
+        app_user_repo.inner(),
+        cache_context.inner(),
+        admin.0 .0.id,
+        id,
+    )
+    .await
+    .map_err(user_admin_error)?;
+
+    let event = NewAuditEvent::deleted(AuditEntity::User, id, Some(&existing));
+    record_audit_event(audit_log, event.by(admin.0 .0.id)).await;
+
+    tracing::info!("👤 User id {id} deleted by admin id {}", admin.0 .0.id);
+    Ok(NoContent)
+}
+
+/// Disable a user: they can no longer log in and their sessions are revoked.
+#[rocket::put("/users/<id>/disable")]
+pub async fn disable_user(
+    app_user_repo: &State<AppUserTableTraitPtr>,
+    role_repo: &State<RoleCodeTableTraitPtr>,
+    cache_context: &State<CacheContextTraitPtr>,
+    audit_log: &State<AuditLogTraitPtr>,
+    admin: RequirePermission<UserManage>,
+    id: i32,
+) -> Result<Value, Custom<Value>> {
+    // ---
+    let before = find_user(app_user_repo.inner(), id)
+        .await
+        .map_err(user_admin_error)?;
+    check_can_manage(
+        app_user_repo.inner(),
+        role_repo.inner(),
+        &admin.0 .0,
+        &before.roles,
+    )
+    .await
+    .map_err(user_admin_error)?;
+
+    let user = set_account_disabled(
+        app_user_repo.inner(),
+        cache_context.inner(),
+        Some(admin.0 .0.id),
+        id,
+        true,
+    )
+    .await
+    .map_err(user_admin_error)?;
+
+    let event = NewAuditEvent::updated(AuditEntity::User, id, Some(&before), &user);
+    record_audit_event(audit_log, event.by(admin.0 .0.id)).await;
+
+    tracing::info!("👤 User id {id} disabled by admin id {}", admin.0 .0.id);
+    Ok(json!(user))
+}
+
+/// Re-enable a disabled user.
+#[rocket::put("/users/<id>/enable")]
+pub async fn enable_user(
+    app_user_repo: &State<AppUserTableTraitPtr>,
+    role_repo: &State<RoleCodeTableTraitPtr>,
+    cache_context: &State<CacheContextTraitPtr>,
+    audit_log: &State<AuditLogTraitPtr>,
+    admin: RequirePermission<UserManage>,
+    id: i32,
+) -> Result<Value, Custom<Value>> {
+    // ---
+    let before = find_user(app_user_repo.inner(), id)
+        .await
+        .map_err(user_admin_error)?;
+    check_can_manage(
+        app_user_repo.inner(),
+        role_repo.inner(),
+        &admin.0 .0,
+        &before.roles,
+    )
+    .await
+    .map_err(user_admin_error)?;
+
+    let user = set_account_disabled(
+        app_user_repo.inner(),
+        cache_context.inner(),
+        Some(admin.0 .0.id),
+        id,
+        false,
+    )
+    .await
+    .map_err(user_admin_error)?;
+
+    let event = NewAuditEvent::updated(AuditEntity::User, id, Some(&before), &user);
+    record_audit_event(audit_log, event.by(admin.0 .0.id)).await;
+
+    tracing::info!("👤 User id {id} enabled by admin id {}", admin.0 .0.id);
+    Ok(json!(user))
+}
+
+#[cfg(test)]
+mod tests {
+    // ---
+    use super::*;
+    use crate::domain::{
+        //
+        AppUser,
+        AuditAction,
+        Permission,
+        RoleCode,
+    };
+    use crate::rocket_routes::GuardedAppUser;
+    use crate::test_support::{MockAppUserRepo, MockAuditLog, MockCacheContext, MockRoleRepo};
+    use chrono::Utc;
+    use std::sync::Arc;
+
+    /// User 1 is the Admin making requests.
+    fn user_repo() -> AppUserTableTraitPtr {
+        Arc::new(MockAppUserRepo::default().with_user("admin@example.com", vec![RoleCode::Admin]))
+    }
+
+    fn admin() -> RequirePermission<UserManage> {
+        RequirePermission::new(GuardedAppUser(AppUser {
+            id: 1,
+            username: "admin@example.com".into(),
+            password: "admin-hash".into(),
+            created_at: Utc::now().naive_utc(),
+            author_id: None,
+            disabled_at: None,
+        }))
+    }
+
+    fn account(username: &str, roles: Vec<RoleCode>) -> Json<NewUserAccount> {
+        Json(NewUserAccount {
+            username: username.into(),
+            password: "secret-password".into(),
+            roles,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_create_view_and_list_users() {
+        // ---
+        let users: AppUserTableTraitPtr = user_repo();
+        let roles: RoleCodeTableTraitPtr = Arc::new(MockRoleRepo::default());
+        let audit: AuditLogTraitPtr = Arc::new(MockAuditLog::default());
+
+        let created = create_user(
+            State::from(&users),
+            State::from(&roles),
+            State::from(&audit),
+            admin(),
+            account("ann@example.com", vec![RoleCode::Editor]),
+        )
+        .await
+        .unwrap();
+        assert_eq!(created.0, Status::Created);
+        assert_eq!(created.1["roles"], json!(["Editor"]));
+        assert!(created.1.get("password").is_none());
+
+        let duplicate = create_user(
+            State::from(&users),
+            State::from(&roles),
+            State::from(&audit),
+            admin(),
+            account("ann@example.com", vec![]),
+        )
+        .await;
+        assert_eq!(duplicate.unwrap_err().0, Status::Conflict);
+
+        let weak = create_user(
+            State::from(&users),
+            State::from(&roles),
+            State::from(&audit),
+            admin(),
+            Json(NewUserAccount {
+                username: "carol@example.com".into(),
+                password: "short".into(),
+                roles: vec![],
+            }),
+        )
+        .await;
+        assert_eq!(weak.unwrap_err().0, Status::BadRequest);
+
+        let unknown_role = create_user(
+            State::from(&users),
+            State::from(&roles),
+            State::from(&audit),
+            admin(),
+            account("bob@example.com", vec![RoleCode::Custom("Ghost".into())]),
+        )
+        .await;
+        assert_eq!(unknown_role.unwrap_err().0, Status::NotFound);
+
+        let viewed = view_user(State::from(&users), admin(), 2).await.unwrap();
+        assert_eq!(viewed["username"], "ann@example.com");
+        assert!(viewed.get("password").is_none());
+
+        let missing = view_user(State::from(&users), admin(), 99).await;
+        assert_eq!(missing.unwrap_err().0, Status::NotFound);
+
+        let listed = list_users(State::from(&users), admin()).await.unwrap();
+        let listed = listed.as_array().unwrap();
+        assert_eq!(listed.len(), 2);
+        assert!(listed.iter().all(|user| user.get("password").is_none()));
+    }
+
+    #[tokio::test]
+    async fn test_user_changes_are_audited() {
+        // ---
+        let users: AppUserTableTraitPtr = user_repo();
+        let roles: RoleCodeTableTraitPtr = Arc::new(MockRoleRepo::default());
+        let cache: CacheContextTraitPtr = Arc::new(MockCacheContext::default());
+        let mock_log = Arc::new(MockAuditLog::default());
+        let audit: AuditLogTraitPtr = mock_log.clone();
+
+        create_user(
+            State::from(&users),
+            State::from(&roles),
+            State::from(&audit),
+            admin(),
+            account("ann@example.com", vec![RoleCode::Editor]),
+        )
+        .await
+        .unwrap();
+        let disabled = disable_user(
+            State::from(&users),
+            State::from(&roles),
+            State::from(&cache),
+            State::from(&audit),
+            admin(),
+            2,
+        )
+        .await
+        .unwrap();
+        assert!(!disabled["disabled_at"].is_null());
+        enable_user(
+            State::from(&users),
+            State::from(&roles),
+            State::from(&cache),
+            State::from(&audit),
+            admin(),
+            2,
+        )
+        .await
+        .unwrap();
+
+        // Refused changes are not audited
+        let active = delete_user(
+            State::from(&users),
+            State::from(&roles),
+            State::from(&cache),
+            State::from(&audit),
+            admin(),
+            2,
+        )
+        .await;
+        assert_eq!(active.unwrap_err().0, Status::BadRequest);
+
+        disable_user(
+            State::from(&users),
+            State::from(&roles),
+            State::from(&cache),
+            State::from(&audit),
+            admin(),
+            2,
+        )
+        .await
+        .unwrap();
+        delete_user(
+            State::from(&users),
+            State::from(&roles),
+            State::from(&cache),
+            State::from(&audit),
+            admin(),
+            2,
+        )
+        .await
+        .unwrap();
+
+        let myself = delete_user(
+            State::from(&users),
+            State::from(&roles),
+            State::from(&cache),
+            State::from(&audit),
+            admin(),
+            1,
+        )
+        .await;
+        assert_eq!(myself.unwrap_err().0, Status::BadRequest);
+
+        let events = mock_log.events.lock().unwrap();
+        let actions: Vec<AuditAction> = events.iter().map(|event| event.action).collect();
+        assert_eq!(
+            actions,
+            vec![
+                AuditAction::Create,
+                AuditAction::Update,
+                AuditAction::Update,
+                AuditAction::Update,
+                AuditAction::Delete
+            ]
+        );
+        assert!(events
+            .iter()
+            .all(|event| event.entity_type == AuditEntity::User
+                && event.entity_id == 2
+                && event.actor_user_id == Some(1)));
+
+        let (created, disabled, enabled, deleted) =
+            (&events[0], &events[1], &events[2], &events[4]);
+        assert_eq!(created.after.as_ref().unwrap()["roles"], json!(["Editor"]));
+        assert!(created.after.as_ref().unwrap().get("password").is_none());
+        assert!(disabled.before.as_ref().unwrap()["disabled_at"].is_null());
+        assert!(!disabled.after.as_ref().unwrap()["disabled_at"].is_null());
+        assert!(!enabled.before.as_ref().unwrap()["disabled_at"].is_null());
+        assert!(enabled.after.as_ref().unwrap()["disabled_at"].is_null());
+        assert_eq!(
+            deleted.before.as_ref().unwrap()["username"],
+            "ann@example.com"
+        );
+        assert!(deleted.after.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_user_manage_cannot_reach_above_own_roles() {
+        // ---
+        use Permission::*;
+
+        let mock_users = Arc::new(
+            MockAppUserRepo::default()
+                .with_user("admin@example.com", vec![RoleCode::Admin])
+                .with_user(
+                    "support@example.com",
+                    vec![RoleCode::Custom("Support".into())],
+                )
+                .with_user("lead@example.com", vec![RoleCode::Custom("Lead".into())])
+                .with_user("ed@example.com", vec![RoleCode::Editor]),
+        );
+        let users: AppUserTableTraitPtr = mock_users.clone();
+        let roles: RoleCodeTableTraitPtr = Arc::new(
+            MockRoleRepo::default()
+                .with_role("Support", vec![UserManage, CrateRead, AuthorRead])
+                .with_role(
+                    "Lead",
+                    vec![UserManage, RoleManage, CrateRead, CrateWriteOwn, AuthorRead],
+                ),
+        );
+        let cache: CacheContextTraitPtr = Arc::new(MockCacheContext::default());
+        let audit: AuditLogTraitPtr = Arc::new(MockAuditLog::default());
+        let as_user = |id| RequirePermission::new(GuardedAppUser(mock_users.user(id)));
+        let create = |id, username: &str, with_roles| {
+            create_user(
+                State::from(&users),
+                State::from(&roles),
+                State::from(&audit),
+                as_user(id),
+                account(username, with_roles),
+            )
+        };
+
+        // Assigning roles takes role:manage, and only roles granting no more than the
+        // caller's own
+        let result = create(2, "viewer@example.com", vec![RoleCode::Viewer]).await;
+        assert_eq!(result.unwrap_err().0, Status::Forbidden);
+        let result = create(3, "editor@example.com", vec![RoleCode::Editor]).await;
+        assert_eq!(result.unwrap_err().0, Status::Forbidden);
+        let result = create(3, "root@example.com", vec![RoleCode::Admin]).await;
+        assert_eq!(result.unwrap_err().0, Status::Forbidden);
+
+        let created = create(3, "viewer@example.com", vec![RoleCode::Viewer]).await;
+        assert_eq!(created.unwrap().0, Status::Created);
+        let created = create(2, "plain@example.com", vec![]).await;
+        assert_eq!(created.unwrap().0, Status::Created);
+
+        // Admins and users whose roles grant more cannot be disabled or deleted
+        for target in [1, 4] {
+            let result = disable_user(
+                State::from(&users),
+                State::from(&roles),
+                State::from(&cache),
+                State::from(&audit),
+                as_user(3),
+                target,
+            )
+            .await;
+            assert_eq!(result.unwrap_err().0, Status::Forbidden);
+
+            let result = delete_user(
+                State::from(&users),
+                State::from(&roles),
+                State::from(&cache),
+                State::from(&audit),
+                as_user(3),
+                target,
+            )
+            .await;
+            assert_eq!(result.unwrap_err().0, Status::Forbidden);
+        }
+        assert!(mock_users.user(1).disabled_at.is_none());
+        assert!(mock_users.user(4).disabled_at.is_none());
+
+        let disabled = disable_user(
+            State::from(&users),
+            State::from(&roles),
+            State::from(&cache),
+            State::from(&audit),
+            as_user(3),
+            5,
+        )
+        .await
+        .unwrap();
+        assert!(!disabled["disabled_at"].is_null());
+    }
+}
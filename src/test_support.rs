@@ -0,0 +1,760 @@
+// src/test_support.rs
+//! In-memory stand-ins for the user and role repositories, session cache, audit log
+//! and mailer,
+//! shared by the unit tests of `domain/` and `rocket_routes/`.
+//!
+//! They behave like the real stores closely enough that tests assert on the
+//! resulting state (users, sessions, tokens) instead of on the calls made.
+
+use crate::domain::{
+    //
+    AccessToken,
+    AppUser,
+    AppUserTableTrait,
+    AppUserWithRoleCodes,
+    AuditEvent,
+    AuditFilter,
+    AuditLogTrait,
+    CacheContextTrait,
+    CrateSummary,
+    MailerTrait,
+    NewAuditEvent,
+    NewRole,
+    NewSession,
+    NewUser,
+    Permission,
+    RecoveryCode,
+    Role,
+    RoleCode,
+    RoleCodeTableTrait,
+    SessionRecord,
+    TotpSecret,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+
+/// Users with their roles; ids are handed out in insertion order starting at 1.
+#[derive(Default)]
+pub(crate) struct MockAppUserRepo {
+    pub users: Mutex<Vec<AppUserWithRoleCodes>>,
+    /// Linked single sign-on identities as `(issuer, subject, user id)`.
+    pub identities: Mutex<Vec<(String, String, i32)>>,
+    pub totp: Mutex<HashMap<i32, TotpSecret>>,
+    pub totp_last_step: Mutex<HashMap<i32, u64>>,
+    /// Recovery codes as `(code id, user id, hash, used)`.
+    pub recovery_codes: Mutex<Vec<(i32, i32, String, bool)>>,
+}
+
+impl MockAppUserRepo {
+    // ---
+
+    /// Add a user whose password is not meant to be checked.
+    pub fn with_user(self, username: &str, role_codes: Vec<RoleCode>) -> Self {
+        self.add_user(username, "hash", role_codes);
+        self
+    }
+
+    /// Add a user who can log in with `password`.
+    pub fn with_login(self, username: &str, password: &str, role_codes: Vec<RoleCode>) -> Self {
+        // ---
+        let hasher = crate::auth::create_password_hasher().unwrap();
+        let password_hash = hasher.hash_password(password).unwrap();
+        self.add_user(username, &password_hash, role_codes);
+        self
+    }
+
+    pub fn add_user(
+        &self,
+        username: &str,
+        password_hash: &str,
+        role_codes: Vec<RoleCode>,
+    ) -> AppUser {
+        // ---
+        let mut users = self.users.lock().unwrap();
+        let user = AppUser {
+            id: users.last().map_or(1, |(user, _)| user.id + 1),
+            username: username.into(),
+            password: password_hash.into(),
+            created_at: Utc::now().naive_utc(),
+            author_id: None,
+            disabled_at: None,
+        };
+        users.push((user.clone(), role_codes));
+        user
+    }
+
+    /// The stored user; panics if it does not exist.
+    pub fn user(&self, id: i32) -> AppUser {
+        self.edit_user(id, |user| user.clone())
+    }
+
+    /// Change a stored user in place, e.g. to disable it; panics if it does not exist.
+    pub fn edit_user<T>(&self, id: i32, edit: impl FnOnce(&mut AppUser) -> T) -> T {
+        // ---
+        let mut users = self.users.lock().unwrap();
+        let (user, _) = users
+            .iter_mut()
+            .find(|(user, _)| user.id == id)
+            .unwrap_or_else(|| panic!("no user {id}"));
+        edit(user)
+    }
+
+    pub fn roles(&self, id: i32) -> Vec<RoleCode> {
+        // ---
+        let users = self.users.lock().unwrap();
+        let found = users.iter().find(|(user, _)| user.id == id);
+        found.map(|(_, roles)| roles.clone()).unwrap_or_default()
+    }
+
+    fn edit_roles(&self, id: i32, edit: impl FnOnce(&mut Vec<RoleCode>)) -> Result<()> {
+        // ---
+        let mut users = self.users.lock().unwrap();
+        let Some((_, roles)) = users.iter_mut().find(|(user, _)| user.id == id) else {
+            anyhow::bail!("no user {id}");
+        };
+        edit(roles);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AppUserTableTrait for MockAppUserRepo {
+    // ---
+    async fn create(&self, new_user: NewUser, role_codes: Vec<RoleCode>) -> Result<AppUser> {
+        // ---
+        anyhow::ensure!(
+            self.find_by_username(&new_user.username).await.is_err(),
+            "duplicate username {}",
+            new_user.username
+        );
+        Ok(self.add_user(&new_user.username, &new_user.password, role_codes))
+    }
+
+    async fn find(&self, id: i32) -> Result<AppUser> {
+        // ---
+        let users = self.users.lock().unwrap();
+        let user = users.iter().find(|(user, _)| user.id == id);
+        user.map(|(user, _)| user.clone())
+            .ok_or_else(|| anyhow::anyhow!("no user {id}"))
+    }
+
+    async fn find_roles_by_user(&self, user: &AppUser) -> Result<Vec<RoleCode>> {
+        Ok(self.roles(user.id))
+    }
+
+    async fn find_by_username(&self, username: &str) -> Result<AppUser> {
+        // ---
+        let users = self.users.lock().unwrap();
+        let user = users.iter().find(|(user, _)| user.username == username);
+        user.map(|(user, _)| user.clone())
+            .ok_or_else(|| anyhow::anyhow!("no user {username}"))
+    }
+
+    async fn update_password(&self, user_id: i32, password_hash: &str) -> Result<()> {
+        // ---
+        self.find(user_id).await?;
+        self.edit_user(user_id, |user| user.password = password_hash.into());
+        Ok(())
+    }
+
+    async fn delete_by_id(&self, user_id: i32) -> Result<()> {
+        // ---
+        let mut users = self.users.lock().unwrap();
+        users.retain(|(user, _)| user.id != user_id);
+        Ok(())
+    }
+
+    async fn delete_by_username(&self, username: &str) -> Result<()> {
+        // ---
+        let mut users = self.users.lock().unwrap();
+        users.retain(|(user, _)| user.username != username);
+        Ok(())
+    }
+
+    async fn find_with_roles(&self) -> Result<Vec<AppUserWithRoleCodes>> {
+        Ok(self.users.lock().unwrap().clone())
+    }
+
+    async fn grant_roles(&self, user_id: i32, role_codes: &[RoleCode]) -> Result<()> {
+        // ---
+        self.edit_roles(user_id, |roles| {
+            for code in role_codes {
+                if !roles.contains(code) {
+                    roles.push(code.clone());
+                }
+            }
+        })
+    }
+
+    async fn revoke_roles(&self, user_id: i32, role_codes: &[RoleCode]) -> Result<()> {
+        self.edit_roles(user_id, |roles| {
+            roles.retain(|code| !role_codes.contains(code))
+        })
+    }
+
+    async fn find_totp(&self, user_id: i32) -> Result<Option<TotpSecret>> {
+        Ok(self.totp.lock().unwrap().get(&user_id).cloned())
+    }
+
+    async fn set_pending_totp(&self, user_id: i32, secret: &str) -> Result<()> {
+        // ---
+        let pending = TotpSecret {
+            secret: secret.into(),
+            enabled: false,
+        };
+        self.totp.lock().unwrap().insert(user_id, pending);
+        self.totp_last_step.lock().unwrap().remove(&user_id);
+        Ok(())
+    }
+
+    async fn enable_totp(&self, user_id: i32, recovery_code_hashes: &[String]) -> Result<()> {
+        // ---
+        let mut totp = self.totp.lock().unwrap();
+        let Some(secret) = totp.get_mut(&user_id) else {
+            anyhow::bail!("no pending TOTP secret for user {user_id}");
+        };
+        secret.enabled = true;
+
+        let mut codes = self.recovery_codes.lock().unwrap();
+        codes.retain(|(_, owner, _, _)| *owner != user_id);
+        let next_id = codes.iter().map(|(id, ..)| *id).max().unwrap_or(0) + 1;
+        for (offset, hash) in recovery_code_hashes.iter().enumerate() {
+            codes.push((next_id + offset as i32, user_id, hash.clone(), false));
+        }
+        Ok(())
+    }
+
+    async fn find_recovery_codes(&self, user_id: i32) -> Result<Vec<RecoveryCode>> {
+        // ---
+        let codes = self.recovery_codes.lock().unwrap();
+        Ok(codes
+            .iter()
+            .filter(|(_, owner, _, used)| *owner == user_id && !used)
+            .map(|(id, _, code_hash, _)| RecoveryCode {
+                id: *id,
+                code_hash: code_hash.clone(),
+            })
+            .collect())
+    }
+
+    async fn consume_recovery_code(&self, code_id: i32) -> Result<bool> {
+        // ---
+        let mut codes = self.recovery_codes.lock().unwrap();
+        let code = codes.iter_mut().find(|(id, ..)| *id == code_id);
+        Ok(code.is_some_and(|code| !std::mem::replace(&mut code.3, true)))
+    }
+
+    async fn use_totp_step(&self, user_id: i32, step: u64) -> Result<bool> {
+        // ---
+        let mut last_steps = self.totp_last_step.lock().unwrap();
+        if last_steps.get(&user_id).is_some_and(|last| *last >= step) {
+            return Ok(false);
+        }
+        last_steps.insert(user_id, step);
+        Ok(true)
+    }
+
+    async fn find_by_identity(&self, issuer: &str, subject: &str) -> Result<Option<AppUser>> {
+        // ---
+        let user_id = self
+            .identities
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(i, s, _)| i == issuer && s == subject)
+            .map(|(_, _, id)| *id);
+
+        match user_id {
+            Some(id) => Ok(self.find(id).await.ok()),
+            None => Ok(None),
+        }
+    }
+
+    async fn link_identity(&self, user_id: i32, issuer: &str, subject: &str) -> Result<()> {
+        // ---
+        self.identities
+            .lock()
+            .unwrap()
+            .push((issuer.into(), subject.into(), user_id));
+        Ok(())
+    }
+
+    async fn find_by_author(&self, author_id: i32) -> Result<Option<AppUser>> {
+        // ---
+        let users = self.users.lock().unwrap();
+        let user = users
+            .iter()
+            .find(|(user, _)| user.author_id == Some(author_id));
+        Ok(user.map(|(user, _)| user.clone()))
+    }
+
+    async fn set_author(&self, user_id: i32, author_id: Option<i32>) -> Result<()> {
+        // ---
+        self.find(user_id).await?;
+        self.edit_user(user_id, |user| user.author_id = author_id);
+        Ok(())
+    }
+
+    async fn set_disabled(&self, user_id: i32, disabled: bool) -> Result<bool> {
+        // ---
+        if self.find(user_id).await.is_err() {
+            return Ok(false);
+        }
+        self.edit_user(user_id, |user| match disabled {
+            true => {
+                user.disabled_at
+                    .get_or_insert_with(|| Utc::now().naive_utc());
+            }
+            false => user.disabled_at = None,
+        });
+        Ok(true)
+    }
+}
+
+/// Sessions plus every kind of short-lived token the Redis cache keeps.
+///
+/// Session ids are `s-1`, `s-2`, ... in creation order. A `jwt()` cache also issues
+/// `access-<user id>-<session id>` access tokens, like JWT auth mode.
+#[derive(Default)]
+pub(crate) struct MockCacheContext {
+    /// Active sessions as `(token, record)`.
+    pub sessions: Mutex<Vec<(String, SessionRecord)>>,
+    pub reset_tokens: Mutex<HashMap<String, i32>>,
+    pub challenges: Mutex<HashMap<String, i32>>,
+    pub oidc_states: Mutex<HashMap<String, String>>,
+    pub author_claims: Mutex<HashMap<String, String>>,
+    pub login_failures: Mutex<HashMap<String, u64>>,
+    pub login_locks: Mutex<HashMap<String, u64>>,
+    session_count: AtomicU32,
+    jwt: bool,
+    failing: bool,
+}
+
+impl MockCacheContext {
+    // ---
+
+    /// A cache in JWT auth mode.
+    pub fn jwt() -> Self {
+        Self {
+            jwt: true,
+            ..Self::default()
+        }
+    }
+
+    /// A cache whose session operations all fail, like an unreachable Redis.
+    pub fn failing() -> Self {
+        Self {
+            failing: true,
+            ..Self::default()
+        }
+    }
+
+    /// Add a session for `user_id` under `token`.
+    pub fn add_session(&self, token: &str, user_id: i32) -> SessionRecord {
+        // ---
+        self.insert_session(
+            token,
+            NewSession {
+                user_id,
+                client_ip: Some("127.0.0.1".into()),
+                user_agent: Some("cr8s-test".into()),
+                role_codes: vec![],
+            },
+        )
+    }
+
+    /// Tokens of the active sessions of `user_id`, oldest first.
+    pub fn session_tokens(&self, user_id: i32) -> Vec<String> {
+        // ---
+        let sessions = self.sessions.lock().unwrap();
+        let owned = sessions.iter().filter(|(_, s)| s.user_id == user_id);
+        owned.map(|(token, _)| token.clone()).collect()
+    }
+
+    fn insert_session(&self, token: &str, new_session: NewSession) -> SessionRecord {
+        // ---
+        let count = self.session_count.fetch_add(1, Ordering::SeqCst) + 1;
+        let now = Utc::now().naive_utc();
+        let record = SessionRecord {
+            id: format!("s-{count}"),
+            user_id: new_session.user_id,
+            created_at: now,
+            last_seen: now,
+            client_ip: new_session.client_ip,
+            user_agent: new_session.user_agent,
+            role_codes: new_session.role_codes,
+        };
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions.push((token.into(), record.clone()));
+        record
+    }
+
+    /// Remove the sessions matching `revoke`, return how many were removed.
+    fn remove_sessions(&self, revoke: impl Fn(&str, &SessionRecord) -> bool) -> Result<u64> {
+        // ---
+        anyhow::ensure!(!self.failing, "simulated Redis failure");
+        let mut sessions = self.sessions.lock().unwrap();
+        let before = sessions.len();
+        sessions.retain(|(token, session)| !revoke(token, session));
+        Ok((before - sessions.len()) as u64)
+    }
+}
+
+#[async_trait]
+impl CacheContextTrait for MockCacheContext {
+    // ---
+    async fn get_user_id_by_session_token(&self, token: &str) -> Result<Option<i32>> {
+        // ---
+        anyhow::ensure!(!self.failing, "simulated Redis failure");
+        let sessions = self.sessions.lock().unwrap();
+        let session = sessions.iter().find(|(t, _)| t == token);
+        Ok(session.map(|(_, session)| session.user_id))
+    }
+
+    async fn set_user_session_token(
+        &self,
+        token: &str,
+        new_session: NewSession,
+    ) -> Result<SessionRecord> {
+        // ---
+        anyhow::ensure!(!self.failing, "simulated Redis failure");
+        Ok(self.insert_session(token, new_session))
+    }
+
+    async fn touch_session_token(&self, token: &str) -> Result<()> {
+        // ---
+        anyhow::ensure!(!self.failing, "simulated Redis failure");
+        let mut sessions = self.sessions.lock().unwrap();
+        if let Some((_, session)) = sessions.iter_mut().find(|(t, _)| t == token) {
+            session.last_seen = Utc::now().naive_utc();
+        }
+        Ok(())
+    }
+
+    async fn clear_session_token(&self, token: &str) -> Result<bool> {
+        Ok(self.remove_sessions(|t, _| t == token)? > 0)
+    }
+
+    async fn clear_all_session_tokens(&self, user_id: i32) -> Result<u64> {
+        self.remove_sessions(|_, session| session.user_id == user_id)
+    }
+
+    async fn list_user_sessions(&self, user_id: i32) -> Result<Vec<SessionRecord>> {
+        // ---
+        let sessions = self.sessions.lock().unwrap();
+        let owned = sessions.iter().filter(|(_, s)| s.user_id == user_id);
+        Ok(owned.map(|(_, session)| session.clone()).collect())
+    }
+
+    async fn clear_user_session(&self, user_id: i32, session_id: &str) -> Result<bool> {
+        let removed = self.remove_sessions(|_, s| s.user_id == user_id && s.id == session_id)?;
+        Ok(removed > 0)
+    }
+
+    async fn clear_other_session_tokens(&self, user_id: i32, keep_token: &str) -> Result<u64> {
+        self.remove_sessions(|token, session| session.user_id == user_id && token != keep_token)
+    }
+
+    async fn update_session_roles(&self, user_id: i32, role_codes: &[RoleCode]) -> Result<u64> {
+        // ---
+        let mut sessions = self.sessions.lock().unwrap();
+        let mut updated = 0;
+        for (_, session) in sessions.iter_mut().filter(|(_, s)| s.user_id == user_id) {
+            session.role_codes = role_codes.to_vec();
+            updated += 1;
+        }
+        Ok(updated)
+    }
+
+    async fn set_password_reset_token(
+        &self,
+        token: &str,
+        user_id: i32,
+        _ttl_secs: u64,
+    ) -> Result<()> {
+        // ---
+        self.reset_tokens
+            .lock()
+            .unwrap()
+            .insert(token.into(), user_id);
+        Ok(())
+    }
+
+    async fn find_password_reset_token(&self, token: &str) -> Result<Option<i32>> {
+        Ok(self.reset_tokens.lock().unwrap().get(token).copied())
+    }
+
+    async fn take_password_reset_token(&self, token: &str) -> Result<Option<i32>> {
+        Ok(self.reset_tokens.lock().unwrap().remove(token))
+    }
+
+    async fn set_login_challenge(&self, token: &str, user_id: i32, _ttl_secs: u64) -> Result<()> {
+        // ---
+        self.challenges
+            .lock()
+            .unwrap()
+            .insert(token.into(), user_id);
+        Ok(())
+    }
+
+    async fn take_login_challenge(&self, token: &str) -> Result<Option<i32>> {
+        Ok(self.challenges.lock().unwrap().remove(token))
+    }
+
+    async fn set_oidc_login_state(&self, state: &str, value: &str, _ttl_secs: u64) -> Result<()> {
+        // ---
+        self.oidc_states
+            .lock()
+            .unwrap()
+            .insert(state.into(), value.into());
+        Ok(())
+    }
+
+    async fn take_oidc_login_state(&self, state: &str) -> Result<Option<String>> {
+        Ok(self.oidc_states.lock().unwrap().remove(state))
+    }
+
+    async fn set_author_claim(&self, token: &str, value: &str, _ttl_secs: u64) -> Result<()> {
+        // ---
+        self.author_claims
+            .lock()
+            .unwrap()
+            .insert(token.into(), value.into());
+        Ok(())
+    }
+
+    async fn take_author_claim(&self, token: &str) -> Result<Option<String>> {
+        Ok(self.author_claims.lock().unwrap().remove(token))
+    }
+
+    async fn incr_login_failures(&self, key: &str, _window_secs: u64) -> Result<u64> {
+        // ---
+        let mut failures = self.login_failures.lock().unwrap();
+        let count = failures.entry(key.into()).or_insert(0);
+        *count += 1;
+        Ok(*count)
+    }
+
+    async fn lock_login(&self, key: &str, secs: u64) -> Result<()> {
+        self.login_locks.lock().unwrap().insert(key.into(), secs);
+        Ok(())
+    }
+
+    async fn login_lock_ttl(&self, key: &str) -> Result<Option<u64>> {
+        Ok(self.login_locks.lock().unwrap().get(key).copied())
+    }
+
+    async fn clear_login_failures(&self, key: &str) -> Result<()> {
+        // ---
+        self.login_failures.lock().unwrap().remove(key);
+        self.login_locks.lock().unwrap().remove(key);
+        Ok(())
+    }
+
+    async fn issue_access_token(
+        &self,
+        user: &AppUser,
+        session: &SessionRecord,
+    ) -> Result<Option<AccessToken>> {
+        // ---
+        Ok(self.jwt.then(|| AccessToken {
+            token: format!("access-{}-{}", user.id, session.id),
+            expires_in: 300,
+        }))
+    }
+
+    fn requires_access_tokens(&self) -> bool {
+        self.jwt
+    }
+}
+
+/// Roles with their permissions, seeded with Admin, Editor and Viewer as in
+/// `db-init.sql`.
+pub(crate) struct MockRoleRepo {
+    pub roles: Mutex<Vec<Role>>,
+}
+
+impl Default for MockRoleRepo {
+    fn default() -> Self {
+        // ---
+        use Permission::*;
+
+        let seeded = [
+            (RoleCode::Admin, Permission::ALL.to_vec()),
+            (
+                RoleCode::Editor,
+                vec![
+                    CrateRead,
+                    CrateWrite,
+                    CrateWriteOwn,
+                    CrateDelete,
+                    AuthorRead,
+                    AuthorWrite,
+                    AuthorDelete,
+                ],
+            ),
+            (RoleCode::Viewer, vec![CrateRead, CrateWriteOwn, AuthorRead]),
+        ];
+        let roles = seeded
+            .into_iter()
+            .zip(1..)
+            .map(|((code, permissions), id)| Role {
+                id,
+                name: code.to_string(),
+                code,
+                permissions,
+            })
+            .collect();
+        Self {
+            roles: Mutex::new(roles),
+        }
+    }
+}
+
+impl MockRoleRepo {
+    // ---
+
+    /// Add a role granting `permissions`.
+    pub fn with_role(self, code: &str, permissions: Vec<Permission>) -> Self {
+        // ---
+        let mut roles = self.roles.lock().unwrap();
+        let id = roles.len() as i32 + 1;
+        roles.push(Role {
+            id,
+            code: RoleCode::Custom(code.into()),
+            name: code.into(),
+            permissions,
+        });
+        drop(roles);
+        self
+    }
+}
+
+#[async_trait]
+impl RoleCodeTableTrait for MockRoleRepo {
+    // ---
+    async fn find_role_codes_by_user(&self, _user_id: i32) -> Result<Vec<RoleCode>> {
+        unreachable!()
+    }
+
+    async fn find_all(&self) -> Result<Vec<RoleCode>> {
+        let roles = self.roles.lock().unwrap();
+        Ok(roles.iter().map(|role| role.code.clone()).collect())
+    }
+
+    async fn find_role_name_by_code(&self, _code: RoleCode) -> Result<Role> {
+        unreachable!()
+    }
+
+    async fn find_roles(&self) -> Result<Vec<Role>> {
+        Ok(self.roles.lock().unwrap().clone())
+    }
+
+    async fn find_role(&self, code: &RoleCode) -> Result<Option<Role>> {
+        let roles = self.roles.lock().unwrap();
+        Ok(roles.iter().find(|role| role.code == *code).cloned())
+    }
+
+    async fn create_role(&self, _new_role: &NewRole) -> Result<Role> {
+        unreachable!()
+    }
+
+    async fn rename_role(&self, _code: &RoleCode, _name: &str) -> Result<bool> {
+        unreachable!()
+    }
+
+    async fn set_role_permissions(
+        &self,
+        _code: &RoleCode,
+        _permissions: &[Permission],
+    ) -> Result<bool> {
+        unreachable!()
+    }
+
+    async fn delete_role(&self, _code: &RoleCode) -> Result<bool> {
+        unreachable!()
+    }
+
+    async fn find_permissions_by_roles(&self, codes: &[RoleCode]) -> Result<Vec<Permission>> {
+        // ---
+        let roles = self.roles.lock().unwrap();
+        let mut granted: Vec<Permission> = roles
+            .iter()
+            .filter(|role| codes.contains(&role.code))
+            .flat_map(|role| role.permissions.iter().copied())
+            .collect();
+        granted.sort_by_key(|permission| permission.to_string());
+        granted.dedup();
+        Ok(granted)
+    }
+}
+
+/// Keeps recorded events in memory.
+#[derive(Default)]
+pub(crate) struct MockAuditLog {
+    pub events: Mutex<Vec<NewAuditEvent>>,
+}
+
+impl MockAuditLog {
+    pub fn events(&self) -> Vec<NewAuditEvent> {
+        self.events.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl AuditLogTrait for MockAuditLog {
+    // ---
+    async fn record(&self, event: NewAuditEvent) -> Result<AuditEvent> {
+        // ---
+        let mut events = self.events.lock().unwrap();
+        events.push(event.clone());
+        Ok(AuditEvent {
+            id: events.len() as i32,
+            actor_user_id: event.actor_user_id,
+            action: event.action,
+            entity_type: event.entity_type,
+            entity_id: event.entity_id,
+            before: event.before,
+            after: event.after,
+            created_at: Utc::now().naive_utc(),
+        })
+    }
+
+    async fn find(&self, _filter: &AuditFilter) -> Result<Vec<AuditEvent>> {
+        unreachable!()
+    }
+}
+
+/// Records every templated message as `(to, template, context)` instead of sending it.
+#[derive(Default)]
+pub(crate) struct MockMailer {
+    pub sent: Mutex<Vec<(String, String, Value)>>,
+}
+
+#[async_trait]
+impl MailerTrait for MockMailer {
+    // ---
+    async fn send_digest(&self, _to: &str, _crates: &[CrateSummary]) -> Result<()> {
+        unreachable!()
+    }
+
+    async fn send_templated(
+        &self,
+        to: &str,
+        _subject: &str,
+        template: &str,
+        context: Value,
+    ) -> Result<()> {
+        // ---
+        self.sent
+            .lock()
+            .unwrap()
+            .push((to.to_string(), template.to_string(), context));
+        Ok(())
+    }
+}
@@ -99,7 +99,7 @@ async fn test_complete_cli_workflow() -> Result<()> {
         "--username",
         "test-user-1",
         "--password",
-        "pass123",
+        "test-pass-123",
         "--roles",
         "admin",
     ])
@@ -109,7 +109,7 @@ async fn test_complete_cli_workflow() -> Result<()> {
         "--username",
         "test-user-2",
         "--password",
-        "pass456",
+        "test-pass-456",
         "--roles",
         "editor",
     ])
@@ -128,6 +128,8 @@ async fn test_complete_cli_workflow() -> Result<()> {
 
     // Step 5: Delete users and verify
     println!("🗑️  Step 5: Testing user deletion");
+    assert_cli_failure(&["delete-user-by-name", "test-user-1"], 1).await?;
+    assert_cli_success(&["disable-user", "test-user-1"]).await?;
     assert_cli_success(&["delete-user-by-name", "test-user-1"]).await?;
 
     // Step 5b: Verify deleted user doesn't exist
@@ -149,7 +151,7 @@ async fn test_complete_cli_workflow() -> Result<()> {
         "--username",
         "short-admin",
         "--password",
-        "pass",
+        "role-shortcut-pass",
         "--roles",
         "a",
     ])
@@ -170,7 +172,7 @@ async fn test_complete_cli_workflow() -> Result<()> {
             "--username",
             "bad-role-user",
             "--password",
-            "pass",
+            "role-check-pass",
             "--roles",
             "invalid",
         ],
@@ -178,6 +180,259 @@ async fn test_complete_cli_workflow() -> Result<()> {
     )
     .await?;
 
+    // Step 7a: Passwords must satisfy the password policy
+    println!("🔐 Step 7a: Testing password policy");
+    assert_cli_failure(
+        &[
+            "create-user",
+            "--username",
+            "weak-user",
+            "--password",
+            "short",
+            "--roles",
+            "viewer",
+        ],
+        1,
+    )
+    .await?;
+    assert_cli_failure(&["user-exists", "weak-user"], 1).await?;
+    assert_cli_failure(
+        &[
+            "reset-password",
+            "--username",
+            "test-user-2",
+            "--password",
+            "test-user-2-pass",
+        ],
+        1,
+    )
+    .await?;
+
+    // Step 7b: Password reset for an existing and a missing user
+    println!("🔑 Step 7b: Testing password reset");
+    let output = assert_cli_success(&[
+        "reset-password",
+        "--username",
+        "test-user-2",
+        "--password",
+        "new-pass789",
+    ])
+    .await?;
+    ensure!(
+        output.contains("Reset password for user: test-user-2"),
+        "reset-password did not confirm: {output}"
+    );
+    assert_cli_failure(
+        &[
+            "reset-password",
+            "--username",
+            "junk-user",
+            "--password",
+            "whatever",
+        ],
+        1,
+    )
+    .await?;
+
+    // Step 7c: Clearing login lockouts succeeds whether or not the user is locked
+    println!("🔓 Step 7c: Testing unlock-user");
+    let output = assert_cli_success(&["unlock-user", "test-user-2", "--ip", "10.0.0.1"]).await?;
+    ensure!(
+        output.contains("Unlocked user: test-user-2"),
+        "unlock-user did not confirm: {output}"
+    );
+
+    // Step 7d: API token lifecycle; the secret is printed once and never listed
+    println!("🎟️  Step 7d: Testing API tokens");
+    let output = assert_cli_success(&[
+        "token",
+        "create",
+        "--username",
+        "test-user-2",
+        "--name",
+        "ci-pipeline",
+        "--scopes",
+        "crates:read,crates:write",
+        "--expires-days",
+        "7",
+    ])
+    .await?;
+    ensure!(
+        output.contains("cr8s_pat_"),
+        "token create did not print the secret: {output}"
+    );
+    let token_id = output
+        .split("(ID: ")
+        .nth(1)
+        .and_then(|rest| rest.split(')').next())
+        .ok_or_else(|| anyhow::anyhow!("token create did not print an ID: {output}"))?
+        .to_string();
+
+    let output = assert_cli_success(&["token", "list", "--username", "test-user-2"]).await?;
+    ensure!(
+        output.contains("ci-pipeline") && output.contains("crates:write"),
+        "token list is missing the new token: {output}"
+    );
+    ensure!(!output.contains("cr8s_pat_"), "token list leaks secrets");
+
+    assert_cli_success(&["token", "revoke", "--username", "test-user-2", &token_id]).await?;
+    assert_cli_failure(
+        &["token", "revoke", "--username", "test-user-2", &token_id],
+        1,
+    )
+    .await?;
+    assert_cli_failure(
+        &[
+            "token",
+            "create",
+            "--username",
+            "test-user-2",
+            "--name",
+            "bad",
+            "--scopes",
+            "crates:delete",
+        ],
+        2,
+    )
+    .await?;
+
+    // Step 7e: Role lifecycle; seeded roles cannot be deleted
+    println!("🛡️  Step 7e: Testing roles");
+    let _ = run_cli_command(&["role", "delete", "CliPublisher"]).await?;
+    let output = assert_cli_success(&[
+        "role",
+        "create",
+        "--code",
+        "CliPublisher",
+        "--name",
+        "Publisher",
+        "--permissions",
+        "crate:read,crate:write",
+    ])
+    .await?;
+    ensure!(
+        output.contains("Created role CliPublisher"),
+        "role create did not confirm: {output}"
+    );
+
+    assert_cli_success(&[
+        "role",
+        "update",
+        "CliPublisher",
+        "--permissions",
+        "crate:read",
+    ])
+    .await?;
+    let output = assert_cli_success(&["role", "list"]).await?;
+    ensure!(
+        output.contains("CliPublisher") && output.contains("user:manage"),
+        "role list is missing roles or permissions: {output}"
+    );
+
+    // Step 7f: Grant and revoke roles of an existing user
+    println!("🎭 Step 7f: Testing grant-role and revoke-role");
+    let output = assert_cli_success(&[
+        "grant-role",
+        "--username",
+        "test-user-2",
+        "--roles",
+        "CliPublisher",
+    ])
+    .await?;
+    ensure!(
+        output.contains("test-user-2 now has roles: [Editor, CliPublisher]"),
+        "grant-role did not confirm: {output}"
+    );
+
+    let output = assert_cli_success(&[
+        "revoke-role",
+        "--username",
+        "test-user-2",
+        "--roles",
+        "Editor",
+    ])
+    .await?;
+    ensure!(
+        output.contains("test-user-2 now has roles: [CliPublisher]"),
+        "revoke-role did not confirm: {output}"
+    );
+    assert_cli_failure(
+        &[
+            "grant-role",
+            "--username",
+            "test-user-2",
+            "--roles",
+            "NoSuchRole",
+        ],
+        1,
+    )
+    .await?;
+
+    assert_cli_success(&["role", "delete", "CliPublisher"]).await?;
+    assert_cli_failure(&["role", "delete", "CliPublisher"], 1).await?;
+    assert_cli_failure(&["role", "delete", "Viewer"], 1).await?;
+
+    // Step 7g: Author links need an existing author
+    println!("🔗 Step 7g: Testing link-author and unlink-author");
+    assert_cli_failure(
+        &[
+            "link-author",
+            "--username",
+            "test-user-2",
+            "--author-id",
+            "999999",
+        ],
+        1,
+    )
+    .await?;
+    let output = assert_cli_success(&["unlink-author", "--username", "test-user-2"]).await?;
+    ensure!(
+        output.contains("test-user-2 is no longer linked to an author"),
+        "unlink-author did not confirm: {output}"
+    );
+
+    // Step 7h: Disable and re-enable an account
+    println!("🚫 Step 7h: Testing disable-user and enable-user");
+    assert_cli_success(&["disable-user", "test-user-2"]).await?;
+    let user_list = assert_cli_success(&["list-users"]).await?;
+    ensure!(
+        user_list
+            .lines()
+            .any(|line| line.contains("test-user-2") && line.contains("(disabled)")),
+        "test-user-2 not shown as disabled: {user_list}"
+    );
+    assert_cli_success(&["enable-user", "test-user-2"]).await?;
+    let user_list = assert_cli_success(&["list-users"]).await?;
+    ensure!(
+        !user_list
+            .lines()
+            .any(|line| line.contains("test-user-2") && line.contains("(disabled)")),
+        "test-user-2 still disabled: {user_list}"
+    );
+    assert_cli_failure(&["disable-user", "junk-user"], 1).await?;
+
+    // Step 7i: User changes made above are in the audit log, without an actor
+    println!("🧾 Step 7i: Testing audit tail");
+    let output =
+        assert_cli_success(&["audit", "tail", "--entity-type", "user", "-n", "500"]).await?;
+    ensure!(
+        output
+            .lines()
+            .any(|line| line.contains("cli") && line.contains("create user")),
+        "create-user not audited: {output}"
+    );
+    ensure!(
+        output.lines().any(|line| line.contains("delete user")),
+        "delete-user-by-name not audited: {output}"
+    );
+    ensure!(
+        output
+            .lines()
+            .any(|line| line.contains("update user") && line.contains("disabled_at")),
+        "disable-user not audited: {output}"
+    );
+    assert_cli_failure(&["audit", "tail", "--entity-type", "role"], 2).await?;
+
     // Step 8: Test digest (email functionality)
     // Note: Exit code 0 means actual SMTP delivery succeeded (not just a stub)
     // Requires SMTP_HOST, SMTP_USERNAME, SMTP_PASSWORD env vars
@@ -196,8 +451,10 @@ async fn test_complete_cli_workflow() -> Result<()> {
 
     // Step 9: Cleanup
     println!("🧹 Step 9: Final cleanup");
-    assert_cli_success(&["delete-user-by-name", "test-user-2"]).await?;
-    assert_cli_success(&["delete-user-by-name", "short-admin"]).await?;
+    for username in ["test-user-2", "short-admin"] {
+        assert_cli_success(&["disable-user", username]).await?;
+        assert_cli_success(&["delete-user-by-name", username]).await?;
+    }
 
     // Step 10: Verify cleanup worked
     println!("🔍 Step 10: Verifying all test users removed");